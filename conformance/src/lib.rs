@@ -79,6 +79,15 @@ fn check_triple(triple: &Triple, defined: &HashSet<String>) -> bool {
         if !po.object.iter().all(|t| check_term(t, defined)) {
             return false;
         }
+
+        if !po.annotations.iter().all(|a| {
+            a.po.iter().all(|po| {
+                check_term(&po.predicate, defined)
+                    && po.object.iter().all(|t| check_term(t, defined))
+            })
+        }) {
+            return false;
+        }
     }
 
     true
@@ -102,6 +111,11 @@ fn check_term(term: &Term, defined: &HashSet<String>) -> bool {
             defined.contains(prefix)
         }
         Term::Collection(spanneds) => spanneds.iter().all(|t| check_term(t, defined)),
+        Term::Triple(triple) => {
+            check_term(&triple.subject, defined)
+                && check_term(&triple.predicate, defined)
+                && check_term(&triple.object, defined)
+        }
         _ => true,
     }
 }
@@ -4,6 +4,7 @@ use bevy_ecs::{
     bundle::Bundle,
     component::Component,
     entity::Entity,
+    query::{Has, Or, With},
     schedule::ScheduleLabel,
     world::{CommandQueue, World},
 };
@@ -17,7 +18,14 @@ use ropey::Rope;
 use tower_lsp::{jsonrpc::Result, LanguageServer};
 use tracing::info;
 
-use crate::{feature::goto_definition::GotoDefinitionRequest, prelude::*, Startup};
+use crate::{
+    feature::{
+        diagnostics::{pull_diagnostics, pull_workspace_diagnostics, result_id},
+        goto_definition::GotoDefinitionRequest,
+    },
+    prelude::*,
+    Startup, WorkspaceIndex,
+};
 
 #[derive(Debug)]
 pub struct Backend {
@@ -25,6 +33,8 @@ pub struct Backend {
     sender: CommandSender,
     #[allow(unused)]
     client: tower_lsp::Client,
+    /// Capabilities the client announced in `initialize`
+    capabilities: Arc<Mutex<ClientCapabilities>>,
     semantic_tokens: Vec<SemanticTokenType>,
 }
 
@@ -38,6 +48,7 @@ impl Backend {
             entities: Default::default(),
             sender,
             client,
+            capabilities: Default::default(),
             semantic_tokens: tokens,
         }
     }
@@ -87,6 +98,54 @@ impl Backend {
 
         rx.await.unwrap_or_default()
     }
+
+    /// The embedded snippet at `pos` of a host document, with `pos` relative to the snippet.
+    async fn snippet_at(
+        &self,
+        entity: Entity,
+        pos: Position,
+    ) -> Option<(Entity, systems::Snippet, Position)> {
+        self.run(move |world| systems::snippet_at(world, entity, pos))
+            .await
+            .flatten()
+    }
+
+    /// Parse the configuration, telling the client when it is invalid.
+    async fn parse_config(&self, value: serde_json::Value) -> Option<Config> {
+        match serde_json::from_value(value) {
+            Ok(config) => Some(config),
+            Err(e) => {
+                let message = format!("Invalid swls configuration: {}", e);
+                tracing::error!("{}", message);
+                self.client.log_message(MessageType::ERROR, message).await;
+                None
+            }
+        }
+    }
+
+    async fn pull_config(&self) -> Option<Config> {
+        let items = vec![ConfigurationItem {
+            scope_uri: None,
+            section: Some(String::from("swls")),
+        }];
+        let mut values = self.client.configuration(items).await.ok()?;
+        self.parse_config(values.pop()?).await
+    }
+
+    async fn resolve_type_hierarchy(
+        &self,
+        request: TypeHierarchyRequest,
+    ) -> Option<Vec<TypeHierarchyItem>> {
+        self.run(move |world| {
+            world.insert_resource(request);
+            world.run_schedule(TypeHierarchyLabel);
+            world
+                .remove_resource::<TypeHierarchyRequest>()
+                .map(|x| x.items)
+        })
+        .await
+        .flatten()
+    }
 }
 
 #[tower_lsp::async_trait]
@@ -94,10 +153,24 @@ impl LanguageServer for Backend {
     #[tracing::instrument(skip(self, init))]
     async fn initialize(&self, init: InitializeParams) -> Result<InitializeResult> {
         info!("Initialize");
+        *self.capabilities.lock().await = init.capabilities.clone();
         let workspaces = init.workspace_folders.clone().unwrap_or_default();
-        let config: Config =
-            serde_json::from_value(init.initialization_options.clone().unwrap_or_default())
-                .unwrap_or_default();
+        let pull_diagnostics = init
+            .capabilities
+            .text_document
+            .as_ref()
+            .is_some_and(|x| x.diagnostic.is_some());
+        let refresh_diagnostics = init
+            .capabilities
+            .workspace
+            .as_ref()
+            .and_then(|x| x.diagnostic.as_ref())
+            .and_then(|x| x.refresh_support)
+            .unwrap_or(false);
+        let config = match init.initialization_options.clone() {
+            Some(options) => self.parse_config(options).await.unwrap_or_default(),
+            None => Config::default(),
+        };
 
         let server_config = ServerConfig { config, workspaces };
         info!("Initialize {:?}", server_config);
@@ -116,8 +189,14 @@ impl LanguageServer for Backend {
         .collect();
 
         self.run(|world| {
+            if let Some(mut publisher) = world.get_resource_mut::<DiagnosticPublisher>() {
+                publisher.set_levels(server_config.config.diagnostics.clone());
+                publisher.set_push(!pull_diagnostics);
+                publisher.set_refresh_support(refresh_diagnostics);
+            }
             world.insert_resource(server_config);
             world.run_schedule(Startup);
+            world.run_schedule(WorkspaceIndex);
         })
         .await;
 
@@ -127,9 +206,19 @@ impl LanguageServer for Backend {
             capabilities: ServerCapabilities {
                 inlay_hint_provider: Some(OneOf::Left(true)),
                 text_document_sync: Some(TextDocumentSyncCapability::Kind(
-                    TextDocumentSyncKind::FULL,
+                    TextDocumentSyncKind::INCREMENTAL,
+                )),
+                code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+                document_symbol_provider: Some(OneOf::Left(true)),
+                workspace_symbol_provider: Some(OneOf::Left(true)),
+                diagnostic_provider: Some(DiagnosticServerCapabilities::Options(
+                    DiagnosticOptions {
+                        identifier: Some(String::from("swls")),
+                        inter_file_dependencies: true,
+                        workspace_diagnostics: true,
+                        work_done_progress_options: Default::default(),
+                    },
                 )),
-                code_action_provider: None,
                 completion_provider: Some(CompletionOptions {
                     resolve_provider: Some(false),
                     trigger_characters: Some(vec![String::from(":")]),
@@ -173,19 +262,79 @@ impl LanguageServer for Backend {
         })
     }
 
+    #[tracing::instrument(skip(self))]
+    async fn initialized(&self, _: InitializedParams) {
+        let watchers = DidChangeWatchedFilesRegistrationOptions {
+            watchers: vec![FileSystemWatcher {
+                glob_pattern: GlobPattern::String(systems::workspace_files_glob()),
+                kind: None,
+            }],
+        };
+        let capabilities = self.capabilities.lock().await.clone();
+        let text_document = capabilities.text_document.as_ref();
+        let workspace = capabilities.workspace.as_ref();
+
+        // Type hierarchy is not part of the static server capabilities
+        let mut registrations = Vec::new();
+        if text_document
+            .and_then(|x| x.type_hierarchy.as_ref())
+            .and_then(|x| x.dynamic_registration)
+            .unwrap_or(false)
+        {
+            registrations.push(Registration {
+                id: String::from("type_hierarchy"),
+                method: String::from("textDocument/prepareTypeHierarchy"),
+                register_options: None,
+            });
+        }
+        if workspace
+            .and_then(|x| x.did_change_configuration.as_ref())
+            .and_then(|x| x.dynamic_registration)
+            .unwrap_or(false)
+        {
+            registrations.push(Registration {
+                id: String::from("configuration"),
+                method: String::from("workspace/didChangeConfiguration"),
+                register_options: None,
+            });
+        }
+        if workspace
+            .and_then(|x| x.did_change_watched_files.as_ref())
+            .and_then(|x| x.dynamic_registration)
+            .unwrap_or(false)
+        {
+            registrations.push(Registration {
+                id: String::from("watched_files"),
+                method: String::from("workspace/didChangeWatchedFiles"),
+                register_options: serde_json::to_value(watchers).ok(),
+            });
+        }
+
+        // One registration the client rejects should not prevent the others
+        for registration in registrations {
+            let method = registration.method.clone();
+            if let Err(e) = self.client.register_capability(vec![registration]).await {
+                tracing::error!("Failed to register {} {}", method, e);
+            }
+        }
+    }
+
     async fn did_change_workspace_folders(&self, params: DidChangeWorkspaceFoldersParams) -> () {
         self.run(move |world| {
             let mut config = world.resource_mut::<ServerConfig>();
             let WorkspaceFoldersChangeEvent { added, removed } = params.event;
 
-            for r in removed {
-                if let Some(idx) = config.workspaces.iter().position(|x| x == &r) {
+            for r in &removed {
+                if let Some(idx) = config.workspaces.iter().position(|x| x == r) {
                     config.workspaces.remove(idx);
                 }
             }
 
-            // This is nice and all, but we don't bubble this event up in the world
             config.workspaces.extend(added);
+            for r in removed {
+                systems::remove_workspace_folder(r.uri)(world);
+            }
+            world.run_schedule(WorkspaceIndex);
         })
         .await;
         ()
@@ -222,6 +371,19 @@ impl LanguageServer for Backend {
             }
         };
 
+        // Documents that embed snippets are highlighted through their snippets
+        if let Some(Some(data)) = self
+            .run(move |world| systems::snippet_semantic_tokens(world, entity))
+            .await
+        {
+            return Ok(Some(SemanticTokensResult::Tokens(
+                lsp_types::SemanticTokens {
+                    result_id: None,
+                    data,
+                },
+            )));
+        }
+
         if let Some(res) = self
             .run_schedule::<HighlightRequest>(entity, SemanticLabel, HighlightRequest(vec![]))
             .await
@@ -376,16 +538,26 @@ impl LanguageServer for Backend {
             pos.character
         };
 
+        let snippet = self.snippet_at(entity, pos).await;
+        let (entity, pos) = snippet
+            .as_ref()
+            .map(|(e, _, pos)| (*e, *pos))
+            .unwrap_or((entity, pos));
+
         if let Some(hover) = self
             .run_schedule::<HoverRequest>(entity, HoverLabel, (request, PositionComponent(pos)))
             .await
         {
             if hover.0.len() > 0 {
+                let range = match &snippet {
+                    Some((_, snippet, _)) => hover.1.map(|x| snippet.range_to_host(x)),
+                    None => hover.1,
+                };
                 return Ok(Some(lsp_types::Hover {
                     contents: lsp_types::HoverContents::Array(
                         hover.0.into_iter().map(MarkedString::String).collect(),
                     ),
-                    range: hover.1,
+                    range,
                 }));
             }
         }
@@ -432,6 +604,238 @@ impl LanguageServer for Backend {
         Ok(request.and_then(|x| x.0))
     }
 
+    #[tracing::instrument(skip(self, params), fields(uri = %params.text_document.uri.as_str()))]
+    async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
+        let entity = {
+            let map = self.entities.lock().await;
+            if let Some(entity) = map.get(params.text_document.uri.as_str()) {
+                entity.clone()
+            } else {
+                return Ok(None);
+            }
+        };
+
+        let request = CodeActionRequest::new(params.range, params.context.diagnostics);
+        let actions = self
+            .run_schedule::<CodeActionRequest>(entity, CodeActionLabel, request)
+            .await
+            .map(|x| x.actions);
+
+        Ok(actions)
+    }
+
+    #[tracing::instrument(skip(self, params), fields(uri = %params.text_document.uri.as_str()))]
+    async fn diagnostic(
+        &self,
+        params: DocumentDiagnosticParams,
+    ) -> Result<DocumentDiagnosticReportResult> {
+        let entity = {
+            let map = self.entities.lock().await;
+            map.get(params.text_document.uri.as_str()).cloned()
+        };
+
+        let items = match entity {
+            Some(entity) => self
+                .run(move |world| pull_diagnostics(world, &[entity]).pop())
+                .await
+                .flatten()
+                .unwrap_or_default(),
+            None => Vec::new(),
+        };
+
+        let result_id = result_id(&items);
+        let report = if params.previous_result_id.as_ref() == Some(&result_id) {
+            DocumentDiagnosticReport::Unchanged(RelatedUnchangedDocumentDiagnosticReport {
+                related_documents: None,
+                unchanged_document_diagnostic_report: UnchangedDocumentDiagnosticReport {
+                    result_id,
+                },
+            })
+        } else {
+            DocumentDiagnosticReport::Full(RelatedFullDocumentDiagnosticReport {
+                related_documents: None,
+                full_document_diagnostic_report: FullDocumentDiagnosticReport {
+                    result_id: Some(result_id),
+                    items,
+                },
+            })
+        };
+
+        Ok(DocumentDiagnosticReportResult::Report(report))
+    }
+
+    #[tracing::instrument(skip(self, params))]
+    async fn workspace_diagnostic(
+        &self,
+        params: WorkspaceDiagnosticParams,
+    ) -> Result<WorkspaceDiagnosticReportResult> {
+        let previous: HashMap<Url, String> = params
+            .previous_result_ids
+            .into_iter()
+            .map(|x| (x.uri, x.value))
+            .collect();
+
+        let documents = self
+            .run(|world| {
+                let documents: Vec<_> = world
+                    .query_filtered::<
+                        (Entity, &Label, &Wrapped<TextDocumentItem>, Has<Open>),
+                        Or<(With<Open>, With<WorkspaceFile>)>,
+                    >()
+                    .iter(world)
+                    .map(|(e, label, item, open)| {
+                        (e, label.0.clone(), open.then_some(item.version as i64))
+                    })
+                    .collect();
+
+                let entities: Vec<_> = documents.iter().map(|x| x.0).collect();
+                let diagnostics = pull_workspace_diagnostics(world, &entities);
+                documents
+                    .into_iter()
+                    .zip(diagnostics)
+                    .map(|((_, uri, version), items)| (uri, version, items))
+                    .collect::<Vec<_>>()
+            })
+            .await
+            .unwrap_or_default();
+
+        let items = documents
+            .into_iter()
+            .map(|(uri, version, items)| {
+                let result_id = result_id(&items);
+                if previous.get(&uri) == Some(&result_id) {
+                    WorkspaceDocumentDiagnosticReport::Unchanged(
+                        WorkspaceUnchangedDocumentDiagnosticReport {
+                            uri,
+                            version,
+                            unchanged_document_diagnostic_report:
+                                UnchangedDocumentDiagnosticReport { result_id },
+                        },
+                    )
+                } else {
+                    WorkspaceDocumentDiagnosticReport::Full(WorkspaceFullDocumentDiagnosticReport {
+                        uri,
+                        version,
+                        full_document_diagnostic_report: FullDocumentDiagnosticReport {
+                            result_id: Some(result_id),
+                            items,
+                        },
+                    })
+                }
+            })
+            .collect();
+
+        Ok(WorkspaceDiagnosticReportResult::Report(
+            WorkspaceDiagnosticReport { items },
+        ))
+    }
+
+    #[tracing::instrument(skip(self, params), fields(uri = %params.text_document.uri.as_str()))]
+    async fn document_symbol(
+        &self,
+        params: DocumentSymbolParams,
+    ) -> Result<Option<DocumentSymbolResponse>> {
+        let entity = {
+            let map = self.entities.lock().await;
+            if let Some(entity) = map.get(params.text_document.uri.as_str()) {
+                entity.clone()
+            } else {
+                return Ok(None);
+            }
+        };
+
+        let symbols = self
+            .run_schedule::<DocumentSymbolRequest>(
+                entity,
+                DocumentSymbolLabel,
+                DocumentSymbolRequest::default(),
+            )
+            .await
+            .map(|x| DocumentSymbolResponse::Nested(x.0));
+
+        Ok(symbols)
+    }
+
+    #[tracing::instrument(skip(self, params), fields(query = %params.query))]
+    async fn symbol(
+        &self,
+        params: WorkspaceSymbolParams,
+    ) -> Result<Option<Vec<SymbolInformation>>> {
+        let symbols = self
+            .run(move |world| {
+                world.insert_resource(WorkspaceSymbolRequest::new(params.query));
+                world.run_schedule(WorkspaceSymbolLabel);
+                world
+                    .remove_resource::<WorkspaceSymbolRequest>()
+                    .map(|x| x.symbols)
+            })
+            .await
+            .flatten();
+
+        Ok(symbols)
+    }
+
+    #[tracing::instrument(skip(self, params), fields(uri = %params.text_document_position_params.text_document.uri.as_str()))]
+    async fn prepare_type_hierarchy(
+        &self,
+        params: TypeHierarchyPrepareParams,
+    ) -> Result<Option<Vec<TypeHierarchyItem>>> {
+        let entity = {
+            let map = self.entities.lock().await;
+            if let Some(entity) = map.get(
+                params
+                    .text_document_position_params
+                    .text_document
+                    .uri
+                    .as_str(),
+            ) {
+                entity.clone()
+            } else {
+                return Ok(None);
+            }
+        };
+
+        let mut pos = params.text_document_position_params.position;
+        pos.character = if pos.character > 0 {
+            pos.character - 1
+        } else {
+            pos.character
+        };
+
+        let items = self
+            .run_schedule::<PrepareTypeHierarchyRequest>(
+                entity,
+                PrepareTypeHierarchyLabel,
+                (
+                    PositionComponent(pos),
+                    PrepareTypeHierarchyRequest::default(),
+                ),
+            )
+            .await
+            .map(|x| x.0)
+            .filter(|x| !x.is_empty());
+
+        Ok(items)
+    }
+
+    #[tracing::instrument(skip(self, params), fields(item = %params.item.name))]
+    async fn supertypes(
+        &self,
+        params: TypeHierarchySupertypesParams,
+    ) -> Result<Option<Vec<TypeHierarchyItem>>> {
+        let request = TypeHierarchyRequest::new(params.item, TypeHierarchyDirection::Supertypes);
+        Ok(self.resolve_type_hierarchy(request).await)
+    }
+
+    #[tracing::instrument(skip(self, params), fields(item = %params.item.name))]
+    async fn subtypes(
+        &self,
+        params: TypeHierarchySubtypesParams,
+    ) -> Result<Option<Vec<TypeHierarchyItem>>> {
+        let request = TypeHierarchyRequest::new(params.item, TypeHierarchyDirection::Subtypes);
+        Ok(self.resolve_type_hierarchy(request).await)
+    }
+
     #[tracing::instrument(skip(self, params), fields(uri = %params.text_document.uri.as_str()))]
     async fn did_open(&self, params: DidOpenTextDocumentParams) {
         let item = params.text_document;
@@ -463,6 +867,7 @@ impl LanguageServer for Backend {
                 info!("Running diagnostics");
                 world.run_schedule(DiagnosticsLabel);
                 info!("Done diagnostics");
+                systems::sync_snippets(id)(world);
                 id
             })
             .await;
@@ -475,6 +880,32 @@ impl LanguageServer for Backend {
         info!("Semantic tokens refresh");
     }
 
+    #[tracing::instrument(skip(self, params), fields(uri = %params.text_document.uri.as_str()))]
+    async fn did_close(&self, params: DidCloseTextDocumentParams) {
+        let entity = {
+            let mut map = self.entities.lock().await;
+            if let Some(entity) = map.remove(params.text_document.uri.as_str()) {
+                entity
+            } else {
+                info!("Didn't find entity {}", params.text_document.uri.as_str());
+                return;
+            }
+        };
+
+        let Some(url) = self.run(systems::close_document(entity)).await.flatten() else {
+            return;
+        };
+
+        // The workspace index follows the file on disk, not the unsaved editor content
+        let Some(fs) = self.run(|world| world.resource::<Fs>().clone()).await else {
+            return;
+        };
+        if let Some(content) = fs.0.read_file(&url).await {
+            self.run(systems::refresh_workspace_file(url, content))
+                .await;
+        }
+    }
+
     #[tracing::instrument(skip(self, params), fields(uri = %params.text_document.uri.as_str()))]
     async fn did_change(&self, params: DidChangeTextDocumentParams) {
         let entity = {
@@ -487,28 +918,82 @@ impl LanguageServer for Backend {
             }
         };
 
-        let change = {
-            if let Some(c) = params.content_changes.into_iter().next() {
-                c
-            } else {
+        let changes = params.content_changes;
+        if changes.is_empty() {
+            return;
+        }
+
+        self.run(move |world| {
+            let mut entity_mut = world.entity_mut(entity);
+            let Some(mut rope_c) = entity_mut.take::<RopeC>() else {
+                info!("Entity has no rope");
                 return;
+            };
+
+            let mut changed = Changes::default();
+            for change in &changes {
+                if let Some((removed, inserted)) = apply_change(change, &mut rope_c.0) {
+                    changed.push(removed, inserted);
+                } else {
+                    tracing::error!("Failed to apply change {:?}", change.range);
+                }
             }
-        };
 
-        self.run(move |world| {
-            let rope_c = RopeC(Rope::from_str(&change.text));
-            world
-                .entity_mut(entity)
-                .insert((Source(change.text), rope_c));
+            let source = Source(rope_c.0.to_string());
+            entity_mut.insert((source, rope_c, changed));
             world.run_schedule(ParseLabel);
             world.flush();
             info!("Running diagnostics");
             world.run_schedule(DiagnosticsLabel);
             info!("Running diagnostics done");
+            systems::sync_snippets(entity)(world);
         })
         .await;
     }
 
+    #[tracing::instrument(skip(self, params))]
+    async fn did_change_configuration(&self, params: DidChangeConfigurationParams) {
+        let settings = match params.settings.get("swls") {
+            Some(settings) => settings.clone(),
+            None => params.settings,
+        };
+
+        // Clients that only notify about the change expect us to pull the configuration
+        let config = if settings.as_object().is_some_and(|x| !x.is_empty()) {
+            self.parse_config(settings).await
+        } else {
+            self.pull_config().await
+        };
+
+        let Some(config) = config else {
+            info!("Didn't find a valid configuration");
+            return;
+        };
+        info!("Configuration changed {:?}", config);
+
+        self.run(systems::apply_config(config)).await;
+        let _ = self.client.send_request::<SemanticTokensRefresh>(()).await;
+    }
+
+    #[tracing::instrument(skip(self, params))]
+    async fn did_change_watched_files(&self, params: DidChangeWatchedFilesParams) {
+        let Some(fs) = self.run(|world| world.resource::<Fs>().clone()).await else {
+            return;
+        };
+
+        for FileEvent { uri, typ } in params.changes {
+            info!("Watched file {} changed ({:?})", uri, typ);
+            if typ == FileChangeType::DELETED {
+                self.run(systems::remove_workspace_file(uri)).await;
+            } else if let Some(content) = fs.0.read_file(&uri).await {
+                self.run(systems::refresh_workspace_file(uri, content))
+                    .await;
+            }
+        }
+
+        let _ = self.client.send_request::<SemanticTokensRefresh>(()).await;
+    }
+
     #[tracing::instrument(skip(self, params), fields(uri = %params.text_document.uri.as_str()))]
     async fn did_save(&self, params: DidSaveTextDocumentParams) {
         let _ = params;
@@ -621,6 +1106,12 @@ impl LanguageServer for Backend {
             pos.character
         };
 
+        let snippet = self.snippet_at(entity, pos).await;
+        let (entity, pos) = snippet
+            .as_ref()
+            .map(|(e, _, pos)| (*e, *pos))
+            .unwrap_or((entity, pos));
+
         let completions: Option<Vec<lsp_types::CompletionItem>> = self
             .run_schedule::<CompletionRequest>(
                 entity,
@@ -628,7 +1119,18 @@ impl LanguageServer for Backend {
                 (CompletionRequest(vec![]), PositionComponent(pos)),
             )
             .await
-            .map(|x| x.0.into_iter().map(|x| x.into()).collect());
+            .map(|x| {
+                x.0.into_iter()
+                    .map(|mut x| {
+                        if let Some((_, snippet, _)) = &snippet {
+                            for edit in &mut x.edits {
+                                edit.range = snippet.range_to_host(edit.range);
+                            }
+                        }
+                        x.into()
+                    })
+                    .collect()
+            });
 
         Ok(completions.map(|c| CompletionResponse::Array(c)))
     }
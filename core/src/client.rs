@@ -9,6 +9,16 @@ pub struct Resp {
     pub status: u16,
 }
 
+impl Resp {
+    /// Value of the first header called `name`, header names are case insensitive.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
 #[tower_lsp::async_trait]
 pub trait Client: Clone + ClientSync {
     async fn log_message<M: Display + Sync + Send + 'static>(&self, ty: MessageType, msg: M) -> ();
@@ -18,6 +28,8 @@ pub trait Client: Clone + ClientSync {
         diags: Vec<Diagnostic>,
         version: Option<i32>,
     ) -> ();
+    /// Ask the client to pull diagnostics again.
+    async fn refresh_diagnostics(&self) -> ();
 }
 
 pub trait ClientSync {
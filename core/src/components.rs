@@ -39,6 +39,44 @@ pub struct Source(pub String);
 #[derive(Component, AsRef, Deref, AsMut, DerefMut, Debug)]
 pub struct RopeC(pub ropey::Rope);
 
+/// [`Component`] containing the byte ranges of [`Source`] that changed with the last edit.
+///
+/// Ranges are expressed in the coordinates of the current [`Source`].
+#[derive(Component, AsRef, Deref, AsMut, DerefMut, Debug, Default, Clone)]
+pub struct Changes(pub Vec<std::ops::Range<usize>>);
+
+impl Changes {
+    /// Records that the bytes `removed` (in the previous document) are replaced with
+    /// `inserted` bytes, shifting or merging earlier recorded ranges.
+    pub fn push(&mut self, removed: std::ops::Range<usize>, inserted: usize) {
+        let new_end = removed.start + inserted;
+        let shift = |x: usize| x + new_end - removed.end;
+
+        let mut start = removed.start;
+        let mut end = new_end;
+        self.0.retain_mut(|range| {
+            if range.start > removed.end {
+                range.start = shift(range.start);
+                range.end = shift(range.end);
+                true
+            } else if range.end < removed.start {
+                true
+            } else {
+                start = start.min(range.start);
+                end = end.max(if range.end > removed.end {
+                    shift(range.end)
+                } else {
+                    new_end
+                });
+                false
+            }
+        });
+
+        self.0.push(start..end);
+        self.0.sort_by_key(|x| x.start);
+    }
+}
+
 /// [`Component`] that allows for language specific implementation for certain things, reducing
 /// code duplication.
 #[derive(Component, Debug, AsRef, Deref)]
@@ -50,6 +88,11 @@ pub struct DynLang(pub Box<dyn LangHelper + 'static + Send + Sync>);
 #[derive(Component, Debug)]
 pub struct Open;
 
+/// [`Component`] indicating that the document was found while indexing one of the workspace
+/// folders.
+#[derive(Component, Debug)]
+pub struct WorkspaceFile;
+
 /// [`Component`] indicating whether or not the document is dirty, a dirty document parsed with
 /// errors.
 ///
@@ -161,6 +204,16 @@ impl<'a> TypeHierarchy<'a> {
         })
     }
 
+    /// Iterates the direct super classes of a type.
+    pub fn direct_supertypes<'b>(&'b self, id: TypeId) -> impl Iterator<Item = TypeId> + 'b {
+        self.subclass[id.0].iter().copied()
+    }
+
+    /// Iterates the direct sub classes of a type.
+    pub fn direct_subtypes<'b>(&'b self, id: TypeId) -> impl Iterator<Item = TypeId> + 'b {
+        self.superclass[id.0].iter().copied()
+    }
+
     pub fn type_name(&self, id: TypeId) -> Cow<'a, str> {
         self.nodes[id.0].clone()
     }
@@ -193,13 +246,27 @@ pub struct ServerConfig {
     pub config: Config,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
 pub struct Config {
-    #[serde(default = "debug")]
     pub log: String,
     pub turtle: Option<bool>,
     pub jsonld: Option<bool>,
     pub sparql: Option<bool>,
+    /// Options used when formatting documents.
+    pub format: FormatConfig,
+    /// Severity of diagnostics per reason (`syntax`, `undefined_prefix`, `shacl_validation`,
+    /// `shex_validation`).
+    pub diagnostics: HashMap<String, DiagnosticLevel>,
+    /// Never fetch vocabularies, shapes or contexts over the network.
+    pub offline: bool,
+    /// Location of the vocabulary per namespace, used instead of looking the namespace up.
+    pub vocabularies: HashMap<String, String>,
+    /// Locations of SHACL shapes or ShEx schemas that every document is validated against.
+    pub shapes: Vec<String>,
+    /// ShEx shape map associations (`ex:alice@ex:Person`) that select the nodes to validate in
+    /// every document.
+    pub shape_map: Vec<String>,
 }
 impl Default for Config {
     fn default() -> Self {
@@ -208,10 +275,70 @@ impl Default for Config {
             turtle: None,
             jsonld: None,
             sparql: None,
+            format: FormatConfig::default(),
+            diagnostics: HashMap::new(),
+            offline: false,
+            vocabularies: HashMap::new(),
+            shapes: Vec::new(),
+            shape_map: Vec::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Configured shape locations as urls, plain paths are interpreted as file paths.
+    pub fn shape_urls(&self) -> Vec<lsp_types::Url> {
+        self.shapes
+            .iter()
+            .flat_map(|x| lsp_types::Url::parse(x).or_else(|_| lsp_types::Url::from_file_path(x)))
+            .collect()
+    }
+
+    /// Configured vocabulary location for the namespace.
+    pub fn vocabulary(&self, namespace: &str) -> Option<lsp_types::Url> {
+        let location = self.vocabularies.get(namespace)?;
+        lsp_types::Url::parse(location)
+            .or_else(|_| lsp_types::Url::from_file_path(location))
+            .ok()
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default, rename_all = "camelCase")]
+pub struct FormatConfig {
+    pub enabled: bool,
+    pub tab_size: u32,
+    /// Put `@context`, `@id` and `@type` first in JSON-LD objects and sort the other keys.
+    pub jsonld_sort_keys: bool,
+}
+impl Default for FormatConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            tab_size: 2,
+            jsonld_sort_keys: false,
         }
     }
 }
 
-fn debug() -> String {
-    String::from("debug")
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagnosticLevel {
+    Error,
+    Warning,
+    Information,
+    Hint,
+    Off,
+}
+
+impl DiagnosticLevel {
+    pub fn severity(&self) -> Option<lsp_types::DiagnosticSeverity> {
+        match self {
+            DiagnosticLevel::Error => Some(lsp_types::DiagnosticSeverity::ERROR),
+            DiagnosticLevel::Warning => Some(lsp_types::DiagnosticSeverity::WARNING),
+            DiagnosticLevel::Information => Some(lsp_types::DiagnosticSeverity::INFORMATION),
+            DiagnosticLevel::Hint => Some(lsp_types::DiagnosticSeverity::HINT),
+            DiagnosticLevel::Off => None,
+        }
+    }
 }
@@ -0,0 +1,32 @@
+use bevy_ecs::{component::Component, schedule::ScheduleLabel, world::World};
+use lsp_types::{CodeActionOrCommand, Diagnostic};
+
+/// [`Component`] indicating that the current document is currently handling a CodeAction request.
+///
+/// Systems in the CodeAction schedule push their actions to `actions`, based on the requested
+/// `range` and the `diagnostics` the client sent along.
+#[derive(Component, Debug)]
+pub struct CodeActionRequest {
+    pub range: lsp_types::Range,
+    pub diagnostics: Vec<Diagnostic>,
+    pub actions: Vec<CodeActionOrCommand>,
+}
+
+impl CodeActionRequest {
+    pub fn new(range: lsp_types::Range, diagnostics: Vec<Diagnostic>) -> Self {
+        Self {
+            range,
+            diagnostics,
+            actions: Vec::new(),
+        }
+    }
+}
+
+/// [`ScheduleLabel`] related to the CodeAction schedule, this is language specific
+#[derive(ScheduleLabel, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Label;
+
+pub fn setup_schedule(world: &mut World) {
+    let code_action = bevy_ecs::schedule::Schedule::new(Label);
+    world.add_schedule(code_action);
+}
@@ -1,11 +1,16 @@
 use std::{collections::HashMap, fmt::Display, hash::Hash, ops::Range};
 
-use bevy_ecs::{prelude::*, schedule::ScheduleLabel};
+use bevy_ecs::{
+    component::{ComponentTicks, Tick},
+    prelude::*,
+    schedule::ScheduleLabel,
+};
 use chumsky::prelude::Simple;
 use futures::channel::mpsc;
 use lsp_types::{Diagnostic, DiagnosticSeverity, TextDocumentItem, Url};
 /// [`ScheduleLabel`] related to the PrepareRename schedule
-pub use systems::prefix::undefined_prefix;
+pub use systems::prefix::{pull_undefined_prefix, undefined_prefix};
+pub use systems::pull_shapes;
 
 use crate::prelude::*;
 #[derive(ScheduleLabel, Clone, Eq, PartialEq, Debug, Hash)]
@@ -15,12 +20,162 @@ pub fn setup_schedule(world: &mut World) {
     let mut diagnostics = Schedule::new(Label);
     diagnostics.add_systems((undefined_prefix,));
     world.add_schedule(diagnostics);
+
+    let mut pull = Schedule::new(Pull);
+    pull.add_systems((pull_undefined_prefix, pull_shapes));
+    world.add_schedule(pull);
+}
+
+/// [`ScheduleLabel`] related to the pull Diagnostics schedule, computing all diagnostics of
+/// documents with a [`DiagnosticRequest`] on demand.
+#[derive(ScheduleLabel, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Pull;
+
+/// [`Component`] indicating that the current document is currently handling a pull Diagnostic request.
+#[derive(Component, Debug, Default)]
+pub struct DiagnosticRequest(pub Vec<(Diagnostic, &'static str)>);
+
+impl DiagnosticRequest {
+    pub fn push(&mut self, diagnostics: Vec<Diagnostic>, reason: &'static str) {
+        self.0.extend(diagnostics.into_iter().map(|x| (x, reason)));
+    }
+}
+
+/// [`Component`] indicating that a document handling a [`DiagnosticRequest`] is not validated
+/// against shapes, only the diagnostics of the document itself are computed.
+#[derive(Component, Debug, Default)]
+pub struct SkipValidation;
+
+/// Compute the diagnostics of all `entities`, in order, with configured severities applied.
+///
+/// Diagnostics of the [`Snippets`](systems::Snippets) embedded in a document are part of the
+/// diagnostics of that document.
+pub fn pull_diagnostics(world: &mut World, entities: &[Entity]) -> Vec<Vec<Diagnostic>> {
+    let snippets: Vec<Entity> = entities
+        .iter()
+        .flat_map(|e| world.get::<systems::Snippets>(*e))
+        .flat_map(|x| x.0.iter().copied())
+        .collect();
+
+    for e in entities.iter().chain(&snippets) {
+        if let Some(mut entity) = world.get_entity_mut(*e) {
+            entity.insert(DiagnosticRequest::default());
+        }
+    }
+
+    world.run_schedule(Pull);
+
+    let mut out = Vec::with_capacity(entities.len());
+    for e in entities {
+        let mut request = world
+            .get_entity_mut(*e)
+            .and_then(|mut entity| entity.take::<DiagnosticRequest>())
+            .unwrap_or_default();
+        request
+            .0
+            .extend(systems::take_snippet_diagnostics(world, *e));
+
+        let publisher = world.resource::<DiagnosticPublisher>();
+        let mut diagnostics = Vec::new();
+        for (diagnostic, reason) in request.0 {
+            diagnostics.extend(publisher.apply_level(reason, diagnostic));
+        }
+        out.push(diagnostics);
+    }
+    out
+}
+
+/// [`Component`] caching the diagnostics of the last workspace diagnostic pull of a document.
+#[derive(Component, Debug)]
+pub struct PulledDiagnostics {
+    pub diagnostics: Vec<Diagnostic>,
+    tick: Tick,
+    shapes: usize,
+}
+
+/// Like [`pull_diagnostics`], but only documents that changed since their last pull are
+/// validated again, others return their [`PulledDiagnostics`].
+///
+/// A document changed when its content, links or prefixes changed, when it is [`Dirty`], or when
+/// the shapes or the configuration changed.
+pub fn pull_workspace_diagnostics(world: &mut World, entities: &[Entity]) -> Vec<Vec<Diagnostic>> {
+    let this_run = world.change_tick();
+    let shapes = systems::shape_ticks(world);
+    let config = world.get_resource_change_ticks::<ServerConfig>();
+
+    let outdated: Vec<Entity> = entities
+        .iter()
+        .copied()
+        .filter(|e| {
+            let Some(entity) = world.get_entity(*e) else {
+                return false;
+            };
+            let Some(cached) = entity.get::<PulledDiagnostics>() else {
+                return true;
+            };
+            let changed = |ticks: Option<ComponentTicks>| {
+                ticks.is_some_and(|x| x.is_changed(cached.tick, this_run))
+            };
+
+            entity.contains::<Dirty>()
+                || cached.shapes != shapes.len()
+                || shapes
+                    .iter()
+                    .any(|x| x.is_newer_than(cached.tick, this_run))
+                || changed(config)
+                || changed(entity.get_change_ticks::<RopeC>())
+                || changed(entity.get_change_ticks::<Triples>())
+                || changed(entity.get_change_ticks::<DocumentLinks>())
+                || changed(entity.get_change_ticks::<Prefixes>())
+        })
+        .collect();
+
+    let diagnostics = pull_diagnostics(world, &outdated);
+
+    // Changes made from now on have a newer tick than the cached diagnostics
+    let tick = world.increment_change_tick();
+    let shapes = shapes.len();
+    for (e, diagnostics) in outdated.into_iter().zip(diagnostics) {
+        if let Some(mut entity) = world.get_entity_mut(e) {
+            entity.insert(PulledDiagnostics {
+                diagnostics,
+                tick,
+                shapes,
+            });
+        }
+    }
+
+    entities
+        .iter()
+        .map(|e| {
+            world
+                .get::<PulledDiagnostics>(*e)
+                .map(|x| x.diagnostics.clone())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// Result id of a diagnostic report, equal diagnostics result in equal ids.
+pub fn result_id(diagnostics: &[Diagnostic]) -> String {
+    use std::hash::Hasher as _;
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    hasher.write(
+        serde_json::to_string(diagnostics)
+            .unwrap_or_default()
+            .as_bytes(),
+    );
+    format!("{:x}", hasher.finish())
 }
 
 #[derive(Resource)]
 pub struct DiagnosticPublisher {
     tx: mpsc::UnboundedSender<DiagnosticItem>,
     diagnostics: HashMap<lsp_types::Url, Vec<(Diagnostic, &'static str)>>,
+    levels: HashMap<String, DiagnosticLevel>,
+    push: bool,
+    refresh: Option<mpsc::UnboundedSender<()>>,
+    refresh_support: bool,
 }
 
 impl DiagnosticPublisher {
@@ -30,6 +185,10 @@ impl DiagnosticPublisher {
             Self {
                 tx,
                 diagnostics: HashMap::new(),
+                levels: HashMap::new(),
+                push: true,
+                refresh: None,
+                refresh_support: false,
             },
             rx,
         )
@@ -41,16 +200,100 @@ impl DiagnosticPublisher {
         diagnostics: Vec<Diagnostic>,
         reason: &'static str,
     ) -> Option<()> {
+        let diagnostics: Vec<_> = diagnostics
+            .into_iter()
+            .flat_map(|x| self.apply_level(reason, x))
+            .collect();
         let items = self.diagnostics.entry(params.uri.clone()).or_default();
         items.retain(|(_, r)| *r != reason);
         items.extend(diagnostics.into_iter().map(|x| (x, reason)));
-        let diagnostics: Vec<_> = items.iter().map(|(x, _)| x).cloned().collect();
-        let uri = params.uri.clone();
-        let version = Some(params.version);
+        self.send(params)
+    }
+
+    /// Replace all diagnostics of the document, whatever their reason.
+    pub fn publish_all(
+        &mut self,
+        params: &TextDocumentItem,
+        diagnostics: Vec<(Diagnostic, &'static str)>,
+    ) -> Option<()> {
+        let diagnostics: Vec<_> = diagnostics
+            .into_iter()
+            .flat_map(|(x, reason)| Some((self.apply_level(reason, x)?, reason)))
+            .collect();
+        self.diagnostics.insert(params.uri.clone(), diagnostics);
+        self.send(params)
+    }
+
+    fn send(&self, params: &TextDocumentItem) -> Option<()> {
+        if !self.push {
+            return Some(());
+        }
+        let diagnostics: Vec<_> = self
+            .diagnostics
+            .get(&params.uri)
+            .into_iter()
+            .flatten()
+            .map(|(x, _)| x)
+            .cloned()
+            .collect();
         let item = DiagnosticItem {
             diagnostics,
-            uri,
-            version,
+            uri: params.uri.clone(),
+            version: Some(params.version),
+        };
+        self.tx.unbounded_send(item).ok()
+    }
+
+    /// Override the severity of published diagnostics per reason, [`DiagnosticLevel::Off`]
+    /// drops them.
+    pub fn set_levels(&mut self, levels: HashMap<String, DiagnosticLevel>) {
+        self.levels = levels;
+    }
+
+    /// Enable or disable pushing diagnostics, clients that pull diagnostics don't need them.
+    pub fn set_push(&mut self, push: bool) {
+        self.push = push;
+    }
+
+    /// Receiver of the refresh requests sent by [`DiagnosticPublisher::refresh`].
+    pub fn refresh_receiver(&mut self) -> mpsc::UnboundedReceiver<()> {
+        let (tx, rx) = mpsc::unbounded();
+        self.refresh = Some(tx);
+        rx
+    }
+
+    /// Enable refresh requests, only when the client supports `workspace/diagnostic/refresh`.
+    pub fn set_refresh_support(&mut self, refresh_support: bool) {
+        self.refresh_support = refresh_support;
+    }
+
+    /// Ask a client that pulls diagnostics to pull them again, for example when a vocabulary or
+    /// shapes finished loading.
+    pub fn refresh(&self) -> Option<()> {
+        if self.push || !self.refresh_support {
+            return Some(());
+        }
+        self.refresh.as_ref()?.unbounded_send(()).ok()
+    }
+
+    /// Apply the configured severity for `reason`, returns nothing when the reason is turned off.
+    pub fn apply_level(&self, reason: &str, mut diagnostic: Diagnostic) -> Option<Diagnostic> {
+        if let Some(level) = self.levels.get(reason) {
+            diagnostic.severity = Some(level.severity()?);
+        }
+        Some(diagnostic)
+    }
+
+    /// Forget all diagnostics of the document, publishing an empty list to the client.
+    pub fn clear(&mut self, uri: &Url) -> Option<()> {
+        self.diagnostics.remove(uri);
+        if !self.push {
+            return Some(());
+        }
+        let item = DiagnosticItem {
+            diagnostics: Vec::new(),
+            uri: uri.clone(),
+            version: None,
         };
         self.tx.unbounded_send(item).ok()
     }
@@ -199,32 +442,60 @@ pub fn publish_diagnostics<L: Lang>(
 {
     for (token_errors, element_errors, params, rope, label) in &query {
         tracing::info!("Publish diagnostics for {}", label.0);
-        use std::iter::Iterator as _;
-        let token_iter = token_errors
-            .0
-            .iter()
-            .cloned()
-            .map(|x| Into::<SimpleDiagnostic>::into(x));
-        let turtle_iter = element_errors
-            .0
-            .iter()
-            .cloned()
-            .map(|x| Into::<SimpleDiagnostic>::into(x));
-
-        let diagnostics: Vec<_> = Iterator::chain(token_iter, turtle_iter)
-            .flat_map(|item| {
-                let (span, message) = (item.range, item.msg);
-                let start_position = offset_to_position(span.start, &rope.0)?;
-                let end_position = offset_to_position(span.end, &rope.0)?;
-                Some(Diagnostic {
-                    range: lsp_types::Range::new(start_position, end_position),
-                    message,
-                    severity: item.severity,
-                    ..Default::default()
-                })
-            })
-            .collect();
-
+        let diagnostics = syntax_diagnostics(token_errors, element_errors, rope);
         let _ = client.publish(&params.0, diagnostics, "syntax");
     }
 }
+
+pub fn pull_syntax_diagnostics<L: Lang>(
+    mut query: Query<(
+        &Errors<L::TokenError>,
+        &Errors<L::ElementError>,
+        &RopeC,
+        &mut DiagnosticRequest,
+    )>,
+) where
+    L::TokenError: 'static + Clone,
+    L::ElementError: 'static + Clone,
+{
+    for (token_errors, element_errors, rope, mut request) in &mut query {
+        let diagnostics = syntax_diagnostics(token_errors, element_errors, rope);
+        request.push(diagnostics, "syntax");
+    }
+}
+
+fn syntax_diagnostics<T, E>(
+    token_errors: &Errors<T>,
+    element_errors: &Errors<E>,
+    rope: &RopeC,
+) -> Vec<Diagnostic>
+where
+    T: Into<SimpleDiagnostic> + Clone,
+    E: Into<SimpleDiagnostic> + Clone,
+{
+    use std::iter::Iterator as _;
+    let token_iter = token_errors
+        .0
+        .iter()
+        .cloned()
+        .map(|x| Into::<SimpleDiagnostic>::into(x));
+    let turtle_iter = element_errors
+        .0
+        .iter()
+        .cloned()
+        .map(|x| Into::<SimpleDiagnostic>::into(x));
+
+    Iterator::chain(token_iter, turtle_iter)
+        .flat_map(|item| {
+            let (span, message) = (item.range, item.msg);
+            let start_position = offset_to_position(span.start, &rope.0)?;
+            let end_position = offset_to_position(span.end, &rope.0)?;
+            Some(Diagnostic {
+                range: lsp_types::Range::new(start_position, end_position),
+                message,
+                severity: item.severity,
+                ..Default::default()
+            })
+        })
+        .collect()
+}
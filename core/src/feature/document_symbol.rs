@@ -0,0 +1,49 @@
+use bevy_ecs::{component::Component, schedule::ScheduleLabel, world::World};
+use derive_more::{AsMut, AsRef, Deref, DerefMut};
+use lsp_types::{DocumentSymbol, SymbolKind};
+use ropey::Rope;
+
+use crate::util::range_to_range;
+
+/// [`Component`] indicating that the current document is currently handling a DocumentSymbol
+/// request.
+#[derive(Component, AsRef, Deref, AsMut, DerefMut, Debug, Default)]
+pub struct DocumentSymbolRequest(pub Vec<DocumentSymbol>);
+
+/// [`ScheduleLabel`] related to the DocumentSymbol schedule, this is language specific
+#[derive(ScheduleLabel, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Label;
+
+pub fn setup_schedule(world: &mut World) {
+    let symbols = bevy_ecs::schedule::Schedule::new(Label);
+    world.add_schedule(symbols);
+}
+
+/// Creates a [`DocumentSymbol`] without children, spanning `range` and selecting `selection`.
+pub fn document_symbol(
+    name: impl Into<String>,
+    kind: SymbolKind,
+    range: &std::ops::Range<usize>,
+    selection: &std::ops::Range<usize>,
+    rope: &Rope,
+) -> Option<DocumentSymbol> {
+    let name = name.into();
+    // Editors refuse symbols without a name
+    let name = if name.is_empty() {
+        String::from("<empty>")
+    } else {
+        name
+    };
+
+    #[allow(deprecated)]
+    Some(DocumentSymbol {
+        name,
+        detail: None,
+        kind,
+        tags: None,
+        deprecated: None,
+        range: range_to_range(range, rope)?,
+        selection_range: range_to_range(selection, rope)?,
+        children: None,
+    })
+}
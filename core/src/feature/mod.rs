@@ -10,7 +10,7 @@ pub use parse::Label as ParseLabel;
 pub mod rename;
 pub use rename::{PrepareRename as PrepareRenameLabel, Rename as RenameLabel};
 pub mod diagnostics;
-pub use diagnostics::Label as DiagnosticsLabel;
+pub use diagnostics::{Label as DiagnosticsLabel, Pull as PullDiagnosticsLabel};
 pub mod save;
 pub use save::Label as SaveLabel;
 pub mod inlay;
@@ -25,3 +25,11 @@ pub mod goto_definition;
 pub use goto_definition::Label as GotoDefinitionLabel;
 pub mod goto_type;
 pub use goto_type::Label as GotoTypeLabel;
+pub mod code_action;
+pub use code_action::Label as CodeActionLabel;
+pub mod document_symbol;
+pub use document_symbol::Label as DocumentSymbolLabel;
+pub mod workspace_symbol;
+pub use workspace_symbol::Label as WorkspaceSymbolLabel;
+pub mod type_hierarchy;
+pub use type_hierarchy::{Prepare as PrepareTypeHierarchyLabel, Resolve as TypeHierarchyLabel};
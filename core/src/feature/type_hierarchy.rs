@@ -0,0 +1,150 @@
+use bevy_ecs::{
+    component::Component,
+    schedule::{IntoSystemConfigs, Schedule, ScheduleLabel},
+    system::Resource,
+    world::World,
+};
+use lsp_types::TypeHierarchyItem;
+
+pub use crate::util::token::get_current_token;
+
+/// [`Component`] indicating that the current document is currently handling a
+/// PrepareTypeHierarchy request.
+#[derive(Component, Debug, Default)]
+pub struct PrepareTypeHierarchyRequest(pub Vec<TypeHierarchyItem>);
+
+/// Direction of a [`TypeHierarchyRequest`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TypeHierarchyDirection {
+    Supertypes,
+    Subtypes,
+}
+
+/// [`Resource`] indicating that the server is currently resolving the super or sub types of a
+/// previously prepared [`TypeHierarchyItem`].
+#[derive(Resource, Debug)]
+pub struct TypeHierarchyRequest {
+    pub item: TypeHierarchyItem,
+    pub direction: TypeHierarchyDirection,
+    pub items: Vec<TypeHierarchyItem>,
+}
+
+impl TypeHierarchyRequest {
+    pub fn new(item: TypeHierarchyItem, direction: TypeHierarchyDirection) -> Self {
+        Self {
+            item,
+            direction,
+            items: Vec::new(),
+        }
+    }
+}
+
+/// [`ScheduleLabel`] related to the PrepareTypeHierarchy schedule
+#[derive(ScheduleLabel, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Prepare;
+
+/// [`ScheduleLabel`] related to the TypeHierarchy schedule, resolving supertypes and subtypes
+#[derive(ScheduleLabel, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Resolve;
+
+pub fn setup_schedules(world: &mut World) {
+    let mut prepare = Schedule::new(Prepare);
+    prepare.add_systems((
+        get_current_token,
+        system::prepare_type_hierarchy.after(get_current_token),
+    ));
+    world.add_schedule(prepare);
+
+    let mut resolve = Schedule::new(Resolve);
+    resolve.add_systems(system::resolve_type_hierarchy);
+    world.add_schedule(resolve);
+}
+
+mod system {
+    use bevy_ecs::prelude::*;
+    use lsp_types::{SymbolKind, TypeHierarchyItem};
+
+    use super::{PrepareTypeHierarchyRequest, TypeHierarchyDirection, TypeHierarchyRequest};
+    use crate::{prelude::*, systems::DefinedClasses, util::token_to_location};
+
+    /// Creates a [`TypeHierarchyItem`] for `iri`, located at its [`DefinedClass`](crate::systems::DefinedClass).
+    fn type_hierarchy_item(
+        iri: &str,
+        project: &Query<(&Wrapped<DefinedClasses>, &RopeC, &Label, Option<&Prefixes>)>,
+    ) -> Option<TypeHierarchyItem> {
+        for (classes, rope, label, prefixes) in project {
+            let Some(class) = classes.iter().find(|x| x.term.as_str() == iri) else {
+                continue;
+            };
+            let Some(location) = token_to_location(&class.location, label, &rope.0) else {
+                continue;
+            };
+
+            let name = prefixes
+                .and_then(|x| x.shorten(iri))
+                .unwrap_or_else(|| iri.to_string());
+
+            return Some(TypeHierarchyItem {
+                name,
+                kind: SymbolKind::CLASS,
+                tags: None,
+                detail: Some(iri.to_string()),
+                uri: location.uri,
+                range: location.range,
+                selection_range: location.range,
+                data: Some(serde_json::Value::String(iri.to_string())),
+            });
+        }
+
+        None
+    }
+
+    pub fn prepare_type_hierarchy(
+        mut query: Query<(&TokenComponent, &Prefixes, &mut PrepareTypeHierarchyRequest)>,
+        project: Query<(&Wrapped<DefinedClasses>, &RopeC, &Label, Option<&Prefixes>)>,
+        hierarchy: Res<TypeHierarchy<'static>>,
+    ) {
+        for (token, prefixes, mut req) in &mut query {
+            let Some(iri) = prefixes
+                .expand(&token.token)
+                .or_else(|| prefixes.expand_json(&token.token))
+            else {
+                continue;
+            };
+
+            if hierarchy.get_id_ref(&iri).is_none() {
+                tracing::debug!("{} is not part of the type hierarchy", iri);
+                continue;
+            }
+
+            req.0.extend(type_hierarchy_item(&iri, &project));
+        }
+    }
+
+    pub fn resolve_type_hierarchy(
+        project: Query<(&Wrapped<DefinedClasses>, &RopeC, &Label, Option<&Prefixes>)>,
+        hierarchy: Res<TypeHierarchy<'static>>,
+        mut request: ResMut<TypeHierarchyRequest>,
+    ) {
+        let iri = match (&request.item.data, &request.item.detail) {
+            (Some(serde_json::Value::String(iri)), _) => iri.clone(),
+            (_, Some(iri)) => iri.clone(),
+            _ => return,
+        };
+
+        let Some(id) = hierarchy.get_id_ref(&iri) else {
+            return;
+        };
+
+        let related: Vec<_> = match request.direction {
+            TypeHierarchyDirection::Supertypes => hierarchy.direct_supertypes(id).collect(),
+            TypeHierarchyDirection::Subtypes => hierarchy.direct_subtypes(id).collect(),
+        };
+
+        let items: Vec<_> = related
+            .into_iter()
+            .flat_map(|id| type_hierarchy_item(&hierarchy.type_name(id), &project))
+            .collect();
+        request.items.extend(items);
+    }
+}
@@ -0,0 +1,148 @@
+use bevy_ecs::{
+    schedule::{Schedule, ScheduleLabel},
+    system::Resource,
+    world::World,
+};
+use lsp_types::SymbolInformation;
+
+/// [`Resource`] indicating that the server is currently handling a WorkspaceSymbol request.
+///
+/// Unlike most requests, this request is not bound to a single document.
+#[derive(Resource, Debug, Default)]
+pub struct WorkspaceSymbolRequest {
+    pub query: String,
+    pub symbols: Vec<SymbolInformation>,
+}
+
+impl WorkspaceSymbolRequest {
+    pub fn new(query: String) -> Self {
+        Self {
+            query,
+            symbols: Vec::new(),
+        }
+    }
+}
+
+/// [`ScheduleLabel`] related to the WorkspaceSymbol schedule
+#[derive(ScheduleLabel, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Label;
+
+pub fn setup_schedule(world: &mut World) {
+    let mut symbols = Schedule::new(Label);
+    symbols.add_systems(system::workspace_symbols);
+    world.add_schedule(symbols);
+}
+
+mod system {
+    use std::collections::HashMap;
+
+    use bevy_ecs::prelude::*;
+    use lsp_types::{SymbolInformation, SymbolKind};
+    use sophia_api::term::{Term, TermKind};
+
+    use super::WorkspaceSymbolRequest;
+    use crate::{
+        prelude::*,
+        systems::{OntologyExtractor, Snippet},
+        util::{
+            ns::{rdf, rdfs},
+            token_to_location,
+        },
+    };
+
+    /// Maximum number of symbols in a response, clients show only the first ones anyway.
+    const MAX_SYMBOLS: usize = 256;
+
+    /// What is known about a subject of a document, collected in one pass over its triples.
+    struct Subject<'a> {
+        term: &'a MyTerm<'static>,
+        label: Option<&'a str>,
+        kind: SymbolKind,
+    }
+
+    /// Index the iri subjects of `triples` in document order, with their `rdfs:label` and kind.
+    fn index_subjects<'a>(triples: &'a Triples, extractor: &OntologyExtractor) -> Vec<Subject<'a>> {
+        let mut subjects: Vec<Subject<'a>> = Vec::new();
+        let mut indices: HashMap<&'a str, usize> = HashMap::new();
+
+        for triple in triples.iter() {
+            if triple.subject.kind() != TermKind::Iri {
+                continue;
+            }
+
+            let idx = *indices.entry(triple.subject.as_str()).or_insert_with(|| {
+                subjects.push(Subject {
+                    term: &triple.subject,
+                    label: None,
+                    kind: SymbolKind::OBJECT,
+                });
+                subjects.len() - 1
+            });
+            let subject = &mut subjects[idx];
+
+            if Term::eq(&triple.predicate, rdfs::label) {
+                subject.label.get_or_insert(triple.object.as_str());
+            } else if Term::eq(&triple.predicate, rdf::type_) {
+                let object = triple.object.as_str();
+                if extractor.classes().iter().any(|x| x.as_str() == object) {
+                    subject.kind = SymbolKind::CLASS;
+                } else if subject.kind != SymbolKind::CLASS
+                    && extractor.properties().iter().any(|x| x.as_str() == object)
+                {
+                    subject.kind = SymbolKind::PROPERTY;
+                }
+            }
+        }
+
+        subjects
+    }
+
+    /// Searches the subjects of every document with [`Triples`], matching the full iri, the
+    /// prefixed iri and the `rdfs:label`. At most [`MAX_SYMBOLS`] symbols are returned.
+    ///
+    /// [`Snippet`]s are skipped, their labels are not documents known to the client.
+    pub fn workspace_symbols(
+        query: Query<(&Triples, &Label, &RopeC, Option<&Prefixes>), Without<Snippet>>,
+        extractor: Res<OntologyExtractor>,
+        mut request: ResMut<WorkspaceSymbolRequest>,
+    ) {
+        let needle = request.query.to_lowercase();
+        let matches = |st: &str| st.to_lowercase().contains(&needle);
+
+        let mut symbols = Vec::new();
+        'documents: for (triples, label, rope, prefixes) in &query {
+            for subject in index_subjects(triples, &extractor) {
+                let iri = subject.term.as_str();
+                let short = prefixes.and_then(|x| x.shorten(iri));
+
+                let found = matches(iri)
+                    || short.as_ref().map(|x| matches(x)).unwrap_or(false)
+                    || subject.label.map(|x| matches(x)).unwrap_or(false);
+                if !found {
+                    continue;
+                }
+
+                let Some(location) = token_to_location(&subject.term.span, label, &rope.0) else {
+                    continue;
+                };
+
+                #[allow(deprecated)]
+                symbols.push(SymbolInformation {
+                    name: short.unwrap_or_else(|| iri.to_string()),
+                    kind: subject.kind,
+                    tags: None,
+                    deprecated: None,
+                    location,
+                    container_name: Some(label.0.to_string()),
+                });
+
+                if symbols.len() >= MAX_SYMBOLS {
+                    break 'documents;
+                }
+            }
+        }
+
+        tracing::info!("Found {} workspace symbols for {}", symbols.len(), needle);
+        request.symbols.extend(symbols);
+    }
+}
@@ -13,7 +13,7 @@
 //! respond to requests.
 //!
 //! Language specific implementations that handle things like tokenizing and parsing are
-//! implemented in separate crates. The binary currently supports [Turtle](../lang_turtle/index.html), [JSON-LD](../lang_jsonld/index.html) and [SPARQL](../lang_sparql/index.html).
+//! implemented in separate crates. The binary currently supports [Turtle](../lang_turtle/index.html), [TriG](../lang_trig/index.html), [N-Triples and N-Quads](../lang_ntriples/index.html), [N3](../lang_n3/index.html), [RDF/XML](../lang_rdfxml/index.html), [ShEx](../lang_shex/index.html), [JSON-LD](../lang_jsonld/index.html) and [SPARQL](../lang_sparql/index.html).
 //! The goal is that each language at least generates [`Tokens`], [`Triples`] and
 //! [`Prefixes`].
 //! These components are then used to derive properties for autcompletion but also derive
@@ -82,7 +82,9 @@
 
 use bevy_ecs::{prelude::*, schedule::ScheduleLabel};
 use prelude::SemanticTokensDict;
-use systems::{init_onology_extractor, OntologyExtractor};
+use systems::{
+    index_workspace_folders, init_onology_extractor, load_configured_shapes, OntologyExtractor,
+};
 
 use crate::prelude::*;
 
@@ -133,6 +135,10 @@ pub fn setup_schedule_labels<C: Client + Resource>(world: &mut World) {
     inlay::setup_schedule(world);
     goto_definition::setup_schedule(world);
     goto_type::setup_schedule(world);
+    code_action::setup_schedule(world);
+    document_symbol::setup_schedule(world);
+    workspace_symbol::setup_schedule(world);
+    type_hierarchy::setup_schedules(world);
 
     semantic::setup_world(world);
 
@@ -141,6 +147,10 @@ pub fn setup_schedule_labels<C: Client + Resource>(world: &mut World) {
     let mut schedule = Schedule::new(Startup);
     schedule.add_systems(init_onology_extractor);
     world.add_schedule(schedule);
+
+    let mut schedule = Schedule::new(WorkspaceIndex);
+    schedule.add_systems((index_workspace_folders::<C>, load_configured_shapes::<C>));
+    world.add_schedule(schedule);
 }
 
 /// Event triggers when a document is opened
@@ -178,7 +188,8 @@ pub fn setup_schedule_labels<C: Client + Resource>(world: &mut World) {
 ///                 .insert((TurtleLang, DynLang(Box::new(TurtleHelper))));
 ///             return;
 ///         }
-///         _ => {}
+///         Some(_) => return,
+///         None => {}
 ///     }
 ///     if trigger.event().url.as_str().ends_with(".ttl") {
 ///         commands
@@ -205,3 +216,11 @@ pub struct Tasks;
 
 #[derive(ScheduleLabel, Clone, Eq, PartialEq, Debug, Hash)]
 pub struct Startup;
+
+/// [`ScheduleLabel`] related to the WorkspaceIndex schedule
+/// This schedule runs after startup and whenever the workspace folders or the configuration
+/// change.
+///
+/// For example [`systems::index_workspace_folders`] reads all documents in new workspace folders.
+#[derive(ScheduleLabel, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct WorkspaceIndex;
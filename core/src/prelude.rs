@@ -5,14 +5,23 @@ pub use crate::{
     components::*,
     feature::{
         self,
+        code_action::CodeActionRequest,
         completion::{CompletionRequest, SimpleCompletion},
-        diagnostics::{DiagnosticItem, DiagnosticPublisher, DiagnosticSender, SimpleDiagnostic},
+        diagnostics::{
+            DiagnosticItem, DiagnosticPublisher, DiagnosticRequest, DiagnosticSender,
+            SimpleDiagnostic, SkipValidation,
+        },
+        document_symbol::DocumentSymbolRequest,
         format::FormatRequest,
         hover::HoverRequest,
         inlay::InlayRequest,
         rename::PrepareRenameRequest,
         rename::RenameEdits,
         semantic::{HighlightRequest, SemanticTokensDict},
+        type_hierarchy::{
+            PrepareTypeHierarchyRequest, TypeHierarchyDirection, TypeHierarchyRequest,
+        },
+        workspace_symbol::WorkspaceSymbolRequest,
         *,
     },
     lang::{Lang, LangHelper, TokenTrait},
@@ -20,8 +29,8 @@ pub use crate::{
     systems::prefix::{Prefix, Prefixes},
     systems::spawn_or_insert,
     util::{
-        fs::*, lsp_range_to_range, offset_to_position, offsets_to_range, position_to_offset,
-        range_to_range, spanned, token::*, triple::*, Spanned,
+        apply_change, fs::*, lsp_range_to_range, offset_to_position, offsets_to_range,
+        position_to_offset, range_to_range, spanned, token::*, triple::*, Spanned,
     },
     CreateEvent,
 };
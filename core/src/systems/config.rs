@@ -0,0 +1,25 @@
+use bevy_ecs::prelude::*;
+
+use crate::{prelude::*, WorkspaceIndex};
+
+/// Replace the [`Config`] of the server and re-run the schedules that depend on it.
+///
+/// All open documents are parsed again, so diagnostics are published with the new severities.
+pub fn apply_config(config: Config) -> impl FnOnce(&mut World) + Send + Sync + 'static {
+    move |world: &mut World| {
+        if let Some(mut publisher) = world.get_resource_mut::<DiagnosticPublisher>() {
+            publisher.set_levels(config.diagnostics.clone());
+        }
+        world.resource_mut::<ServerConfig>().config = config;
+        world.run_schedule(WorkspaceIndex);
+
+        let mut query = world.query_filtered::<&mut Source, With<Open>>();
+        for mut source in query.iter_mut(world) {
+            source.set_changed();
+        }
+
+        world.run_schedule(ParseLabel);
+        world.run_schedule(DiagnosticsLabel);
+        world.run_schedule(SaveLabel);
+    }
+}
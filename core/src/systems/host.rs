@@ -0,0 +1,529 @@
+//! Host documents embed documents of the supported languages: JSON-LD in the
+//! `<script type="application/ld+json">` blocks of HTML pages and Turtle, SPARQL or JSON-LD in the
+//! fenced code blocks of Markdown files.
+//!
+//! Each embedded region becomes a [`Snippet`] entity that goes through the regular pipeline of
+//! its language. Positions of a snippet are relative to the start of the region,
+//! [`Snippet::to_host`] and [`Snippet::to_snippet`] map them to and from the host document.
+use std::{collections::HashMap, ops::Range};
+
+use bevy_ecs::prelude::*;
+use lsp_types::{Diagnostic, Position, SemanticToken, TextDocumentItem, Url};
+use ropey::Rope;
+use tracing::debug;
+
+use crate::{feature::diagnostics::Pull, prelude::*, util::range_to_range};
+
+/// A region of a host document that contains a document of another language.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region {
+    pub language_id: &'static str,
+    /// Byte range of the embedded document in the host document
+    pub range: Range<usize>,
+}
+
+/// [`Component`] of a host document with the entities of its embedded snippets, in document
+/// order.
+#[derive(Component, Debug, Default, Clone)]
+pub struct Snippets(pub Vec<Entity>);
+
+/// [`Component`] of a document embedded in the `host` document.
+#[derive(Component, Debug, Clone)]
+pub struct Snippet {
+    pub host: Entity,
+    /// Range of the snippet in the host document
+    pub range: lsp_types::Range,
+}
+
+impl Snippet {
+    /// Position in the host document of a position in the snippet.
+    pub fn to_host(&self, pos: Position) -> Position {
+        let start = self.range.start;
+        if pos.line == 0 {
+            Position::new(start.line, start.character + pos.character)
+        } else {
+            Position::new(start.line + pos.line, pos.character)
+        }
+    }
+
+    pub fn range_to_host(&self, range: lsp_types::Range) -> lsp_types::Range {
+        lsp_types::Range::new(self.to_host(range.start), self.to_host(range.end))
+    }
+
+    /// Position in the snippet of a position in the host document, if the snippet contains it.
+    pub fn to_snippet(&self, pos: Position) -> Option<Position> {
+        let start = self.range.start;
+        if pos < start || pos > self.range.end {
+            return None;
+        }
+
+        if pos.line == start.line {
+            Some(Position::new(0, pos.character - start.character))
+        } else {
+            Some(Position::new(pos.line - start.line, pos.character))
+        }
+    }
+}
+
+/// Find the embedded regions of a host document, based on its language id or the extension of
+/// `url`. Returns `None` for documents that are not a host document.
+pub fn host_regions(url: &Url, language_id: Option<&str>, source: &str) -> Option<Vec<Region>> {
+    let ext = url.path().rsplit_once('.').map(|x| x.1);
+    match (language_id, ext) {
+        (Some("html"), _) | (_, Some("html" | "htm")) => Some(html_regions(source)),
+        (Some("markdown"), _) | (_, Some("md" | "markdown")) => Some(markdown_regions(source)),
+        _ => None,
+    }
+}
+
+fn script_language(tag: &str) -> Option<&'static str> {
+    let ty = tag.match_indices("type").find_map(|(i, _)| {
+        if !tag[..i].ends_with(char::is_whitespace) {
+            return None;
+        }
+        tag[i + 4..].trim_start().strip_prefix('=')
+    })?;
+
+    let ty = ty.trim_start().trim_start_matches(['"', '\'']);
+    let ty = ty
+        .split(|c: char| c == '"' || c == '\'' || c == ';' || c == '>' || c.is_whitespace())
+        .next()?;
+
+    match ty {
+        "application/ld+json" => Some("jsonld"),
+        "text/turtle" => Some("turtle"),
+        _ => None,
+    }
+}
+
+/// Find the `<script>` elements with JSON-LD (or Turtle) content.
+pub fn html_regions(source: &str) -> Vec<Region> {
+    // Lowercasing ascii keeps the byte offsets intact
+    let lower = source.to_ascii_lowercase();
+    let mut out = Vec::new();
+
+    let mut at = 0;
+    while let Some(open) = lower[at..].find("<script").map(|x| x + at) {
+        let Some(start) = lower[open..].find('>').map(|x| x + open + 1) else {
+            break;
+        };
+        let tag = &lower[open..start];
+        if tag.ends_with("/>") {
+            at = start;
+            continue;
+        }
+
+        let end = lower[start..]
+            .find("</script")
+            .map(|x| x + start)
+            .unwrap_or(source.len());
+
+        if let Some(language_id) = script_language(tag) {
+            out.push(Region {
+                language_id,
+                range: start..end,
+            });
+        }
+        at = end;
+    }
+
+    out
+}
+
+fn fence_language(info: &str) -> Option<&'static str> {
+    let lang = info.split_whitespace().next()?.to_ascii_lowercase();
+    match lang.as_str() {
+        "turtle" | "ttl" => Some("turtle"),
+        "sparql" | "rq" => Some("sparql"),
+        "jsonld" | "json-ld" => Some("jsonld"),
+        _ => None,
+    }
+}
+
+/// Find the fenced code blocks (with backticks or tildes) of a Markdown document with a
+/// supported language in their info string.
+pub fn markdown_regions(source: &str) -> Vec<Region> {
+    let mut out = Vec::new();
+    // fence character, fence length, language and start of the content
+    let mut open: Option<(char, usize, Option<&'static str>, usize)> = None;
+
+    let mut offset = 0;
+    for line in source.split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+
+        let content = line.trim_end().trim_start_matches(' ');
+        if line.len() - line.trim_start_matches(' ').len() > 3 {
+            continue;
+        }
+
+        match open {
+            Some((fence, length, language_id, start)) => {
+                let count = content.chars().take_while(|c| *c == fence).count();
+                if count >= length && content[count..].trim().is_empty() {
+                    if let Some(language_id) = language_id {
+                        out.push(Region {
+                            language_id,
+                            range: start..line_start,
+                        });
+                    }
+                    open = None;
+                }
+            }
+            None => {
+                let fence = match content.chars().next() {
+                    Some(c @ ('`' | '~')) => c,
+                    _ => continue,
+                };
+                let length = content.chars().take_while(|c| *c == fence).count();
+                if length < 3 {
+                    continue;
+                }
+                open = Some((fence, length, fence_language(&content[length..]), offset));
+            }
+        }
+    }
+
+    // Unclosed blocks run until the end of the document
+    if let Some((_, _, Some(language_id), start)) = open {
+        out.push(Region {
+            language_id,
+            range: start..source.len(),
+        });
+    }
+
+    out
+}
+
+/// Spawn, update or despawn the [`Snippet`]s of a host document after its [`Source`] changed,
+/// parses them and publishes their diagnostics on the host document.
+///
+/// Snippets are labeled with the url of the host document, with a fragment derived from their
+/// language and index.
+pub fn sync_snippets(entity: Entity) -> impl FnOnce(&mut World) + Send + Sync + 'static {
+    move |world: &mut World| {
+        let Some(entity_ref) = world.get_entity(entity) else {
+            return;
+        };
+        let (Some(source), Some(label)) = (entity_ref.get::<Source>(), entity_ref.get::<Label>())
+        else {
+            return;
+        };
+        let language_id = entity_ref
+            .get::<Wrapped<TextDocumentItem>>()
+            .map(|x| x.language_id.as_str());
+        let Some(regions) = host_regions(&label.0, language_id, &source.0) else {
+            return;
+        };
+
+        let source = source.0.clone();
+        let url = label.0.clone();
+        let rope = Rope::from_str(&source);
+        let old = entity_ref
+            .get::<Snippets>()
+            .map(|x| x.0.clone())
+            .unwrap_or_default();
+
+        let mut snippets = Vec::with_capacity(regions.len());
+        for (i, region) in regions.into_iter().enumerate() {
+            let mut url = url.clone();
+            url.set_fragment(Some(&format!("{}-{}", region.language_id, i)));
+
+            let chars = rope.byte_to_char(region.range.start)..rope.byte_to_char(region.range.end);
+            let Some(range) = range_to_range(&chars, &rope) else {
+                continue;
+            };
+            let snippet = Snippet {
+                host: entity,
+                range,
+            };
+            let text = &source[region.range];
+
+            let existing = old
+                .iter()
+                .copied()
+                .find(|e| world.get::<Label>(*e).is_some_and(|l| l.0 == url));
+
+            let e = match existing {
+                Some(e) if world.get::<Source>(e).is_some_and(|s| s.0 == text) => {
+                    world.entity_mut(e).insert(snippet);
+                    e
+                }
+                Some(e) => {
+                    world.entity_mut(e).insert((
+                        Source(text.to_string()),
+                        RopeC(Rope::from_str(text)),
+                        snippet,
+                    ));
+                    e
+                }
+                None => {
+                    debug!("Spawning snippet {}", url);
+                    let e = world
+                        .spawn((
+                            Source(text.to_string()),
+                            RopeC(Rope::from_str(text)),
+                            Label(url.clone()),
+                            DocumentLinks(Vec::new()),
+                            Types(HashMap::new()),
+                            Open,
+                            snippet,
+                        ))
+                        .id();
+                    world.trigger_targets(
+                        CreateEvent {
+                            url,
+                            language_id: Some(region.language_id.to_string()),
+                        },
+                        e,
+                    );
+                    e
+                }
+            };
+            snippets.push(e);
+        }
+
+        for e in old {
+            if !snippets.contains(&e) {
+                world.despawn(e);
+            }
+        }
+        world.entity_mut(entity).insert(Snippets(snippets.clone()));
+
+        world.flush();
+        world.run_schedule(ParseLabel);
+
+        // Validating against shapes on every change is too expensive, that happens on pulls
+        for e in &snippets {
+            world
+                .entity_mut(*e)
+                .insert((DiagnosticRequest::default(), SkipValidation));
+        }
+        world.run_schedule(Pull);
+        let diagnostics = take_snippet_diagnostics(world, entity);
+        for e in &snippets {
+            world.entity_mut(*e).remove::<SkipValidation>();
+        }
+
+        let Some(item) = world
+            .get::<Wrapped<TextDocumentItem>>(entity)
+            .map(|x| x.0.clone())
+        else {
+            return;
+        };
+        if let Some(mut publisher) = world.get_resource_mut::<DiagnosticPublisher>() {
+            let _ = publisher.publish_all(&item, diagnostics);
+        }
+    }
+}
+
+/// Sync the snippets of all host documents again, for example after a vocabulary they use
+/// finished loading.
+pub fn sync_all_snippets(world: &mut World) {
+    let hosts: Vec<Entity> = world
+        .query_filtered::<Entity, With<Snippets>>()
+        .iter(world)
+        .collect();
+    for host in hosts {
+        sync_snippets(host)(world);
+    }
+}
+
+/// The snippet of the `host` document that contains `pos`, together with `pos` relative to that
+/// snippet.
+pub fn snippet_at(
+    world: &World,
+    host: Entity,
+    pos: Position,
+) -> Option<(Entity, Snippet, Position)> {
+    world.get::<Snippets>(host)?.0.iter().find_map(|e| {
+        let snippet = world.get::<Snippet>(*e)?;
+        let pos = snippet.to_snippet(pos)?;
+        Some((*e, snippet.clone(), pos))
+    })
+}
+
+/// Take the [`DiagnosticRequest`]s of the snippets of `host`, mapped to the host document.
+///
+/// Severities are not applied yet.
+pub(crate) fn take_snippet_diagnostics(
+    world: &mut World,
+    host: Entity,
+) -> Vec<(Diagnostic, &'static str)> {
+    let Some(snippets) = world.get::<Snippets>(host).cloned() else {
+        return Vec::new();
+    };
+
+    let mut out = Vec::new();
+    for e in snippets.0 {
+        let Some(snippet) = world.get::<Snippet>(e).cloned() else {
+            continue;
+        };
+        let request = world
+            .get_entity_mut(e)
+            .and_then(|mut entity| entity.take::<DiagnosticRequest>())
+            .unwrap_or_default();
+
+        out.extend(request.0.into_iter().map(|(mut diagnostic, reason)| {
+            diagnostic.range = snippet.range_to_host(diagnostic.range);
+            (diagnostic, reason)
+        }));
+    }
+    out
+}
+
+/// Semantic tokens of the snippets of `host`, delta encoded relative to the host document.
+///
+/// Returns `None` when the document is not a host document.
+pub fn snippet_semantic_tokens(world: &mut World, host: Entity) -> Option<Vec<SemanticToken>> {
+    let snippets = world.get::<Snippets>(host)?.0.clone();
+    for e in &snippets {
+        world.entity_mut(*e).insert(HighlightRequest(vec![]));
+    }
+    world.run_schedule(SemanticLabel);
+
+    let mut tokens = Vec::new();
+    for e in snippets {
+        let Some(snippet) = world.get::<Snippet>(e).cloned() else {
+            continue;
+        };
+        let Some(request) = world.entity_mut(e).take::<HighlightRequest>() else {
+            continue;
+        };
+
+        let mut pos = Position::new(0, 0);
+        for token in request.0 {
+            pos = if token.delta_line == 0 {
+                Position::new(pos.line, pos.character + token.delta_start)
+            } else {
+                Position::new(pos.line + token.delta_line, token.delta_start)
+            };
+            tokens.push((snippet.to_host(pos), token));
+        }
+    }
+
+    let mut pre = Position::new(0, 0);
+    let out = tokens
+        .into_iter()
+        .map(|(pos, token)| {
+            let delta_line = pos.line - pre.line;
+            let delta_start = if delta_line == 0 {
+                pos.character - pre.character
+            } else {
+                pos.character
+            };
+            pre = pos;
+            SemanticToken {
+                delta_line,
+                delta_start,
+                ..token
+            }
+        })
+        .collect();
+
+    Some(out)
+}
+
+/// Despawn the snippets of a host document.
+pub(crate) fn despawn_snippets(world: &mut World, host: Entity) {
+    let Some(snippets) = world.get::<Snippets>(host).cloned() else {
+        return;
+    };
+    for e in snippets.0 {
+        world.despawn(e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn regions<'a>(source: &'a str, regions: &[Region]) -> Vec<(&'static str, &'a str)> {
+        regions
+            .iter()
+            .map(|x| (x.language_id, &source[x.range.clone()]))
+            .collect()
+    }
+
+    #[test]
+    fn html_script_regions() {
+        let source = r#"<html><head>
+<script src="app.js"></script>
+<SCRIPT id="types" type = "application/ld+json">{"@id": "ex:me"}</SCRIPT>
+<script type='text/turtle'>
+<a> <b> <c>.
+</script>
+<script type="application/json">{}</script>
+<script type="application/ld+json">{"@id": "ex:you"}"#;
+
+        assert_eq!(
+            regions(source, &html_regions(source)),
+            vec![
+                ("jsonld", r#"{"@id": "ex:me"}"#),
+                ("turtle", "\n<a> <b> <c>.\n"),
+                ("jsonld", r#"{"@id": "ex:you"}"#),
+            ]
+        );
+    }
+
+    #[test]
+    fn markdown_fence_regions() {
+        let source = "# Title
+```turtle
+<a> <b> <c>.
+```
+
+```rust
+let x = 5;
+```
+
+~~~~ SPARQL {.query}
+SELECT * WHERE { ?s ?p ?o }
+~~~
+~~~~
+   ```json-ld
+{}";
+
+        assert_eq!(
+            regions(source, &markdown_regions(source)),
+            vec![
+                ("turtle", "<a> <b> <c>.\n"),
+                ("sparql", "SELECT * WHERE { ?s ?p ?o }\n~~~\n"),
+                ("jsonld", "{}"),
+            ]
+        );
+    }
+
+    #[test]
+    fn host_documents() {
+        let url = Url::parse("file:///docs/index.md").unwrap();
+        assert!(host_regions(&url, None, "").is_some());
+
+        let url = Url::parse("file:///docs/page").unwrap();
+        assert!(host_regions(&url, Some("html"), "").is_some());
+        assert!(host_regions(&url, Some("markdown"), "").is_some());
+        assert!(host_regions(&url, None, "").is_none());
+    }
+
+    #[test]
+    fn snippet_positions() {
+        let snippet = Snippet {
+            host: Entity::PLACEHOLDER,
+            range: lsp_types::Range::new(Position::new(2, 10), Position::new(5, 3)),
+        };
+
+        assert_eq!(snippet.to_host(Position::new(0, 2)), Position::new(2, 12));
+        assert_eq!(snippet.to_host(Position::new(1, 2)), Position::new(3, 2));
+
+        assert_eq!(
+            snippet.to_snippet(Position::new(2, 12)),
+            Some(Position::new(0, 2))
+        );
+        assert_eq!(
+            snippet.to_snippet(Position::new(4, 0)),
+            Some(Position::new(2, 0))
+        );
+        assert_eq!(snippet.to_snippet(Position::new(2, 9)), None);
+        assert_eq!(snippet.to_snippet(Position::new(5, 4)), None);
+    }
+}
@@ -9,14 +9,17 @@ pub fn derive_prefix_links(
     mut commands: Commands,
     // helper: Res<LovHelper>,
     fs: Res<Fs>,
+    config: Res<ServerConfig>,
 ) {
     const SOURCE: &'static str = "prefix import";
     for (e, prefixes, mut links) in &mut query {
         let mut new_links = Vec::new();
         for u in prefixes.0.iter() {
-            let url: Url =
-                fs.0.lov_url(u.url.as_str(), &u.prefix)
-                    .unwrap_or(u.url.clone());
+            let url: Url = config
+                .config
+                .vocabulary(u.url.as_str())
+                .or_else(|| fs.0.lov_url(u.url.as_str(), &u.prefix))
+                .unwrap_or(u.url.clone());
             tracing::debug!(
                 "Mapping prefix {}: {} to {}",
                 u.prefix,
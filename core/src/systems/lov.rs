@@ -11,6 +11,7 @@ use sophia_api::{
 };
 use tracing::{debug, error, info, instrument, span};
 
+use super::{language_id_from_content, sync_all_snippets};
 use crate::{
     prelude::*,
     util::{
@@ -68,12 +69,15 @@ async fn extract_file_url(prefix: &str, client: &impl Client) -> Option<String>
     }
 }
 
+/// Open the documents imported with `owl:imports`, remote imports are not fetched in offline
+/// mode.
 pub fn open_imports<C: Client + Resource>(
     query: Query<(&Triples, &RopeC), Changed<Triples>>,
     mut opened: Local<HashSet<String>>,
     sender: Res<CommandSender>,
     fs: Res<Fs>,
     client: Res<C>,
+    config: Res<ServerConfig>,
 ) {
     for (triples, _) in &query {
         for object in triples
@@ -85,13 +89,17 @@ pub fn open_imports<C: Client + Resource>(
             if opened.contains(object.as_str()) {
                 continue;
             }
+            if config.config.offline && object.scheme().starts_with("http") {
+                debug!("Offline, not fetching import {}", object);
+                continue;
+            }
             opened.insert(object.as_str().to_string());
 
             let fs = fs.clone();
             let sender = sender.clone();
             let fut = async move {
                 if let Some(content) = fs.0.read_file(&object).await {
-                    spawn_document(object, content, &sender.0, |_, _| {});
+                    spawn_document(object, content, None, &sender.0, |_, _| {});
 
                     let mut command_queue = CommandQueue::default();
                     command_queue.push(move |world: &mut World| {
@@ -121,10 +129,27 @@ pub fn fetch_lov_properties<C: Client + Resource>(
     mut prefixes: Local<HashSet<String>>,
     client: Res<C>,
     fs: Res<Fs>,
+    config: Res<ServerConfig>,
 ) {
     for prefs in &query {
         for prefix in prefs.0.iter() {
+            if let Some(location) = config.config.vocabulary(prefix.url.as_str()) {
+                let remote = location.scheme().starts_with("http");
+                if remote && config.config.offline {
+                    debug!("Offline, not fetching {}", location);
+                } else if !prefixes.contains(location.as_str()) {
+                    prefixes.insert(location.to_string());
+                    debug!("Configured vocabulary {} at {}", prefix.url, location);
+                    configured_vocabulary::<C>(prefix.clone(), location, &sender, &client, &fs);
+                }
+                continue;
+            }
+
             if !prefixes.contains(prefix.url.as_str()) {
+                if config.config.offline && !is_local_prefix(prefix) {
+                    debug!("Offline, not fetching {}", prefix.url);
+                    continue;
+                }
                 prefixes.insert(prefix.url.to_string());
                 if let Some(url) = fs.0.lov_url(prefix.url.as_str(), &prefix.prefix) {
                     info!("Other virtual url {}", url);
@@ -150,18 +175,65 @@ pub fn fetch_lov_properties<C: Client + Resource>(
     }
 }
 
+fn is_local_prefix(prefix: &Prefix) -> bool {
+    lov::LOCAL_PREFIXES
+        .iter()
+        .any(|x| x.location == prefix.url.as_str())
+}
+
+fn configured_vocabulary<C: Client + Resource>(
+    prefix: Prefix,
+    location: Url,
+    sender: &Res<CommandSender>,
+    client: &Res<C>,
+    fs: &Res<Fs>,
+) {
+    let fs = fs.clone();
+    let sender = sender.0.clone();
+    let c = client.as_ref().clone();
+    let fut = async move {
+        let content = if location.scheme().starts_with("http") {
+            match c.fetch(location.as_str(), &HashMap::new()).await {
+                Ok(resp) if resp.status == 200 => {
+                    let content_type = resp.header("content-type").map(String::from);
+                    Some((resp.body, content_type))
+                }
+                _ => None,
+            }
+        } else {
+            fs.0.read_file(&location).await.map(|x| (x, None))
+        };
+
+        if let Some((content, content_type)) = content {
+            let content_type = content_type.as_deref();
+            spawn_document(location, content, content_type, &sender, move |e, world| {
+                world.entity_mut(e).insert(FromPrefix(prefix));
+            });
+        } else {
+            info!("No content found for vocabulary {}", location);
+        }
+    };
+    client.spawn(fut);
+}
+
 type Sender = futures::channel::mpsc::UnboundedSender<CommandQueue>;
+/// Spawn a fetched or cached vocabulary, its language is derived from `content_type` and the
+/// content itself, see [`language_id_from_content`].
 fn spawn_document(
     url: Url,
     content: String,
+    content_type: Option<&str>,
     sender: &Sender,
     extra: impl FnOnce(Entity, &mut World) -> () + Send + Sync + 'static,
 ) {
+    let language_id = language_id_from_content(&url, content_type, &content);
+    debug!("Spawning {} as {}", url, language_id);
+
     let mut command_queue = CommandQueue::default();
     let item = TextDocumentItem {
         version: 1,
         uri: url.clone(),
-        language_id: String::from("turtle"),
+        language_id: String::from(language_id),
         text: String::new(),
     };
 
@@ -174,7 +246,7 @@ fn spawn_document(
             Wrapped(item),
             Types(HashMap::new()),
         ),
-        Some("turtle".into()),
+        Some(language_id.into()),
         (),
     );
 
@@ -186,6 +258,10 @@ fn spawn_document(
         extra(e, world);
 
         world.run_schedule(ParseLabel);
+        sync_all_snippets(world);
+        if let Some(publisher) = world.get_resource::<DiagnosticPublisher>() {
+            publisher.refresh();
+        }
         drop(_enter);
     });
 
@@ -212,9 +288,10 @@ async fn fetch_lov<C: Client + Resource>(prefix: Prefix, label: Url, c: C, sende
     if let Some(url) = extract_file_url(&prefix.prefix, &c).await {
         match c.fetch(&url, &std::collections::HashMap::new()).await {
             Ok(resp) if resp.status == 200 => {
+                let content_type = resp.header("content-type").map(String::from);
                 let extra =
                     extra_from_lov::<C>(FromPrefix(prefix), resp.body.clone(), label.clone(), fs);
-                spawn_document(label, resp.body, &sender, extra);
+                spawn_document(label, resp.body, content_type.as_deref(), &sender, extra);
             }
             Ok(resp) => {
                 error!("Fetch ({}) failed status {}", url, resp.status);
@@ -240,7 +317,7 @@ fn local_lov<C: Client + Resource>(
     });
 
     let extra = extra_from_lov::<C>(from, local.content.to_string(), label.clone(), fs);
-    spawn_document(label, local.content.to_string(), &sender.0, extra);
+    spawn_document(label, local.content.to_string(), None, &sender.0, extra);
 }
 
 #[derive(Component)]
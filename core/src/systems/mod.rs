@@ -16,6 +16,20 @@ pub use properties::{
     complete_class, complete_properties, derive_classes, derive_properties, hover_class,
     hover_property, DefinedClass, DefinedClasses, DefinedProperties, DefinedProperty,
 };
+mod workspace;
+pub use workspace::{
+    close_document, index_workspace_folders, is_language_id, language_id_from_content,
+    language_id_from_url, load_configured_shapes, refresh_workspace_file, remove_workspace_file,
+    remove_workspace_folder, workspace_files_glob,
+};
+mod config;
+pub use config::apply_config;
+mod host;
+pub(crate) use host::{despawn_snippets, take_snippet_diagnostics};
+pub use host::{
+    host_regions, html_regions, markdown_regions, snippet_at, snippet_semantic_tokens,
+    sync_all_snippets, sync_snippets, Region, Snippet, Snippets,
+};
 mod lov;
 pub use lov::{
     check_added_ontology_extract, fetch_lov_properties, init_onology_extractor, open_imports,
@@ -40,6 +54,10 @@ pub fn spawn_or_insert(
             entity
         } else {
             let entity = world.spawn(bundle).insert(extra).id();
+            // Languages trust the language id, unknown ids fall back to the extension
+            let language_id = language_id
+                .filter(|x| is_language_id(x))
+                .or_else(|| language_id_from_url(&url).map(String::from));
             world.trigger_targets(CreateEvent { url, language_id }, entity);
             entity
         };
@@ -1,7 +1,13 @@
-use std::{collections::HashSet, ops::Deref};
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Deref,
+};
 
 use bevy_ecs::prelude::*;
-use lsp_types::{CompletionItemKind, Diagnostic, DiagnosticSeverity, TextDocumentItem, TextEdit};
+use lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CompletionItemKind, Diagnostic,
+    DiagnosticSeverity, Position, TextDocumentItem, TextEdit, WorkspaceEdit,
+};
 use tracing::{debug, instrument};
 
 use crate::prelude::*;
@@ -19,6 +25,9 @@ lazy_static::lazy_static! {
     };
 }
 
+/// Start of the message of an undefined prefix diagnostic, followed by the prefix.
+pub const UNDEFINED_PREFIX: &'static str = "Undefined prefix ";
+
 /// One defined prefix, maps prefix to url
 #[derive(Debug, Clone)]
 pub struct Prefix {
@@ -152,29 +161,133 @@ pub fn undefined_prefix(
     mut client: ResMut<DiagnosticPublisher>,
 ) {
     for (tokens, prefixes, item, rope) in &query {
-        let mut diagnostics: Vec<Diagnostic> = Vec::new();
-        for t in &tokens.0 {
-            match t.value() {
-                Token::PNameLN(x, _) => {
-                    let pref = x.as_ref().map(|x| x.as_str()).unwrap_or("");
-                    let found = prefixes.0.iter().find(|x| x.prefix == pref).is_some();
-                    if !found {
-                        if let Some(range) = range_to_range(t.span(), &rope) {
-                            diagnostics.push(Diagnostic {
-                                range,
-                                severity: Some(DiagnosticSeverity::ERROR),
-                                source: Some(String::from("SWLS")),
-                                message: format!("Undefined prefix {}", pref),
-                                related_information: None,
-                                ..Default::default()
-                            })
-                        }
+        let diagnostics = undefined_prefix_diagnostics(tokens, prefixes, rope);
+        let _ = client.publish(&item.0, diagnostics, "undefined_prefix");
+    }
+}
+
+pub fn pull_undefined_prefix(
+    mut query: Query<(&Tokens, &Prefixes, &RopeC, &mut DiagnosticRequest)>,
+) {
+    for (tokens, prefixes, rope, mut request) in &mut query {
+        let diagnostics = undefined_prefix_diagnostics(tokens, prefixes, rope);
+        request.push(diagnostics, "undefined_prefix");
+    }
+}
+
+fn undefined_prefix_diagnostics(
+    tokens: &Tokens,
+    prefixes: &Prefixes,
+    rope: &RopeC,
+) -> Vec<Diagnostic> {
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+    for t in &tokens.0 {
+        match t.value() {
+            Token::PNameLN(x, _) => {
+                let pref = x.as_ref().map(|x| x.as_str()).unwrap_or("");
+                let found = prefixes.0.iter().find(|x| x.prefix == pref).is_some();
+                if !found {
+                    if let Some(range) = range_to_range(t.span(), &rope) {
+                        diagnostics.push(Diagnostic {
+                            range,
+                            severity: Some(DiagnosticSeverity::ERROR),
+                            source: Some(String::from("SWLS")),
+                            message: format!("{}{}", UNDEFINED_PREFIX, pref),
+                            related_information: None,
+                            ..Default::default()
+                        })
                     }
                 }
-                _ => {}
             }
+            _ => {}
         }
-        let _ = client.publish(&item.0, diagnostics, "undefined_prefix");
+    }
+    diagnostics
+}
+
+/// Finds the location of a well known prefix, first looking at the bundled prefix table, then at
+/// [`lov::LOCAL_PREFIXES`].
+pub fn prefix_location(name: &str) -> Option<&'static str> {
+    HASHMAP
+        .iter()
+        .find(|(prefix, _)| *prefix == name)
+        .map(|(_, location)| *location)
+        .or_else(|| {
+            lov::LOCAL_PREFIXES
+                .iter()
+                .find(|x| x.name == name)
+                .map(|x| x.location)
+        })
+}
+
+/// Position where a new prefix declaration goes: the start of the line after the prologue, which
+/// ends at the largest of the character offsets `ends`, or the start of the document.
+///
+/// Also returns the text to insert before the declaration, a line break when the prologue ends on
+/// the last line of the document.
+pub fn prologue_insert_position(
+    ends: impl Iterator<Item = usize>,
+    rope: &ropey::Rope,
+) -> (Position, &'static str) {
+    let Some(pos) = ends.max().and_then(|end| offset_to_position(end, rope)) else {
+        return (Position::default(), "");
+    };
+
+    if (pos.line as usize + 1) < rope.len_lines() {
+        (Position::new(pos.line + 1, 0), "")
+    } else {
+        let end = offset_to_position(rope.len_chars(), rope).unwrap_or(pos);
+        (end, "\n")
+    }
+}
+
+/// Adds a quick fix for each "Undefined prefix" diagnostic in the [`CodeActionRequest`] that
+/// overlaps the requested range.
+///
+/// `extra_edits` creates the language specific prefix declaration, for a prefix name and location.
+pub fn undefined_prefix_action_helper(
+    request: &mut CodeActionRequest,
+    label: &Label,
+    mut extra_edits: impl FnMut(&str, &str) -> Option<Vec<TextEdit>>,
+) {
+    let range = request.range;
+    let mut prefixes: Vec<(&str, Vec<Diagnostic>)> = Vec::new();
+    for diagnostic in &request.diagnostics {
+        if diagnostic.range.end < range.start || range.end < diagnostic.range.start {
+            continue;
+        }
+        let Some(prefix) = diagnostic.message.strip_prefix(UNDEFINED_PREFIX) else {
+            continue;
+        };
+
+        if let Some((_, diagnostics)) = prefixes.iter_mut().find(|(p, _)| *p == prefix) {
+            diagnostics.push(diagnostic.clone());
+        } else {
+            prefixes.push((prefix, vec![diagnostic.clone()]));
+        }
+    }
+
+    for (prefix, diagnostics) in prefixes {
+        let Some(location) = prefix_location(prefix) else {
+            continue;
+        };
+        let Some(edits) = extra_edits(prefix, location) else {
+            continue;
+        };
+
+        let mut changes = HashMap::new();
+        changes.insert(label.0.clone(), edits);
+
+        request
+            .actions
+            .push(CodeActionOrCommand::CodeAction(CodeAction {
+                title: format!("Add prefix {}: <{}>", prefix, location),
+                kind: Some(CodeActionKind::QUICKFIX),
+                diagnostics: Some(diagnostics),
+                edit: Some(WorkspaceEdit::new(changes)),
+                is_preferred: Some(true),
+                ..Default::default()
+            }));
     }
 }
 
@@ -10,7 +10,7 @@ use sophia_api::{
     quad::Quad,
     term::Term,
 };
-use systems::OntologyExtractor;
+use systems::{OntologyExtractor, TypeId};
 use tracing::{debug, info, instrument};
 
 use crate::{
@@ -238,29 +238,42 @@ pub fn complete_properties(
         &DocumentLinks,
         &Label,
         &Types,
+        &Triples,
         &mut CompletionRequest,
     )>,
     other: Query<(&Label, &Wrapped<DefinedProperties>)>,
     hierarchy: Res<TypeHierarchy<'static>>,
 ) {
     debug!("Complete properties");
-    for (token, triple, prefixes, links, this_label, types, mut request) in &mut query {
+    for (token, triple, prefixes, links, this_label, types, triples, mut request) in &mut query {
         debug!("target {:?} text {}", triple.target, token.text);
         debug!("links {:?}", links);
         if triple.target == TripleTarget::Predicate {
-            let tts = types.get(&triple.triple.subject.value);
-            for (label, properties) in &other {
-                // Check if this thing is actually linked
-                if links
-                    .iter()
-                    .find(|link| link.0.as_str().starts_with(label.0.as_str()))
-                    .is_none()
-                    && label.0 != this_label.0
-                {
-                    debug!("This link is ignored {}", label.as_str());
-                    continue;
-                }
+            let linked: Vec<_> = other
+                .iter()
+                .filter(|(label, _)| {
+                    // Check if this thing is actually linked
+                    let is_linked = links
+                        .iter()
+                        .any(|link| link.0.as_str().starts_with(label.0.as_str()))
+                        || label.0 == this_label.0;
+                    if !is_linked {
+                        debug!("This link is ignored {}", label.as_str());
+                    }
+                    is_linked
+                })
+                .map(|(_, properties)| properties)
+                .collect();
+
+            let subject = &triple.triple.subject;
+            let tts = match types.get(&subject.value) {
+                Some(tts) => tts.clone(),
+                // Untyped subjects, like the intermediate nodes of a property path, have the
+                // range of the properties pointing to them
+                None => range_types(subject, triples, &linked, &hierarchy),
+            };
 
+            for properties in linked {
                 for class in properties.0.iter() {
                     let to_beat = prefixes
                         .shorten(&class.predicate.value)
@@ -277,11 +290,7 @@ pub fn complete_properties(
                     if to_beat.starts_with(&token.text) {
                         let correct_domain = class.domain.iter().any(|domain| {
                             if let Some(domain_id) = hierarchy.get_id_ref(&domain) {
-                                if let Some(tts) = tts {
-                                    tts.iter().any(|tt| *tt == domain_id)
-                                } else {
-                                    false
-                                }
+                                tts.iter().any(|tt| *tt == domain_id)
                             } else {
                                 false
                             }
@@ -311,6 +320,28 @@ pub fn complete_properties(
     }
 }
 
+/// Types of `subject` given by the `rdfs:range` of the properties of the triples with `subject`
+/// as object.
+fn range_types(
+    subject: &MyTerm<'_>,
+    triples: &Triples,
+    properties: &[&Wrapped<DefinedProperties>],
+    hierarchy: &TypeHierarchy<'static>,
+) -> Vec<TypeId> {
+    triples
+        .iter()
+        .filter(|t| &t.object == subject)
+        .flat_map(|t| {
+            properties
+                .iter()
+                .flat_map(|x| x.0.iter())
+                .filter(move |p| p.predicate.value == t.predicate.value)
+        })
+        .flat_map(|p| p.range.iter())
+        .flat_map(|range| hierarchy.get_id_ref(range))
+        .collect()
+}
+
 #[instrument(skip(query, other))]
 pub fn hover_property(
     mut query: Query<(
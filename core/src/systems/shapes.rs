@@ -99,6 +99,15 @@ pub fn derive_shapes(
     }
 }
 
+/// Change ticks of all compiled shapes, used to know whether shape validations are outdated.
+pub(crate) fn shape_ticks(world: &mut World) -> Vec<bevy_ecs::component::Tick> {
+    world
+        .query::<Ref<ShaclShapes>>()
+        .iter(world)
+        .map(|x| x.last_changed())
+        .collect()
+}
+
 fn get_path(
     source: Option<&Object>,
     s: &CompiledShape<RdfData>,
@@ -186,12 +195,11 @@ fn push_diagnostics(
 fn derive_shapes_diagnostics_for(
     rope: &RopeC,
     label: &Label,
-    links: &DocumentLinks,
-    item: &Wrapped<TextDocumentItem>,
+    links: &[(lsp_types::Url, &'static str)],
     triples: &Triples,
     other: &Query<(&Label, &ShaclShapes, &Prefixes)>,
-    client: &mut DiagnosticPublisher,
-) {
+    configured: &[lsp_types::Url],
+) -> Vec<lsp_types::Diagnostic> {
     let mut diagnostics: Vec<lsp_types::Diagnostic> = Vec::new();
 
     let build_validator = || {
@@ -221,6 +229,7 @@ fn derive_shapes_diagnostics_for(
             .find(|link| link.0.as_str().starts_with(other_label.0.as_str()))
             .is_none()
             && label.0 != other_label.0
+            && !configured.contains(&other_label.0)
         {
             continue;
         }
@@ -239,7 +248,7 @@ fn derive_shapes_diagnostics_for(
         }
     }
 
-    let _ = client.publish(&item.0, diagnostics, "shacl_validation");
+    diagnostics
 }
 
 /// System evaluates linked shapes
@@ -256,18 +265,46 @@ pub fn validate_shapes(
         (Changed<Triples>, Without<Dirty>, With<Open>),
     >,
     other: Query<(&Label, &ShaclShapes, &Prefixes)>,
+    config: Res<ServerConfig>,
     mut client: ResMut<DiagnosticPublisher>,
 ) {
+    let configured = config.config.shape_urls();
     for (rope, label, links, item, triples) in &query {
         info!("Validate shapes {}", label.as_str());
-        derive_shapes_diagnostics_for(rope, label, links, item, triples, &other, &mut client);
+        let diagnostics =
+            derive_shapes_diagnostics_for(rope, label, links, triples, &other, &configured);
+        let _ = client.publish(&item.0, diagnostics, "shacl_validation");
+    }
+}
+
+/// System evaluates linked shapes for documents handling a [`DiagnosticRequest`]
+pub fn pull_shapes(
+    mut query: Query<
+        (
+            &RopeC,
+            &Label,
+            Option<&DocumentLinks>,
+            &Triples,
+            &mut DiagnosticRequest,
+        ),
+        (Without<Dirty>, Without<SkipValidation>),
+    >,
+    other: Query<(&Label, &ShaclShapes, &Prefixes)>,
+    config: Res<ServerConfig>,
+) {
+    let configured = config.config.shape_urls();
+    for (rope, label, links, triples, mut request) in &mut query {
+        let links = links.map(|x| &x.0[..]).unwrap_or(&[]);
+        let diagnostics =
+            derive_shapes_diagnostics_for(rope, label, links, triples, &other, &configured);
+        request.push(diagnostics, "shacl_validation");
     }
 }
 
 /// System checks what entities should retrigger a shape evaluation when a shape changes
-#[instrument(skip(changed_schemas, query, other, client))]
+#[instrument(skip(changed_schemas, query, other, config, client))]
 pub fn validate_with_updated_shapes(
-    changed_schemas: Query<&Label, (Changed<Wrapped<ShaclShape>>, With<Open>)>,
+    changed_schemas: Query<&Label, Changed<ShaclShapes>>,
     query: Query<
         (
             &RopeC,
@@ -279,26 +316,24 @@ pub fn validate_with_updated_shapes(
         With<Open>,
     >,
     other: Query<(&Label, &ShaclShapes, &Prefixes)>,
+    config: Res<ServerConfig>,
     mut client: ResMut<DiagnosticPublisher>,
 ) {
+    let configured = config.config.shape_urls();
     for l in &changed_schemas {
         info!("Changed schema {}", l.as_str());
+        let is_configured = configured.contains(&l.0);
         for (rope, label, links, item, triples) in &query {
-            if links
-                .iter()
-                .find(|(url, _)| url.as_str().starts_with(l.as_str()))
-                .is_some()
+            if is_configured
+                || links
+                    .iter()
+                    .find(|(url, _)| url.as_str().starts_with(l.as_str()))
+                    .is_some()
             {
                 debug!("Found reverse linked document! {}", label.as_str());
-                derive_shapes_diagnostics_for(
-                    rope,
-                    label,
-                    links,
-                    item,
-                    triples,
-                    &other,
-                    &mut client,
-                );
+                let diagnostics =
+                    derive_shapes_diagnostics_for(rope, label, links, triples, &other, &configured);
+                let _ = client.publish(&item.0, diagnostics, "shacl_validation");
             }
         }
     }
@@ -36,7 +36,7 @@ pub fn extract_type_hierarchy(
                 .quads_matching(Any, [rdfs::subClassOf], [q.s()], Any)
                 .flatten()
             {
-                let subject = hierarchy.get_id(is_sub_class_of.o().as_str());
+                let subject = hierarchy.get_id(is_sub_class_of.s().as_str());
                 hierarchy.set_subclass_of(subject, id);
             }
         }
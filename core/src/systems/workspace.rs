@@ -0,0 +1,393 @@
+use std::collections::HashMap;
+
+use bevy_ecs::{prelude::*, world::CommandQueue};
+use hashbrown::HashSet;
+use lsp_types::{TextDocumentItem, Url};
+use tracing::{debug, info};
+
+use super::{
+    lov::{FromPrefix, OntologyExtract},
+    sync_all_snippets,
+};
+use crate::{prelude::*, util::fs::Fs};
+
+/// Extensions of the workspace files that are indexed and watched, with their language id.
+const EXTENSIONS: &[(&str, &str)] = &[
+    ("ttl", "turtle"),
+    ("trig", "trig"),
+    ("nt", "ntriples"),
+    ("nq", "nquads"),
+    ("n3", "n3"),
+    ("jsonld", "jsonld"),
+    ("rdf", "rdfxml"),
+    ("owl", "rdfxml"),
+    ("sq", "sparql"),
+    ("rq", "sparql"),
+    ("ru", "sparql"),
+    ("shex", "shex"),
+];
+
+/// Derive the language id from the extension of a workspace file.
+///
+/// Files with an unknown extension are not indexed.
+pub fn language_id_from_url(url: &Url) -> Option<&'static str> {
+    let path = url.path();
+    let ext = path.rsplit_once('.')?.1;
+    EXTENSIONS
+        .iter()
+        .find(|(x, _)| *x == ext)
+        .map(|(_, language_id)| *language_id)
+}
+
+/// Glob pattern of the workspace files with a known extension, see [`language_id_from_url`].
+pub fn workspace_files_glob() -> String {
+    let extensions: Vec<_> = EXTENSIONS.iter().map(|(x, _)| *x).collect();
+    format!("**/*.{{{}}}", extensions.join(","))
+}
+
+/// Whether the server supports documents with this language id.
+pub fn is_language_id(language_id: &str) -> bool {
+    matches!(
+        language_id,
+        "turtle"
+            | "trig"
+            | "ntriples"
+            | "nquads"
+            | "n3"
+            | "jsonld"
+            | "rdfxml"
+            | "sparql"
+            | "sparql-update"
+            | "shex"
+    )
+}
+
+/// Derive the language id of a fetched or cached document.
+///
+/// The content type of the response is used first, then the extension of `url`. Only when the
+/// extension is unknown the content itself is sniffed, documents that match nothing are read as
+/// Turtle.
+pub fn language_id_from_content(
+    url: &Url,
+    content_type: Option<&str>,
+    content: &str,
+) -> &'static str {
+    let mime = content_type
+        .and_then(|x| x.split(';').next())
+        .map(|x| x.trim().to_ascii_lowercase());
+    match mime.as_deref() {
+        Some("text/turtle" | "application/x-turtle") => return "turtle",
+        Some("application/rdf+xml") => return "rdfxml",
+        Some("application/n-triples") => return "ntriples",
+        Some("application/n-quads") => return "nquads",
+        Some("application/trig") => return "trig",
+        Some("text/n3" | "text/rdf+n3") => return "n3",
+        Some("application/ld+json") => return "jsonld",
+        Some("text/shex") => return "shex",
+        _ => {}
+    }
+
+    if let Some(language_id) = language_id_from_url(url) {
+        return language_id;
+    }
+
+    let start = content.trim_start();
+    if start.starts_with("<?xml") || start.starts_with("<rdf:RDF") || start.starts_with("<!DOCTYPE")
+    {
+        return "rdfxml";
+    }
+    if start.starts_with('{') {
+        return "jsonld";
+    }
+
+    "turtle"
+}
+
+/// Index all workspace folders that were not yet indexed.
+///
+/// Every file with a known extension is read in the background and spawned as a document that
+/// is not [`Open`], marked with [`WorkspaceFile`].
+pub fn index_workspace_folders<C: Client + Resource>(
+    config: Res<ServerConfig>,
+    mut indexed: Local<HashSet<Url>>,
+    sender: Res<CommandSender>,
+    fs: Res<Fs>,
+    client: Res<C>,
+) {
+    indexed.retain(|folder| config.workspaces.iter().any(|x| &x.uri == folder));
+
+    for folder in &config.workspaces {
+        if indexed.contains(&folder.uri) {
+            continue;
+        }
+        indexed.insert(folder.uri.clone());
+        info!("Indexing workspace folder {}", folder.uri);
+
+        let folder = folder.uri.clone();
+        let fs = fs.clone();
+        let sender = sender.clone();
+        let fut = async move {
+            for url in fs.0.list_files(&folder).await {
+                let Some(language_id) = language_id_from_url(&url) else {
+                    continue;
+                };
+
+                if let Some(content) = fs.0.read_file(&url).await {
+                    debug!("Indexed {} ({})", url, language_id);
+                    spawn_workspace_file(url, content, language_id, &sender);
+                }
+            }
+
+            run_save(&sender);
+        };
+        client.spawn(fut);
+    }
+}
+
+/// Load the SHACL shapes configured in [`Config::shapes`] that were not yet loaded.
+///
+/// Remote shapes are not fetched in offline mode.
+pub fn load_configured_shapes<C: Client + Resource>(
+    config: Res<ServerConfig>,
+    mut loaded: Local<HashSet<Url>>,
+    sender: Res<CommandSender>,
+    fs: Res<Fs>,
+    client: Res<C>,
+) {
+    let shapes = config.config.shape_urls();
+    loaded.retain(|url| shapes.contains(url));
+
+    for url in shapes {
+        if loaded.contains(&url) {
+            continue;
+        }
+
+        let remote = url.scheme().starts_with("http");
+        if remote && config.config.offline {
+            debug!("Offline, not fetching shapes {}", url);
+            continue;
+        }
+        loaded.insert(url.clone());
+
+        let fs = fs.clone();
+        let sender = sender.clone();
+        let c = client.as_ref().clone();
+        let fut = async move {
+            let content = if remote {
+                match c.fetch(url.as_str(), &HashMap::new()).await {
+                    Ok(resp) if resp.status == 200 => Some(resp.body),
+                    _ => None,
+                }
+            } else {
+                fs.0.read_file(&url).await
+            };
+
+            if let Some(content) = content {
+                let language_id = language_id_from_url(&url).unwrap_or("turtle");
+                spawn_workspace_file(url, content, language_id, &sender);
+                run_save(&sender);
+            } else {
+                info!("No content found for shapes {}", url);
+            }
+        };
+        client.spawn(fut);
+    }
+}
+
+fn run_save(sender: &CommandSender) {
+    let mut command_queue = CommandQueue::default();
+    command_queue.push(move |world: &mut World| {
+        world.run_schedule(SaveLabel);
+        sync_all_snippets(world);
+        if let Some(publisher) = world.get_resource::<DiagnosticPublisher>() {
+            publisher.refresh();
+        }
+    });
+    let _ = sender.unbounded_send(command_queue);
+}
+
+fn spawn_workspace_file(url: Url, content: String, language_id: &str, sender: &CommandSender) {
+    let item = TextDocumentItem {
+        version: 1,
+        uri: url.clone(),
+        language_id: language_id.to_string(),
+        text: String::new(),
+    };
+
+    let spawn = spawn_or_insert(
+        url.clone(),
+        (
+            RopeC(ropey::Rope::from_str(&content)),
+            Source(content),
+            Label(url.clone()),
+            Wrapped(item),
+            Types(HashMap::new()),
+        ),
+        Some(language_id.to_string()),
+        WorkspaceFile,
+    );
+
+    let mut command_queue = CommandQueue::default();
+    command_queue.push(move |world: &mut World| {
+        // Documents that are already known (open in the editor for example) are more up to date
+        // than the file on disk.
+        if let Some(entity) = find_entity(world, &url) {
+            world.entity_mut(entity).insert(WorkspaceFile);
+            return;
+        }
+
+        spawn(world);
+    });
+
+    let _ = sender.unbounded_send(command_queue);
+}
+
+fn find_entity(world: &mut World, url: &Url) -> Option<Entity> {
+    world
+        .query::<(Entity, &Label)>()
+        .iter(&world)
+        .find(|x| &x.1 .0 == url)
+        .map(|x| x.0)
+}
+
+/// Update the document after it was created or changed on disk.
+///
+/// [`Open`] documents are left alone, the editor knows their content better than the file system.
+pub fn refresh_workspace_file(
+    url: Url,
+    content: String,
+) -> impl FnOnce(&mut World) + Send + Sync + 'static {
+    move |world: &mut World| {
+        if let Some(entity) = find_entity(world, &url) {
+            let mut entity_mut = world.entity_mut(entity);
+            if entity_mut.contains::<Open>() {
+                return;
+            }
+            entity_mut.insert((
+                RopeC(ropey::Rope::from_str(&content)),
+                Source(content),
+                WorkspaceFile,
+            ));
+            world.run_schedule(ParseLabel);
+        } else if let Some(language_id) = language_id_from_url(&url) {
+            let item = TextDocumentItem {
+                version: 1,
+                uri: url.clone(),
+                language_id: language_id.to_string(),
+                text: String::new(),
+            };
+            spawn_or_insert(
+                url.clone(),
+                (
+                    RopeC(ropey::Rope::from_str(&content)),
+                    Source(content),
+                    Label(url),
+                    Wrapped(item),
+                    Types(HashMap::new()),
+                ),
+                Some(language_id.to_string()),
+                WorkspaceFile,
+            )(world);
+        } else {
+            return;
+        }
+
+        world.run_schedule(SaveLabel);
+    }
+}
+
+/// Despawn the document after it was deleted on disk, together with its diagnostics.
+///
+/// [`Open`] documents are kept, they are just not part of the workspace anymore.
+pub fn remove_workspace_file(url: Url) -> impl FnOnce(&mut World) + Send + Sync + 'static {
+    move |world: &mut World| {
+        if let Some(entity) = find_entity(world, &url) {
+            if world.entity(entity).contains::<Open>() {
+                world.entity_mut(entity).remove::<WorkspaceFile>();
+                return;
+            }
+            world.despawn(entity);
+        }
+
+        if let Some(mut publisher) = world.get_resource_mut::<DiagnosticPublisher>() {
+            publisher.clear(&url);
+        }
+
+        workspace_file.then_some(url)
+    }
+}
+
+/// Remove the documents of a workspace folder that is no longer part of the workspace, as with
+/// [`remove_workspace_file`].
+///
+/// Files that are part of another configured workspace folder are kept.
+pub fn remove_workspace_folder(folder: Url) -> impl FnOnce(&mut World) + Send + Sync + 'static {
+    move |world: &mut World| {
+        let as_dir = |url: &Url| {
+            let st = url.as_str();
+            if st.ends_with('/') {
+                st.to_string()
+            } else {
+                format!("{}/", st)
+            }
+        };
+        let folder = as_dir(&folder);
+        let others: Vec<String> = world
+            .get_resource::<ServerConfig>()
+            .map(|config| config.workspaces.iter().map(|x| as_dir(&x.uri)).collect())
+            .unwrap_or_default();
+
+        let urls: Vec<Url> = world
+            .query_filtered::<&Label, With<WorkspaceFile>>()
+            .iter(world)
+            .map(|x| x.0.clone())
+            .filter(|url| {
+                url.as_str().starts_with(&folder)
+                    && !others.iter().any(|x| url.as_str().starts_with(x.as_str()))
+            })
+            .collect();
+
+        for url in urls {
+            debug!("Removing {} of workspace folder {}", url, folder);
+            remove_workspace_file(url)(world);
+        }
+    }
+}
+
+/// Close the document: it is not [`Open`] anymore and its diagnostics are cleared.
+///
+/// Documents that are neither a [`WorkspaceFile`] nor linked from other documents through
+/// [`DocumentLinks`] are despawned, together with their embedded snippets.
+///
+/// Returns the url of a closed [`WorkspaceFile`]: the editor may close it without saving, so its
+/// content should be read from disk again with [`refresh_workspace_file`].
+pub fn close_document(
+    entity: Entity,
+) -> impl FnOnce(&mut World) -> Option<Url> + Send + Sync + 'static {
+    move |world: &mut World| {
+        let mut entity_mut = world.get_entity_mut(entity)?;
+        entity_mut.remove::<Open>();
+
+        let url = entity_mut.get::<Label>().map(|x| x.0.clone())?;
+        let workspace_file = entity_mut.contains::<WorkspaceFile>();
+        // Prefix and ontology documents are fetched only once, despawning them loses them
+        let keep = workspace_file
+            || entity_mut.contains::<FromPrefix>()
+            || entity_mut.contains::<OntologyExtract>();
+
+        let linked = world
+            .query::<(Entity, &DocumentLinks)>()
+            .iter(&world)
+            .any(|(e, links)| e != entity && links.iter().any(|(u, _)| u == &url));
+
+        if !keep && !linked {
+            debug!("Despawning closed document {}", url);
+            super::despawn_snippets(world, entity);
+            world.despawn(entity);
+        }
+
+        if let Some(mut publisher) = world.get_resource_mut::<DiagnosticPublisher>() {
+            publisher.clear(&url);
+        }
+    }
+}
@@ -19,4 +19,11 @@ pub trait FsTrait: Send + Sync + 'static + std::fmt::Debug {
     }
     async fn read_file(&self, url: &lsp_types::Url) -> Option<String>;
     async fn write_file(&self, url: &lsp_types::Url, content: &str) -> Option<()>;
+    /// Recursively list the files with a known extension in the directory at `url`, skipping
+    /// hidden and build directories.
+    ///
+    /// File systems that cannot list directories return nothing.
+    async fn list_files(&self, _url: &lsp_types::Url) -> Vec<lsp_types::Url> {
+        Vec::new()
+    }
 }
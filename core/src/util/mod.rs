@@ -3,7 +3,7 @@ use std::{
     ops::{Deref, DerefMut},
 };
 
-use lsp_types::{Location, Position, Range};
+use lsp_types::{Location, Position, Range, TextDocumentContentChangeEvent};
 use ropey::Rope;
 
 use crate::Label;
@@ -61,6 +61,45 @@ pub fn position_to_offset(position: Position, rope: &Rope) -> Option<usize> {
         None
     }
 }
+/// Like [`position_to_offset`], but allows positions at the end of a line (or document), as used
+/// by the edges of an edit.
+fn position_to_char(position: Position, rope: &Rope) -> Option<usize> {
+    let line_offset = rope.try_line_to_char(position.line as usize).ok()?;
+    let line_length = rope
+        .get_line(position.line as usize)
+        .map(|x| x.len_chars())
+        .unwrap_or(0);
+
+    Some(line_offset + (position.character as usize).min(line_length))
+}
+
+/// Applies a single [`TextDocumentContentChangeEvent`] to the rope.
+///
+/// Returns the replaced byte range of the previous document together with the length of the
+/// inserted text, a change without range replaces the complete document.
+pub fn apply_change(
+    change: &TextDocumentContentChangeEvent,
+    rope: &mut Rope,
+) -> Option<(std::ops::Range<usize>, usize)> {
+    let Some(range) = change.range else {
+        let removed = 0..rope.len_bytes();
+        *rope = Rope::from_str(&change.text);
+        return Some((removed, change.text.len()));
+    };
+
+    let start = position_to_char(range.start, rope)?;
+    let end = position_to_char(range.end, rope)?;
+    if end < start {
+        return None;
+    }
+
+    let removed = rope.char_to_byte(start)..rope.char_to_byte(end);
+    rope.try_remove(start..end).ok()?;
+    rope.try_insert(start, &change.text).ok()?;
+
+    Some((removed, change.text.len()))
+}
+
 pub fn offsets_to_range(start: usize, end: usize, rope: &Rope) -> Option<Range> {
     let start = offset_to_position(start, rope)?;
     let end = offset_to_position(end, rope)?;
@@ -178,3 +217,69 @@ pub fn token_to_location(
         uri: label.0.clone(),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::{Position, Range, TextDocumentContentChangeEvent};
+    use ropey::Rope;
+
+    use super::apply_change;
+    use crate::components::Changes;
+
+    fn change(start: (u32, u32), end: (u32, u32), text: &str) -> TextDocumentContentChangeEvent {
+        TextDocumentContentChangeEvent {
+            range: Some(Range::new(
+                Position::new(start.0, start.1),
+                Position::new(end.0, end.1),
+            )),
+            range_length: None,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn apply_change_insert_and_remove() {
+        let mut rope = Rope::from_str("<a> <b> <c>.\n<d> <e> <f>.\n");
+
+        let range = apply_change(&change((1, 1), (1, 2), "dd"), &mut rope);
+        assert_eq!(rope.to_string(), "<a> <b> <c>.\n<dd> <e> <f>.\n");
+        assert_eq!(range, Some((14..15, 2)));
+
+        let range = apply_change(&change((0, 4), (0, 8), ""), &mut rope);
+        assert_eq!(rope.to_string(), "<a> <c>.\n<dd> <e> <f>.\n");
+        assert_eq!(range, Some((4..8, 0)));
+
+        let range = apply_change(&change((2, 0), (2, 0), "<g> <h> <i>."), &mut rope);
+        assert_eq!(rope.to_string(), "<a> <c>.\n<dd> <e> <f>.\n<g> <h> <i>.");
+        assert_eq!(range, Some((23..23, 12)));
+    }
+
+    #[test]
+    fn apply_change_full() {
+        let mut rope = Rope::from_str("<a> <b> <c>.");
+        let full = TextDocumentContentChangeEvent {
+            range: None,
+            range_length: None,
+            text: "<d> <e> <f>.\n".to_string(),
+        };
+
+        let range = apply_change(&full, &mut rope);
+        assert_eq!(rope.to_string(), "<d> <e> <f>.\n");
+        assert_eq!(range, Some((0..12, 13)));
+    }
+
+    #[test]
+    fn changes_shift_and_merge() {
+        let mut changes = Changes::default();
+        changes.push(10..12, 4);
+        assert_eq!(changes.0, vec![10..14]);
+
+        // insert before the earlier change, shifting it
+        changes.push(2..2, 3);
+        assert_eq!(changes.0, vec![2..5, 13..17]);
+
+        // remove across the second change, merging it
+        changes.push(15..20, 0);
+        assert_eq!(changes.0, vec![2..5, 13..15]);
+    }
+}
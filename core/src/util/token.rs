@@ -348,6 +348,36 @@ derive_enum!(
         Undef => "UNDEF",
         Minus => "MINUS",
         Filter => "FILTER",
+        Separator => "SEPARATOR",
+        Into => "INTO",
+        To => "TO",
+    }
+);
+
+derive_enum!(
+    #[derive(Clone, PartialEq, Eq,Ord, PartialOrd, Hash, Debug, EnumIntoGetters, EnumIsA, EnumToGetters)]
+    pub enum ShexKeyword {
+        Import => "IMPORT",
+        Start => "start",
+        External => "EXTERNAL",
+        Closed => "CLOSED",
+        Extra => "EXTRA",
+        Iri => "IRI",
+        Literal => "LITERAL",
+        NonLiteral => "NONLITERAL",
+        BNode => "BNODE",
+        And => "AND",
+        Or => "OR",
+        Not => "NOT",
+        Length => "LENGTH",
+        MinLength => "MINLENGTH",
+        MaxLength => "MAXLENGTH",
+        MinInclusive => "MININCLUSIVE",
+        MinExclusive => "MINEXCLUSIVE",
+        MaxInclusive => "MAXINCLUSIVE",
+        MaxExclusive => "MAXEXCLUSIVE",
+        TotalDigits => "TOTALDIGITS",
+        FractionDigits => "FRACTIONDIGITS",
     }
 );
 
@@ -365,6 +395,8 @@ pub enum Token {
     SparqlAggregate(SparqlAggregate),
     /// Sparql variable
     Variable(String),
+    /// ShEx keyword
+    ShexKeyword(ShexKeyword),
     // Turtle Tokens
     /// @prefix
     PrefixTag,
@@ -390,6 +422,40 @@ pub enum Token {
     BracketOpen,
     /// )
     BracketClose,
+    /// <<
+    QuotedOpen,
+    /// >>
+    QuotedClose,
+    /// {|
+    AnnotationOpen,
+    /// |}
+    AnnotationClose,
+
+    // N3 Tokens
+    /// =>
+    Implies,
+    /// <=
+    ImpliedBy,
+    /// =
+    SameAs,
+    /// @forAll
+    ForAll,
+    /// @forSome
+    ForSome,
+
+    // ShEx Tokens
+    /// @, before a shape reference
+    At,
+    /// |
+    Pipe,
+    /// ^
+    Caret,
+    /// *
+    Star,
+    /// +
+    Plus,
+    /// ?
+    QuestionMark,
 
     /// ^^
     DataTypeDelim,
@@ -455,8 +521,14 @@ impl TokenTrait for Token {
             | Token::SparqlPrefix
             | Token::SparqlBase
             | Token::PredType
+            | Token::Implies
+            | Token::ImpliedBy
+            | Token::SameAs
+            | Token::ForAll
+            | Token::ForSome
             | Token::SparqlKeyword(_)
-            | Token::SparqlCall(_) => Some(lsp_types::SemanticTokenType::KEYWORD),
+            | Token::SparqlCall(_)
+            | Token::ShexKeyword(_) => Some(lsp_types::SemanticTokenType::KEYWORD),
             Token::True | Token::False => Some(semantic_token::BOOLEAN),
             Token::IRIRef(_) => Some(lsp_types::SemanticTokenType::PROPERTY),
             Token::LangTag(_) => Some(semantic_token::LANG_TAG),
@@ -549,6 +621,21 @@ impl std::fmt::Display for Token {
             Token::SqClose => write!(f, "']'"),
             Token::BracketOpen => write!(f, "'('"),
             Token::BracketClose => write!(f, "')'"),
+            Token::QuotedOpen => write!(f, "'<<'"),
+            Token::QuotedClose => write!(f, "'>>'"),
+            Token::AnnotationOpen => write!(f, "'{{|'"),
+            Token::AnnotationClose => write!(f, "'|}}'"),
+            Token::Implies => write!(f, "'=>'"),
+            Token::ImpliedBy => write!(f, "'<='"),
+            Token::SameAs => write!(f, "'='"),
+            Token::ForAll => write!(f, "'@forAll'"),
+            Token::ForSome => write!(f, "'@forSome'"),
+            Token::At => write!(f, "'@'"),
+            Token::Pipe => write!(f, "'|'"),
+            Token::Caret => write!(f, "'^'"),
+            Token::Star => write!(f, "'*'"),
+            Token::Plus => write!(f, "'+'"),
+            Token::QuestionMark => write!(f, "'?'"),
             Token::DataTypeDelim => write!(f, "'^^'"),
             Token::Stop => write!(f, "'.'"),
             Token::PredicateSplit => write!(f, "';'"),
@@ -573,6 +660,7 @@ impl std::fmt::Display for Token {
             Token::SparqlCall(_) => write!(f, "sparql call"),
             Token::SparqlAggregate(_) => write!(f, "sparql aggregate"),
             Token::Variable(_) => write!(f, "sparql variable"),
+            Token::ShexKeyword(x) => write!(f, "'{}'", x.complete()),
         }
     }
 }
@@ -54,7 +54,7 @@ impl TripleComponent {
             TripleTarget::Subject => self.triple.s(),
             TripleTarget::Predicate => self.triple.p(),
             TripleTarget::Object => self.triple.o(),
-            TripleTarget::Graph => return None,
+            TripleTarget::Graph => return self.triple.graph.as_ref(),
         };
         Some(target)
     }
@@ -66,6 +66,14 @@ impl TripleComponent {
 #[derive(Component, AsRef, Deref, AsMut, DerefMut, Debug)]
 pub struct Triples(pub Vec<MyQuad<'static>>);
 
+/// [`Component`] containing triples a document mentions without asserting them.
+///
+/// These are the triples inside N3 formulae, their graph is the blank node naming the formula,
+/// the segments of negated SPARQL property paths and the properties of undefined JSON-LD terms.
+/// They are kept out of [`Triples`], only the current triple of a request is looked up in them.
+#[derive(Component, AsRef, Deref, AsMut, DerefMut, Debug, Default)]
+pub struct UnassertedTriples(pub Vec<MyQuad<'static>>);
+
 impl Triples {
     pub fn object<'s, S, P>(&'s self, subj: S, pred: P) -> Option<&'s MyTerm<'s>>
     where
@@ -103,10 +111,16 @@ impl Triples {
 
 #[instrument(skip(query, commands))]
 pub fn get_current_triple(
-    query: Query<(Entity, &PositionComponent, &Triples, &RopeC)>,
+    query: Query<(
+        Entity,
+        &PositionComponent,
+        &Triples,
+        Option<&UnassertedTriples>,
+        &RopeC,
+    )>,
     mut commands: Commands,
 ) {
-    for (e, position, triples, rope) in &query {
+    for (e, position, triples, unasserted, rope) in &query {
         commands.entity(e).remove::<TripleComponent>();
 
         let Some(offset) = position_to_offset(position.0, &rope.0) else {
@@ -114,48 +128,90 @@ pub fn get_current_triple(
             continue;
         };
 
+        // Property paths result in several triples with the same span, prefer the triple with
+        // a term, like the path segment, under the cursor
         if let Some(t) = triples
             .0
             .iter()
+            .chain(unasserted.into_iter().flat_map(|x| x.0.iter()))
             .filter(|triple| triple.span.contains(&offset))
-            .min_by_key(|x| x.span.end - x.span.start)
+            .min_by_key(|x| {
+                let on_term = [&x.subject, &x.predicate, &x.object]
+                    .iter()
+                    .any(|term| term.span.contains(&offset));
+                (!on_term, x.span.end - x.span.start)
+            })
         {
-            let target = [
-                (TripleTarget::Subject, &t.subject.span),
-                (TripleTarget::Predicate, &t.predicate.span),
-                (TripleTarget::Object, &t.object.span),
-            ]
-            .into_iter()
-            .filter(|x| x.1.contains(&offset))
-            .min_by_key(|x| x.1.end - x.1.start)
-            .map(|x| x.0)
-            .unwrap_or(TripleTarget::Subject);
-
-            debug!("Current triple {} {:?}", t, target);
-            commands.entity(e).insert(TripleComponent {
-                triple: t.clone(),
-                target,
-            });
+            let mut triple = t.clone();
+            let mut target = target_at(&triple, offset);
+
+            // Quoted triples are not asserted, target the inner triple when the offset is on
+            // one of its terms
+            while let Some(inner) = quoted_at(&triple, &target, offset) {
+                triple = inner;
+                target = target_at(&triple, offset);
+            }
+
+            debug!("Current triple {} {:?}", triple, target);
+            commands
+                .entity(e)
+                .insert(TripleComponent { triple, target });
         } else {
             debug!("No current triple found");
         }
     }
 }
 
+fn target_at(t: &MyQuad<'_>, offset: usize) -> TripleTarget {
+    [
+        (TripleTarget::Subject, &t.subject.span),
+        (TripleTarget::Predicate, &t.predicate.span),
+        (TripleTarget::Object, &t.object.span),
+    ]
+    .into_iter()
+    .filter(|x| x.1.contains(&offset))
+    .min_by_key(|x| x.1.end - x.1.start)
+    .map(|x| x.0)
+    .unwrap_or(TripleTarget::Subject)
+}
+
+fn quoted_at(t: &MyQuad<'static>, target: &TripleTarget, offset: usize) -> Option<MyQuad<'static>> {
+    let term = match target {
+        TripleTarget::Subject => &t.subject,
+        TripleTarget::Predicate => &t.predicate,
+        TripleTarget::Object => &t.object,
+        TripleTarget::Graph => return None,
+    };
+    let [s, p, o] = term.quoted()?;
+    if !term.span.contains(&offset) || ![s, p, o].iter().any(|x| x.span.contains(&offset)) {
+        return None;
+    }
+
+    Some(MyQuad {
+        subject: s.clone(),
+        predicate: p.clone(),
+        object: o.clone(),
+        graph: t.graph.clone(),
+        span: term.span.clone(),
+    })
+}
+
 #[derive(Debug, Clone)]
 pub struct MyQuad<'a> {
     pub subject: MyTerm<'a>,
     pub predicate: MyTerm<'a>,
     pub object: MyTerm<'a>,
+    /// Name of the graph containing this quad, `None` for the default graph.
+    pub graph: Option<MyTerm<'a>>,
     pub span: std::ops::Range<usize>,
 }
 impl<'a> std::fmt::Display for MyQuad<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{} {} {}. # {:?}",
-            self.subject, self.predicate, self.object, self.span
-        )
+        write!(f, "{} {} {}", self.subject, self.predicate, self.object)?;
+        if let Some(graph) = &self.graph {
+            write!(f, " {}", graph)?;
+        }
+        write!(f, ". # {:?}", self.span)
     }
 }
 
@@ -165,6 +221,7 @@ impl<'a> MyQuad<'a> {
             subject: self.subject.to_owned(),
             predicate: self.predicate.to_owned(),
             object: self.object.to_owned(),
+            graph: self.graph.as_ref().map(|x| x.to_owned()),
             span: self.span.clone(),
         }
     }
@@ -186,11 +243,11 @@ impl<'a> Quad for MyQuad<'a> {
     }
 
     fn g(&self) -> GraphName<sophia_api::quad::QBorrowTerm<Self>> {
-        None
+        self.graph.as_ref().map(|x| x.borrow_term())
     }
 
     fn to_spog(self) -> sophia_api::quad::Spog<Self::Term> {
-        ([self.subject, self.predicate, self.object], None)
+        ([self.subject, self.predicate, self.object], self.graph)
     }
 }
 // pub type MyQuad<'a> = ([MyTerm<'a>; 3], GraphName<MyTerm<'a>>);
@@ -199,6 +256,8 @@ impl<'a> Quad for MyQuad<'a> {
 pub struct MyTerm<'a> {
     pub value: Cow<'a, str>,
     ty: Option<TermKind>,
+    /// Subject, predicate and object of a quoted triple.
+    inner: Option<Box<[MyTerm<'a>; 3]>>,
     pub span: std::ops::Range<usize>,
 }
 
@@ -223,6 +282,7 @@ impl<'a> std::fmt::Display for MyTerm<'a> {
             TermKind::Iri => write!(f, "<{}>", self.value),
             TermKind::Literal => write!(f, "\"{}\"", self.value),
             TermKind::BlankNode => write!(f, "_:{}", self.value),
+            TermKind::Triple if self.inner.is_some() => write!(f, "<< {} >>", self.value),
             TermKind::Triple => write!(f, "<{}>", self.value),
             TermKind::Variable => write!(f, "?{}", self.value),
         }
@@ -235,6 +295,10 @@ impl<'a> MyTerm<'a> {
         MyTerm {
             value,
             ty: self.ty.clone(),
+            inner: self
+                .inner
+                .as_ref()
+                .map(|[s, p, o]| Box::new([s.to_owned(), p.to_owned(), o.to_owned()])),
             span: self.span.clone(),
         }
     }
@@ -242,6 +306,7 @@ impl<'a> MyTerm<'a> {
         Self {
             value: value.into(),
             ty: TermKind::Variable.into(),
+            inner: None,
             span,
         }
     }
@@ -249,6 +314,7 @@ impl<'a> MyTerm<'a> {
         Self {
             value: value.into(),
             ty: TermKind::Iri.into(),
+            inner: None,
             span,
         }
     }
@@ -256,6 +322,7 @@ impl<'a> MyTerm<'a> {
         Self {
             value: value.into(),
             ty: TermKind::BlankNode.into(),
+            inner: None,
             span,
         }
     }
@@ -263,6 +330,7 @@ impl<'a> MyTerm<'a> {
         Self {
             value: value.into(),
             ty: TermKind::Literal.into(),
+            inner: None,
             span,
         }
     }
@@ -271,10 +339,31 @@ impl<'a> MyTerm<'a> {
         Self {
             value: Cow::default(),
             ty: None,
+            inner: None,
+            span,
+        }
+    }
+
+    /// RDF 1.2 quoted triple `<< s p o >>`, the value is the text of the inner triple.
+    pub fn quoted_triple(
+        subject: MyTerm<'a>,
+        predicate: MyTerm<'a>,
+        object: MyTerm<'a>,
+        span: std::ops::Range<usize>,
+    ) -> Self {
+        Self {
+            value: format!("{} {} {}", subject, predicate, object).into(),
+            ty: TermKind::Triple.into(),
+            inner: Some(Box::new([subject, predicate, object])),
             span,
         }
     }
 
+    /// Subject, predicate and object if this term is a quoted triple.
+    pub fn quoted(&self) -> Option<&[MyTerm<'a>; 3]> {
+        self.inner.as_deref()
+    }
+
     pub fn as_str(&'a self) -> &'a str {
         &self.value
     }
@@ -321,14 +410,14 @@ impl<'a> Term for MyTerm<'a> {
     }
 
     fn triple(&self) -> Option<[Self::BorrowTerm<'_>; 3]> {
-        panic!("MyTerm does not supported triples")
+        self.quoted().map(|[s, p, o]| [s, p, o])
     }
 
     fn to_triple(self) -> Option<[Self; 3]>
     where
         Self: Sized,
     {
-        panic!("MyTerm does not supported triples")
+        self.inner.map(|x| *x)
     }
 }
 
@@ -336,17 +425,21 @@ impl<'a> Term for MyTerm<'a> {
 pub struct Triples2<'a> {
     pub base_url: String,
     pub triples: Vec<MyQuad<'a>>,
+    /// Triples that are mentioned but not asserted, see [`UnassertedTriples`].
+    pub unasserted: Vec<MyQuad<'a>>,
     pub base: Option<MyTerm<'a>>,
 }
 
 impl<'a> Triples2<'a> {
     pub fn to_owned(&self) -> Triples2<'static> {
         let triples = self.triples.iter().map(|q| q.to_owned()).collect();
+        let unasserted = self.unasserted.iter().map(|q| q.to_owned()).collect();
         let base: Option<MyTerm<'static>> = self.base.as_ref().map(|x| x.to_owned());
 
         Triples2 {
             base,
             triples,
+            unasserted,
             base_url: self.base_url.clone(),
         }
     }
@@ -0,0 +1,140 @@
+use std::collections::HashSet;
+
+use bevy_ecs::prelude::*;
+use lsp_core::{
+    components::*,
+    prelude::*,
+    systems::{DefinedClasses, DefinedProperties},
+};
+use lsp_types::{CompletionItemKind, TextEdit};
+use tracing::debug;
+
+use crate::lang::context::TermDefinitions;
+
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+
+/// Whether the document at `label` is linked from the current document.
+pub(crate) fn is_linked(links: &DocumentLinks, label: &Label, this_label: &Label) -> bool {
+    label.0 == this_label.0
+        || links
+            .iter()
+            .any(|link| link.0.as_str().starts_with(label.0.as_str()))
+}
+
+/// Complete keys with the terms of the active context.
+///
+/// Terms that look like a prefix (ending with `/` or `#`) are skipped, compact iris with these
+/// prefixes are completed by [`complete_properties`](lsp_core::systems::complete_properties).
+pub fn complete_terms(
+    mut query: Query<(
+        &TokenComponent,
+        &TripleComponent,
+        &TermDefinitions,
+        &DocumentLinks,
+        &Label,
+        &mut CompletionRequest,
+    )>,
+    other: Query<(&Label, &Wrapped<DefinedProperties>)>,
+) {
+    for (token, triple, terms, links, this_label, mut request) in &mut query {
+        if triple.target != TripleTarget::Predicate {
+            continue;
+        }
+
+        let mut seen = HashSet::new();
+        for def in terms.terms.iter().rev() {
+            if !seen.insert(def.term.as_str()) {
+                continue;
+            }
+            if def.iri.ends_with(['/', '#']) || !def.term.starts_with(&token.text) {
+                continue;
+            }
+
+            let kind = if def.iri.starts_with('@') {
+                CompletionItemKind::KEYWORD
+            } else {
+                CompletionItemKind::PROPERTY
+            };
+
+            let comment = other
+                .iter()
+                .filter(|(label, _)| is_linked(links, label, this_label))
+                .flat_map(|(_, properties)| properties.0.iter())
+                .find(|x| x.predicate.value == def.iri)
+                .map(|x| x.comment.clone());
+
+            debug!("Term {} completes {}", def.term, token.text);
+            request.push(
+                SimpleCompletion::new(
+                    kind,
+                    def.term.clone(),
+                    TextEdit {
+                        range: token.range.clone(),
+                        new_text: def.term.clone(),
+                    },
+                )
+                .label_description(&def.iri)
+                .m_documentation(comment),
+            );
+        }
+    }
+}
+
+/// Complete `@type` values with the classes of linked documents that are defined as term, or
+/// that are relative to `@vocab`.
+pub fn complete_type_terms(
+    mut query: Query<(
+        &TokenComponent,
+        &TripleComponent,
+        &TermDefinitions,
+        &DocumentLinks,
+        &Label,
+        &mut CompletionRequest,
+    )>,
+    other: Query<(&Label, &Wrapped<DefinedClasses>)>,
+) {
+    for (token, triple, terms, links, this_label, mut request) in &mut query {
+        if triple.triple.predicate.value != RDF_TYPE || triple.target != TripleTarget::Object {
+            continue;
+        }
+
+        for (label, classes) in &other {
+            if !is_linked(links, label, this_label) {
+                continue;
+            }
+
+            for class in classes.0.iter() {
+                let iri: &str = &class.term.value;
+                let term = terms
+                    .terms
+                    .iter()
+                    .rev()
+                    .find(|x| !x.reverse && x.iri == iri)
+                    .map(|x| x.term.as_str())
+                    .or_else(|| {
+                        terms
+                            .vocab
+                            .as_ref()
+                            .and_then(|vocab| iri.strip_prefix(vocab.as_str()))
+                    });
+
+                let Some(term) = term.filter(|x| x.starts_with(&token.text)) else {
+                    continue;
+                };
+
+                request.push(
+                    SimpleCompletion::new(
+                        CompletionItemKind::CLASS,
+                        term.to_string(),
+                        TextEdit {
+                            range: token.range.clone(),
+                            new_text: term.to_string(),
+                        },
+                    )
+                    .label_description(iri)
+                    .documentation(&class.comment),
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+
+use bevy_ecs::{prelude::*, world::CommandQueue};
+use lsp_core::prelude::*;
+use tracing::{debug, error, info, instrument};
+
+use crate::lang::{
+    context::{ContextChanged, ContextLinks, RemoteContext, RemoteContexts},
+    parser::parse,
+    tokenizer::tokenize,
+};
+
+type Sender = futures::channel::mpsc::UnboundedSender<CommandQueue>;
+
+/// Location of the cached copy of the remote context at `url`.
+fn context_cache_url(fs: &Fs, url: &lsp_types::Url) -> Option<lsp_types::Url> {
+    let name: String = url
+        .as_str()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    fs.0.virtual_url(&format!("contexts/{}.jsonld", name))
+}
+
+/// Fetch the remote contexts linked from JSON-LD documents.
+///
+/// Contexts are read from the cache first, fetched contexts are written to the cache.
+/// When a context is loaded, the documents linking to it are derived again, and contexts it
+/// refers to are fetched in turn.
+/// Contexts that failed to load are fetched again when the configuration changes.
+#[instrument(skip(query, contexts, sender, client, fs, config))]
+pub fn fetch_remote_contexts<C: Client + Resource>(
+    query: Query<Ref<ContextLinks>>,
+    mut contexts: ResMut<RemoteContexts>,
+    sender: Res<CommandSender>,
+    client: Res<C>,
+    fs: Res<Fs>,
+    config: Res<ServerConfig>,
+) {
+    let retry = config.is_changed();
+    if retry {
+        // Failed contexts are not used, forgetting them does not change the processed contexts
+        contexts
+            .bypass_change_detection()
+            .0
+            .retain(|_, context| !matches!(context, RemoteContext::Failed));
+    }
+
+    for links in &query {
+        if !retry && !links.is_changed() {
+            continue;
+        }
+        for url in &links.0 {
+            if contexts.0.contains_key(url.as_str()) {
+                continue;
+            }
+
+            // Pending contexts do not change the processed contexts
+            contexts
+                .bypass_change_detection()
+                .0
+                .insert(url.to_string(), RemoteContext::Pending);
+
+            debug!("Fetching context {}", url);
+            client.spawn(fetch_context(
+                url.clone(),
+                config.config.offline,
+                client.as_ref().clone(),
+                fs.clone(),
+                sender.0.clone(),
+            ));
+        }
+    }
+}
+
+async fn fetch_context<C: Client + Resource>(
+    url: lsp_types::Url,
+    offline: bool,
+    c: C,
+    fs: Fs,
+    sender: Sender,
+) {
+    let remote = url.scheme().starts_with("http");
+    // Local contexts are not cached
+    let cache = remote.then(|| context_cache_url(&fs, &url)).flatten();
+
+    let mut content = None;
+    if let Some(cache) = &cache {
+        content = fs.0.read_file(cache).await;
+    }
+    let cached = content.is_some();
+
+    if !cached {
+        if offline && remote {
+            info!("Offline, not fetching context {}", url);
+            // Not failed, the context is fetched when going online
+            let mut command_queue = CommandQueue::default();
+            command_queue.push(move |world: &mut World| {
+                world
+                    .resource_mut::<RemoteContexts>()
+                    .bypass_change_detection()
+                    .0
+                    .remove(url.as_str());
+            });
+            let _ = sender.unbounded_send(command_queue);
+            return;
+        } else if remote {
+            let mut headers = HashMap::new();
+            headers.insert(
+                "Accept".to_string(),
+                "application/ld+json, application/json".to_string(),
+            );
+            match c.fetch(url.as_str(), &headers).await {
+                Ok(resp) if resp.status == 200 => content = Some(resp.body),
+                Ok(resp) => error!("Fetch ({}) failed status {}", url, resp.status),
+                Err(e) => error!("Fetch ({}) failed {:?}", url, e),
+            }
+        } else {
+            content = fs.0.read_file(&url).await;
+        }
+    }
+
+    let context = match content {
+        Some(content) => {
+            let (tokens, token_errors) = tokenize(&content);
+            let (json, errors) = parse(&content, tokens);
+            if token_errors.is_empty() && errors.is_empty() {
+                if let Some(cache) = cache.as_ref().filter(|_| !cached) {
+                    fs.0.write_file(cache, &content).await;
+                }
+                RemoteContext::Loaded(json)
+            } else {
+                error!("Context {} is not valid json", url);
+                RemoteContext::Failed
+            }
+        }
+        None => RemoteContext::Failed,
+    };
+
+    let mut command_queue = CommandQueue::default();
+    command_queue.push(move |world: &mut World| {
+        world
+            .resource_mut::<RemoteContexts>()
+            .0
+            .insert(url.to_string(), context);
+
+        let linked: Vec<_> = world
+            .query::<(Entity, &ContextLinks)>()
+            .iter(world)
+            .filter(|(_, links)| links.0.contains(&url))
+            .map(|(e, _)| e)
+            .collect();
+        for e in linked {
+            world.entity_mut(e).insert(ContextChanged);
+        }
+        world.run_schedule(ParseLabel);
+    });
+    let _ = sender.unbounded_send(command_queue);
+}
@@ -0,0 +1,123 @@
+use bevy_ecs::prelude::*;
+use lsp_core::{components::*, prelude::*};
+use lsp_types::{Position, Range};
+use tracing::info;
+
+use crate::{lang::formatter::format_jsonld, JsonLd};
+
+pub fn format_jsonld_system(
+    mut query: Query<(&RopeC, &Element<JsonLd>, &mut FormatRequest), Without<Dirty>>,
+    config: Res<ServerConfig>,
+) {
+    info!("Format JSON-LD system");
+    let format = &config.config.format;
+    if !format.enabled {
+        return;
+    }
+
+    for (source, json, mut request) in &mut query {
+        if request.0.is_some() {
+            info!("Didn't format with the JSON-LD format system, already formatted");
+            continue;
+        }
+        info!("Formatting with JSON-LD format system");
+
+        let formatted = format_jsonld(
+            json.0.value(),
+            lsp_types::FormattingOptions {
+                tab_size: format.tab_size,
+                insert_spaces: true,
+                ..Default::default()
+            },
+            format.jsonld_sort_keys,
+        );
+
+        request.0 = formatted.map(|x| {
+            vec![lsp_types::TextEdit::new(
+                Range::new(
+                    Position::new(0, 0),
+                    Position::new(source.0.len_lines() as u32 + 1, 0),
+                ),
+                x,
+            )]
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use lsp_core::prelude::FormatLabel;
+    use ropey::Rope;
+    use test_utils::{create_file, setup_world, TestClient};
+
+    use super::*;
+
+    #[test]
+    fn format_does_it() {
+        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world::<TestClient>);
+
+        let entity = create_file(
+            &mut world,
+            r#"{"name": "Arthur", "@id": "http://example.com/ns#me"}"#,
+            "http://example.com/ns#",
+            "jsonld",
+            Open,
+        );
+
+        world.entity_mut(entity).insert(FormatRequest(None));
+        world.run_schedule(FormatLabel);
+        let m_formatted: Option<FormatRequest> = world.entity_mut(entity).take();
+        let m_formatted = m_formatted.and_then(|x| x.0);
+
+        assert!(m_formatted.is_some());
+        let formatted = &m_formatted.unwrap()[0].new_text;
+        assert_eq!(
+            formatted,
+            "{\n  \"name\": \"Arthur\",\n  \"@id\": \"http://example.com/ns#me\"\n}\n"
+        );
+
+        world
+            .resource_mut::<ServerConfig>()
+            .config
+            .format
+            .jsonld_sort_keys = true;
+        world.entity_mut(entity).insert(FormatRequest(None));
+        world.run_schedule(FormatLabel);
+        let formatted = world
+            .entity_mut(entity)
+            .take::<FormatRequest>()
+            .and_then(|x| x.0)
+            .expect("formatted");
+        assert_eq!(
+            formatted[0].new_text,
+            "{\n  \"@id\": \"http://example.com/ns#me\",\n  \"name\": \"Arthur\"\n}\n"
+        );
+    }
+
+    #[test]
+    fn dirty_documents_are_not_formatted() {
+        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world::<TestClient>);
+
+        let entity = create_file(
+            &mut world,
+            r#"{"name": "Arthur"}"#,
+            "http://example.com/ns#",
+            "jsonld",
+            Open,
+        );
+
+        let t2 = r#"{"name": "Arthur", "foaf"}"#;
+        world
+            .entity_mut(entity)
+            .insert((Source(t2.to_string()), RopeC(Rope::from_str(t2))));
+        world.run_schedule(ParseLabel);
+
+        world.entity_mut(entity).insert(FormatRequest(None));
+        world.run_schedule(FormatLabel);
+        let formatted = world
+            .entity_mut(entity)
+            .take::<FormatRequest>()
+            .and_then(|x| x.0);
+        assert!(formatted.is_none());
+    }
+}
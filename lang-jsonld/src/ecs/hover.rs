@@ -0,0 +1,66 @@
+use bevy_ecs::prelude::*;
+use lsp_core::{
+    components::*,
+    prelude::*,
+    systems::{DefinedClasses, DefinedProperties},
+};
+
+use super::completion::is_linked;
+use crate::lang::{context::TermDefinitions, triples::get_str};
+
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+
+/// Hover keys and `@type` values with their expanded iri, and the label and comment of the
+/// property or class in linked documents.
+pub fn hover_terms(
+    mut query: Query<(
+        &TokenComponent,
+        &TripleComponent,
+        &TermDefinitions,
+        &DocumentLinks,
+        &Label,
+        &mut HoverRequest,
+    )>,
+    properties: Query<(&Label, &Wrapped<DefinedProperties>)>,
+    classes: Query<(&Label, &Wrapped<DefinedClasses>)>,
+) {
+    for (token, triple, terms, links, this_label, mut request) in &mut query {
+        let is_type = triple.triple.predicate.value == RDF_TYPE;
+        let is_key = triple.target == TripleTarget::Predicate;
+        if !is_key && !(is_type && triple.target == TripleTarget::Object) {
+            continue;
+        }
+
+        let Some(text) = get_str(token.token.value()) else {
+            continue;
+        };
+        if terms.keyword(text).is_some() {
+            continue;
+        }
+        let Some(iri) = terms.expand_vocab(text) else {
+            continue;
+        };
+
+        request.0.push(format!("IRI: <{}>", iri));
+
+        if is_key {
+            for (label, properties) in &properties {
+                if !is_linked(links, label, this_label) {
+                    continue;
+                }
+                for p in properties.iter().filter(|p| p.predicate.value == iri) {
+                    request.0.push(format!("{}: {}", p.label, p.comment));
+                }
+            }
+        } else {
+            for (label, classes) in &classes {
+                if !is_linked(links, label, this_label) {
+                    continue;
+                }
+                for c in classes.iter().filter(|c| c.term.value == iri) {
+                    request.0.push(format!("{}: {}", c.label, c.comment));
+                }
+            }
+        }
+    }
+}
@@ -1,14 +1,27 @@
-use bevy_ecs::{schedule::IntoSystemConfigs as _, world::World};
+use bevy_ecs::{schedule::IntoSystemConfigs as _, system::Resource, world::World};
 use lsp_core::prelude::*;
 mod highlight;
 pub use highlight::*;
 
+mod completion;
+pub use completion::{complete_terms, complete_type_terms};
+mod context;
+pub use context::fetch_remote_contexts;
+mod format;
+pub use format::format_jsonld_system;
+mod hover;
+pub use hover::hover_terms;
 mod parse;
 use parse::derive_triples;
 pub use parse::{parse_jsonld_system, parse_source};
+mod symbol;
+pub use symbol::jsonld_document_symbols;
 
-pub fn setup_parse(world: &mut World) {
+use crate::lang::context::RemoteContexts;
+
+pub fn setup_parse<C: Client + Resource>(world: &mut World) {
     use lsp_core::prelude::parse::*;
+    world.init_resource::<RemoteContexts>();
     world.schedule_scope(ParseLabel, |_, schedule| {
         schedule.add_systems((
             parse_source,
@@ -17,24 +30,61 @@ pub fn setup_parse(world: &mut World) {
                 .after(parse_jsonld_system)
                 .before(triples)
                 .before(prefixes),
+            fetch_remote_contexts::<C>.after(derive_triples),
         ));
     });
 }
 
+pub fn setup_completion(world: &mut World) {
+    use lsp_core::feature::completion::*;
+    world.schedule_scope(CompletionLabel, |_, schedule| {
+        schedule.add_systems((
+            complete_terms.after(get_current_triple),
+            complete_type_terms.after(get_current_triple),
+        ));
+    });
+}
+
+pub fn setup_hover(world: &mut World) {
+    use lsp_core::feature::hover::*;
+    world.schedule_scope(HoverLabel, |_, schedule| {
+        schedule.add_systems(hover_terms.after(get_current_triple));
+    });
+}
+
+pub fn setup_formatting(world: &mut World) {
+    world.schedule_scope(FormatLabel, |_, schedule| {
+        schedule.add_systems(format_jsonld_system);
+    });
+}
+
+pub fn setup_document_symbol(world: &mut World) {
+    world.schedule_scope(DocumentSymbolLabel, |_, schedule| {
+        schedule.add_systems(jsonld_document_symbols);
+    });
+}
+
 #[cfg(test)]
 mod tests {
     use completion::CompletionRequest;
     use futures::executor::block_on;
-    use lsp_core::{components::*, prelude::*, util::lsp_range_to_range, Tasks};
+    use lsp_core::{
+        components::*, feature::diagnostics::pull_diagnostics, prelude::*, systems,
+        util::lsp_range_to_range, Tasks,
+    };
+    use lsp_types::CompletionItemKind;
     use ropey::Rope;
     use test_utils::{create_file, setup_world, TestClient};
     use tracing::info;
 
-    use crate::JsonLd;
+    use crate::{
+        lang::context::{ContextChanged, ContextLinks, RemoteContexts, TermDefinitions},
+        JsonLd,
+    };
 
     #[test]
     fn parse_works() {
-        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world);
+        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world::<TestClient>);
 
         let t1 = r#"
 {
@@ -62,7 +112,7 @@ mod tests {
 
     #[test_log::test]
     fn current_triple_works() {
-        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world);
+        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world::<TestClient>);
 
         let t1 = r#"{
     "@context" : { "foaf": "http://xmlns.com/foaf/0.1/" },
@@ -115,7 +165,7 @@ mod tests {
 
     #[test_log::test]
     fn current_triple_works_2() {
-        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world);
+        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world::<TestClient>);
 
         let t1 = r#"{
   "@context": {
@@ -152,7 +202,7 @@ mod tests {
 
     #[test_log::test]
     fn current_triple_works_corrupt() {
-        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world);
+        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world::<TestClient>);
         lang_turtle::setup_world(&mut world);
 
         let t1 = r#"{
@@ -212,12 +262,13 @@ mod tests {
             info!("comp {} {} -> {}", comp.label, txt, comp.edits[0].new_text);
         }
 
-        assert_eq!(comppletions.len(), 63);
+        // Properties of foaf and the JSON-LD keywords
+        assert_eq!(comppletions.len(), 75);
     }
 
     #[test_log::test]
     fn current_triple_works_corrupt_bn() {
-        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world);
+        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world::<TestClient>);
         lang_turtle::setup_world(&mut world);
 
         let t1 = r#"{
@@ -276,6 +327,304 @@ mod tests {
             info!("comp {} {} -> {}", comp.label, txt, comp.edits[0].new_text);
         }
 
-        assert_eq!(comppletions.len(), 63);
+        // Properties of foaf and the JSON-LD keywords
+        assert_eq!(comppletions.len(), 75);
+    }
+
+    #[test_log::test]
+    fn remote_contexts_are_fetched() {
+        let mut client = TestClient::new();
+        client.add_res(
+            "http://example.com/context.jsonld",
+            r#"{ "@context": [ "foaf.jsonld", { "ex": "http://example.com/ns#" } ] }"#,
+        );
+        client.add_res(
+            "http://example.com/foaf.jsonld",
+            r#"{ "@context": { "foaf": "http://xmlns.com/foaf/0.1/", "name": "foaf:name" } }"#,
+        );
+        client.add_res(
+            "http://example.com/other-context.jsonld",
+            r#"{ "@context": { "ex": "http://example.com/ns#" } }"#,
+        );
+        let (mut world, _) = setup_world(client, crate::setup_world::<TestClient>);
+        let c = world.resource::<TestClient>().clone();
+
+        // Documents are only derived again when a context they link to is loaded
+        let t2 = r#"{
+    "@context": "http://example.com/other-context.jsonld",
+    "@id": "ex:other"
+}"#;
+        let other = create_file(
+            &mut world,
+            t2,
+            "http://example.com/other.jsonld",
+            "jsonld",
+            Open,
+        );
+        block_on(c.await_futures(|| world.run_schedule(Tasks)));
+        let other_tick = world
+            .entity(other)
+            .get_ref::<Triples>()
+            .expect("triples exists")
+            .last_changed();
+
+        let t1 = r#"{
+    "@context": "http://example.com/context.jsonld",
+    "@id": "ex:me",
+    "name": "Arthur"
+}"#;
+        let entity = create_file(
+            &mut world,
+            t1,
+            "http://example.com/doc.jsonld",
+            "jsonld",
+            Open,
+        );
+
+        block_on(c.await_futures(|| world.run_schedule(Tasks)));
+
+        assert_eq!(
+            world
+                .entity(other)
+                .get_ref::<Triples>()
+                .expect("triples exists")
+                .last_changed(),
+            other_tick
+        );
+        assert!(!world.entity(entity).contains::<ContextChanged>());
+
+        let links = world
+            .entity(entity)
+            .get::<ContextLinks>()
+            .expect("context links");
+        assert_eq!(links.0.len(), 2);
+
+        let terms = world
+            .entity(entity)
+            .get::<TermDefinitions>()
+            .expect("term definitions");
+        assert_eq!(
+            terms.get("name").map(|x| x.iri.as_str()),
+            Some("http://xmlns.com/foaf/0.1/name")
+        );
+
+        let prefixes = world.entity(entity).get::<Prefixes>().expect("prefixes");
+        assert!(prefixes.iter().any(|x| x.prefix == "ex"));
+        assert!(prefixes.iter().any(|x| x.prefix == "foaf"));
+
+        let triples = world
+            .entity(entity)
+            .get::<Triples>()
+            .expect("triples exists");
+        assert!(triples
+            .0
+            .iter()
+            .any(|t| t.subject.value == "http://example.com/ns#me"
+                && t.predicate.value == "http://xmlns.com/foaf/0.1/name"));
+    }
+
+    #[test_log::test]
+    fn offline_contexts_are_fetched_when_online() {
+        let mut client = TestClient::new();
+        client.add_res(
+            "http://example.com/context.jsonld",
+            r#"{ "@context": { "name": "http://xmlns.com/foaf/0.1/name" } }"#,
+        );
+        let (mut world, _) = setup_world(client, crate::setup_world::<TestClient>);
+        let c = world.resource::<TestClient>().clone();
+        world.resource_mut::<ServerConfig>().config.offline = true;
+
+        let t1 = r#"{
+    "@context": "http://example.com/context.jsonld",
+    "name": "Arthur"
+}"#;
+        let entity = create_file(
+            &mut world,
+            t1,
+            "http://example.com/doc.jsonld",
+            "jsonld",
+            Open,
+        );
+        block_on(c.await_futures(|| world.run_schedule(Tasks)));
+
+        assert!(!world
+            .resource::<RemoteContexts>()
+            .0
+            .contains_key("http://example.com/context.jsonld"));
+        let terms = world
+            .entity(entity)
+            .get::<TermDefinitions>()
+            .expect("term definitions");
+        assert!(terms.get("name").is_none());
+
+        systems::apply_config(Config::default())(&mut world);
+        block_on(c.await_futures(|| world.run_schedule(Tasks)));
+
+        let terms = world
+            .entity(entity)
+            .get::<TermDefinitions>()
+            .expect("term definitions");
+        assert_eq!(
+            terms.get("name").map(|x| x.iri.as_str()),
+            Some("http://xmlns.com/foaf/0.1/name")
+        );
+    }
+
+    #[test_log::test]
+    fn terms_are_completed_and_hovered() {
+        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world::<TestClient>);
+        lang_turtle::setup_world(&mut world);
+
+        let t1 = r#"{
+    "@context" : { "foaf": "http://xmlns.com/foaf/0.1/", "name": "foaf:name", "Person": "foaf:Person" },
+    "@type": "Person",
+    "name": "Arthur"
+}"#;
+
+        let t2 = r#"{
+    "@context" : { "foaf": "http://xmlns.com/foaf/0.1/", "name": "foaf:name", "Person": "foaf:Person" },
+    "@type": "Pers",
+    "na"
+}"#;
+        let entity = create_file(&mut world, t1, "http://example.com/ns#", "jsonld", Open);
+
+        let c = world.resource::<TestClient>().clone();
+        block_on(c.await_futures(|| world.run_schedule(Tasks)));
+
+        world.entity_mut(entity).insert(HoverRequest::default());
+        world
+            .entity_mut(entity)
+            .insert(PositionComponent(lsp_types::Position {
+                line: 3,
+                character: 7,
+            }));
+        world.run_schedule(HoverLabel);
+        let hover = world
+            .entity_mut(entity)
+            .take::<HoverRequest>()
+            .expect("hover request");
+        assert!(hover
+            .0
+            .iter()
+            .any(|x| x == "IRI: <http://xmlns.com/foaf/0.1/name>"));
+        assert!(hover.0.iter().any(|x| x.starts_with("name: ")));
+
+        world
+            .entity_mut(entity)
+            .insert((Source(t2.to_string()), RopeC(Rope::from_str(t2)), Open));
+        world.run_schedule(ParseLabel);
+
+        let mut complete = |line, character| {
+            world.entity_mut(entity).insert((
+                CompletionRequest(vec![]),
+                PositionComponent(lsp_types::Position { line, character }),
+            ));
+            world.run_schedule(CompletionLabel);
+            world
+                .entity_mut(entity)
+                .take::<CompletionRequest>()
+                .expect("completion request")
+                .0
+        };
+
+        let completions = complete(3, 6);
+        let name = completions
+            .iter()
+            .find(|x| x.label == "name")
+            .expect("name completion");
+        assert_eq!(name.kind, CompletionItemKind::PROPERTY);
+        assert!(completions.iter().any(|x| x.label == "@id"));
+
+        let completions = complete(2, 15);
+        let person = completions
+            .iter()
+            .find(|x| x.label == "Person")
+            .expect("person completion");
+        assert_eq!(person.kind, CompletionItemKind::CLASS);
+    }
+
+    #[test]
+    fn embedded_in_html() {
+        let (mut world, mut rx) = setup_world(TestClient::new(), crate::setup_world::<TestClient>);
+
+        let html = r#"<html>
+<script type="application/ld+json">
+{
+    "@context" : { "foaf": "http://xmlns.com/foaf/0.1/", "name": "foaf:name" },
+    "@id": "http://example.com/ns#me",
+    "name": "Arthur"
+}
+</script>
+<script type="application/ld+json">{"@id": </script>
+</html>"#;
+        let entity = create_file(
+            &mut world,
+            html,
+            "http://example.com/index.html",
+            "html",
+            Open,
+        );
+        systems::sync_snippets(entity)(&mut world);
+
+        let c = world.resource::<TestClient>().clone();
+        block_on(c.await_futures(|| world.run_schedule(Tasks)));
+
+        let snippets = world
+            .entity(entity)
+            .get::<systems::Snippets>()
+            .expect("snippets")
+            .0
+            .clone();
+        assert_eq!(snippets.len(), 2);
+        let triples = world
+            .entity(snippets[0])
+            .get::<Triples>()
+            .expect("triples exists");
+        assert_eq!(triples.0.len(), 1);
+
+        let (snippet, _, pos) = systems::snippet_at(
+            &world,
+            entity,
+            lsp_types::Position {
+                line: 5,
+                character: 7,
+            },
+        )
+        .expect("snippet at position");
+        assert_eq!(snippet, snippets[0]);
+        assert_eq!(pos, lsp_types::Position::new(4, 7));
+
+        world
+            .entity_mut(snippet)
+            .insert((HoverRequest::default(), PositionComponent(pos)));
+        world.run_schedule(HoverLabel);
+        let hover = world
+            .entity_mut(snippet)
+            .take::<HoverRequest>()
+            .expect("hover request");
+        assert!(hover
+            .0
+            .iter()
+            .any(|x| x == "IRI: <http://xmlns.com/foaf/0.1/name>"));
+
+        let diagnostics = pull_diagnostics(&mut world, &[entity]).pop().unwrap();
+        assert!(!diagnostics.is_empty());
+        assert!(diagnostics.iter().any(|x| x.range.start.line == 8));
+        assert!(diagnostics.iter().all(|x| x.range.start.line >= 2));
+
+        // Pushed diagnostics of the host are the pulled ones, severities are applied once
+        let mut pushed = None;
+        while let Ok(Some(item)) = rx.try_next() {
+            if item.uri.as_str() == "http://example.com/index.html" {
+                pushed = Some(item.diagnostics);
+            }
+        }
+        let pushed = pushed.expect("pushed diagnostics");
+        assert_eq!(pushed.len(), diagnostics.len());
+        assert!(pushed.iter().all(|x| diagnostics.contains(x)));
+
+        let tokens = systems::snippet_semantic_tokens(&mut world, entity).expect("host document");
+        assert!(!tokens.is_empty());
+        assert!(tokens[0].delta_line >= 2);
     }
 }
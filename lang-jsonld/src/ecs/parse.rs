@@ -1,12 +1,17 @@
 use bevy_ecs::prelude::*;
 use lsp_core::{
     components::*,
-    prelude::{Tokens, Triples},
+    prelude::{Tokens, Triples, UnassertedTriples},
 };
 use tracing::{info, instrument};
 
 use crate::{
-    lang::{parser::parse, tokenizer::tokenize, triples},
+    lang::{
+        context::{process_contexts, ContextChanged, RemoteContexts},
+        parser::parse,
+        tokenizer::tokenize,
+        triples,
+    },
     JsonLd,
 };
 
@@ -47,14 +52,31 @@ pub fn parse_jsonld_system(
     }
 }
 
-#[instrument(skip(query, commands))]
+/// Derive prefixes, term definitions and triples, also when a remote context the document
+/// links to is loaded.
+#[instrument(skip(query, contexts, commands))]
 pub fn derive_triples(
-    query: Query<(Entity, &Label, &Element<JsonLd>), Changed<Element<JsonLd>>>,
+    query: Query<(Entity, &Label, Ref<Element<JsonLd>>, Has<ContextChanged>)>,
+    contexts: Res<RemoteContexts>,
     mut commands: Commands,
 ) {
-    for (e, l, el) in &query {
-        let prefix = triples::derive_prefixes(&el, &l.0);
-        let triples = triples::derive_triples(&el, &prefix);
-        commands.entity(e).insert((Triples(triples), prefix));
+    for (e, l, el, context_changed) in &query {
+        if !el.is_changed() && !context_changed {
+            continue;
+        }
+
+        let active = process_contexts(&el, &l.0, &contexts);
+        let prefix = triples::derive_prefixes(&active.terms, &l.0);
+        let (triples, unasserted) = triples::derive_triples(&el, &l.0, &contexts);
+        commands
+            .entity(e)
+            .insert((
+                Triples(triples),
+                UnassertedTriples(unasserted),
+                prefix,
+                active.terms,
+                active.links,
+            ))
+            .remove::<ContextChanged>();
     }
 }
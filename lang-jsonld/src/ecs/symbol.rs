@@ -0,0 +1,151 @@
+use std::ops::Range;
+
+use bevy_ecs::prelude::*;
+use lsp_core::{feature::document_symbol::document_symbol, prelude::*};
+use lsp_types::{DocumentSymbol, SymbolKind};
+use ropey::Rope;
+
+use crate::{
+    lang::parser::{Json, ObjectMember},
+    JsonLd,
+};
+
+fn get_str(json: &Json) -> Option<&str> {
+    match json.token()? {
+        Token::Str(x, _) => Some(x),
+        _ => None,
+    }
+}
+
+fn node_symbols(json: &Spanned<Json>, rope: &Rope, out: &mut Vec<DocumentSymbol>) {
+    match json.value() {
+        Json::Array(xs) => xs.iter().for_each(|x| node_symbols(x, rope, out)),
+        Json::Object(members) => object_symbols(members, json.span(), rope, out),
+        _ => {}
+    }
+}
+
+fn object_symbols(
+    members: &[Spanned<ObjectMember>],
+    span: &Range<usize>,
+    rope: &Rope,
+    out: &mut Vec<DocumentSymbol>,
+) {
+    let mut id = None;
+    let mut types = Vec::new();
+    let mut children = Vec::new();
+    let mut has_properties = false;
+
+    for member in members {
+        let Token::Str(key, _) = member.field().value() else {
+            continue;
+        };
+        let Some(value) = member.json_value() else {
+            continue;
+        };
+
+        match key.as_str() {
+            "@context" => {}
+            "@id" => id = value.try_map_ref(get_str),
+            "@type" => match value.value() {
+                Json::Array(xs) => types.extend(xs.iter().flat_map(|x| get_str(x))),
+                x => types.extend(get_str(x)),
+            },
+            "@graph" => {
+                let mut graph = Vec::new();
+                node_symbols(value, rope, &mut graph);
+                if let Some(mut symbol) = document_symbol(
+                    "@graph",
+                    SymbolKind::NAMESPACE,
+                    member.span(),
+                    member.field().span(),
+                    rope,
+                ) {
+                    symbol.children = Some(graph);
+                    children.push(symbol);
+                }
+            }
+            _ => {
+                has_properties = true;
+                node_symbols(value, rope, &mut children);
+            }
+        }
+    }
+
+    // An object without identity and properties only wraps its graph, like the top level object
+    if id.is_none() && types.is_empty() && !has_properties {
+        out.extend(children);
+        return;
+    }
+
+    let (name, selection) = match id {
+        Some(Spanned(id, span)) => (id.to_string(), span),
+        None => (String::from("(blank node)"), span.start..span.start + 1),
+    };
+
+    if let Some(mut symbol) = document_symbol(name, SymbolKind::OBJECT, span, &selection, rope) {
+        if !types.is_empty() {
+            symbol.detail = Some(types.join(", "));
+        }
+        symbol.children = Some(children);
+        out.push(symbol);
+    }
+}
+
+pub fn jsonld_document_symbols(
+    mut query: Query<(&Element<JsonLd>, &RopeC, &mut DocumentSymbolRequest)>,
+) {
+    for (json, rope, mut req) in &mut query {
+        node_symbols(&json.0, &rope.0, &mut req.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_core::{components::*, prelude::*};
+    use lsp_types::SymbolKind;
+    use test_utils::{create_file, setup_world, TestClient};
+
+    #[test_log::test]
+    fn jsonld_outline() {
+        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world::<TestClient>);
+
+        let t1 = r#"{
+  "@context": { "foaf": "http://xmlns.com/foaf/0.1/" },
+  "@graph": [
+    {
+      "@id": "http://example.com/ns#me",
+      "@type": "foaf:Person",
+      "foaf:knows": { "@id": "http://example.com/ns#you" }
+    },
+    { "foaf:name": "Julian" }
+  ]
+}"#;
+        let entity = create_file(&mut world, t1, "http://example.com/ns#", "jsonld", Open);
+
+        world
+            .entity_mut(entity)
+            .insert(DocumentSymbolRequest::default());
+        world.run_schedule(DocumentSymbolLabel);
+
+        let symbols = world
+            .entity_mut(entity)
+            .take::<DocumentSymbolRequest>()
+            .expect("document symbol request")
+            .0;
+
+        assert_eq!(symbols.len(), 1);
+        assert_eq!(symbols[0].name, "@graph");
+        assert_eq!(symbols[0].kind, SymbolKind::NAMESPACE);
+
+        let nodes = symbols[0].children.as_ref().expect("graph nodes");
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(nodes[0].name, "http://example.com/ns#me");
+        assert_eq!(nodes[0].detail.as_deref(), Some("foaf:Person"));
+        assert_eq!(nodes[1].name, "(blank node)");
+
+        let friends = nodes[0].children.as_ref().expect("nested nodes");
+        assert_eq!(friends.len(), 1);
+        assert_eq!(friends[0].name, "http://example.com/ns#you");
+    }
+}
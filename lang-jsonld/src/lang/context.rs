@@ -0,0 +1,530 @@
+use bevy_ecs::prelude::*;
+use hashbrown::HashMap;
+use lsp_core::prelude::*;
+
+use crate::lang::{
+    parser::{Json, ObjectMember},
+    triples::{find_field, get_str},
+};
+
+/// Remote contexts can refer to other contexts, stop following them at this depth.
+const MAX_DEPTH: usize = 8;
+
+/// Definition of a term in a JSON-LD `@context`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TermDefinition {
+    pub term: String,
+    /// Expanded iri of the term, compact iris are expanded with the other terms, or a keyword
+    /// when the term is an alias like `"id": "@id"`
+    pub iri: String,
+    /// Type mapping, like `@id`, `@vocab` or a datatype
+    pub ty: Option<String>,
+    /// Container mapping, like `@list`, `@set` or `@language`
+    pub container: Option<String>,
+    /// Whether the term is defined with `@reverse` instead of `@id`
+    pub reverse: bool,
+    /// Remote context that defines the term, `None` when the document itself defines it
+    pub source: Option<lsp_types::Url>,
+    /// Scoped context, applies to the values of the term or to nodes of the type
+    pub scoped: Option<Spanned<Json>>,
+}
+
+/// [`Component`] with the term definitions of the contexts of a JSON-LD document, including
+/// the definitions of remote contexts.
+#[derive(Component, Clone, Debug, Default, PartialEq)]
+pub struct TermDefinitions {
+    pub terms: Vec<TermDefinition>,
+    /// `@vocab`, expands terms that are not defined
+    pub vocab: Option<String>,
+    /// `@base`, resolved against the location of the document
+    pub base: Option<String>,
+}
+
+impl TermDefinitions {
+    /// Active definition of `term`, later definitions override earlier ones.
+    pub fn get(&self, term: &str) -> Option<&TermDefinition> {
+        self.terms.iter().rev().find(|x| x.term == term)
+    }
+
+    /// Definitions after processing the local context `ctx` on top of these definitions.
+    pub fn with_context(
+        &self,
+        ctx: &Json,
+        base: &lsp_types::Url,
+        source: Option<&lsp_types::Url>,
+        remote: &RemoteContexts,
+    ) -> TermDefinitions {
+        let mut processor = Processor {
+            remote,
+            defs: self.clone(),
+            links: Vec::new(),
+            flatten_scoped: false,
+        };
+        processor.context(ctx, base, source, 0);
+        processor.finish().terms
+    }
+
+    /// The keyword `key` stands for, keywords can be aliased like `"id": "@id"`.
+    pub fn keyword<'s>(&'s self, key: &'s str) -> Option<&'s str> {
+        if key.starts_with('@') {
+            return Some(key);
+        }
+        self.get(key)
+            .map(|x| x.iri.as_str())
+            .filter(|x| x.starts_with('@'))
+    }
+
+    /// Expand a key, `@type` value or `@vocab` typed value with the defined terms and
+    /// `@vocab`.
+    pub fn expand_vocab(&self, value: &str) -> Option<String> {
+        if let Some(def) = self.get(value) {
+            return (!def.iri.starts_with('@') && def.iri.contains(':')).then(|| def.iri.clone());
+        }
+        if value.contains(':') {
+            return Some(self.expand_compact(value));
+        }
+        self.vocab
+            .as_ref()
+            .map(|vocab| format!("{}{}", vocab, value))
+    }
+
+    /// Expand an `@id` value, relative iris are resolved against `@base` or `base`.
+    pub fn expand_id(&self, value: &str, base: &lsp_types::Url) -> Option<String> {
+        if value.contains(':') {
+            return Some(self.expand_compact(value));
+        }
+        let base = match &self.base {
+            Some(b) => base.join(b).ok()?,
+            None => base.clone(),
+        };
+        base.join(value).ok().map(|x| x.to_string())
+    }
+
+    /// Expand a compact iri like `foaf:name`, other values are absolute iris.
+    fn expand_compact(&self, value: &str) -> String {
+        if let Some((prefix, suffix)) = value.split_once(':') {
+            if !suffix.starts_with("//") {
+                if let Some(def) = self.get(prefix).filter(|x| !x.reverse) {
+                    return format!("{}{}", def.iri, suffix);
+                }
+            }
+        }
+        value.to_string()
+    }
+}
+
+/// [`Component`] with the remote contexts a JSON-LD document uses, directly or through other
+/// contexts.
+#[derive(Component, Clone, Debug, Default, PartialEq)]
+pub struct ContextLinks(pub Vec<lsp_types::Url>);
+
+/// Marker [`Component`] for documents linking to a remote context that was loaded after their
+/// triples were derived.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct ContextChanged;
+
+#[derive(Clone, Debug)]
+pub enum RemoteContext {
+    /// The context is being fetched
+    Pending,
+    Loaded(Spanned<Json>),
+    Failed,
+}
+
+/// [`Resource`] with the fetched remote contexts, by url.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct RemoteContexts(pub HashMap<String, RemoteContext>);
+
+/// The processed contexts of a document.
+#[derive(Debug)]
+pub struct ActiveContext {
+    pub terms: TermDefinitions,
+    pub links: ContextLinks,
+}
+
+impl ActiveContext {
+    /// Add the definitions and links of a nested scope, definitions of outer scopes take
+    /// precedence.
+    fn merge(&mut self, scope: &ActiveContext) {
+        for def in &scope.terms.terms {
+            if self.terms.get(&def.term).is_none() {
+                self.terms.terms.push(def.clone());
+            }
+        }
+        if self.terms.vocab.is_none() {
+            self.terms.vocab = scope.terms.vocab.clone();
+        }
+        if self.terms.base.is_none() {
+            self.terms.base = scope.terms.base.clone();
+        }
+        for link in &scope.links.0 {
+            if !self.links.0.contains(link) {
+                self.links.0.push(link.clone());
+            }
+        }
+    }
+}
+
+/// Process all `@context`s in `json`, including those of nested nodes and scoped contexts,
+/// remote contexts are looked up in `remote`.
+///
+/// The `@context` of a nested node is processed on top of the definitions of its enclosing
+/// node, so a `null` context only resets its own scope. The result merges all scopes and
+/// describes the whole document, like the terms that can be completed. Remote contexts that
+/// are not loaded yet are only added to the links.
+pub fn process_contexts(
+    json: &Spanned<Json>,
+    base: &lsp_types::Url,
+    remote: &RemoteContexts,
+) -> ActiveContext {
+    let mut active = ActiveContext {
+        terms: TermDefinitions::default(),
+        links: ContextLinks::default(),
+    };
+
+    visit_scopes(json, &TermDefinitions::default(), base, remote, &mut active);
+
+    active
+}
+
+/// Process the `@context` of every object in `json` on top of the definitions of the
+/// enclosing node `parent`, and merge each scope into `active`.
+fn visit_scopes(
+    json: &Json,
+    parent: &TermDefinitions,
+    base: &lsp_types::Url,
+    remote: &RemoteContexts,
+    active: &mut ActiveContext,
+) {
+    match json {
+        Json::Array(xs) => {
+            for x in xs {
+                visit_scopes(x, parent, base, remote, active);
+            }
+        }
+        Json::Object(members) => {
+            let scope = find_field(members, "@context").map(|(ctx, _)| {
+                let mut processor = Processor {
+                    remote,
+                    defs: parent.clone(),
+                    links: Vec::new(),
+                    flatten_scoped: true,
+                };
+                processor.context(ctx, base, None, 0);
+                let scope = processor.finish();
+                active.merge(&scope);
+                scope.terms
+            });
+            let defs = scope.as_ref().unwrap_or(parent);
+
+            for member in members {
+                if get_str(member.field()) == Some("@context") {
+                    continue;
+                }
+                if let Some(value) = member.json_value() {
+                    visit_scopes(value.value(), defs, base, remote, active);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+struct Processor<'a> {
+    remote: &'a RemoteContexts,
+    defs: TermDefinitions,
+    links: Vec<lsp_types::Url>,
+    /// Add the definitions of scoped contexts, when they are not defined otherwise
+    flatten_scoped: bool,
+}
+
+fn str_field<'a>(mem: &'a [Spanned<ObjectMember>], field: &str) -> Option<&'a str> {
+    find_field(mem, field).and_then(|(x, _)| match x.value() {
+        Json::Token(tok) => get_str(tok),
+        // Containers like `["@set", "@index"]`
+        Json::Array(xs) => xs.first().and_then(|x| x.token()).and_then(get_str),
+        _ => None,
+    })
+}
+
+impl<'a> Processor<'a> {
+    fn context(
+        &mut self,
+        ctx: &Json,
+        base: &lsp_types::Url,
+        source: Option<&lsp_types::Url>,
+        depth: usize,
+    ) {
+        if depth > MAX_DEPTH {
+            return;
+        }
+
+        match ctx {
+            Json::Array(xs) => {
+                for x in xs {
+                    self.context(x, base, source, depth);
+                }
+            }
+            // A null context resets the active context
+            Json::Token(Token::Null) => self.defs = TermDefinitions::default(),
+            Json::Token(Token::Str(iri, _)) => self.remote_context(iri, base, depth),
+            Json::Object(members) => self.definitions(members, base, source, depth),
+            _ => {}
+        }
+    }
+
+    fn remote_context(&mut self, iri: &str, base: &lsp_types::Url, depth: usize) {
+        let Ok(url) = base.join(iri) else {
+            return;
+        };
+
+        // Also guards against contexts that include each other
+        if self.links.contains(&url) {
+            return;
+        }
+        self.links.push(url.clone());
+
+        let remote = self.remote;
+        if let Some(RemoteContext::Loaded(doc)) = remote.0.get(url.as_str()) {
+            // Context documents have their context under a top level `@context`
+            if let Json::Object(members) = doc.value() {
+                if let Some((ctx, _)) = find_field(members, "@context") {
+                    self.context(ctx, &url, Some(&url), depth + 1);
+                }
+            }
+        }
+    }
+
+    fn definitions(
+        &mut self,
+        members: &[Spanned<ObjectMember>],
+        base: &lsp_types::Url,
+        source: Option<&lsp_types::Url>,
+        depth: usize,
+    ) {
+        for member in members {
+            let Some(term) = get_str(member.field()) else {
+                continue;
+            };
+            let Some(value) = member.json_value() else {
+                continue;
+            };
+            let string = value.token().and_then(get_str);
+
+            match term {
+                "@import" => {
+                    if let Some(iri) = string {
+                        self.remote_context(iri, base, depth);
+                    }
+                    continue;
+                }
+                "@vocab" => {
+                    self.defs.vocab = string.map(String::from);
+                    continue;
+                }
+                "@base" => {
+                    self.defs.base = string.map(String::from);
+                    continue;
+                }
+                // `@language`, `@version`, `@protected` and `@propagate` do not change iris
+                x if x.starts_with('@') => continue,
+                _ => {}
+            }
+
+            let mut def = TermDefinition {
+                term: term.to_string(),
+                iri: term.to_string(),
+                ty: None,
+                container: None,
+                reverse: false,
+                source: source.cloned(),
+                scoped: None,
+            };
+
+            match value.value() {
+                Json::Token(Token::Str(iri, _)) => def.iri = iri.clone(),
+                Json::Object(fields) => {
+                    if let Some(iri) = str_field(fields, "@id") {
+                        def.iri = iri.to_string();
+                    } else if let Some(iri) = str_field(fields, "@reverse") {
+                        def.iri = iri.to_string();
+                        def.reverse = true;
+                    }
+                    def.ty = str_field(fields, "@type").map(String::from);
+                    def.container = str_field(fields, "@container").map(String::from);
+
+                    if let Some((scoped, _)) = find_field(fields, "@context") {
+                        def.scoped = Some(scoped.clone());
+                        if self.flatten_scoped {
+                            self.scoped_context(scoped, base, source, depth);
+                        }
+                    }
+                }
+                // `null` explicitly leaves the term undefined
+                _ => continue,
+            }
+
+            self.defs.terms.push(def);
+        }
+    }
+
+    /// Scoped contexts only apply to the values of a term or type, their definitions are
+    /// added when the term is not defined otherwise.
+    fn scoped_context(
+        &mut self,
+        ctx: &Json,
+        base: &lsp_types::Url,
+        source: Option<&lsp_types::Url>,
+        depth: usize,
+    ) {
+        let mut scoped = Processor {
+            remote: self.remote,
+            defs: TermDefinitions::default(),
+            links: std::mem::take(&mut self.links),
+            flatten_scoped: true,
+        };
+        scoped.context(ctx, base, source, depth + 1);
+
+        self.links = scoped.links;
+        for def in scoped.defs.terms {
+            if self.defs.get(&def.term).is_none() {
+                self.defs.terms.push(def);
+            }
+        }
+    }
+
+    fn finish(self) -> ActiveContext {
+        let mut defs = self.defs;
+
+        // Expand compact iris, terms may use each other as prefix
+        for _ in 0..5 {
+            let mut changed = false;
+            for i in 0..defs.terms.len() {
+                let Some((prefix, suffix)) = defs.terms[i].iri.split_once(':') else {
+                    continue;
+                };
+                if suffix.starts_with("//") {
+                    continue;
+                }
+                let Some(expanded) = defs
+                    .get(prefix)
+                    .filter(|x| !x.reverse && x.term != defs.terms[i].term)
+                    .map(|x| format!("{}{}", x.iri, suffix))
+                else {
+                    continue;
+                };
+                defs.terms[i].iri = expanded;
+                changed = true;
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        // Terms without a colon are relative to `@vocab`
+        if let Some(vocab) = defs.vocab.take() {
+            let vocab = defs.expand_compact(&vocab);
+            for def in &mut defs.terms {
+                if !def.iri.contains(':') && !def.iri.starts_with('@') {
+                    def.iri = format!("{}{}", vocab, def.iri);
+                }
+            }
+            defs.vocab = Some(vocab);
+        }
+
+        ActiveContext {
+            terms: defs,
+            links: ContextLinks(self.links),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::{parser::parse, tokenizer::tokenize};
+
+    fn parse_json(st: &str) -> Spanned<Json> {
+        let (tok, es) = tokenize(st);
+        assert!(es.is_empty());
+        let (jsonld, es) = parse(st, tok);
+        assert!(es.is_empty());
+        jsonld
+    }
+
+    #[test]
+    fn nested_and_scoped_contexts() {
+        let base = lsp_types::Url::parse("http://example.com/doc.jsonld").unwrap();
+        let st = r#"{
+            "@context": [ "context.jsonld", { "ex": "http://example.com/ns#" } ],
+            "@id": "ex:me"
+        }"#;
+        let json = parse_json(st);
+
+        let mut remote = RemoteContexts::default();
+        let active = process_contexts(&json, &base, &remote);
+        assert_eq!(active.terms.terms.len(), 1);
+        assert_eq!(
+            active.links.0,
+            vec![lsp_types::Url::parse("http://example.com/context.jsonld").unwrap()]
+        );
+
+        let context = r#"{ "@context": {
+            "@import": "http://xmlns.com/foaf/context.jsonld",
+            "knows": { "@id": "foaf:knows", "@type": "@id", "@context": { "nick": "foaf:nick" } }
+        } }"#;
+        remote.0.insert(
+            "http://example.com/context.jsonld".to_string(),
+            RemoteContext::Loaded(parse_json(context)),
+        );
+        let foaf = r#"{ "@context": { "foaf": "http://xmlns.com/foaf/0.1/" } }"#;
+        remote.0.insert(
+            "http://xmlns.com/foaf/context.jsonld".to_string(),
+            RemoteContext::Loaded(parse_json(foaf)),
+        );
+
+        let active = process_contexts(&json, &base, &remote);
+        assert_eq!(active.links.0.len(), 2);
+
+        let knows = active.terms.get("knows").expect("knows term");
+        assert_eq!(knows.iri, "http://xmlns.com/foaf/0.1/knows");
+        assert_eq!(knows.ty.as_deref(), Some("@id"));
+        assert_eq!(
+            knows.source.as_ref().map(|x| x.as_str()),
+            Some("http://example.com/context.jsonld")
+        );
+
+        let nick = active.terms.get("nick").expect("scoped nick term");
+        assert_eq!(nick.iri, "http://xmlns.com/foaf/0.1/nick");
+
+        let ex = active.terms.get("ex").expect("ex term");
+        assert_eq!(ex.source, None);
+    }
+
+    #[test]
+    fn nested_contexts_are_scoped() {
+        let base = lsp_types::Url::parse("http://example.com/doc.jsonld").unwrap();
+        let st = r#"{
+            "@context": { "ex": "http://example.com/ns#", "name": "ex:name" },
+            "ex:knows": {
+                "@context": { "nick": "ex:nick" },
+                "ex:knows": { "@context": null, "label": "http://example.com/other#label" }
+            }
+        }"#;
+        let json = parse_json(st);
+
+        let active = process_contexts(&json, &base, &RemoteContexts::default());
+
+        let name = active
+            .terms
+            .get("name")
+            .expect("name survives the nested null");
+        assert_eq!(name.iri, "http://example.com/ns#name");
+
+        let nick = active.terms.get("nick").expect("nick term");
+        assert_eq!(nick.iri, "http://example.com/ns#nick");
+
+        let label = active.terms.get("label").expect("label term");
+        assert_eq!(label.iri, "http://example.com/other#label");
+    }
+}
@@ -0,0 +1,220 @@
+use std::io::{self, Cursor, Error, ErrorKind, Write};
+
+use lsp_core::prelude::*;
+use lsp_types::FormattingOptions;
+
+use crate::lang::parser::{Json, ObjectMember};
+
+/// Keys that come first when the keys are sorted, in this order.
+const FIRST_KEYS: &[&str] = &["@context", "@id", "@type"];
+
+pub struct JsonFormatter {
+    pub indent: String,
+    pub inc: usize,
+    /// Put `@context`, `@id` and `@type` first and sort the other keys, instead of keeping the
+    /// order of the document
+    pub sort_keys: bool,
+}
+
+fn invalid() -> Error {
+    Error::new(ErrorKind::Other, "cannot format invalid json")
+}
+
+fn key_order(key: &str) -> (usize, &str) {
+    let first = FIRST_KEYS
+        .iter()
+        .position(|x| *x == key)
+        .unwrap_or(FIRST_KEYS.len());
+    (first, key)
+}
+
+impl JsonFormatter {
+    pub fn new(options: &FormattingOptions, sort_keys: bool) -> Self {
+        let indent = if options.insert_spaces {
+            " ".repeat(options.tab_size as usize)
+        } else {
+            String::from("\t")
+        };
+
+        Self {
+            indent,
+            inc: 0,
+            sort_keys,
+        }
+    }
+
+    pub fn inc(&mut self) {
+        self.inc += 1;
+    }
+
+    pub fn decr(&mut self) {
+        self.inc -= 1;
+    }
+
+    pub fn line(&mut self, writer: &mut impl Write) -> io::Result<()> {
+        writeln!(writer)?;
+        for _ in 0..self.inc {
+            write!(writer, "{}", &self.indent)?;
+        }
+        Ok(())
+    }
+
+    fn token(&self, token: &Token, writer: &mut impl Write) -> io::Result<()> {
+        match token {
+            Token::Str(x, _) => serde_json::to_writer(&mut *writer, x)?,
+            Token::Number(x) => write!(writer, "{}", x)?,
+            Token::True => write!(writer, "true")?,
+            Token::False => write!(writer, "false")?,
+            Token::Null => write!(writer, "null")?,
+            _ => return Err(invalid()),
+        }
+        Ok(())
+    }
+
+    pub fn format(&mut self, json: &Json, writer: &mut impl Write) -> io::Result<()> {
+        match json {
+            Json::Invalid => return Err(invalid()),
+            Json::Token(t) => self.token(t, writer)?,
+            Json::Array(xs) if xs.is_empty() => write!(writer, "[]")?,
+            Json::Array(xs) => {
+                write!(writer, "[")?;
+                self.inc();
+                self.line(writer)?;
+                let mut first = true;
+                for t in xs {
+                    if !first {
+                        write!(writer, ",")?;
+                        self.line(writer)?;
+                    }
+                    self.format(&t.0, writer)?;
+                    first = false;
+                }
+                self.decr();
+                self.line(writer)?;
+                write!(writer, "]")?;
+            }
+            Json::Object(xs) if xs.is_empty() => write!(writer, "{{}}")?,
+            Json::Object(xs) => {
+                let mut members = Vec::with_capacity(xs.len());
+                for t in xs {
+                    match &t.0 {
+                        ObjectMember::Full(Spanned(Token::Str(key, _), _), value) => {
+                            members.push((key.as_str(), value))
+                        }
+                        _ => return Err(invalid()),
+                    }
+                }
+                if self.sort_keys {
+                    members.sort_by(|(a, _), (b, _)| key_order(a).cmp(&key_order(b)));
+                }
+
+                write!(writer, "{{")?;
+                self.inc();
+                self.line(writer)?;
+                let mut first = true;
+                for (key, value) in members {
+                    if !first {
+                        write!(writer, ",")?;
+                        self.line(writer)?;
+                    }
+                    serde_json::to_writer(&mut *writer, key)?;
+                    write!(writer, ": ")?;
+                    self.format(value, writer)?;
+                    first = false;
+                }
+                self.decr();
+                self.line(writer)?;
+                write!(writer, "}}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Format a JSON-LD document, returns `None` when the document contains invalid json.
+pub fn format_jsonld(json: &Json, options: FormattingOptions, sort_keys: bool) -> Option<String> {
+    let mut formatter = JsonFormatter::new(&options, sort_keys);
+    let mut output = Cursor::new(Vec::new());
+    formatter.format(json, &mut output).ok()?;
+
+    if options.insert_final_newline.unwrap_or(true) {
+        writeln!(output).ok()?;
+    }
+
+    String::from_utf8(output.into_inner()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::FormattingOptions;
+
+    use super::format_jsonld;
+    use crate::lang::{parser::parse, tokenizer::tokenize};
+
+    fn format(st: &str, options: FormattingOptions, sort_keys: bool) -> Option<String> {
+        let (tok, es) = tokenize(st);
+        assert!(es.is_empty());
+        let (json, es) = parse(st, tok);
+        assert!(es.is_empty());
+        format_jsonld(&json, options, sort_keys)
+    }
+
+    fn options(tab_size: u32) -> FormattingOptions {
+        FormattingOptions {
+            tab_size,
+            insert_spaces: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn format_keeps_order() {
+        let st =
+            r#"{"name": "Arthur \"A\"", "@id": "ex:me", "age": 42, "tags": [true, null], "x": {}}"#;
+        let formatted = format(st, options(2), false).expect("formatted");
+        assert_eq!(
+            formatted,
+            r#"{
+  "name": "Arthur \"A\"",
+  "@id": "ex:me",
+  "age": 42,
+  "tags": [
+    true,
+    null
+  ],
+  "x": {}
+}
+"#
+        );
+    }
+
+    #[test]
+    fn format_sorts_keys() {
+        let st =
+            r#"{"name": "Arthur", "@type": "Person", "@id": "ex:me", "age": 4, "@context": {}}"#;
+        let formatted = format(st, options(4), true).expect("formatted");
+        assert_eq!(
+            formatted,
+            r#"{
+    "@context": {},
+    "@id": "ex:me",
+    "@type": "Person",
+    "age": 4,
+    "name": "Arthur"
+}
+"#
+        );
+
+        let formatted = format(
+            r#"{"b": 1, "a": []}"#,
+            FormattingOptions {
+                insert_spaces: false,
+                insert_final_newline: Some(false),
+                ..Default::default()
+            },
+            true,
+        )
+        .expect("formatted");
+        assert_eq!(formatted, "{\n\t\"a\": [],\n\t\"b\": 1\n}");
+    }
+}
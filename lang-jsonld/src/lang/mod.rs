@@ -1,3 +1,5 @@
+pub mod context;
+pub mod formatter;
 pub mod parser;
 pub mod tokenizer;
 pub mod triples;
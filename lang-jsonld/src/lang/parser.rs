@@ -1,7 +1,4 @@
-use std::{
-    io::{self, Write},
-    ops::Range,
-};
+use std::ops::Range;
 
 use chumsky::{prelude::*, Error, Parser, Stream};
 use enum_methods::{EnumIntoGetters, EnumIsA, EnumToGetters};
@@ -203,84 +200,6 @@ impl Json {
     }
 }
 
-pub struct JsonFormatter {
-    pub indent: String,
-    pub inc: usize,
-}
-impl JsonFormatter {
-    pub fn inc(&mut self) {
-        self.inc += 1;
-    }
-
-    pub fn decr(&mut self) {
-        self.inc -= 1;
-    }
-
-    pub fn line(&mut self, writer: &mut impl Write) -> io::Result<()> {
-        write!(writer, "\n")?;
-        for _ in 0..self.inc {
-            write!(writer, "{}", &self.indent)?;
-        }
-        Ok(())
-    }
-
-    pub fn format(&mut self, json: &Json, writer: &mut impl Write) -> io::Result<()> {
-        use std::io::{Error, ErrorKind};
-        match json {
-            Json::Invalid => {
-                return Result::Err(Error::new(ErrorKind::Other, "cannot format invalid json"))
-            }
-            Json::Token(t) => write!(writer, "{}", t)?,
-            Json::Array(xs) => {
-                write!(writer, "[")?;
-                self.inc();
-                self.line(writer)?;
-                let mut first = true;
-                for t in xs {
-                    if !first {
-                        write!(writer, ",")?;
-                        self.line(writer)?;
-                    }
-                    self.format(&t.0, writer)?;
-                    first = false;
-                }
-                self.decr();
-                self.line(writer)?;
-                write!(writer, "]")?;
-            }
-            Json::Object(xs) => {
-                write!(writer, "{{")?;
-                self.inc();
-                self.line(writer)?;
-                let mut first = true;
-                for t in xs {
-                    if !first {
-                        write!(writer, ",")?;
-                        self.line(writer)?;
-                    }
-                    match &t.0 {
-                        ObjectMember::Full(x, y) => {
-                            write!(writer, "{}: ", x.0)?;
-                            self.format(y, writer)?;
-                        }
-                        ObjectMember::Partial(_, _, _) => {
-                            return Result::Err(Error::new(
-                                ErrorKind::Other,
-                                "cannot format invalid json",
-                            ))
-                        }
-                    }
-                    first = false;
-                }
-                self.decr();
-                self.line(writer)?;
-                write!(writer, "}}")?;
-            }
-        }
-        Ok(())
-    }
-}
-
 impl Default for Json {
     fn default() -> Self {
         Self::Invalid
@@ -2,21 +2,12 @@ use std::{borrow::Cow, ops::Range};
 
 use lsp_core::prelude::*;
 
-use crate::lang::parser::{Json, ObjectMember};
+use crate::lang::{
+    context::{RemoteContexts, TermDefinition, TermDefinitions},
+    parser::{Json, ObjectMember},
+};
 
-fn visit_obj(json: &Spanned<Json>, f: &mut dyn FnMut(&[Spanned<ObjectMember>], &Range<usize>)) {
-    match json.value() {
-        Json::Array(vec) => {
-            for v in vec.iter() {
-                visit_obj(v, f);
-            }
-        }
-        Json::Object(vec) => f(&vec, json.span()),
-        _ => {}
-    }
-}
-
-fn get_str(tok: &Token) -> Option<&str> {
+pub(crate) fn get_str(tok: &Token) -> Option<&str> {
     match tok {
         Token::Str(x, _) => Some(x),
         _ => None,
@@ -31,7 +22,7 @@ fn correct_field(obj: &ObjectMember, field: &str) -> bool {
     }
 }
 
-fn find_field<'a>(
+pub(crate) fn find_field<'a>(
     mem: &'a [Spanned<ObjectMember>],
     field: &str,
 ) -> Option<(&'a Spanned<Json>, &'a Range<usize>)> {
@@ -40,63 +31,28 @@ fn find_field<'a>(
         .and_then(|x| x.json_value().map(|y| (y, x.field().span())))
 }
 
-pub fn derive_prefixes(json: &Spanned<Json>, base: &lsp_types::Url) -> Prefixes {
-    let mut options: Vec<(Cow<str>, Cow<str>)> = Vec::new();
+/// Prefixes defined by the term definitions.
+///
+/// Every term of the document itself is a prefix. Remote contexts like schema.org define
+/// thousands of terms, only their terms that end with `/` or `#` are used as prefix.
+pub fn derive_prefixes(terms: &TermDefinitions, base: &lsp_types::Url) -> Prefixes {
+    let mut out: Vec<Prefix> = Vec::new();
 
-    // Extract prefixes
-    visit_obj(json, &mut |mem, _| {
-        let Some((ctx, _)) = find_field(mem, "@context") else {
-            return;
-        };
-
-        visit_obj(ctx, &mut |mems, _| {
-            for mem in mems {
-                let Some(key) = get_str(mem.field()) else {
-                    continue;
-                };
+    for def in &terms.terms {
+        if def.reverse || (def.source.is_some() && !def.iri.ends_with(['/', '#'])) {
+            continue;
+        }
 
-                let Some(value) = mem
-                    .json_value()
-                    .and_then(|x| x.as_ref().try_map(|x| x.token()))
-                    .and_then(|x| x.try_map(|x| get_str(x)))
-                else {
-                    continue;
-                };
+        let Some(url) = lsp_types::Url::parse(&def.iri).ok() else {
+            continue;
+        };
 
-                options.push((Cow::Owned(key.to_string()), Cow::Owned(value.to_string())));
-            }
+        // Later definitions override earlier ones
+        out.retain(|x| x.prefix != def.term);
+        out.push(Prefix {
+            prefix: def.term.clone(),
+            url,
         });
-    });
-
-    // Expand prefixes
-    let mut changed = true;
-    let mut steps = 0;
-    while changed && steps < 5 {
-        changed = false;
-        for i in 0..options.len() {
-            let new_expaned = if let Some(pref) = options[i].1.find(':') {
-                let prefix = &options[i].1[..pref];
-                let Some((_, expaned)) = options.iter().find(|x| x.0 == prefix) else {
-                    continue;
-                };
-                format!("{}{}", expaned, &options[i].1[pref + 1..])
-            } else {
-                continue;
-            };
-            options[i].1 = Cow::from(new_expaned);
-            changed = true;
-        }
-        steps += 1;
-    }
-    let mut out = Vec::new();
-
-    for (k, v) in options {
-        if let Some(url) = lsp_types::Url::parse(&v).ok() {
-            out.push(Prefix {
-                prefix: k.into_owned(),
-                url,
-            });
-        }
     }
 
     Prefixes(out, base.clone())
@@ -106,106 +62,448 @@ fn shorten_span(span: &Range<usize>) -> Range<usize> {
     span.start + 1..span.end - 1
 }
 
-fn derive_triples_sub(
-    json: &Spanned<Json>,
-    prefixes: &Prefixes,
-    out: &mut Vec<MyQuad<'static>>,
-    bn_f: &mut dyn FnMut(Range<usize>) -> MyTerm<'static>,
-) -> Option<MyTerm<'static>> {
-    let mut subj_out = None;
-    visit_obj(json, &mut |mems, span| {
-        let subj = find_field(mems, "@id")
-            .and_then(|x| x.0.as_ref().try_map(|x| x.token()))
-            .and_then(|x| x.try_map(|x| prefixes.expand_json(x)))
-            .map(|Spanned(v, r)| MyTerm::named_node(v, shorten_span(&r)))
-            .unwrap_or_else(|| bn_f(span.clone()));
-
-        if let Some((ctx, _)) = find_field(mems, "@graph") {
-            derive_triples_sub(ctx, prefixes, out, bn_f);
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+const RDF_FIRST: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#first";
+const RDF_REST: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#rest";
+const RDF_NIL: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#nil";
+
+/// Member of a node object with the keyword `kwd`, or one of its aliases.
+fn keyword_field<'a>(
+    mems: &'a [Spanned<ObjectMember>],
+    ctx: &TermDefinitions,
+    kwd: &str,
+) -> Option<(&'a Spanned<Json>, &'a Range<usize>)> {
+    mems.iter()
+        .find(|x| get_str(x.field()).and_then(|k| ctx.keyword(k)) == Some(kwd))
+        .and_then(|x| x.json_value().map(|y| (y, x.field().span())))
+}
+
+/// The items of a value that can be an array.
+fn items(json: &Spanned<Json>) -> Vec<&Spanned<Json>> {
+    match json.value() {
+        Json::Array(xs) => xs.iter().collect(),
+        _ => vec![json],
+    }
+}
+
+/// Expands JSON-LD to triples, following the JSON-LD 1.1 expansion algorithm for the features
+/// that result in triples.
+///
+/// Literals only keep their lexical form, like in the other languages, so datatypes and
+/// languages of values are not kept.
+struct Expander<'a> {
+    base: &'a lsp_types::Url,
+    remote: &'a RemoteContexts,
+    out: Vec<MyQuad<'static>>,
+    /// Triples of undefined terms, JSON-LD processors drop them
+    unasserted: Vec<MyQuad<'static>>,
+    bn_count: usize,
+}
+
+impl<'a> Expander<'a> {
+    fn blank_node(&mut self, span: Range<usize>) -> MyTerm<'static> {
+        let out = MyTerm::blank_node(format!("_:{}", self.bn_count), span);
+        self.bn_count += 1;
+        out
+    }
+
+    fn push(
+        &mut self,
+        subject: MyTerm<'static>,
+        predicate: MyTerm<'static>,
+        object: MyTerm<'static>,
+        graph: Option<&MyTerm<'static>>,
+        span: Range<usize>,
+    ) {
+        self.out.push(MyQuad {
+            subject,
+            predicate,
+            object,
+            graph: graph.cloned(),
+            span,
+        });
+    }
+
+    /// Context after applying the scoped context of `def`.
+    fn scoped<'c>(
+        &self,
+        ctx: &'c TermDefinitions,
+        def: Option<&TermDefinition>,
+    ) -> Cow<'c, TermDefinitions> {
+        match def.and_then(|d| d.scoped.as_ref().map(|s| (d, s))) {
+            Some((def, scoped)) => {
+                let base = def.source.as_ref().unwrap_or(self.base);
+                Cow::Owned(ctx.with_context(scoped, base, def.source.as_ref(), self.remote))
+            }
+            None => Cow::Borrowed(ctx),
+        }
+    }
+
+    fn id(&self, ctx: &TermDefinitions, id: &str, span: Range<usize>) -> MyTerm<'static> {
+        if id.starts_with("_:") {
+            return MyTerm::blank_node(id.to_string(), span);
+        }
+        ctx.expand_id(id, self.base)
+            .map(|x| MyTerm::named_node(x, span.clone()))
+            .unwrap_or_else(|| MyTerm::invalid(span))
+    }
+
+    fn top(&mut self, json: &Spanned<Json>, ctx: &TermDefinitions) {
+        match json.value() {
+            Json::Array(xs) => {
+                for x in xs {
+                    self.top(x, ctx);
+                }
+            }
+            Json::Object(mems) => {
+                self.node(mems, json.span(), ctx, None);
+            }
+            _ => {}
         }
-        if let Some((mem, span)) = find_field(mems, "@type") {
-            let object = match mem {
-                Spanned(Json::Token(tok), span) => {
-                    if let Some(st) = prefixes.expand_json(&tok) {
-                        MyTerm::named_node(st, span.clone())
-                    } else {
-                        MyTerm::invalid(span.clone())
+    }
+
+    /// Expand a node object, returns its subject.
+    fn node(
+        &mut self,
+        mems: &[Spanned<ObjectMember>],
+        span: &Range<usize>,
+        parent: &TermDefinitions,
+        graph: Option<&MyTerm<'static>>,
+    ) -> MyTerm<'static> {
+        let ctx = match find_field(mems, "@context") {
+            Some((local, _)) => {
+                Cow::Owned(parent.with_context(local, self.base, None, self.remote))
+            }
+            None => Cow::Borrowed(parent),
+        };
+
+        let id = keyword_field(mems, &ctx, "@id")
+            .and_then(|(x, _)| x.token().and_then(get_str).map(|id| (id, x.span())));
+        let subject = match id {
+            Some((id, span)) => self.id(&ctx, id, shorten_span(span)),
+            None => self.blank_node(span.clone()),
+        };
+
+        // Type scoped contexts apply to the properties of this node, not to nested nodes
+        let mut type_ctx = ctx.clone();
+        if let Some((types, key_span)) = keyword_field(mems, &ctx, "@type") {
+            for ty in items(types) {
+                let ty_str = ty.token().and_then(get_str);
+                let object = ty_str
+                    .and_then(|x| ctx.expand_vocab(x))
+                    .map(|x| MyTerm::named_node(x, ty.span().clone()))
+                    .unwrap_or_else(|| MyTerm::invalid(ty.span().clone()));
+
+                let predicate = MyTerm::named_node(RDF_TYPE, key_span.clone());
+                self.push(subject.clone(), predicate, object, graph, ty.span().clone());
+
+                if let Some(def) = ty_str.and_then(|x| ctx.get(x)) {
+                    if def.scoped.is_some() {
+                        type_ctx = Cow::Owned(self.scoped(&type_ctx, Some(def)).into_owned());
                     }
                 }
-                json => derive_triples_sub(json, prefixes, out, bn_f)
-                    .unwrap_or_else(|| MyTerm::invalid(json.span().clone())),
-            };
+            }
+        }
 
-            out.push(MyQuad {
-                subject: subj.clone(),
-                predicate: MyTerm::named_node(
-                    "http://www.w3.org/1999/02/22-rdf-syntax-ns#type",
-                    span.clone(),
-                ),
-                object,
-                span: mem.span().clone(),
-            });
+        if let Some((inner, _)) = keyword_field(mems, &ctx, "@graph") {
+            // A node with an `@id` or properties names the graph, otherwise the nodes are in
+            // the current graph
+            let named = id.is_some()
+                || mems.iter().any(|x| {
+                    get_str(x.field())
+                        .map(|k| ctx.keyword(k).is_none())
+                        .unwrap_or(false)
+                });
+            let name = named.then(|| subject.clone());
+            let graph = name.as_ref().or(graph);
+            for item in items(inner) {
+                if let Json::Object(inner) = item.value() {
+                    self.node(inner, item.span(), &ctx, graph);
+                }
+            }
         }
+
+        self.properties(&subject, mems, &type_ctx, &ctx, graph);
+
+        subject
+    }
+
+    /// Expand the properties of a node, `ctx` expands the keys and values, nested nodes use
+    /// `nested_ctx`.
+    fn properties(
+        &mut self,
+        subject: &MyTerm<'static>,
+        mems: &[Spanned<ObjectMember>],
+        ctx: &TermDefinitions,
+        nested_ctx: &TermDefinitions,
+        graph: Option<&MyTerm<'static>>,
+    ) {
         for mem in mems {
             let field = mem.field();
-            let st = get_str(&field);
-            if st.map(|x| x.starts_with("@")).unwrap_or(false) {
+            let Some(key) = get_str(field) else {
+                continue;
+            };
+
+            if let Some(kwd) = ctx.keyword(key) {
+                let Some(value) = mem.json_value() else {
+                    continue;
+                };
+                match kwd {
+                    "@reverse" => self.reverse(subject, value, ctx, nested_ctx, graph),
+                    "@nest" => {
+                        for item in items(value) {
+                            if let Json::Object(nested) = item.value() {
+                                self.properties(subject, nested, ctx, nested_ctx, graph);
+                            }
+                        }
+                    }
+                    "@included" => {
+                        for item in items(value) {
+                            if let Json::Object(included) = item.value() {
+                                self.node(included, item.span(), nested_ctx, graph);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
                 continue;
             }
 
-            let Some(pred) = prefixes
-                .expand_json(&field)
-                .or_else(|| st.map(|x| x.to_string()))
-            else {
+            let def = ctx.get(key);
+            // Undefined terms are kept as is in the unasserted triples, so they can still be
+            // completed
+            let iri = ctx.expand_vocab(key);
+            let defined = iri.is_some();
+            let iri = iri.unwrap_or_else(|| key.to_string());
+            let predicate = MyTerm::named_node(iri, shorten_span(field.span()));
+            let reverse = def.map(|x| x.reverse).unwrap_or(false);
+            let start = self.out.len();
+
+            let objects = match mem.json_value() {
+                Some(value) => {
+                    let value_ctx = self.scoped(nested_ctx, def);
+                    self.values(value, def, true, &value_ctx, graph)
+                }
+                None => vec![MyTerm::invalid(0..0)],
+            };
+
+            for object in objects {
+                let (s, o) = if reverse {
+                    (object, subject.clone())
+                } else {
+                    (subject.clone(), object)
+                };
+                self.push(s, predicate.clone(), o, graph, mem.span().clone());
+            }
+
+            if !defined {
+                let dropped = self.out.split_off(start);
+                self.unasserted.extend(dropped);
+            }
+        }
+    }
+
+    /// Expand a `@reverse` map, its values are the subjects of the properties.
+    fn reverse(
+        &mut self,
+        object: &MyTerm<'static>,
+        value: &Spanned<Json>,
+        ctx: &TermDefinitions,
+        nested_ctx: &TermDefinitions,
+        graph: Option<&MyTerm<'static>>,
+    ) {
+        let Json::Object(mems) = value.value() else {
+            return;
+        };
+
+        for mem in mems {
+            let field = mem.field();
+            let (Some(key), Some(value)) = (get_str(field), mem.json_value()) else {
                 continue;
             };
-            let pred = MyTerm::named_node(pred, shorten_span(field.span()));
-
-            // get value
-            let object = match mem.json_value() {
-                None => MyTerm::invalid(0..0),
-                // TODO: this might not be a literal, should look it up in the context
-                Some(Spanned(Json::Token(tok), span)) => match tok {
-                    Token::Str(x, _) => MyTerm::literal(x.clone(), span.clone()),
-                    _ => MyTerm::invalid(span.clone()),
-                },
-                Some(json) => derive_triples_sub(json, prefixes, out, bn_f)
-                    .unwrap_or_else(|| MyTerm::invalid(json.span().clone())),
+            let Some(iri) = ctx.expand_vocab(key) else {
+                continue;
             };
+            let predicate = MyTerm::named_node(iri, shorten_span(field.span()));
+
+            let def = ctx.get(key);
+            let value_ctx = self.scoped(nested_ctx, def);
+            for subject in self.values(value, def, true, &value_ctx, graph) {
+                self.push(
+                    subject,
+                    predicate.clone(),
+                    object.clone(),
+                    graph,
+                    mem.span().clone(),
+                );
+            }
+        }
+    }
 
-            out.push(MyQuad {
-                subject: subj.clone(),
-                predicate: pred,
-                object,
-                span: mem.span().clone(),
-            });
+    /// Expand the value of a property, arrays result in multiple objects.
+    ///
+    /// `use_container` is false for the items of a container, like the values of a language
+    /// map.
+    fn values(
+        &mut self,
+        json: &Spanned<Json>,
+        def: Option<&TermDefinition>,
+        use_container: bool,
+        ctx: &TermDefinitions,
+        graph: Option<&MyTerm<'static>>,
+    ) -> Vec<MyTerm<'static>> {
+        let container = def
+            .and_then(|x| x.container.as_deref())
+            .filter(|_| use_container);
+
+        match json.value() {
+            Json::Array(xs) if container == Some("@list") => {
+                vec![self.list(xs, def, ctx, graph, json.span())]
+            }
+            Json::Array(xs) => {
+                let mut out = Vec::new();
+                for x in xs {
+                    out.extend(self.values(x, def, false, ctx, graph));
+                }
+                out
+            }
+            Json::Object(mems) if matches!(container, Some("@language") | Some("@index")) => {
+                let mut out = Vec::new();
+                for value in mems.iter().flat_map(|x| x.json_value()) {
+                    out.extend(self.values(value, def, false, ctx, graph));
+                }
+                out
+            }
+            Json::Object(mems) => {
+                if let Some((value, _)) = keyword_field(mems, ctx, "@value") {
+                    // Value objects are literals, their type and language are not kept
+                    return match value.value() {
+                        Json::Token(Token::Null) => vec![],
+                        Json::Token(Token::Str(x, _)) | Json::Token(Token::Number(x)) => {
+                            vec![MyTerm::literal(x.clone(), value.span().clone())]
+                        }
+                        Json::Token(Token::True) => {
+                            vec![MyTerm::literal("true", value.span().clone())]
+                        }
+                        Json::Token(Token::False) => {
+                            vec![MyTerm::literal("false", value.span().clone())]
+                        }
+                        _ => vec![MyTerm::invalid(value.span().clone())],
+                    };
+                }
+                if let Some((list, _)) = keyword_field(mems, ctx, "@list") {
+                    let xs: Vec<_> = items(list).into_iter().cloned().collect();
+                    return vec![self.list(&xs, def, ctx, graph, json.span())];
+                }
+                if let Some((set, _)) = keyword_field(mems, ctx, "@set") {
+                    return self.values(set, def, false, ctx, graph);
+                }
+                vec![self.node(mems, json.span(), ctx, graph)]
+            }
+            Json::Token(tok) => self
+                .scalar(tok, json.span(), def, ctx)
+                .into_iter()
+                .collect(),
+            Json::Invalid => vec![MyTerm::invalid(json.span().clone())],
+        }
+    }
+
+    /// Expand a string, number or boolean, the type mapping of the term decides whether a
+    /// string is an iri.
+    fn scalar(
+        &self,
+        tok: &Token,
+        span: &Range<usize>,
+        def: Option<&TermDefinition>,
+        ctx: &TermDefinitions,
+    ) -> Option<MyTerm<'static>> {
+        let term = match tok {
+            Token::Null => return None,
+            Token::Str(x, _) => match def.and_then(|x| x.ty.as_deref()) {
+                Some("@id") => self.id(ctx, x, shorten_span(span)),
+                Some("@vocab") => ctx
+                    .expand_vocab(x)
+                    .or_else(|| ctx.expand_id(x, self.base))
+                    .map(|x| MyTerm::named_node(x, shorten_span(span)))
+                    .unwrap_or_else(|| MyTerm::invalid(shorten_span(span))),
+                _ => MyTerm::literal(x.clone(), span.clone()),
+            },
+            Token::Number(x) => MyTerm::literal(x.clone(), span.clone()),
+            Token::True => MyTerm::literal("true", span.clone()),
+            Token::False => MyTerm::literal("false", span.clone()),
+            _ => MyTerm::invalid(span.clone()),
+        };
+        Some(term)
+    }
+
+    /// Expand a list to an RDF collection, returns its head.
+    fn list(
+        &mut self,
+        xs: &[Spanned<Json>],
+        def: Option<&TermDefinition>,
+        ctx: &TermDefinitions,
+        graph: Option<&MyTerm<'static>>,
+        span: &Range<usize>,
+    ) -> MyTerm<'static> {
+        let mut objects = Vec::new();
+        for x in xs {
+            objects.extend(self.values(x, def, false, ctx, graph));
         }
 
-        subj_out = Some(subj);
-    });
-    subj_out
+        let mut rest = MyTerm::named_node(RDF_NIL, span.end..span.end);
+        for object in objects.into_iter().rev() {
+            let node = self.blank_node(object.span.clone());
+            let span = object.span.clone();
+            self.push(
+                node.clone(),
+                MyTerm::named_node(RDF_FIRST, span.clone()),
+                object,
+                graph,
+                span.clone(),
+            );
+            self.push(
+                node.clone(),
+                MyTerm::named_node(RDF_REST, span.clone()),
+                rest,
+                graph,
+                span,
+            );
+            rest = node;
+        }
+        rest
+    }
 }
 
-pub fn derive_triples(json: &Spanned<Json>, prefixes: &Prefixes) -> Vec<MyQuad<'static>> {
-    let mut bn_count = 0;
-    let mut bn = move |span| {
-        let out = MyTerm::blank_node(format!("_:{}", bn_count), span);
-        bn_count += 1;
-        out
+/// Derive the triples of a JSON-LD document at `base`, remote contexts are looked up in
+/// `remote`.
+///
+/// Returns the triples and the unasserted triples of undefined terms, including the triples of
+/// their values.
+pub fn derive_triples(
+    json: &Spanned<Json>,
+    base: &lsp_types::Url,
+    remote: &RemoteContexts,
+) -> (Vec<MyQuad<'static>>, Vec<MyQuad<'static>>) {
+    let mut expander = Expander {
+        base,
+        remote,
+        out: Vec::new(),
+        unasserted: Vec::new(),
+        bn_count: 0,
     };
-    let mut out = Vec::new();
-    derive_triples_sub(json, prefixes, &mut out, &mut bn);
-    out
+    expander.top(json, &TermDefinitions::default());
+    (expander.out, expander.unasserted)
 }
 
 #[cfg(test)]
 mod tests {
 
-    use lsp_core::prelude::{MyQuad, Spanned};
+    use lsp_core::prelude::{MyQuad, MyTerm, Prefixes, Spanned};
     use sophia_api::term::{Term, TermKind};
 
     use super::{derive_prefixes, derive_triples};
     use crate::lang::{
+        context::{process_contexts, RemoteContext, RemoteContexts, TermDefinitions},
         parser::{parse, Json},
         tokenizer::tokenize,
     };
@@ -222,6 +520,15 @@ mod tests {
         Some(jsonld)
     }
 
+    fn active(
+        json: &Spanned<Json>,
+        url: &lsp_types::Url,
+        remote: &RemoteContexts,
+    ) -> (Prefixes, TermDefinitions) {
+        let active = process_contexts(json, url, remote);
+        (derive_prefixes(&active.terms, url), active.terms)
+    }
+
     #[test]
     fn simple_context_foaf_1() {
         let st = r#" { 
@@ -232,7 +539,7 @@ mod tests {
         let url = lsp_types::Url::parse("memory://test.jsonld").unwrap();
 
         let json = parse_json(st).expect("valid json");
-        let prefixes = derive_prefixes(&json, &url);
+        let (prefixes, _) = active(&json, &url, &RemoteContexts::default());
 
         assert_eq!(prefixes.0.len(), 1);
         let foaf_prefix = prefixes
@@ -252,7 +559,7 @@ mod tests {
         let url = lsp_types::Url::parse("memory://test.jsonld").unwrap();
 
         let json = parse_json(st).expect("valid json");
-        let prefixes = derive_prefixes(&json, &url);
+        let (prefixes, _) = active(&json, &url, &RemoteContexts::default());
 
         assert_eq!(prefixes.0.len(), 1);
 
@@ -273,7 +580,7 @@ mod tests {
         let url = lsp_types::Url::parse("memory://test.jsonld").unwrap();
 
         let json = parse_json(st).expect("valid json");
-        let prefixes = derive_prefixes(&json, &url);
+        let (prefixes, _) = active(&json, &url, &RemoteContexts::default());
 
         assert_eq!(prefixes.0.len(), 2);
         let name_prefix = prefixes
@@ -290,23 +597,51 @@ mod tests {
     }
 
     #[test]
-    fn simple_context_foaf_3_ignore_extra_ctx() {
+    fn simple_context_foaf_3_extra_ctx() {
         let st = r#" { 
             "@context": [ {"foaf": "http://xmlns.com/foaf/0.1/"}, "http://xmlns.com/foaf/0.1/context.jsonld" ],
             "@id": "http://example.com/ns#me",
-            "foaf:name": "Arthur"
+            "name": "Arthur"
         } "#;
         let url = lsp_types::Url::parse("memory://test.jsonld").unwrap();
 
         let json = parse_json(st).expect("valid json");
-        let prefixes = derive_prefixes(&json, &url);
 
+        // The remote context is not loaded yet
+        let mut remote = RemoteContexts::default();
+        let (prefixes, terms) = active(&json, &url, &remote);
         assert_eq!(prefixes.0.len(), 1);
+        assert_eq!(terms.get("name"), None);
+
+        let ctx = r#"{ "@context": {
+            "dc": "http://purl.org/dc/terms/",
+            "name": "http://xmlns.com/foaf/0.1/name"
+        } }"#;
+        remote.0.insert(
+            "http://xmlns.com/foaf/0.1/context.jsonld".to_string(),
+            RemoteContext::Loaded(parse_json(ctx).expect("valid context")),
+        );
+        let (prefixes, _) = active(&json, &url, &remote);
+
+        // Only prefix-like terms of remote contexts are prefixes
+        assert_eq!(prefixes.0.len(), 2);
         let foaf_prefix = prefixes
             .iter()
             .find(|x| x.prefix == "foaf")
             .expect("foaf prefix");
         assert_eq!(foaf_prefix.url.as_str(), "http://xmlns.com/foaf/0.1/");
+        let dc_prefix = prefixes
+            .iter()
+            .find(|x| x.prefix == "dc")
+            .expect("dc prefix");
+        assert_eq!(dc_prefix.url.as_str(), "http://purl.org/dc/terms/");
+
+        let (triples, _) = derive_triples(&json, &url, &remote);
+        assert_eq!(triples.len(), 1);
+        assert_eq!(
+            triples[0].predicate.as_str(),
+            "http://xmlns.com/foaf/0.1/name"
+        );
     }
 
     #[test]
@@ -319,8 +654,8 @@ mod tests {
         let url = lsp_types::Url::parse("memory://test.jsonld").unwrap();
 
         let json = parse_json(st).expect("valid json");
-        let prefixes = derive_prefixes(&json, &url);
-        let triples = derive_triples(&json, &prefixes);
+        let remote = RemoteContexts::default();
+        let (triples, _) = derive_triples(&json, &url, &remote);
 
         assert_eq!(triples.len(), 1);
         let MyQuad {
@@ -348,8 +683,8 @@ mod tests {
         let url = lsp_types::Url::parse("memory://test.jsonld").unwrap();
 
         let json = parse_json(st).expect("valid json");
-        let prefixes = derive_prefixes(&json, &url);
-        let triples = derive_triples(&json, &prefixes);
+        let remote = RemoteContexts::default();
+        let (triples, _) = derive_triples(&json, &url, &remote);
 
         assert_eq!(triples.len(), 1);
         let MyQuad {
@@ -379,8 +714,8 @@ mod tests {
         let url = lsp_types::Url::parse("memory://test.jsonld").unwrap();
 
         let json = parse_json(st).expect("valid json");
-        let prefixes = derive_prefixes(&json, &url);
-        let triples = derive_triples(&json, &prefixes);
+        let remote = RemoteContexts::default();
+        let (triples, _) = derive_triples(&json, &url, &remote);
 
         assert_eq!(triples.len(), 1);
         let MyQuad {
@@ -409,8 +744,8 @@ mod tests {
         let url = lsp_types::Url::parse("memory://test.jsonld").unwrap();
 
         let json = parse_json(st).expect("valid json");
-        let prefixes = derive_prefixes(&json, &url);
-        let triples = derive_triples(&json, &prefixes);
+        let remote = RemoteContexts::default();
+        let (triples, _) = derive_triples(&json, &url, &remote);
 
         assert_eq!(triples.len(), 1);
         let MyQuad {
@@ -439,8 +774,8 @@ mod tests {
         let url = lsp_types::Url::parse("memory://test.jsonld").unwrap();
 
         let json = parse_json(st).expect("valid json");
-        let prefixes = derive_prefixes(&json, &url);
-        let triples = derive_triples(&json, &prefixes);
+        let remote = RemoteContexts::default();
+        let (triples, _) = derive_triples(&json, &url, &remote);
 
         assert_eq!(triples.len(), 1);
         let MyQuad {
@@ -472,8 +807,8 @@ mod tests {
         let url = lsp_types::Url::parse("memory://test.jsonld").unwrap();
 
         let json = parse_json(st).expect("valid json");
-        let prefixes = derive_prefixes(&json, &url);
-        let triples = derive_triples(&json, &prefixes);
+        let remote = RemoteContexts::default();
+        let (triples, _) = derive_triples(&json, &url, &remote);
 
         assert_eq!(triples.len(), 4);
         let MyQuad {
@@ -516,4 +851,193 @@ mod tests {
         assert_eq!(friend_friend[0].object.as_str(), "Julian");
         assert_eq!(friend_friend[0].object.kind(), TermKind::Literal);
     }
+
+    fn expand(st: &str) -> Vec<MyQuad<'static>> {
+        let url = lsp_types::Url::parse("http://example.com/doc.jsonld").unwrap();
+        let json = parse_json(st).expect("valid json");
+        derive_triples(&json, &url, &RemoteContexts::default()).0
+    }
+
+    #[test]
+    fn derive_coerced_triples() {
+        let triples = expand(
+            r#" {
+            "@context": {
+                "@vocab": "http://schema.org/",
+                "@base": "http://example.com/people/",
+                "knows": {"@id": "http://xmlns.com/foaf/0.1/knows", "@type": "@id"},
+                "status": {"@type": "@vocab"},
+                "Active": "http://example.com/ns#Active"
+            },
+            "@id": "arthur",
+            "@type": "Person",
+            "knows": "julian",
+            "status": "Active",
+            "age": 42,
+            "alive": true
+        } "#,
+        );
+
+        assert_eq!(triples.len(), 5);
+        for t in &triples {
+            assert_eq!(t.subject.as_str(), "http://example.com/people/arthur");
+        }
+
+        let ty = triples
+            .iter()
+            .find(|x| x.predicate.as_str() == "http://www.w3.org/1999/02/22-rdf-syntax-ns#type")
+            .expect("type triple");
+        assert_eq!(ty.object.as_str(), "http://schema.org/Person");
+
+        let knows = triples
+            .iter()
+            .find(|x| x.predicate.as_str() == "http://xmlns.com/foaf/0.1/knows")
+            .expect("knows triple");
+        assert_eq!(knows.object.as_str(), "http://example.com/people/julian");
+        assert_eq!(knows.object.kind(), TermKind::Iri);
+
+        let status = triples
+            .iter()
+            .find(|x| x.predicate.as_str() == "http://schema.org/status")
+            .expect("status triple");
+        assert_eq!(status.object.as_str(), "http://example.com/ns#Active");
+        assert_eq!(status.object.kind(), TermKind::Iri);
+
+        let age = triples
+            .iter()
+            .find(|x| x.predicate.as_str() == "http://schema.org/age")
+            .expect("age triple");
+        assert_eq!(age.object.as_str(), "42");
+        assert_eq!(age.object.kind(), TermKind::Literal);
+
+        let alive = triples
+            .iter()
+            .find(|x| x.predicate.as_str() == "http://schema.org/alive")
+            .expect("alive triple");
+        assert_eq!(alive.object.as_str(), "true");
+    }
+
+    #[test]
+    fn derive_containers() {
+        let triples = expand(
+            r#" {
+            "@context": {
+                "ex": "http://example.com/ns#",
+                "steps": {"@id": "ex:steps", "@container": "@list"},
+                "label": {"@id": "ex:label", "@container": "@language"},
+                "tags": {"@id": "ex:tags", "@container": "@set"}
+            },
+            "@id": "ex:me",
+            "steps": ["a", "b"],
+            "label": {"en": "Me", "nl": "Ik"},
+            "tags": ["x", {"@value": "y"}, null]
+        } "#,
+        );
+
+        let label: Vec<_> = triples
+            .iter()
+            .filter(|x| x.predicate.as_str() == "http://example.com/ns#label")
+            .map(|x| x.object.as_str())
+            .collect();
+        assert_eq!(label, vec!["Me", "Ik"]);
+
+        let tags: Vec<_> = triples
+            .iter()
+            .filter(|x| x.predicate.as_str() == "http://example.com/ns#tags")
+            .map(|x| x.object.as_str())
+            .collect();
+        assert_eq!(tags, vec!["x", "y"]);
+
+        let head = &triples
+            .iter()
+            .find(|x| x.predicate.as_str() == "http://example.com/ns#steps")
+            .expect("steps triple")
+            .object;
+        assert_eq!(head.kind(), TermKind::BlankNode);
+
+        let first = "http://www.w3.org/1999/02/22-rdf-syntax-ns#first";
+        let rest = "http://www.w3.org/1999/02/22-rdf-syntax-ns#rest";
+        let item = |node: &MyTerm<'static>, pred: &str| {
+            triples
+                .iter()
+                .find(|x| &x.subject == node && x.predicate.as_str() == pred)
+                .expect("list triple")
+                .object
+                .clone()
+        };
+
+        assert_eq!(item(head, first).as_str(), "a");
+        let second = item(head, rest);
+        assert_eq!(item(&second, first).as_str(), "b");
+        assert_eq!(
+            item(&second, rest).as_str(),
+            "http://www.w3.org/1999/02/22-rdf-syntax-ns#nil"
+        );
+    }
+
+    #[test]
+    fn derive_reverse_and_local_context() {
+        let triples = expand(
+            r#" {
+            "@context": {
+                "ex": "http://example.com/ns#",
+                "parent": {"@reverse": "ex:child", "@type": "@id"}
+            },
+            "@id": "ex:me",
+            "parent": "ex:mom",
+            "@reverse": { "ex:knows": {"@id": "ex:friend"} },
+            "ex:pet": {
+                "@context": {"name": "http://schema.org/name"},
+                "name": "Rex"
+            }
+        } "#,
+        );
+
+        let child = triples
+            .iter()
+            .find(|x| x.predicate.as_str() == "http://example.com/ns#child")
+            .expect("child triple");
+        assert_eq!(child.subject.as_str(), "http://example.com/ns#mom");
+        assert_eq!(child.object.as_str(), "http://example.com/ns#me");
+
+        let knows = triples
+            .iter()
+            .find(|x| x.predicate.as_str() == "http://example.com/ns#knows")
+            .expect("knows triple");
+        assert_eq!(knows.subject.as_str(), "http://example.com/ns#friend");
+        assert_eq!(knows.object.as_str(), "http://example.com/ns#me");
+
+        let name = triples
+            .iter()
+            .find(|x| x.predicate.as_str() == "http://schema.org/name")
+            .expect("name triple");
+        assert_eq!(name.subject.kind(), TermKind::BlankNode);
+        assert_eq!(name.object.as_str(), "Rex");
+    }
+
+    #[test]
+    fn undefined_terms_are_not_asserted() {
+        let url = lsp_types::Url::parse("http://example.com/doc.jsonld").unwrap();
+        let json = parse_json(
+            r#" {
+            "@context": {"name": "http://xmlns.com/foaf/0.1/name"},
+            "@id": "http://example.com/ns#me",
+            "name": "Arthur",
+            "pet": {"name": "Rex"}
+        } "#,
+        )
+        .expect("valid json");
+        let (triples, unasserted) = derive_triples(&json, &url, &RemoteContexts::default());
+
+        assert_eq!(triples.len(), 1);
+        assert_eq!(triples[0].object.as_str(), "Arthur");
+
+        // The nested node of the undefined term is dropped as well
+        assert_eq!(unasserted.len(), 2);
+        assert!(unasserted
+            .iter()
+            .any(|x| x.predicate.as_str() == "pet"
+                && x.subject.as_str() == "http://example.com/ns#me"));
+        assert!(unasserted.iter().any(|x| x.object.as_str() == "Rex"));
+    }
 }
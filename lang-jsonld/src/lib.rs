@@ -16,11 +16,15 @@ use ropey::Rope;
 pub mod ecs;
 pub mod lang;
 use crate::{
-    ecs::{highlight_named_nodes, keyword_highlight, setup_parse},
+    ecs::{
+        highlight_named_nodes, keyword_highlight, setup_completion, setup_document_symbol,
+        setup_formatting, setup_hover, setup_parse,
+    },
     lang::parser::Json,
 };
 
-pub fn setup_world(world: &mut World) {
+/// Setup JSON-LD support, remote contexts are fetched with the client `C`.
+pub fn setup_world<C: Client + Resource>(world: &mut World) {
     let mut semantic_token_dict = world.resource_mut::<SemanticTokensDict>();
     JsonLd::LEGEND_TYPES.iter().for_each(|lt| {
         if !semantic_token_dict.contains_key(lt) {
@@ -37,7 +41,9 @@ pub fn setup_world(world: &mut World) {
                     .insert(DynLang(Box::new(JsonLdHelper)));
                 return;
             }
-            _ => {}
+            // Extensions are only used when the client did not tell the language
+            Some(_) => return,
+            None => {}
         }
         // pass
         if trigger.event().url.as_str().ends_with(".jsonld") {
@@ -65,8 +71,16 @@ pub fn setup_world(world: &mut World) {
         use diagnostics::*;
         schedule.add_systems(publish_diagnostics::<JsonLd>);
     });
+    world.schedule_scope(PullDiagnosticsLabel, |_, schedule| {
+        use diagnostics::*;
+        schedule.add_systems(pull_syntax_diagnostics::<JsonLd>);
+    });
 
-    setup_parse(world);
+    setup_parse::<C>(world);
+    setup_completion(world);
+    setup_hover(world);
+    setup_formatting(world);
+    setup_document_symbol(world);
 }
 
 #[derive(Debug, Component)]
@@ -98,6 +112,22 @@ impl Lang for JsonLd {
     ];
 }
 
+/// JSON-LD keywords that are completed as keys of node and value objects.
+const KEYWORDS: &[&str] = &[
+    "@context",
+    "@id",
+    "@type",
+    "@graph",
+    "@reverse",
+    "@included",
+    "@nest",
+    "@value",
+    "@language",
+    "@list",
+    "@set",
+    "@index",
+];
+
 #[derive(Debug)]
 pub struct JsonLdHelper;
 impl LangHelper for JsonLdHelper {
@@ -114,6 +144,6 @@ impl LangHelper for JsonLdHelper {
     }
 
     fn keyword(&self) -> &[&'static str] {
-        &[]
+        KEYWORDS
     }
 }
@@ -0,0 +1,134 @@
+#![doc(
+    html_logo_url = "https://ajuvercr.github.io/semantic-web-lsp/assets/icons/favicon.png",
+    html_favicon_url = "https://ajuvercr.github.io/semantic-web-lsp/assets/icons/favicon.ico"
+)]
+//! Notation3 (N3) support, Turtle with formulae, implications and quantified variables.
+//!
+//! N3 documents are parsed into the same [`Turtle`] model, so all Turtle systems (completion,
+//! formatting, diagnostics, ...) are reused through [`TurtleLike`].
+//! The triples of a formula `{ ... }` are not asserted, they are emitted in [`UnassertedTriples`] in
+//! a graph named by a fresh blank node. [`Triples`] only holds the top-level triples.
+use bevy_ecs::{component::Component, world::World};
+use chumsky::prelude::Simple;
+use lang_turtle::{
+    lang::{model::Turtle, parser::Syntax},
+    setup_turtle_like, TurtleLike, TURTLE_LEGEND_TYPES,
+};
+use lsp_core::{
+    lang::{Lang, LangHelper},
+    prelude::*,
+};
+
+#[derive(Component, Default)]
+pub struct N3Lang;
+
+impl TurtleLike for N3Lang {
+    const SYNTAX: Syntax = Syntax::N3;
+}
+
+#[derive(Debug, Clone)]
+pub struct N3Helper;
+impl LangHelper for N3Helper {
+    fn keyword(&self) -> &[&'static str] {
+        &["@prefix", "@base", "a", "@forAll", "@forSome", "=>"]
+    }
+}
+
+pub fn setup_world(world: &mut World) {
+    setup_turtle_like::<N3Lang, _>(world, "n3", ".n3", N3Helper);
+}
+
+impl Lang for N3Lang {
+    type Token = Token;
+
+    type TokenError = Simple<char>;
+
+    type Element = Turtle;
+
+    type ElementError = Simple<Token>;
+
+    const LANG: &'static str = "n3";
+
+    const TRIGGERS: &'static [&'static str] = &[":"];
+    const CODE_ACTION: bool = true;
+    const HOVER: bool = true;
+
+    const LEGEND_TYPES: &'static [lsp_types::SemanticTokenType] = TURTLE_LEGEND_TYPES;
+
+    const PATTERN: Option<&'static str> = None;
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_core::{components::*, prelude::*};
+    use test_utils::{create_file, setup_world, TestClient};
+
+    fn setup(world: &mut bevy_ecs::world::World) {
+        lang_turtle::setup_world(world);
+        crate::setup_world(world);
+    }
+
+    #[test_log::test]
+    fn n3_formulae_are_kept_out_of_triples() {
+        let (mut world, _) = setup_world(TestClient::new(), setup);
+
+        let t1 = "@prefix foaf: <http://xmlns.com/foaf/0.1/>.
+<a> foaf:name \"A\".
+{ ?x foaf:knows <a> } => { ?x a foaf:Person }.";
+        let entity = create_file(&mut world, t1, "http://example.com/ns.n3", "n3", Open);
+
+        assert!(world.entity(entity).contains::<crate::N3Lang>());
+        assert!(!world.entity(entity).contains::<Dirty>());
+
+        let triples: &Triples = world.entity(entity).get().expect("triples");
+        assert!(triples.iter().all(|t| t.graph.is_none()));
+        let top_level: Vec<_> = triples
+            .iter()
+            .map(|t| t.predicate.as_str().to_string())
+            .collect();
+        assert_eq!(
+            top_level,
+            vec![
+                String::from("http://xmlns.com/foaf/0.1/name"),
+                String::from("http://www.w3.org/2000/10/swap/log#implies"),
+            ]
+        );
+
+        let unasserted: &UnassertedTriples = world.entity(entity).get().expect("formula triples");
+        assert_eq!(unasserted.len(), 2);
+        assert!(unasserted.iter().all(|t| t.graph.is_some()));
+    }
+
+    #[test_log::test]
+    fn current_triple_inside_rule_body() {
+        let (mut world, _) = setup_world(TestClient::new(), setup);
+
+        let t1 = "{ ?x <knows> <a> } => { ?x <name> \"A\" }.";
+        let entity = create_file(&mut world, t1, "http://example.com/ns.n3", "n3", Open);
+        assert!(!world.entity(entity).contains::<Dirty>());
+
+        world.entity_mut(entity).insert((
+            CompletionRequest(vec![]),
+            PositionComponent(lsp_types::Position::new(0, 29)),
+        ));
+        world.run_schedule(CompletionLabel);
+        let triple = world
+            .entity_mut(entity)
+            .take::<TripleComponent>()
+            .expect("triple component");
+
+        assert_eq!(triple.target, TripleTarget::Predicate);
+        assert_eq!(triple.triple.predicate.as_str(), "http://example.com/name");
+        assert!(triple.triple.graph.is_some());
+    }
+
+    #[test_log::test]
+    fn turtle_does_not_allow_formulae() {
+        let (mut world, _) = setup_world(TestClient::new(), setup);
+
+        let t1 = "{ <a> <b> <c> } => { <c> <b> <a> }.";
+        let entity = create_file(&mut world, t1, "http://example.com/ns.ttl", "turtle", Open);
+
+        assert!(world.entity(entity).contains::<Dirty>());
+    }
+}
@@ -0,0 +1,51 @@
+use bevy_ecs::prelude::*;
+use lsp_core::prelude::*;
+use tracing::{info, instrument};
+
+use crate::{lang::parser::parse, NTriplesLang};
+
+pub fn setup_parsing(world: &mut World) {
+    use lsp_core::feature::parse::*;
+    world.schedule_scope(ParseLabel, |_, schedule| {
+        schedule.add_systems(parse_ntriples_system.before(prefixes).before(triples));
+    });
+}
+
+/// Tokenize and parse the document in one pass, line by line.
+///
+/// N-Triples documents tend to be large dumps, so there is no chumsky parser (and no chumsky
+/// error recovery) involved.
+#[instrument(skip(query, commands), name = "parse_ntriples")]
+pub fn parse_ntriples_system(
+    query: Query<(Entity, &Source, &Label, &NTriplesLang), Changed<Source>>,
+    mut commands: Commands,
+) {
+    for (entity, source, label, lang) in &query {
+        let parsed = parse(source.0.as_str(), lang.quads);
+
+        info!(
+            "{} statements ({} errors)",
+            parsed.ntriples.statements.len(),
+            parsed.token_errors.len() + parsed.errors.len()
+        );
+
+        let dirty = !parsed.token_errors.is_empty() || !parsed.errors.is_empty();
+        let triples = Triples(parsed.ntriples.quads());
+
+        let mut entity = commands.entity(entity);
+        entity.insert((
+            Tokens(parsed.tokens),
+            Errors(parsed.token_errors),
+            Errors(parsed.errors),
+            Element::<NTriplesLang>(spanned(parsed.ntriples, 0..source.0.len())),
+            triples,
+            Prefixes(Vec::new(), label.0.clone()),
+        ));
+
+        if dirty {
+            entity.insert(Dirty);
+        } else {
+            entity.remove::<Dirty>();
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use std::fmt::Display;
+
+use lsp_core::prelude::{MyQuad, MyTerm, Spanned};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Term {
+    Iri(String),
+    BlankNode(String),
+    Literal {
+        value: String,
+        lang: Option<String>,
+        datatype: Option<String>,
+    },
+}
+
+impl Term {
+    pub fn to_my_term(&self, span: std::ops::Range<usize>) -> MyTerm<'static> {
+        match self {
+            Term::Iri(x) => MyTerm::named_node(x.clone(), span),
+            Term::BlankNode(x) => MyTerm::blank_node(x.clone(), span),
+            Term::Literal { value, .. } => MyTerm::literal(value.clone(), span),
+        }
+    }
+}
+
+impl Display for Term {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Term::Iri(x) => write!(f, "<{}>", x),
+            Term::BlankNode(x) => write!(f, "_:{}", x),
+            Term::Literal {
+                value,
+                lang,
+                datatype,
+            } => {
+                write!(f, "\"{}\"", value)?;
+                if let Some(lang) = lang {
+                    write!(f, "@{}", lang)?;
+                }
+                if let Some(datatype) = datatype {
+                    write!(f, "^^<{}>", datatype)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// A single line of an N-Triples or N-Quads document.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Statement {
+    pub subject: Spanned<Term>,
+    pub predicate: Spanned<Term>,
+    pub object: Spanned<Term>,
+    /// Graph label, only allowed in N-Quads.
+    pub graph: Option<Spanned<Term>>,
+}
+
+impl Display for Statement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {}",
+            self.subject.value(),
+            self.predicate.value(),
+            self.object.value()
+        )?;
+        if let Some(graph) = &self.graph {
+            write!(f, " {}", graph.value())?;
+        }
+        write!(f, " .")
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct NTriples {
+    pub statements: Vec<Spanned<Statement>>,
+}
+
+impl NTriples {
+    pub fn quads(&self) -> Vec<MyQuad<'static>> {
+        self.statements
+            .iter()
+            .map(|Spanned(st, span)| MyQuad {
+                subject: st.subject.to_my_term(st.subject.span().clone()),
+                predicate: st.predicate.to_my_term(st.predicate.span().clone()),
+                object: st.object.to_my_term(st.object.span().clone()),
+                graph: st.graph.as_ref().map(|g| g.to_my_term(g.span().clone())),
+                span: span.clone(),
+            })
+            .collect()
+    }
+}
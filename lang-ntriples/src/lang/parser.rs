@@ -0,0 +1,300 @@
+use chumsky::prelude::Simple;
+use lsp_core::prelude::{spanned, Spanned, Token};
+
+use super::{
+    model::{NTriples, Statement, Term},
+    tokenizer::tokenize_line,
+};
+
+/// Output of [`parse`], the tokens and the statements of the document with their errors.
+#[derive(Debug, Default)]
+pub struct Parsed {
+    pub tokens: Vec<Spanned<Token>>,
+    pub token_errors: Vec<Simple<char>>,
+    pub ntriples: NTriples,
+    pub errors: Vec<Simple<Token>>,
+}
+
+/// Parse an N-Triples document, or an N-Quads document when `quads` is set.
+///
+/// Every statement lives on its own line, so each line is tokenized and parsed on its own.
+/// An error only invalidates the line it is found on.
+pub fn parse(source: &str, quads: bool) -> Parsed {
+    let mut out = Parsed::default();
+
+    let mut offset = 0;
+    for line in source.split('\n') {
+        let line_offset = offset;
+        offset += line.len() + 1;
+
+        let content = line.strip_suffix('\r').unwrap_or(line);
+        let token_start = out.tokens.len();
+        let error_start = out.token_errors.len();
+
+        tokenize_line(content, line_offset, &mut out.tokens, &mut out.token_errors);
+
+        // Tokenizer errors are already reported, don't report the same line twice
+        if out.token_errors.len() > error_start {
+            continue;
+        }
+
+        let end = line_offset + content.len();
+        match parse_statement(&out.tokens[token_start..], end, quads) {
+            Ok(Some(statement)) => out.ntriples.statements.push(statement),
+            Ok(None) => {}
+            Err(e) => out.errors.push(e),
+        }
+    }
+
+    out
+}
+
+struct Line<'a> {
+    tokens: Vec<&'a Spanned<Token>>,
+    idx: usize,
+    end: usize,
+}
+
+impl<'a> Line<'a> {
+    fn peek(&self) -> Option<&'a Spanned<Token>> {
+        self.tokens.get(self.idx).copied()
+    }
+
+    fn expected(&self, what: &str) -> Simple<Token> {
+        match self.peek() {
+            Some(t) => Simple::custom(
+                t.span().clone(),
+                format!("Expected {}, found {}", what, t.value()),
+            ),
+            None => Simple::custom(self.end..self.end, format!("Expected {}", what)),
+        }
+    }
+
+    fn iri(&mut self) -> Result<Option<Spanned<Term>>, Simple<Token>> {
+        let Some(Spanned(Token::IRIRef(iri), span)) = self.peek() else {
+            return Ok(None);
+        };
+
+        if !iri.contains(':') {
+            return Err(Simple::custom(
+                span.clone(),
+                format!("Expected an absolute IRI, found <{}>", iri),
+            ));
+        }
+
+        self.idx += 1;
+        Ok(Some(spanned(Term::Iri(iri.clone()), span.clone())))
+    }
+
+    fn blank_node(&mut self) -> Option<Spanned<Term>> {
+        let Some(Spanned(Token::BlankNodeLabel(label), span)) = self.peek() else {
+            return None;
+        };
+        self.idx += 1;
+        Some(spanned(Term::BlankNode(label.clone()), span.clone()))
+    }
+
+    fn literal(&mut self) -> Result<Option<Spanned<Term>>, Simple<Token>> {
+        let Some(Spanned(Token::Str(value, _), span)) = self.peek() else {
+            return Ok(None);
+        };
+        self.idx += 1;
+
+        let mut span = span.clone();
+        let mut lang = None;
+        let mut datatype = None;
+        match self.peek() {
+            Some(Spanned(Token::LangTag(tag), s)) => {
+                self.idx += 1;
+                span.end = s.end;
+                lang = Some(tag.clone());
+            }
+            Some(Spanned(Token::DataTypeDelim, _)) => {
+                self.idx += 1;
+                let Some(Spanned(Term::Iri(iri), s)) = self.iri()? else {
+                    return Err(self.expected("a datatype IRI"));
+                };
+                span.end = s.end;
+                datatype = Some(iri);
+            }
+            _ => {}
+        }
+
+        Ok(Some(spanned(
+            Term::Literal {
+                value: value.clone(),
+                lang,
+                datatype,
+            },
+            span,
+        )))
+    }
+
+    fn iri_or_blank(&mut self, what: &str) -> Result<Spanned<Term>, Simple<Token>> {
+        if let Some(iri) = self.iri()? {
+            return Ok(iri);
+        }
+        self.blank_node().ok_or_else(|| self.expected(what))
+    }
+}
+
+fn parse_statement(
+    tokens: &[Spanned<Token>],
+    end: usize,
+    quads: bool,
+) -> Result<Option<Spanned<Statement>>, Simple<Token>> {
+    let mut line = Line {
+        tokens: tokens
+            .iter()
+            .filter(|x| !matches!(x.value(), Token::Comment(_)))
+            .collect(),
+        idx: 0,
+        end,
+    };
+
+    let Some(first) = line.peek() else {
+        return Ok(None);
+    };
+    let start = first.span().start;
+
+    let subject = line.iri_or_blank("a subject")?;
+    let predicate = line.iri()?.ok_or_else(|| line.expected("a predicate"))?;
+
+    let object = match line.iri()? {
+        Some(object) => object,
+        None => match line.blank_node() {
+            Some(object) => object,
+            None => line.literal()?.ok_or_else(|| line.expected("an object"))?,
+        },
+    };
+
+    let graph = match line.peek().map(|x| x.value()) {
+        Some(Token::IRIRef(_)) | Some(Token::BlankNodeLabel(_)) if quads => {
+            Some(line.iri_or_blank("a graph label")?)
+        }
+        Some(Token::IRIRef(_)) | Some(Token::BlankNodeLabel(_)) => {
+            return Err(line.expected("'.', graph labels are only allowed in N-Quads"));
+        }
+        _ => None,
+    };
+
+    let Some(Spanned(Token::Stop, stop)) = line.peek() else {
+        return Err(line.expected("'.'"));
+    };
+    let span = start..stop.end;
+    line.idx += 1;
+
+    if line.peek().is_some() {
+        return Err(line.expected("the end of the line"));
+    }
+
+    Ok(Some(spanned(
+        Statement {
+            subject,
+            predicate,
+            object,
+            graph,
+        },
+        span,
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_ntriples() {
+        let source = "# A comment
+<http://example.com/a> <http://example.com/p> \"A\"@en .
+_:b0 <http://example.com/p> \"1\"^^<http://www.w3.org/2001/XMLSchema#integer> . # trailing
+
+<http://example.com/a> <http://example.com/p> _:b0 .
+";
+        let parsed = parse(source, false);
+        assert!(parsed.token_errors.is_empty());
+        assert!(parsed.errors.is_empty());
+        assert_eq!(parsed.ntriples.statements.len(), 3);
+
+        let st = &parsed.ntriples.statements[1];
+        assert_eq!(
+            st.object.value(),
+            &Term::Literal {
+                value: String::from("1"),
+                lang: None,
+                datatype: Some(String::from("http://www.w3.org/2001/XMLSchema#integer")),
+            }
+        );
+        assert_eq!(
+            &source[st.span().clone()],
+            "_:b0 <http://example.com/p> \"1\"^^<http://www.w3.org/2001/XMLSchema#integer> ."
+        );
+    }
+
+    #[test]
+    fn parse_nquads() {
+        let source = "<http://example.com/a> <http://example.com/p> <http://example.com/b> <http://example.com/g> .
+<http://example.com/a> <http://example.com/p> <http://example.com/b> .";
+
+        let parsed = parse(source, true);
+        assert!(parsed.errors.is_empty());
+        let quads = parsed.ntriples.quads();
+        assert_eq!(quads.len(), 2);
+        assert_eq!(
+            quads[0].graph.as_ref().map(|x| x.as_str()),
+            Some("http://example.com/g")
+        );
+        assert!(quads[1].graph.is_none());
+
+        let parsed = parse(source, false);
+        assert_eq!(parsed.errors.len(), 1);
+        assert_eq!(parsed.ntriples.statements.len(), 1);
+    }
+
+    #[test]
+    fn errors_are_per_line() {
+        let source = "<http://example.com/a> <http://example.com/p> .
+<a> <http://example.com/p> <http://example.com/b> .
+<http://example.com/a> <http://example.com/p> <http://example.com/b>
+\"s\" <http://example.com/p> <http://example.com/b> .
+<http://example.com/a> <http://example.com/p> <http://example.com/b> .";
+
+        let parsed = parse(source, false);
+        let messages: Vec<_> = parsed
+            .errors
+            .iter()
+            .map(|e| match e.reason() {
+                chumsky::error::SimpleReason::Custom(msg) => msg.clone(),
+                _ => String::new(),
+            })
+            .collect();
+
+        assert_eq!(
+            messages,
+            vec![
+                "Expected an object, found '.'",
+                "Expected an absolute IRI, found <a>",
+                "Expected '.'",
+                "Expected a subject, found a string",
+            ]
+        );
+        assert_eq!(parsed.ntriples.statements.len(), 1);
+    }
+
+    #[test]
+    fn parse_large_document() {
+        let source: String = (0..100_000)
+            .map(|i| {
+                format!(
+                    "<http://example.com/s{}> <http://example.com/p> \"{}\" .\n",
+                    i, i
+                )
+            })
+            .collect();
+
+        let parsed = parse(&source, false);
+        assert!(parsed.errors.is_empty());
+        assert_eq!(parsed.ntriples.statements.len(), 100_000);
+        assert_eq!(parsed.tokens.len(), 400_000);
+    }
+}
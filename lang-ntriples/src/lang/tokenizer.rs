@@ -0,0 +1,196 @@
+use chumsky::prelude::Simple;
+use lsp_core::prelude::{spanned, Spanned, StringStyle, Token};
+
+fn is_delimiter(b: u8) -> bool {
+    matches!(b, b' ' | b'\t' | b'<' | b'"' | b'#')
+}
+
+/// Tokenize a single line of an N-Triples or N-Quads document, without the line ending.
+///
+/// `offset` is the offset of the line in the document, the spans of tokens and errors are
+/// absolute. Unknown or unterminated tokens become [`Token::Invalid`] and push an error, the
+/// rest of the line is still tokenized.
+pub fn tokenize_line(
+    line: &str,
+    offset: usize,
+    tokens: &mut Vec<Spanned<Token>>,
+    errors: &mut Vec<Simple<char>>,
+) {
+    let bytes = line.as_bytes();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let start = i;
+        let token = match bytes[i] {
+            b' ' | b'\t' => {
+                i += 1;
+                continue;
+            }
+            b'#' => {
+                i = bytes.len();
+                Token::Comment(line[start..].to_string())
+            }
+            b'<' => match line[start..].find('>') {
+                Some(end) => {
+                    i += end + 1;
+                    Token::IRIRef(line[start + 1..i - 1].to_string())
+                }
+                None => {
+                    i = bytes.len();
+                    errors.push(Simple::custom(
+                        offset + start..offset + i,
+                        "Unterminated IRI, expected '>'",
+                    ));
+                    Token::Invalid(line[start..].to_string())
+                }
+            },
+            b'_' if bytes.get(i + 1) == Some(&b':') => {
+                i += 2;
+                while i < bytes.len() && !is_delimiter(bytes[i]) {
+                    i += 1;
+                }
+                // A blank node label cannot end with a '.', that is the end of the statement
+                while i > start + 2 && bytes[i - 1] == b'.' {
+                    i -= 1;
+                }
+                if i == start + 2 {
+                    errors.push(Simple::custom(
+                        offset + start..offset + i,
+                        "Expected a blank node label",
+                    ));
+                }
+                Token::BlankNodeLabel(line[start + 2..i].to_string())
+            }
+            b'"' => {
+                i += 1;
+                let mut closed = false;
+                while i < bytes.len() {
+                    match bytes[i] {
+                        b'\\' => {
+                            // Skip the escaped character, which is not necessarily ascii
+                            i += 1 + line[i + 1..].chars().next().map_or(0, char::len_utf8);
+                        }
+                        b'"' => {
+                            i += 1;
+                            closed = true;
+                            break;
+                        }
+                        _ => i += 1,
+                    }
+                }
+
+                if closed {
+                    Token::Str(line[start + 1..i - 1].to_string(), StringStyle::Double)
+                } else {
+                    i = bytes.len();
+                    errors.push(Simple::custom(
+                        offset + start..offset + i,
+                        "Unterminated string, expected '\"'",
+                    ));
+                    Token::Invalid(line[start..].to_string())
+                }
+            }
+            b'@' => {
+                i += 1;
+                while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'-') {
+                    i += 1;
+                }
+                if i == start + 1 {
+                    errors.push(Simple::custom(
+                        offset + start..offset + i,
+                        "Expected a language tag",
+                    ));
+                }
+                Token::LangTag(line[start + 1..i].to_string())
+            }
+            b'^' if bytes.get(i + 1) == Some(&b'^') => {
+                i += 2;
+                Token::DataTypeDelim
+            }
+            b'.' => {
+                i += 1;
+                Token::Stop
+            }
+            _ => {
+                i += 1;
+                while i < bytes.len() && !is_delimiter(bytes[i]) {
+                    i += 1;
+                }
+                errors.push(Simple::custom(
+                    offset + start..offset + i,
+                    format!("Unexpected token '{}'", &line[start..i]),
+                ));
+                Token::Invalid(line[start..i].to_string())
+            }
+        };
+
+        tokens.push(spanned(token, offset + start..offset + i));
+    }
+}
+
+/// Tokenize a complete document, line by line.
+pub fn parse_tokens_str(text: &str) -> (Vec<Spanned<Token>>, Vec<Simple<char>>) {
+    let mut tokens = Vec::new();
+    let mut errors = Vec::new();
+
+    let mut offset = 0;
+    for line in text.split('\n') {
+        let content = line.strip_suffix('\r').unwrap_or(line);
+        tokenize_line(content, offset, &mut tokens, &mut errors);
+        offset += line.len() + 1;
+    }
+
+    (tokens, errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokenize_triple() {
+        let (tokens, errors) =
+            parse_tokens_str("<http://a.com/s> <http://a.com/p> _:b0.\n_:b1 <p> \"a \\\" b\"@en .");
+        assert!(errors.is_empty());
+
+        let tokens: Vec<_> = tokens.into_iter().map(|x| x.into_value()).collect();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::IRIRef("http://a.com/s".into()),
+                Token::IRIRef("http://a.com/p".into()),
+                Token::BlankNodeLabel("b0".into()),
+                Token::Stop,
+                Token::BlankNodeLabel("b1".into()),
+                Token::IRIRef("p".into()),
+                Token::Str("a \\\" b".into(), StringStyle::Double),
+                Token::LangTag("en".into()),
+                Token::Stop,
+            ]
+        );
+    }
+
+    #[test]
+    fn tokenize_spans_are_absolute() {
+        let (tokens, _) = parse_tokens_str("# comment\r\n<a> \"é\"^^<b> .");
+        let spans: Vec<_> = tokens.iter().map(|x| x.span().clone()).collect();
+        assert_eq!(spans, vec![0..9, 11..14, 15..19, 19..21, 21..24, 25..26]);
+    }
+
+    #[test]
+    fn tokenize_recovers_per_line() {
+        let (tokens, errors) = parse_tokens_str("<a> <b \"c\n<a> <b> foo .\n<a> <b> <c> .");
+        assert_eq!(errors.len(), 2);
+        assert_eq!(
+            tokens
+                .iter()
+                .filter(|x| matches!(x.value(), Token::Invalid(_)))
+                .count(),
+            2
+        );
+        assert_eq!(
+            tokens.iter().filter(|x| x.value() == &Token::Stop).count(),
+            2
+        );
+    }
+}
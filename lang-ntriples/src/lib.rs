@@ -0,0 +1,145 @@
+#![doc(
+    html_logo_url = "https://ajuvercr.github.io/semantic-web-lsp/assets/icons/favicon.png",
+    html_favicon_url = "https://ajuvercr.github.io/semantic-web-lsp/assets/icons/favicon.ico"
+)]
+//! N-Triples and N-Quads support.
+//!
+//! These formats have one statement per line, so they are tokenized and parsed line by line
+//! without chumsky recovery. This keeps large dumps with hundreds of thousands of lines fast,
+//! and an error never spreads past the line it is found on.
+use bevy_ecs::{component::Component, observer::Trigger, system::Commands, world::World};
+use chumsky::prelude::Simple;
+use ecs::setup_parsing;
+use lang::model::NTriples;
+use lsp_core::{
+    feature::diagnostics::{publish_diagnostics, pull_syntax_diagnostics},
+    lang::{Lang, LangHelper},
+    prelude::*,
+    CreateEvent,
+};
+use lsp_types::SemanticTokenType;
+
+pub mod ecs;
+pub mod lang;
+
+/// Marker component for N-Triples documents, `quads` is set for N-Quads documents.
+#[derive(Component)]
+pub struct NTriplesLang {
+    pub quads: bool,
+}
+
+#[derive(Debug)]
+pub struct NTriplesHelper;
+impl LangHelper for NTriplesHelper {
+    fn keyword(&self) -> &[&'static str] {
+        &[]
+    }
+}
+
+pub fn setup_world(world: &mut World) {
+    let mut semantic_token_dict = world.resource_mut::<SemanticTokensDict>();
+    NTriplesLang::LEGEND_TYPES.iter().for_each(|lt| {
+        if !semantic_token_dict.contains_key(lt) {
+            let l = semantic_token_dict.0.len();
+            semantic_token_dict.insert(lt.clone(), l);
+        }
+    });
+
+    world.observe(|trigger: Trigger<CreateEvent>, mut commands: Commands| {
+        let quads = match &trigger.event().language_id {
+            Some(x) if x == "ntriples" => Some(false),
+            Some(x) if x == "nquads" => Some(true),
+            Some(_) => None,
+            None => {
+                let url = trigger.event().url.as_str();
+                if url.ends_with(".nt") {
+                    Some(false)
+                } else if url.ends_with(".nq") {
+                    Some(true)
+                } else {
+                    None
+                }
+            }
+        };
+
+        if let Some(quads) = quads {
+            commands
+                .entity(trigger.entity())
+                .insert((NTriplesLang { quads }, DynLang(Box::new(NTriplesHelper))));
+        }
+    });
+
+    world.schedule_scope(lsp_core::feature::DiagnosticsLabel, |_, schedule| {
+        schedule.add_systems(publish_diagnostics::<NTriplesLang>);
+    });
+    world.schedule_scope(lsp_core::feature::PullDiagnosticsLabel, |_, schedule| {
+        schedule.add_systems(pull_syntax_diagnostics::<NTriplesLang>);
+    });
+
+    setup_parsing(world);
+}
+
+impl Lang for NTriplesLang {
+    type Token = Token;
+
+    type TokenError = Simple<char>;
+
+    type Element = NTriples;
+
+    type ElementError = Simple<Token>;
+
+    const LANG: &'static str = "ntriples";
+
+    const TRIGGERS: &'static [&'static str] = &[];
+    const CODE_ACTION: bool = false;
+    const HOVER: bool = true;
+
+    const LEGEND_TYPES: &'static [lsp_types::SemanticTokenType] = &[
+        semantic_token::LANG_TAG,
+        SemanticTokenType::COMMENT,
+        SemanticTokenType::NAMESPACE,
+        SemanticTokenType::PROPERTY,
+        SemanticTokenType::STRING,
+    ];
+
+    const PATTERN: Option<&'static str> = None;
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_core::{components::*, prelude::*};
+    use test_utils::{create_file, setup_world, TestClient};
+
+    #[test_log::test]
+    fn nquads_document_has_quads() {
+        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world);
+
+        let t1 = "<http://example.com/a> <http://example.com/p> \"A\" <http://example.com/g> .
+<http://example.com/a> <http://example.com/p> <http://example.com/b> .";
+        let entity = create_file(&mut world, t1, "http://example.com/ns.nq", "nquads", Open);
+
+        assert!(world.entity(entity).contains::<crate::NTriplesLang>());
+        assert!(!world.entity(entity).contains::<Dirty>());
+
+        let triples: &Triples = world.entity(entity).get().expect("triples");
+        assert_eq!(triples.len(), 2);
+        assert_eq!(
+            triples[0].graph.as_ref().map(|x| x.as_str()),
+            Some("http://example.com/g")
+        );
+    }
+
+    #[test_log::test]
+    fn ntriples_errors_mark_document_dirty() {
+        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world);
+
+        let t1 = "<http://example.com/a> <http://example.com/p> <http://example.com/b> <http://example.com/g> .
+<http://example.com/a> <http://example.com/p> <http://example.com/b> .";
+        let entity = create_file(&mut world, t1, "http://example.com/ns.nt", "ntriples", Open);
+
+        assert!(world.entity(entity).contains::<Dirty>());
+
+        let triples: &Triples = world.entity(entity).get().expect("triples");
+        assert_eq!(triples.len(), 1);
+    }
+}
@@ -0,0 +1,70 @@
+use bevy_ecs::prelude::*;
+use lsp_core::prelude::*;
+use lsp_types::Url;
+use tracing::{info, instrument};
+
+use crate::{lang::parser::parse, RdfXmlLang};
+
+pub fn setup_parsing(world: &mut World) {
+    use lsp_core::feature::parse::*;
+    world.schedule_scope(ParseLabel, |_, schedule| {
+        schedule.add_systems(parse_rdfxml_system.before(prefixes).before(triples));
+    });
+}
+
+/// Read the XML and derive the triples in one pass.
+///
+/// The namespace declarations become the [`Prefixes`] of the document, so prefixed names are
+/// checked and completed like in Turtle.
+#[instrument(skip(query, commands), name = "parse_rdfxml")]
+pub fn parse_rdfxml_system(
+    query: Query<(Entity, &Source, &Label), (With<RdfXmlLang>, Changed<Source>)>,
+    mut commands: Commands,
+) {
+    for (entity, source, label) in &query {
+        let parsed = parse(source.0.as_str(), &label.0);
+
+        info!(
+            "{} triples ({} errors)",
+            parsed.rdfxml.triples.len(),
+            parsed.token_errors.len() + parsed.errors.len()
+        );
+
+        let dirty = !parsed.token_errors.is_empty() || !parsed.errors.is_empty();
+        let triples = Triples(parsed.rdfxml.triples.clone());
+        let prefixes = parsed
+            .rdfxml
+            .namespaces
+            .iter()
+            .filter_map(|ns| {
+                let url = Url::parse(&ns.iri).ok()?;
+                Some(Prefix {
+                    prefix: ns.prefix.value().clone(),
+                    url,
+                })
+            })
+            .collect();
+        let base = parsed
+            .rdfxml
+            .base
+            .as_ref()
+            .and_then(|x| Url::parse(x).ok())
+            .unwrap_or_else(|| label.0.clone());
+
+        let mut entity = commands.entity(entity);
+        entity.insert((
+            Tokens(parsed.tokens),
+            Errors(parsed.token_errors),
+            Errors(parsed.errors),
+            Element::<RdfXmlLang>(spanned(parsed.rdfxml, 0..source.0.len())),
+            triples,
+            Prefixes(prefixes, base),
+        ));
+
+        if dirty {
+            entity.insert(Dirty);
+        } else {
+            entity.remove::<Dirty>();
+        }
+    }
+}
@@ -0,0 +1,19 @@
+use lsp_core::prelude::{MyQuad, Spanned};
+
+/// A namespace declaration, `xmlns:prefix="iri"`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Namespace {
+    /// Empty for the default namespace (`xmlns="iri"`).
+    pub prefix: Spanned<String>,
+    pub iri: Spanned<String>,
+}
+
+/// An RDF/XML document, reduced to what the language server needs: the declared namespaces
+/// and the triples it describes.
+#[derive(Clone, Debug, Default)]
+pub struct RdfXml {
+    /// `xml:base` of the root element, if any.
+    pub base: Option<String>,
+    pub namespaces: Vec<Namespace>,
+    pub triples: Vec<MyQuad<'static>>,
+}
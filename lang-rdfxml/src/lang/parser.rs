@@ -0,0 +1,597 @@
+use std::ops::Range;
+
+use chumsky::prelude::Simple;
+use lsp_core::prelude::{spanned, MyQuad, MyTerm, Spanned, Token};
+use lsp_types::Url;
+
+use super::{
+    model::{Namespace, RdfXml},
+    reader::{read, Attribute, Event, Tag},
+};
+
+const RDF: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+const XML: &str = "http://www.w3.org/XML/1998/namespace";
+
+/// Output of [`parse`], the tokens and the triples of the document with their errors.
+#[derive(Debug, Default)]
+pub struct Parsed {
+    pub tokens: Vec<Spanned<Token>>,
+    pub token_errors: Vec<Simple<char>>,
+    pub rdfxml: RdfXml,
+    pub errors: Vec<Simple<Token>>,
+}
+
+enum Kind {
+    /// `rdf:RDF`
+    Root,
+    /// Node element, its children are property elements.
+    ///
+    /// `triple` is set for `rdf:parseType="Resource"`, the triple linking the parent to this node
+    /// is added when the element ends.
+    Node {
+        subject: MyTerm<'static>,
+        li: usize,
+        triple: Option<(MyTerm<'static>, MyTerm<'static>, usize)>,
+    },
+    /// Property element, its object is a nested node element, a resource or its text.
+    Property {
+        subject: MyTerm<'static>,
+        predicate: MyTerm<'static>,
+        object: Option<MyTerm<'static>>,
+        text: Option<Spanned<String>>,
+        start: usize,
+    },
+    /// `rdf:parseType="Literal"`, the content is kept as is.
+    Literal {
+        subject: MyTerm<'static>,
+        predicate: MyTerm<'static>,
+        start: usize,
+        content: usize,
+    },
+    /// `rdf:parseType="Collection"`, every child is an item of an RDF list.
+    Collection {
+        subject: MyTerm<'static>,
+        predicate: MyTerm<'static>,
+        items: Vec<MyTerm<'static>>,
+        start: usize,
+    },
+    /// Content that does not describe triples.
+    Skip,
+}
+
+struct Frame {
+    kind: Kind,
+    base: Option<Url>,
+    /// Length of the namespace stack before this element.
+    namespaces: usize,
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    location: &'a Url,
+    namespaces: Vec<(String, String)>,
+    frames: Vec<Frame>,
+    /// Base of the element that is being started.
+    base: Option<Url>,
+    bnodes: usize,
+    rdfxml: RdfXml,
+    errors: Vec<Simple<Token>>,
+}
+
+fn is_syntax_attribute(iri: &str) -> bool {
+    iri.strip_prefix(RDF).is_some_and(|local| {
+        matches!(
+            local,
+            "about"
+                | "ID"
+                | "nodeID"
+                | "resource"
+                | "parseType"
+                | "datatype"
+                | "bagID"
+                | "aboutEach"
+        )
+    })
+}
+
+fn attribute_span(attribute: &Attribute) -> Range<usize> {
+    attribute.name.span().start..attribute.value.span().end + 1
+}
+
+impl<'a> Parser<'a> {
+    fn error(&mut self, span: Range<usize>, msg: impl ToString) {
+        self.errors.push(Simple::custom(span, msg.to_string()));
+    }
+
+    fn parent_base(&self) -> Option<&Url> {
+        match self.frames.last() {
+            Some(frame) => frame.base.as_ref(),
+            None => Some(self.location),
+        }
+    }
+
+    fn resolve(&self, iri: &str) -> String {
+        self.base
+            .as_ref()
+            .and_then(|base| base.join(iri).ok())
+            .map(|x| x.to_string())
+            .unwrap_or_else(|| iri.to_string())
+    }
+
+    fn bnode(&mut self, span: Range<usize>) -> MyTerm<'static> {
+        self.bnodes += 1;
+        MyTerm::blank_node(format!("internal_bnode_{}", self.bnodes), span)
+    }
+
+    fn push(
+        &mut self,
+        subject: MyTerm<'static>,
+        predicate: MyTerm<'static>,
+        object: MyTerm<'static>,
+        span: Range<usize>,
+    ) {
+        self.rdfxml.triples.push(MyQuad {
+            subject,
+            predicate,
+            object,
+            graph: None,
+            span,
+        });
+    }
+
+    /// Expands a qualified name to an IRI, undeclared prefixes are reported by the
+    /// undefined prefix diagnostics.
+    fn expand(&self, name: &str) -> Option<String> {
+        let (prefix, local) = name.split_once(':').unwrap_or(("", name));
+        if prefix == "xml" {
+            return Some(format!("{}{}", XML, local));
+        }
+        self.namespaces
+            .iter()
+            .rev()
+            .find(|(p, _)| p == prefix)
+            .map(|(_, ns)| format!("{}{}", ns, local))
+    }
+
+    /// Declares the namespaces of this tag, returns the remaining attributes with their
+    /// expanded names.
+    fn attributes<'t>(&mut self, tag: &'t Tag) -> Vec<(String, &'t Attribute)> {
+        for attribute in &tag.attributes {
+            let prefix = match attribute.name.as_str() {
+                "xmlns" => "",
+                x => match x.strip_prefix("xmlns:") {
+                    Some(prefix) => prefix,
+                    None => continue,
+                },
+            };
+
+            self.namespaces
+                .push((prefix.to_string(), attribute.value.value().clone()));
+            let start = attribute.name.span().end - prefix.len();
+            self.rdfxml.namespaces.push(Namespace {
+                prefix: spanned(prefix.to_string(), start..attribute.name.span().end),
+                iri: attribute.value.clone(),
+            });
+        }
+
+        tag.attributes
+            .iter()
+            .filter(|x| x.name.as_str() != "xmlns" && !x.name.starts_with("xmlns:"))
+            .filter_map(|x| self.expand(&x.name).map(|iri| (iri, x)))
+            .collect()
+    }
+
+    fn start(&mut self, tag: &Tag) {
+        let namespaces = self.namespaces.len();
+        let attributes = self.attributes(tag);
+
+        let xml_base = attributes
+            .iter()
+            .find(|(iri, _)| iri == &format!("{}base", XML))
+            .and_then(|(_, x)| match self.parent_base() {
+                Some(base) => base.join(&x.value).ok(),
+                None => Url::parse(&x.value).ok(),
+            });
+        if self.frames.is_empty() {
+            self.rdfxml.base = xml_base.as_ref().map(|x| x.to_string());
+        }
+        self.base = xml_base.or_else(|| self.parent_base().cloned());
+
+        let attributes: Vec<_> = attributes
+            .into_iter()
+            .filter(|(iri, _)| !iri.starts_with(XML))
+            .collect();
+
+        let kind = match self.expand(&tag.name) {
+            Some(iri) => self.element(tag, &iri, &attributes),
+            None => Kind::Skip,
+        };
+
+        if tag.empty {
+            self.end_kind(kind, tag.span.end);
+            self.namespaces.truncate(namespaces);
+        } else {
+            self.frames.push(Frame {
+                kind,
+                base: self.base.clone(),
+                namespaces,
+            });
+        }
+    }
+
+    fn element(&mut self, tag: &Tag, iri: &str, attributes: &[(String, &Attribute)]) -> Kind {
+        let parent = self.frames.last_mut().map(|x| &mut x.kind);
+        match parent {
+            None if iri == format!("{}RDF", RDF) => Kind::Root,
+            None | Some(Kind::Root) => self.node(tag, iri, attributes),
+            Some(Kind::Node { subject, li, .. }) => {
+                let subject = subject.clone();
+                let predicate = if iri == format!("{}li", RDF) {
+                    *li += 1;
+                    format!("{}_{}", RDF, li)
+                } else {
+                    iri.to_string()
+                };
+                self.property(tag, subject, predicate, attributes)
+            }
+            Some(Kind::Property { .. }) | Some(Kind::Collection { .. }) => {
+                let node = self.node(tag, iri, attributes);
+                let Kind::Node { subject, .. } = &node else {
+                    return node;
+                };
+                let subject = subject.clone();
+
+                match self.frames.last_mut().map(|x| &mut x.kind) {
+                    Some(Kind::Property {
+                        object: Some(_), ..
+                    }) => self.error(
+                        tag.span.clone(),
+                        "A property element can only have one object",
+                    ),
+                    Some(Kind::Property { object, .. }) => *object = Some(subject),
+                    Some(Kind::Collection { items, .. }) => items.push(subject),
+                    _ => {}
+                }
+
+                node
+            }
+            Some(Kind::Literal { .. }) | Some(Kind::Skip) => Kind::Skip,
+        }
+    }
+
+    fn node(&mut self, tag: &Tag, iri: &str, attributes: &[(String, &Attribute)]) -> Kind {
+        let find = |name: &str| {
+            attributes
+                .iter()
+                .find(|(x, _)| x.strip_prefix(RDF) == Some(name))
+                .map(|(_, x)| *x)
+        };
+
+        let subject = if let Some(about) = find("about") {
+            MyTerm::named_node(self.resolve(&about.value), about.value.span().clone())
+        } else if let Some(id) = find("ID") {
+            let iri = self.resolve(&format!("#{}", id.value.value()));
+            MyTerm::named_node(iri, id.value.span().clone())
+        } else if let Some(node_id) = find("nodeID") {
+            MyTerm::blank_node(node_id.value.value().clone(), node_id.value.span().clone())
+        } else {
+            self.bnode(tag.name.span().clone())
+        };
+
+        if iri != format!("{}Description", RDF) {
+            let predicate = MyTerm::named_node(format!("{}type", RDF), tag.name.span().clone());
+            let object = MyTerm::named_node(iri.to_string(), tag.name.span().clone());
+            self.push(subject.clone(), predicate, object, tag.span.clone());
+        }
+
+        self.property_attributes(&subject, attributes);
+
+        Kind::Node {
+            subject,
+            li: 0,
+            triple: None,
+        }
+    }
+
+    fn property_attributes(
+        &mut self,
+        subject: &MyTerm<'static>,
+        attributes: &[(String, &Attribute)],
+    ) {
+        for (iri, attribute) in attributes {
+            if is_syntax_attribute(iri) {
+                continue;
+            }
+
+            let predicate = MyTerm::named_node(iri.clone(), attribute.name.span().clone());
+            let span = attribute.value.span().clone();
+            let object = if iri == &format!("{}type", RDF) {
+                MyTerm::named_node(self.resolve(&attribute.value), span)
+            } else {
+                MyTerm::literal(attribute.value.value().clone(), span)
+            };
+            self.push(
+                subject.clone(),
+                predicate,
+                object,
+                attribute_span(attribute),
+            );
+        }
+    }
+
+    fn property(
+        &mut self,
+        tag: &Tag,
+        subject: MyTerm<'static>,
+        predicate: String,
+        attributes: &[(String, &Attribute)],
+    ) -> Kind {
+        let predicate = MyTerm::named_node(predicate, tag.name.span().clone());
+        let start = tag.span.start;
+        let find = |name: &str| {
+            attributes
+                .iter()
+                .find(|(x, _)| x.strip_prefix(RDF) == Some(name))
+                .map(|(_, x)| *x)
+        };
+
+        if let Some(parse_type) = find("parseType") {
+            return match parse_type.value.as_str() {
+                "Resource" => Kind::Node {
+                    subject: self.bnode(tag.name.span().clone()),
+                    li: 0,
+                    triple: Some((subject, predicate, start)),
+                },
+                "Collection" => Kind::Collection {
+                    subject,
+                    predicate,
+                    items: Vec::new(),
+                    start,
+                },
+                _ => Kind::Literal {
+                    subject,
+                    predicate,
+                    start,
+                    content: tag.span.end,
+                },
+            };
+        }
+
+        let object = if let Some(resource) = find("resource") {
+            Some(MyTerm::named_node(
+                self.resolve(&resource.value),
+                resource.value.span().clone(),
+            ))
+        } else if let Some(node_id) = find("nodeID") {
+            Some(MyTerm::blank_node(
+                node_id.value.value().clone(),
+                node_id.value.span().clone(),
+            ))
+        } else if attributes.iter().any(|(x, _)| !is_syntax_attribute(x)) {
+            Some(self.bnode(tag.name.span().clone()))
+        } else {
+            None
+        };
+
+        if let Some(object) = &object {
+            self.property_attributes(object, attributes);
+        }
+
+        Kind::Property {
+            subject,
+            predicate,
+            object,
+            text: None,
+            start,
+        }
+    }
+
+    fn text(&mut self, text: &Spanned<String>) {
+        match self.frames.last_mut().map(|x| &mut x.kind) {
+            Some(Kind::Property {
+                object: None,
+                text: current,
+                ..
+            }) => match current {
+                Some(Spanned(value, span)) => {
+                    value.push_str(text);
+                    span.end = text.span().end;
+                }
+                None => *current = Some(text.clone()),
+            },
+            Some(Kind::Literal { .. }) | Some(Kind::Skip) => {}
+            _ if text.trim().is_empty() => {}
+            _ => self.error(text.span().clone(), "Unexpected text, expected an element"),
+        }
+    }
+
+    fn end(&mut self, end: usize) {
+        let Some(frame) = self.frames.pop() else {
+            return;
+        };
+        self.namespaces.truncate(frame.namespaces);
+        self.end_kind(frame.kind, end);
+    }
+
+    fn end_kind(&mut self, kind: Kind, end: usize) {
+        match kind {
+            Kind::Root | Kind::Skip => {}
+            Kind::Node {
+                subject, triple, ..
+            } => {
+                if let Some((parent, predicate, start)) = triple {
+                    self.push(parent, predicate, subject, start..end);
+                }
+            }
+            Kind::Property {
+                subject,
+                predicate,
+                object,
+                text,
+                start,
+            } => {
+                let object = match (object, text) {
+                    (Some(object), Some(text)) if !text.trim().is_empty() => {
+                        self.error(
+                            text.span().clone(),
+                            "Unexpected text, the property already has an object",
+                        );
+                        object
+                    }
+                    (Some(object), _) => object,
+                    (None, Some(Spanned(text, span))) => MyTerm::literal(text, span),
+                    (None, None) => MyTerm::literal("", start..end),
+                };
+                self.push(subject, predicate, object, start..end);
+            }
+            Kind::Literal {
+                subject,
+                predicate,
+                start,
+                content,
+            } => {
+                let content = content.min(end);
+                let value_end = self.source[content..end].rfind("</").map(|i| content + i);
+                let value_end = value_end.unwrap_or(end);
+                let object = MyTerm::literal(
+                    self.source[content..value_end].to_string(),
+                    content..value_end,
+                );
+                self.push(subject, predicate, object, start..end);
+            }
+            Kind::Collection {
+                subject,
+                predicate,
+                items,
+                start,
+            } => {
+                let mut list = MyTerm::named_node(format!("{}nil", RDF), end..end);
+                for item in items.into_iter().rev() {
+                    let span = item.span.clone();
+                    let node = self.bnode(span.clone());
+                    let first = MyTerm::named_node(format!("{}first", RDF), span.clone());
+                    let rest = MyTerm::named_node(format!("{}rest", RDF), span.clone());
+                    self.push(node.clone(), first, item, span.clone());
+                    self.push(node.clone(), rest, list, span);
+                    list = node;
+                }
+                self.push(subject, predicate, list, start..end);
+            }
+        }
+    }
+}
+
+/// Parse an RDF/XML document into its namespaces and triples.
+///
+/// Relative IRIs are resolved against `xml:base`, or against `location` when no base is set.
+/// Elements with undeclared prefixes are skipped, together with their content.
+pub fn parse(source: &str, location: &Url) -> Parsed {
+    let read = read(source);
+
+    let mut parser = Parser {
+        source,
+        location,
+        namespaces: Vec::new(),
+        frames: Vec::new(),
+        base: None,
+        bnodes: 0,
+        rdfxml: RdfXml::default(),
+        errors: Vec::new(),
+    };
+
+    for event in &read.events {
+        match event {
+            Event::Start(tag) => parser.start(tag),
+            Event::End { span, .. } => parser.end(span.end),
+            Event::Text(text) => parser.text(text),
+        }
+    }
+
+    Parsed {
+        tokens: read.tokens,
+        token_errors: read.errors,
+        rdfxml: parser.rdfxml,
+        errors: parser.errors,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quads(source: &str) -> Vec<String> {
+        let location = Url::parse("http://example.com/doc.rdf").unwrap();
+        let parsed = parse(source, &location);
+        assert!(parsed.errors.is_empty(), "{:?}", parsed.errors);
+        assert!(parsed.token_errors.is_empty(), "{:?}", parsed.token_errors);
+        parsed
+            .rdfxml
+            .triples
+            .iter()
+            .map(|q| format!("{} {} {}", q.subject, q.predicate, q.object))
+            .collect()
+    }
+
+    #[test]
+    fn parse_nodes_and_properties() {
+        let source = r##"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+         xmlns:ex="http://example.com/">
+  <ex:Person rdf:about="#alice" ex:name="Alice">
+    <ex:knows rdf:resource="http://example.com/bob"/>
+    <ex:age>42</ex:age>
+  </ex:Person>
+</rdf:RDF>"##;
+
+        let quads = quads(source);
+        assert_eq!(
+            quads,
+            vec![
+                "<http://example.com/doc.rdf#alice> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://example.com/Person>",
+                "<http://example.com/doc.rdf#alice> <http://example.com/name> \"Alice\"",
+                "<http://example.com/doc.rdf#alice> <http://example.com/knows> <http://example.com/bob>",
+                "<http://example.com/doc.rdf#alice> <http://example.com/age> \"42\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_nested_nodes_and_collections() {
+        let source = r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+         xmlns:ex="http://example.com/" xml:base="http://example.com/">
+  <rdf:Description rdf:about="a">
+    <ex:p>
+      <rdf:Description rdf:about="b"/>
+    </ex:p>
+    <ex:q rdf:parseType="Resource">
+      <ex:r>R</ex:r>
+    </ex:q>
+    <ex:list rdf:parseType="Collection">
+      <rdf:Description rdf:about="c"/>
+    </ex:list>
+  </rdf:Description>
+</rdf:RDF>"#;
+
+        let quads = quads(source);
+        assert_eq!(quads.len(), 6, "{:#?}", quads);
+        assert!(quads.contains(&String::from(
+            "<http://example.com/a> <http://example.com/p> <http://example.com/b>"
+        )));
+        assert!(quads.contains(&String::from(
+            "_:internal_bnode_1 <http://example.com/r> \"R\""
+        )));
+        assert!(quads.contains(&String::from(
+            "_:internal_bnode_2 <http://www.w3.org/1999/02/22-rdf-syntax-ns#first> <http://example.com/c>"
+        )));
+    }
+
+    #[test]
+    fn subject_span_is_about_value() {
+        let source = r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"><rdf:Description rdf:about="http://example.com/a"><rdf:value>A</rdf:value></rdf:Description></rdf:RDF>"#;
+        let location = Url::parse("http://example.com/doc.rdf").unwrap();
+        let parsed = parse(source, &location);
+
+        let triple = &parsed.rdfxml.triples[0];
+        assert_eq!(&source[triple.subject.span.clone()], "http://example.com/a");
+        assert_eq!(&source[triple.span.clone()], "<rdf:value>A</rdf:value>");
+    }
+}
@@ -0,0 +1,468 @@
+use std::{collections::HashMap, ops::Range};
+
+use chumsky::prelude::Simple;
+use lsp_core::prelude::{spanned, Spanned, StringStyle, Token};
+
+/// Attribute of a start tag, entities in the value are already replaced.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Attribute {
+    pub name: Spanned<String>,
+    /// The value, its span excludes the quotes.
+    pub value: Spanned<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Tag {
+    pub name: Spanned<String>,
+    pub attributes: Vec<Attribute>,
+    /// `<name/>`, no end tag follows.
+    pub empty: bool,
+    pub span: Range<usize>,
+}
+
+impl Tag {
+    pub fn attribute(&self, name: &str) -> Option<&Attribute> {
+        self.attributes.iter().find(|x| x.name.as_str() == name)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event {
+    Start(Tag),
+    End {
+        name: Spanned<String>,
+        span: Range<usize>,
+    },
+    /// Character data, including CDATA sections.
+    Text(Spanned<String>),
+}
+
+/// Output of [`read`], the XML events with the tokens used for highlighting.
+#[derive(Debug, Default)]
+pub struct Read {
+    pub events: Vec<Event>,
+    pub tokens: Vec<Spanned<Token>>,
+    pub errors: Vec<Simple<char>>,
+}
+
+struct Reader<'a> {
+    source: &'a str,
+    idx: usize,
+    entities: HashMap<String, String>,
+    open: Vec<String>,
+    out: Read,
+}
+
+fn is_name_end(c: u8) -> bool {
+    c.is_ascii_whitespace() || c == b'/' || c == b'>' || c == b'='
+}
+
+/// Token of a qualified name, names of the `xml` and `xmlns` namespaces are not tokens.
+fn name_token(name: &Spanned<String>) -> Option<Spanned<Token>> {
+    let (prefix, local) = name.split_once(':')?;
+    if prefix == "xml" || prefix == "xmlns" {
+        return None;
+    }
+    Some(spanned(
+        Token::PNameLN(Some(prefix.to_string()), local.to_string()),
+        name.span().clone(),
+    ))
+}
+
+impl<'a> Reader<'a> {
+    fn rest(&self) -> &'a str {
+        &self.source[self.idx..]
+    }
+
+    fn error(&mut self, span: Range<usize>, msg: impl ToString) {
+        self.out.errors.push(Simple::custom(span, msg.to_string()));
+    }
+
+    /// Moves past `end`, returns the content before it.
+    fn until(&mut self, end: &str) -> Option<Spanned<&'a str>> {
+        let start = self.idx;
+        match self.rest().find(end) {
+            Some(i) => {
+                self.idx += i + end.len();
+                Some(spanned(&self.source[start..start + i], start..start + i))
+            }
+            None => {
+                self.idx = self.source.len();
+                self.error(
+                    start..self.source.len(),
+                    format!("Expected '{}' before the end of the document", end),
+                );
+                None
+            }
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let bytes = self.source.as_bytes();
+        while self.idx < bytes.len() && bytes[self.idx].is_ascii_whitespace() {
+            self.idx += 1;
+        }
+    }
+
+    fn name(&mut self) -> Spanned<String> {
+        let bytes = self.source.as_bytes();
+        let start = self.idx;
+        while self.idx < bytes.len() && !is_name_end(bytes[self.idx]) {
+            self.idx += 1;
+        }
+        spanned(self.source[start..self.idx].to_string(), start..self.idx)
+    }
+
+    /// Replaces character references and entities, unknown entities are kept as is.
+    fn unescape(&mut self, Spanned(value, span): Spanned<&str>) -> String {
+        if !value.contains('&') {
+            return value.to_string();
+        }
+
+        let mut out = String::with_capacity(value.len());
+        let mut rest = value;
+        while let Some(i) = rest.find('&') {
+            out += &rest[..i];
+            let offset = span.start + value.len() - rest.len() + i;
+            rest = &rest[i..];
+
+            let Some(end) = rest.find(';') else {
+                self.error(offset..offset + 1, "Unterminated entity reference");
+                break;
+            };
+
+            let name = &rest[1..end];
+            let replaced = match name {
+                "lt" => Some(String::from("<")),
+                "gt" => Some(String::from(">")),
+                "amp" => Some(String::from("&")),
+                "quot" => Some(String::from("\"")),
+                "apos" => Some(String::from("'")),
+                _ if name.starts_with("#x") => u32::from_str_radix(&name[2..], 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .map(String::from),
+                _ if name.starts_with('#') => name[1..]
+                    .parse::<u32>()
+                    .ok()
+                    .and_then(char::from_u32)
+                    .map(String::from),
+                _ => self.entities.get(name).cloned(),
+            };
+
+            match replaced {
+                Some(x) => out += &x,
+                None => {
+                    self.error(
+                        offset..offset + end + 1,
+                        format!("Unknown entity '&{};'", name),
+                    );
+                    out += &rest[..end + 1];
+                }
+            }
+            rest = &rest[end + 1..];
+        }
+        out += rest;
+        out
+    }
+
+    /// `<!DOCTYPE rdf:RDF [ <!ENTITY owl "http://www.w3.org/2002/07/owl#" > ]>`, only the
+    /// entity declarations are used.
+    fn doctype(&mut self) {
+        let start = self.idx;
+        let end = match (self.rest().find('['), self.rest().find('>')) {
+            (Some(open), Some(close)) if open < close => {
+                self.idx += open + 1;
+                let Some(subset) = self.until("]") else {
+                    return;
+                };
+                self.entity_declarations(subset.value());
+                self.until(">")
+            }
+            _ => self.until(">"),
+        };
+
+        if end.is_none() {
+            self.error(start..self.source.len(), "Unterminated DOCTYPE");
+        }
+    }
+
+    fn entity_declarations(&mut self, subset: &str) {
+        for decl in subset.split("<!ENTITY").skip(1) {
+            let decl = decl.trim_start();
+            let Some((name, rest)) = decl.split_once(char::is_whitespace) else {
+                continue;
+            };
+            let rest = rest.trim_start();
+            let Some(quote) = rest.chars().next().filter(|x| *x == '"' || *x == '\'') else {
+                continue;
+            };
+            if let Some((value, _)) = rest[1..].split_once(quote) {
+                self.entities.insert(name.to_string(), value.to_string());
+            }
+        }
+    }
+
+    fn comment(&mut self) {
+        let start = self.idx;
+        self.idx += 4;
+        if self.until("-->").is_some() {
+            let span = start..self.idx;
+            self.out.tokens.push(spanned(
+                Token::Comment(self.source[span.clone()].to_string()),
+                span,
+            ));
+        }
+    }
+
+    fn text(&mut self) {
+        let start = self.idx;
+        let end = self
+            .rest()
+            .find('<')
+            .map(|i| start + i)
+            .unwrap_or(self.source.len());
+        self.idx = end;
+
+        let raw = spanned(&self.source[start..end], start..end);
+        let value = self.unescape(raw);
+
+        let trimmed = self.source[start..end].trim();
+        if !trimmed.is_empty() {
+            let offset = self.source[start..end].find(trimmed).unwrap_or(0);
+            let span = start + offset..start + offset + trimmed.len();
+            self.out.tokens.push(spanned(
+                Token::Str(value.trim().to_string(), StringStyle::Double),
+                span,
+            ));
+        }
+
+        self.out
+            .events
+            .push(Event::Text(spanned(value, start..end)));
+    }
+
+    fn cdata(&mut self) {
+        self.idx += "<![CDATA[".len();
+        if let Some(Spanned(value, span)) = self.until("]]>") {
+            self.out
+                .events
+                .push(Event::Text(spanned(value.to_string(), span)));
+        }
+    }
+
+    fn end_tag(&mut self) {
+        let start = self.idx;
+        self.idx += 2;
+        let name = self.name();
+        self.skip_whitespace();
+        if self.rest().starts_with('>') {
+            self.idx += 1;
+        } else {
+            self.error(self.idx..self.idx, "Expected '>'");
+        }
+
+        self.out.tokens.extend(name_token(&name));
+
+        match self.open.pop() {
+            Some(open) if open == name.as_str() => {}
+            Some(open) => self.error(
+                name.span().clone(),
+                format!("Expected </{}>, found </{}>", open, name.value()),
+            ),
+            None => self.error(
+                name.span().clone(),
+                format!("Unexpected </{}>", name.value()),
+            ),
+        }
+
+        self.out.events.push(Event::End {
+            name,
+            span: start..self.idx,
+        });
+    }
+
+    fn start_tag(&mut self) {
+        let start = self.idx;
+        self.idx += 1;
+        let name = self.name();
+        if name.is_empty() {
+            self.error(start..start + 1, "Expected a tag name");
+        }
+        self.out.tokens.extend(name_token(&name));
+
+        let mut attributes = Vec::new();
+        let empty = loop {
+            self.skip_whitespace();
+            let rest = self.rest();
+            if rest.is_empty() {
+                self.error(start..self.idx, format!("Unterminated <{}>", name.value()));
+                break false;
+            }
+            if rest.starts_with("/>") {
+                self.idx += 2;
+                break true;
+            }
+            if rest.starts_with('>') {
+                self.idx += 1;
+                break false;
+            }
+
+            match self.attribute() {
+                Some(attribute) => attributes.push(attribute),
+                None => {
+                    // Skip the rest of the tag
+                    let end = self.rest().find('>').map(|i| self.idx + i + 1);
+                    self.idx = end.unwrap_or(self.source.len());
+                    break false;
+                }
+            }
+        };
+
+        if !empty {
+            self.open.push(name.value().clone());
+        }
+
+        self.out.events.push(Event::Start(Tag {
+            name,
+            attributes,
+            empty,
+            span: start..self.idx,
+        }));
+    }
+
+    fn attribute(&mut self) -> Option<Attribute> {
+        let name = self.name();
+        if name.is_empty() {
+            self.error(self.idx..self.idx + 1, "Expected an attribute name");
+            return None;
+        }
+
+        self.skip_whitespace();
+        if !self.rest().starts_with('=') {
+            self.error(self.idx..self.idx, "Expected '='");
+            return None;
+        }
+        self.idx += 1;
+        self.skip_whitespace();
+
+        let (quote, style) = match self.rest().chars().next() {
+            Some('"') => ("\"", StringStyle::Double),
+            Some('\'') => ("'", StringStyle::Single),
+            _ => {
+                self.error(self.idx..self.idx, "Expected a quoted attribute value");
+                return None;
+            }
+        };
+        self.idx += 1;
+        let raw = self.until(quote)?;
+        let span = raw.span().clone();
+        let value = self.unescape(raw);
+
+        self.out.tokens.extend(name_token(&name));
+        self.out.tokens.push(spanned(
+            Token::Str(value.clone(), style),
+            span.start - 1..span.end + 1,
+        ));
+
+        Some(Attribute {
+            name,
+            value: spanned(value, span),
+        })
+    }
+}
+
+/// Read an XML document into events.
+///
+/// Entity declarations from the internal DOCTYPE subset are replaced, as RDF/XML ontologies often
+/// use them for namespaces (`rdf:about="&owl;Thing"`).
+pub fn read(source: &str) -> Read {
+    let mut reader = Reader {
+        source,
+        idx: 0,
+        entities: HashMap::new(),
+        open: Vec::new(),
+        out: Read::default(),
+    };
+
+    while reader.idx < source.len() {
+        let rest = reader.rest();
+        if rest.starts_with("<!--") {
+            reader.comment();
+        } else if rest.starts_with("<![CDATA[") {
+            reader.cdata();
+        } else if rest.starts_with("<?") {
+            reader.until("?>");
+        } else if rest.starts_with("<!DOCTYPE") {
+            reader.doctype();
+        } else if rest.starts_with("</") {
+            reader.end_tag();
+        } else if rest.starts_with('<') {
+            reader.start_tag();
+        } else {
+            reader.text();
+        }
+    }
+
+    for open in std::mem::take(&mut reader.open) {
+        let end = source.len();
+        reader.error(end..end, format!("Expected </{}>", open));
+    }
+
+    reader.out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_tags_and_entities() {
+        let source = r#"<?xml version="1.0"?>
+<!DOCTYPE rdf:RDF [
+    <!ENTITY ex "http://example.com/" >
+]>
+<!-- comment -->
+<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+  <rdf:Description rdf:about="&ex;a">A &amp; B<![CDATA[<c>]]></rdf:Description>
+  <ex:b/>
+</rdf:RDF>"#;
+
+        let read = read(source);
+        assert!(read.errors.is_empty(), "{:?}", read.errors);
+
+        let events: Vec<_> = read
+            .events
+            .iter()
+            .filter(|x| !matches!(x, Event::Text(t) if t.trim().is_empty()))
+            .collect();
+
+        let Event::Start(tag) = &events[1] else {
+            panic!("expected a start tag, found {:?}", events[1]);
+        };
+        assert_eq!(tag.name.as_str(), "rdf:Description");
+        let about = tag.attribute("rdf:about").expect("about");
+        assert_eq!(about.value.as_str(), "http://example.com/a");
+        assert_eq!(&source[about.value.span().clone()], "&ex;a");
+
+        assert!(matches!(events[2], Event::Text(x) if x.as_str() == "A & B"));
+        assert!(matches!(events[3], Event::Text(x) if x.as_str() == "<c>"));
+
+        let Event::Start(tag) = &events[5] else {
+            panic!("expected a start tag, found {:?}", events[5]);
+        };
+        assert!(tag.empty);
+        assert_eq!(events.len(), 7);
+
+        assert!(read
+            .tokens
+            .iter()
+            .any(|t| matches!(t.value(), Token::Comment(_))));
+    }
+
+    #[test]
+    fn mismatched_tags_are_errors() {
+        let read = read("<a><b></a>");
+        assert_eq!(read.errors.len(), 2);
+    }
+}
@@ -0,0 +1,144 @@
+#![doc(
+    html_logo_url = "https://ajuvercr.github.io/semantic-web-lsp/assets/icons/favicon.png",
+    html_favicon_url = "https://ajuvercr.github.io/semantic-web-lsp/assets/icons/favicon.ico"
+)]
+//! RDF/XML read support.
+//!
+//! Many vocabularies are only published as RDF/XML (`.rdf` and `.owl` files). Documents are
+//! read with a small XML reader that keeps spans, the triples are derived from the XML events
+//! so goto definition, hover and completion work on vocabularies in this format.
+//! There is no formatter.
+use bevy_ecs::{component::Component, observer::Trigger, system::Commands, world::World};
+use chumsky::prelude::Simple;
+use ecs::setup_parsing;
+use lang::model::RdfXml;
+use lsp_core::{
+    feature::diagnostics::{publish_diagnostics, pull_syntax_diagnostics},
+    lang::{Lang, LangHelper},
+    prelude::*,
+    CreateEvent,
+};
+use lsp_types::SemanticTokenType;
+
+pub mod ecs;
+pub mod lang;
+
+/// Marker component for RDF/XML documents.
+#[derive(Component)]
+pub struct RdfXmlLang;
+
+#[derive(Debug)]
+pub struct RdfXmlHelper;
+impl LangHelper for RdfXmlHelper {
+    fn keyword(&self) -> &[&'static str] {
+        &[]
+    }
+}
+
+pub fn setup_world(world: &mut World) {
+    let mut semantic_token_dict = world.resource_mut::<SemanticTokensDict>();
+    RdfXmlLang::LEGEND_TYPES.iter().for_each(|lt| {
+        if !semantic_token_dict.contains_key(lt) {
+            let l = semantic_token_dict.0.len();
+            semantic_token_dict.insert(lt.clone(), l);
+        }
+    });
+
+    world.observe(|trigger: Trigger<CreateEvent>, mut commands: Commands| {
+        let is_rdfxml = match &trigger.event().language_id {
+            Some(x) => x == "rdfxml",
+            None => {
+                let url = trigger.event().url.as_str();
+                url.ends_with(".rdf") || url.ends_with(".owl")
+            }
+        };
+
+        if is_rdfxml {
+            commands
+                .entity(trigger.entity())
+                .insert((RdfXmlLang, DynLang(Box::new(RdfXmlHelper))));
+        }
+    });
+
+    world.schedule_scope(lsp_core::feature::DiagnosticsLabel, |_, schedule| {
+        schedule.add_systems(publish_diagnostics::<RdfXmlLang>);
+    });
+    world.schedule_scope(lsp_core::feature::PullDiagnosticsLabel, |_, schedule| {
+        schedule.add_systems(pull_syntax_diagnostics::<RdfXmlLang>);
+    });
+
+    setup_parsing(world);
+}
+
+impl Lang for RdfXmlLang {
+    type Token = Token;
+
+    type TokenError = Simple<char>;
+
+    type Element = RdfXml;
+
+    type ElementError = Simple<Token>;
+
+    const LANG: &'static str = "rdfxml";
+
+    const TRIGGERS: &'static [&'static str] = &[];
+    const CODE_ACTION: bool = false;
+    const HOVER: bool = true;
+
+    const LEGEND_TYPES: &'static [lsp_types::SemanticTokenType] = &[
+        SemanticTokenType::COMMENT,
+        SemanticTokenType::NAMESPACE,
+        SemanticTokenType::ENUM_MEMBER,
+        SemanticTokenType::PROPERTY,
+        SemanticTokenType::STRING,
+    ];
+
+    const PATTERN: Option<&'static str> = None;
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_core::{components::*, prelude::*};
+    use test_utils::{create_file, setup_world, TestClient};
+
+    #[test_log::test]
+    fn rdfxml_document_has_triples() {
+        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world);
+
+        let t1 = r#"<?xml version="1.0"?>
+<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+         xmlns:rdfs="http://www.w3.org/2000/01/rdf-schema#">
+  <rdfs:Class rdf:about="http://example.com/Person">
+    <rdfs:label>Person</rdfs:label>
+  </rdfs:Class>
+</rdf:RDF>"#;
+        let entity = create_file(&mut world, t1, "http://example.com/ns.rdf", "rdfxml", Open);
+
+        assert!(world.entity(entity).contains::<crate::RdfXmlLang>());
+        assert!(!world.entity(entity).contains::<Dirty>());
+
+        let triples: &Triples = world.entity(entity).get().expect("triples");
+        assert_eq!(triples.len(), 2);
+        assert_eq!(triples[0].subject.as_str(), "http://example.com/Person");
+        assert_eq!(
+            &t1[triples[0].subject.span.clone()],
+            "http://example.com/Person"
+        );
+
+        let prefixes: &Prefixes = world.entity(entity).get().expect("prefixes");
+        assert_eq!(prefixes.len(), 2);
+    }
+
+    #[test_log::test]
+    fn rdfxml_errors_mark_document_dirty() {
+        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world);
+
+        let t1 = r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+  <rdf:Description rdf:about="http://example.com/a">
+</rdf:RDF>"#;
+        let entity = create_file(&mut world, t1, "http://example.com/ns.owl", "", Open);
+
+        assert!(world.entity(entity).contains::<crate::RdfXmlLang>());
+        assert!(world.entity(entity).contains::<Dirty>());
+    }
+}
@@ -0,0 +1,118 @@
+use bevy_ecs::prelude::*;
+use lsp_core::{
+    prelude::*,
+    systems::prefix::{prefix_completion_helper, undefined_prefix_action_helper},
+};
+use lsp_types::{Position, Range, TextEdit};
+use tracing::{info, instrument};
+
+use crate::{
+    lang::{parser::parse, tokenizer::parse_tokens_str},
+    ShexLang,
+};
+
+mod validate;
+pub use validate::{pull_shex, validate_shex, validate_with_updated_shex};
+
+pub fn setup_parsing(world: &mut World) {
+    use lsp_core::feature::parse::*;
+    world.schedule_scope(ParseLabel, |_, schedule| {
+        schedule.add_systems(parse_shex_system.before(prefixes).before(triples));
+    });
+}
+
+pub fn setup_completion(world: &mut World) {
+    use lsp_core::feature::completion::*;
+    world.schedule_scope(CompletionLabel, |_, schedule| {
+        schedule.add_systems(shex_lov_undefined_prefix_completion.after(get_current_token));
+    });
+}
+
+pub fn setup_code_action(world: &mut World) {
+    world.schedule_scope(CodeActionLabel, |_, schedule| {
+        schedule.add_systems(shex_undefined_prefix_action);
+    });
+}
+
+pub fn setup_validation(world: &mut World) {
+    world.schedule_scope(SaveLabel, |_, schedule| {
+        schedule.add_systems((validate_shex, validate_with_updated_shex));
+    });
+    world.schedule_scope(PullDiagnosticsLabel, |_, schedule| {
+        schedule.add_systems(pull_shex);
+    });
+}
+
+/// Tokenize and parse the schema in one pass.
+///
+/// The `PREFIX` declarations become the [`Prefixes`] of the document, so undefined prefixes are
+/// reported and prefixes are completed like in Turtle.
+#[instrument(skip(query, commands), name = "parse_shex")]
+pub fn parse_shex_system(
+    query: Query<(Entity, &Source, &Label), (With<ShexLang>, Changed<Source>)>,
+    mut commands: Commands,
+) {
+    for (entity, source, label) in &query {
+        let (tokens, token_errors) = parse_tokens_str(source.0.as_str());
+        let (schema, errors) = parse(&tokens, &label.0);
+        info!(
+            "{} shapes ({} errors)",
+            schema.shapes.len(),
+            token_errors.len() + errors.len()
+        );
+
+        let dirty = !token_errors.is_empty() || !errors.is_empty();
+        let prefixes = schema
+            .prefixes
+            .iter()
+            .filter_map(|x| {
+                let url = schema.base.join(x.iri.value()).ok()?;
+                Some(Prefix {
+                    prefix: x.prefix.value().clone(),
+                    url,
+                })
+            })
+            .collect();
+        let base = schema.base.clone();
+
+        let mut entity = commands.entity(entity);
+        entity.insert((
+            Tokens(tokens),
+            Errors(token_errors),
+            Errors(errors),
+            Element::<ShexLang>(spanned(schema, 0..source.0.len())),
+            Prefixes(prefixes, base),
+        ));
+
+        if dirty {
+            entity.insert(Dirty);
+        } else {
+            entity.remove::<Dirty>();
+        }
+    }
+}
+
+/// `PREFIX` declaration that is inserted at the top of the schema.
+fn prefix_edit(name: &str, location: &str) -> Option<Vec<TextEdit>> {
+    let start = Position::new(0, 0);
+    Some(vec![TextEdit {
+        range: Range::new(start, start),
+        new_text: format!("PREFIX {}: <{}>\n", name, location),
+    }])
+}
+
+pub fn shex_undefined_prefix_action(
+    mut query: Query<(&Label, &mut CodeActionRequest), With<ShexLang>>,
+) {
+    for (label, mut req) in &mut query {
+        undefined_prefix_action_helper(&mut req, label, prefix_edit);
+    }
+}
+
+pub fn shex_lov_undefined_prefix_completion(
+    mut query: Query<(&TokenComponent, &Prefixes, &mut CompletionRequest), With<ShexLang>>,
+) {
+    for (word, prefixes, mut req) in &mut query {
+        prefix_completion_helper(word, prefixes, &mut req.0, prefix_edit);
+    }
+}
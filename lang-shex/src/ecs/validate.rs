@@ -0,0 +1,339 @@
+use bevy_ecs::prelude::*;
+use lsp_core::prelude::*;
+use lsp_types::{Diagnostic, DiagnosticSeverity, TextDocumentItem, Url};
+use tracing::{debug, info, instrument, warn};
+
+use crate::{
+    lang::{
+        model::{Name, Schema},
+        shape_map::{parse_shape_map, Association, NodeSelector, ShapeSelector},
+        validate::Validator,
+    },
+    ShexLang,
+};
+
+const REASON: &str = "shex_validation";
+
+type Schemas<'w, 's> = Query<'w, 's, (&'static Label, &'static Element<ShexLang>), Without<Dirty>>;
+
+/// Whether the schema at `schema` applies to the data document at `label`, the same way SHACL
+/// shapes apply: it is linked, configured or the same document.
+fn is_linked(label: &Url, schema: &Url, links: &[(Url, &str)], configured: &[Url]) -> bool {
+    label == schema
+        || configured.contains(schema)
+        || links
+            .iter()
+            .any(|(url, _)| url.as_str().starts_with(schema.as_str()))
+}
+
+/// Shape map associations of the data document, from the configuration and from
+/// `# shapeMap: ...` comments.
+///
+/// Comments that are no valid shape map become a diagnostic.
+fn shape_map(rope: &RopeC, config: &Config, diagnostics: &mut Vec<Diagnostic>) -> Vec<Association> {
+    let mut out = Vec::new();
+    for map in &config.shape_map {
+        match parse_shape_map(map) {
+            Ok(x) => out.extend(x),
+            Err(e) => warn!("Invalid configured shape map {:?}: {}", map, e),
+        }
+    }
+
+    for (i, line) in rope.0.lines().enumerate() {
+        let line = line.to_string();
+        let Some(comment) = line.trim_start().strip_prefix('#') else {
+            continue;
+        };
+        let Some(map) = comment.trim_start().strip_prefix("shapeMap:") else {
+            continue;
+        };
+
+        match parse_shape_map(map) {
+            Ok(x) => out.extend(x),
+            Err(e) => {
+                let start = rope.0.line_to_char(i);
+                let span = start..start + line.trim_end().chars().count();
+                if let Some(range) = range_to_range(&span, &rope.0) {
+                    diagnostics.push(Diagnostic {
+                        range,
+                        severity: Some(DiagnosticSeverity::ERROR),
+                        source: Some(String::from("SWLS")),
+                        message: format!("Invalid shape map: {}", e),
+                        ..Default::default()
+                    });
+                }
+            }
+        }
+    }
+
+    out
+}
+
+fn expand(name: &Name, prefixes: Option<&Prefixes>, schemas: &[&Schema]) -> Option<String> {
+    let token = match name {
+        Name::Iri(x) => Token::IRIRef(x.clone()),
+        Name::PName(p, l) => Token::PNameLN(Some(p.clone()), l.clone()),
+        _ => return schemas.first().and_then(|x| x.expand(name)),
+    };
+    prefixes
+        .and_then(|x| x.expand(&token))
+        .or_else(|| schemas.iter().find_map(|x| x.expand(name)))
+}
+
+fn term(name: &Name, prefixes: Option<&Prefixes>, schemas: &[&Schema]) -> Option<MyTerm<'static>> {
+    match name {
+        Name::BlankNode(x) => Some(MyTerm::blank_node(x.clone(), 0..0)),
+        _ => Some(MyTerm::named_node(expand(name, prefixes, schemas)?, 0..0)),
+    }
+}
+
+/// Focus nodes selected by `node`, together with the span of their first occurrence.
+fn focus_nodes(
+    node: &NodeSelector,
+    triples: &Triples,
+    prefixes: Option<&Prefixes>,
+    schemas: &[&Schema],
+) -> Vec<(MyTerm<'static>, std::ops::Range<usize>)> {
+    let mut out: Vec<(MyTerm<'static>, std::ops::Range<usize>)> = Vec::new();
+    let mut push = |term: &MyTerm<'static>| {
+        if !out.iter().any(|(x, _)| x == term) {
+            out.push((term.clone(), term.span.clone()));
+        }
+    };
+    let selects = |name: &Option<Name>, node: &MyTerm<'static>| match name {
+        Some(name) => term(name, prefixes, schemas).is_some_and(|x| &x == node),
+        None => true,
+    };
+
+    match node {
+        NodeSelector::Node(name) => {
+            let Some(node) = term(name, prefixes, schemas) else {
+                return out;
+            };
+            // Nodes that are not part of the document have no location
+            if let Some(t) = triples
+                .iter()
+                .find(|t| t.subject == node || t.object == node)
+            {
+                push(if t.subject == node {
+                    &t.subject
+                } else {
+                    &t.object
+                });
+            }
+        }
+        NodeSelector::Subjects { predicate, object } => {
+            let Some(predicate) = expand(predicate, prefixes, schemas) else {
+                return out;
+            };
+            for t in triples.iter() {
+                if t.predicate.as_str() == predicate && selects(object, &t.object) {
+                    push(&t.subject);
+                }
+            }
+        }
+        NodeSelector::Objects { subject, predicate } => {
+            let Some(predicate) = expand(predicate, prefixes, schemas) else {
+                return out;
+            };
+            for t in triples.iter() {
+                if t.predicate.as_str() == predicate && selects(subject, &t.subject) {
+                    push(&t.object);
+                }
+            }
+        }
+    }
+
+    out
+}
+
+fn derive_shex_diagnostics_for(
+    rope: &RopeC,
+    label: &Label,
+    links: &[(Url, &'static str)],
+    prefixes: Option<&Prefixes>,
+    triples: &Triples,
+    schemas: &Schemas,
+    config: &Config,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let associations = shape_map(rope, config, &mut diagnostics);
+    if associations.is_empty() {
+        return diagnostics;
+    }
+
+    // Linked schemas come first, they define the start shape
+    let configured = config.shape_urls();
+    let (mut linked, other): (Vec<_>, Vec<_>) = schemas
+        .iter()
+        .partition(|(l, _)| is_linked(&label.0, &l.0, links, &configured));
+    let start_schemas = linked.len();
+    linked.extend(other);
+    let all: Vec<&Schema> = linked.iter().map(|(_, x)| x.value()).collect();
+
+    let mut validator = Validator::new(&all[..start_schemas], &triples.0);
+    let mut label_validator = Validator::new(&all, &triples.0);
+
+    for association in associations {
+        let (shape, result_label) = match &association.shape {
+            ShapeSelector::Start => {
+                if !all[..start_schemas].iter().any(|x| x.start.is_some()) {
+                    debug!("No start shape for {}", label.as_str());
+                    continue;
+                }
+                (None, String::from("START"))
+            }
+            ShapeSelector::Label(name) => {
+                // Schema prefixes first, the prefixes of the document otherwise
+                let defined = |l: &String| all.iter().any(|x| x.shape(l).is_some());
+                let Some(shape) = all
+                    .iter()
+                    .find_map(|x| x.expand(name).filter(|l| x.shape(l).is_some()))
+                    .or_else(|| expand(name, prefixes, &all).filter(defined))
+                else {
+                    debug!("Shape {} is not defined in any schema", name);
+                    continue;
+                };
+                (Some(shape), name.to_string())
+            }
+        };
+
+        for (node, span) in focus_nodes(&association.node, triples, prefixes, &all) {
+            let result = match &shape {
+                Some(shape) => label_validator.check_label(&node, shape),
+                None => validator.check_start(&node),
+            };
+
+            let Err(reason) = result else {
+                continue;
+            };
+            let Some(range) = range_to_range(&span, &rope.0) else {
+                continue;
+            };
+
+            let node = prefixes
+                .and_then(|x| x.shorten(node.as_str()))
+                .unwrap_or_else(|| node.to_string());
+            diagnostics.push(Diagnostic {
+                range,
+                severity: Some(DiagnosticSeverity::ERROR),
+                source: Some(String::from("SWLS")),
+                message: format!(
+                    "Node {} does not conform to {}: {}",
+                    node, result_label, reason
+                ),
+                ..Default::default()
+            });
+        }
+    }
+
+    diagnostics
+}
+
+/// System validates open documents against ShEx schemas with their shape map
+#[instrument(skip(query, schemas, config, client))]
+pub fn validate_shex(
+    query: Query<
+        (
+            &RopeC,
+            &Label,
+            Option<&DocumentLinks>,
+            Option<&Prefixes>,
+            &Wrapped<TextDocumentItem>,
+            &Triples,
+        ),
+        (Changed<Triples>, Without<Dirty>, With<Open>),
+    >,
+    schemas: Schemas,
+    config: Res<ServerConfig>,
+    mut client: ResMut<DiagnosticPublisher>,
+) {
+    for (rope, label, links, prefixes, item, triples) in &query {
+        info!("Validate ShEx {}", label.as_str());
+        let links = links.map(|x| &x.0[..]).unwrap_or(&[]);
+        let diagnostics = derive_shex_diagnostics_for(
+            rope,
+            label,
+            links,
+            prefixes,
+            triples,
+            &schemas,
+            &config.config,
+        );
+        let _ = client.publish(&item.0, diagnostics, REASON);
+    }
+}
+
+/// System validates documents handling a [`DiagnosticRequest`] against ShEx schemas
+pub fn pull_shex(
+    mut query: Query<
+        (
+            &RopeC,
+            &Label,
+            Option<&DocumentLinks>,
+            Option<&Prefixes>,
+            &Triples,
+            &mut DiagnosticRequest,
+        ),
+        (Without<Dirty>, Without<SkipValidation>),
+    >,
+    schemas: Schemas,
+    config: Res<ServerConfig>,
+) {
+    for (rope, label, links, prefixes, triples, mut request) in &mut query {
+        let links = links.map(|x| &x.0[..]).unwrap_or(&[]);
+        let diagnostics = derive_shex_diagnostics_for(
+            rope,
+            label,
+            links,
+            prefixes,
+            triples,
+            &schemas,
+            &config.config,
+        );
+        request.push(diagnostics, REASON);
+    }
+}
+
+/// System revalidates open documents when a ShEx schema changes
+///
+/// Shape labels are looked up in every schema, so every open document is revalidated.
+#[instrument(skip(changed_schemas, query, schemas, config, client))]
+pub fn validate_with_updated_shex(
+    changed_schemas: Query<&Label, (Changed<Element<ShexLang>>, Without<Dirty>)>,
+    query: Query<
+        (
+            &RopeC,
+            &Label,
+            Option<&DocumentLinks>,
+            Option<&Prefixes>,
+            &Wrapped<TextDocumentItem>,
+            &Triples,
+        ),
+        (With<Open>, Without<Dirty>),
+    >,
+    schemas: Schemas,
+    config: Res<ServerConfig>,
+    mut client: ResMut<DiagnosticPublisher>,
+) {
+    if changed_schemas.is_empty() {
+        return;
+    }
+    for l in &changed_schemas {
+        info!("Changed ShEx schema {}", l.as_str());
+    }
+
+    for (rope, label, links, prefixes, item, triples) in &query {
+        let links = links.map(|x| &x.0[..]).unwrap_or(&[]);
+        let diagnostics = derive_shex_diagnostics_for(
+            rope,
+            label,
+            links,
+            prefixes,
+            triples,
+            &schemas,
+            &config.config,
+        );
+        let _ = client.publish(&item.0, diagnostics, REASON);
+    }
+}
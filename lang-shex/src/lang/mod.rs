@@ -0,0 +1,5 @@
+pub mod model;
+pub mod parser;
+pub mod shape_map;
+pub mod tokenizer;
+pub mod validate;
@@ -0,0 +1,212 @@
+use lsp_core::prelude::Spanned;
+use lsp_types::Url;
+
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+
+/// A name as written in the schema: an IRI, a prefixed name or a blank node label.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Name {
+    Iri(String),
+    PName(String, String),
+    BlankNode(String),
+    /// `a`, only valid as predicate
+    A,
+}
+
+impl std::fmt::Display for Name {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Name::Iri(x) => write!(f, "<{}>", x),
+            Name::PName(p, l) => write!(f, "{}:{}", p, l),
+            Name::BlankNode(x) => write!(f, "_:{}", x),
+            Name::A => write!(f, "a"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShexPrefix {
+    pub prefix: Spanned<String>,
+    pub iri: Spanned<String>,
+}
+
+/// `min` and `max` occurrences of a triple expression, `max` is `None` when unbounded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cardinality {
+    pub min: usize,
+    pub max: Option<usize>,
+}
+
+impl Default for Cardinality {
+    fn default() -> Self {
+        Self {
+            min: 1,
+            max: Some(1),
+        }
+    }
+}
+
+impl Cardinality {
+    pub fn allows(&self, count: usize) -> bool {
+        count >= self.min && self.max.map(|max| count <= max).unwrap_or(true)
+    }
+}
+
+impl std::fmt::Display for Cardinality {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.max {
+            Some(max) if max == self.min => write!(f, "{}", max),
+            Some(max) => write!(f, "{} to {}", self.min, max),
+            None => write!(f, "at least {}", self.min),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NodeKind {
+    Iri,
+    BNode,
+    NonLiteral,
+    Literal,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Facet {
+    Length(usize),
+    MinLength(usize),
+    MaxLength(usize),
+    /// Numeric facets keep their lexical form.
+    MinInclusive(String),
+    MinExclusive(String),
+    MaxInclusive(String),
+    MaxExclusive(String),
+    TotalDigits(usize),
+    FractionDigits(usize),
+}
+
+/// A value of a value set `[ ex:a "b" 1 ]`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Value {
+    Iri(Name),
+    Literal {
+        value: String,
+        lang: Option<String>,
+        datatype: Option<Name>,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct NodeConstraint {
+    pub kind: Option<NodeKind>,
+    pub datatype: Option<Spanned<Name>>,
+    pub values: Option<Vec<Spanned<Value>>>,
+    pub facets: Vec<Spanned<Facet>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TripleConstraint {
+    /// `^ex:p`, the focus node is the object.
+    pub inverse: bool,
+    pub predicate: Spanned<Name>,
+    /// `None` for `.`, any value.
+    pub value: Option<Box<Spanned<ShapeExpr>>>,
+    pub cardinality: Cardinality,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TripleExpr {
+    /// `a | b`
+    OneOf(Vec<Spanned<TripleExpr>>),
+    /// `a ; b`
+    EachOf(Vec<Spanned<TripleExpr>>),
+    /// `( a ; b ) *`
+    Group {
+        expr: Box<Spanned<TripleExpr>>,
+        cardinality: Cardinality,
+    },
+    Constraint(TripleConstraint),
+}
+
+impl TripleExpr {
+    /// All triple constraints of this expression.
+    pub fn constraints<'a>(&'a self, out: &mut Vec<&'a TripleConstraint>) {
+        match self {
+            TripleExpr::OneOf(xs) | TripleExpr::EachOf(xs) => {
+                xs.iter().for_each(|x| x.constraints(out));
+            }
+            TripleExpr::Group { expr, .. } => expr.constraints(out),
+            TripleExpr::Constraint(x) => out.push(x),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct Shape {
+    pub closed: bool,
+    pub extra: Vec<Spanned<Name>>,
+    pub expression: Option<Spanned<TripleExpr>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ShapeExpr {
+    Or(Vec<Spanned<ShapeExpr>>),
+    And(Vec<Spanned<ShapeExpr>>),
+    Not(Box<Spanned<ShapeExpr>>),
+    /// `@ex:Shape`
+    Ref(Spanned<Name>),
+    NodeConstraint(NodeConstraint),
+    Shape(Shape),
+    /// `.`
+    Any,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShapeDecl {
+    pub label: Spanned<Name>,
+    /// `EXTERNAL`, the shape is defined elsewhere.
+    pub external: bool,
+    pub expr: Option<Spanned<ShapeExpr>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Schema {
+    /// Base of the schema, the `BASE` directive or the location of the document.
+    pub base: Url,
+    pub prefixes: Vec<Spanned<ShexPrefix>>,
+    pub imports: Vec<Spanned<Name>>,
+    pub start: Option<Spanned<ShapeExpr>>,
+    pub shapes: Vec<Spanned<ShapeDecl>>,
+}
+
+impl Schema {
+    pub fn new(base: Url) -> Self {
+        Self {
+            base,
+            prefixes: Vec::new(),
+            imports: Vec::new(),
+            start: None,
+            shapes: Vec::new(),
+        }
+    }
+
+    /// Expand a name to an IRI, blank nodes become `_:label`.
+    pub fn expand(&self, name: &Name) -> Option<String> {
+        match name {
+            Name::Iri(x) => self.base.join(x).ok().map(|x| x.to_string()),
+            Name::PName(prefix, local) => {
+                let prefix = self.prefixes.iter().find(|x| x.prefix.as_str() == prefix)?;
+                Some(format!("{}{}", prefix.iri.value(), local))
+            }
+            Name::BlankNode(x) => Some(format!("_:{}", x)),
+            Name::A => Some(RDF_TYPE.to_string()),
+        }
+    }
+
+    /// The declaration of the shape with this (expanded) label.
+    pub fn shape(&self, label: &str) -> Option<&ShapeDecl> {
+        self.shapes
+            .iter()
+            .map(|x| x.value())
+            .find(|x| self.expand(&x.label).as_deref() == Some(label))
+    }
+}
@@ -0,0 +1,722 @@
+use chumsky::prelude::Simple;
+use lsp_core::prelude::{spanned, ShexKeyword, Spanned, Token};
+use lsp_types::Url;
+
+use super::model::*;
+
+type PResult<T> = Result<T, ()>;
+
+struct Parser<'a> {
+    tokens: Vec<&'a Spanned<Token>>,
+    pos: usize,
+    /// Offset used for errors at the end of the document.
+    eof: usize,
+    errors: Vec<Simple<Token>>,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a Token> {
+        self.peek_at(0)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<&'a Token> {
+        self.tokens.get(self.pos + offset).map(|x| x.value())
+    }
+
+    fn next(&mut self) -> Option<&'a Spanned<Token>> {
+        let out = self.tokens.get(self.pos).copied();
+        if out.is_some() {
+            self.pos += 1;
+        }
+        out
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_keyword(&mut self, kwd: ShexKeyword) -> bool {
+        self.eat(&Token::ShexKeyword(kwd))
+    }
+
+    /// Start offset of the next token.
+    fn start(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .map(|x| x.span().start)
+            .unwrap_or(self.eof)
+    }
+
+    /// End offset of the last consumed token.
+    fn end(&self) -> usize {
+        self.pos
+            .checked_sub(1)
+            .and_then(|x| self.tokens.get(x))
+            .map(|x| x.span().end)
+            .unwrap_or(0)
+    }
+
+    fn error<T>(&mut self, expected: &str) -> PResult<T> {
+        let (span, found) = match self.tokens.get(self.pos) {
+            Some(t) => (t.span().clone(), t.value().to_string()),
+            None => (self.eof..self.eof, String::from("end of input")),
+        };
+        self.errors.push(Simple::custom(
+            span,
+            format!("Expected {}, found {}", expected, found),
+        ));
+        Err(())
+    }
+
+    fn expect(&mut self, token: Token) -> PResult<()> {
+        if self.eat(&token) {
+            Ok(())
+        } else {
+            self.error(&format!("'{}'", token))
+        }
+    }
+
+    fn is_name(token: Option<&Token>) -> bool {
+        matches!(
+            token,
+            Some(Token::IRIRef(_) | Token::PNameLN(..) | Token::BlankNodeLabel(_))
+        )
+    }
+
+    fn is_iri(token: Option<&Token>) -> bool {
+        matches!(token, Some(Token::IRIRef(_) | Token::PNameLN(..)))
+    }
+
+    fn name(&mut self) -> PResult<Spanned<Name>> {
+        let Some(t) = self.tokens.get(self.pos).copied() else {
+            return self.error("a shape label");
+        };
+        let name = match t.value() {
+            Token::IRIRef(x) => Name::Iri(x.clone()),
+            Token::PNameLN(p, l) => Name::PName(p.clone().unwrap_or_default(), l.clone()),
+            Token::BlankNodeLabel(x) => Name::BlankNode(x.clone()),
+            _ => return self.error("a shape label"),
+        };
+        self.pos += 1;
+        Ok(spanned(name, t.span().clone()))
+    }
+
+    fn iri(&mut self) -> PResult<Spanned<Name>> {
+        if Self::is_iri(self.peek()) {
+            self.name()
+        } else {
+            self.error("an IRI")
+        }
+    }
+
+    fn integer(&mut self) -> PResult<usize> {
+        if let Some(Token::Number(x)) = self.peek() {
+            if let Ok(x) = x.parse() {
+                self.pos += 1;
+                return Ok(x);
+            }
+        }
+        self.error("a positive integer")
+    }
+
+    fn number(&mut self) -> PResult<String> {
+        if let Some(Token::Number(x)) = self.peek() {
+            self.pos += 1;
+            return Ok(x.clone());
+        }
+        self.error("a number")
+    }
+
+    fn is_directive(token: Option<&Token>) -> bool {
+        matches!(
+            token,
+            Some(
+                Token::SparqlPrefix
+                    | Token::SparqlBase
+                    | Token::ShexKeyword(ShexKeyword::Import | ShexKeyword::Start)
+            )
+        )
+    }
+
+    /// Skip the rest of a broken declaration, up to the closing `}` of its shape or the next
+    /// directive.
+    fn recover(&mut self, decl_start: usize) {
+        let mut depth: usize = 0;
+        for t in &self.tokens[decl_start..self.pos] {
+            match t.value() {
+                Token::CurlOpen => depth += 1,
+                Token::CurlClose => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+
+        if self.pos == decl_start {
+            self.pos += 1;
+        }
+
+        while let Some(t) = self.peek() {
+            match t {
+                x if depth == 0 && Self::is_directive(Some(x)) => return,
+                Token::CurlOpen => depth += 1,
+                Token::CurlClose => {
+                    depth = depth.saturating_sub(1);
+                    if depth == 0 {
+                        self.pos += 1;
+                        return;
+                    }
+                }
+                _ => {}
+            }
+            self.pos += 1;
+        }
+    }
+
+    fn schema(&mut self, location: &Url) -> Schema {
+        let mut schema = Schema::new(location.clone());
+
+        while self.pos < self.tokens.len() {
+            let decl_start = self.pos;
+            if self.declaration(&mut schema).is_err() {
+                self.recover(decl_start);
+            }
+        }
+
+        schema
+    }
+
+    fn declaration(&mut self, schema: &mut Schema) -> PResult<()> {
+        let start = self.start();
+        match self.peek() {
+            Some(Token::SparqlPrefix) => {
+                self.pos += 1;
+                let prefix = match self.next() {
+                    Some(t) => match t.value() {
+                        Token::PNameLN(p, l) if l.is_empty() => {
+                            spanned(p.clone().unwrap_or_default(), t.span().clone())
+                        }
+                        _ => {
+                            self.pos -= 1;
+                            return self.error("a prefix like 'ex:'");
+                        }
+                    },
+                    None => return self.error("a prefix like 'ex:'"),
+                };
+                let iri = self.iri_ref()?;
+                schema
+                    .prefixes
+                    .push(spanned(ShexPrefix { prefix, iri }, start..self.end()));
+            }
+            Some(Token::SparqlBase) => {
+                self.pos += 1;
+                let iri = self.iri_ref()?;
+                if let Ok(base) = schema.base.join(iri.value()) {
+                    schema.base = base;
+                }
+            }
+            Some(Token::ShexKeyword(ShexKeyword::Import)) => {
+                self.pos += 1;
+                let iri = self.iri()?;
+                schema.imports.push(iri);
+            }
+            Some(Token::ShexKeyword(ShexKeyword::Start)) => {
+                self.pos += 1;
+                self.expect(Token::SameAs)?;
+                let expr = self.shape_expr()?;
+                schema.start = Some(expr);
+            }
+            x if Self::is_name(x) => {
+                let label = self.name()?;
+                let (external, expr) = if self.eat_keyword(ShexKeyword::External) {
+                    (true, None)
+                } else {
+                    (false, Some(self.shape_expr()?))
+                };
+                schema.shapes.push(spanned(
+                    ShapeDecl {
+                        label,
+                        external,
+                        expr,
+                    },
+                    start..self.end(),
+                ));
+            }
+            _ => return self.error("a directive or shape declaration"),
+        }
+        Ok(())
+    }
+
+    fn iri_ref(&mut self) -> PResult<Spanned<String>> {
+        match self.tokens.get(self.pos).copied() {
+            Some(t) => match t.value() {
+                Token::IRIRef(x) => {
+                    self.pos += 1;
+                    Ok(spanned(x.clone(), t.span().clone()))
+                }
+                _ => self.error("an IRI like <http://example.com/>"),
+            },
+            None => self.error("an IRI like <http://example.com/>"),
+        }
+    }
+
+    fn shape_expr(&mut self) -> PResult<Spanned<ShapeExpr>> {
+        let start = self.start();
+        let mut items = vec![self.shape_and()?];
+        while self.eat_keyword(ShexKeyword::Or) {
+            items.push(self.shape_and()?);
+        }
+        if items.len() == 1 {
+            return Ok(items.pop().unwrap());
+        }
+        Ok(spanned(ShapeExpr::Or(items), start..self.end()))
+    }
+
+    fn shape_and(&mut self) -> PResult<Spanned<ShapeExpr>> {
+        let start = self.start();
+        let mut items = vec![self.shape_not()?];
+        while self.eat_keyword(ShexKeyword::And) {
+            items.push(self.shape_not()?);
+        }
+        if items.len() == 1 {
+            return Ok(items.pop().unwrap());
+        }
+        Ok(spanned(ShapeExpr::And(items), start..self.end()))
+    }
+
+    fn shape_not(&mut self) -> PResult<Spanned<ShapeExpr>> {
+        let start = self.start();
+        if self.eat_keyword(ShexKeyword::Not) {
+            let inner = self.shape_atom()?;
+            return Ok(spanned(ShapeExpr::Not(Box::new(inner)), start..self.end()));
+        }
+        self.shape_atom()
+    }
+
+    fn starts_shape(&self) -> bool {
+        matches!(
+            self.peek(),
+            Some(
+                Token::CurlOpen
+                    | Token::At
+                    | Token::ShexKeyword(ShexKeyword::Closed | ShexKeyword::Extra)
+            )
+        ) && !self.starts_repeat()
+    }
+
+    /// `{` followed by a number is a cardinality, not a shape.
+    fn starts_repeat(&self) -> bool {
+        self.peek() == Some(&Token::CurlOpen) && matches!(self.peek_at(1), Some(Token::Number(_)))
+    }
+
+    fn shape_atom(&mut self) -> PResult<Spanned<ShapeExpr>> {
+        let start = self.start();
+        match self.peek() {
+            Some(Token::BracketOpen) => {
+                self.pos += 1;
+                let expr = self.shape_expr()?;
+                self.expect(Token::BracketClose)?;
+                Ok(expr)
+            }
+            Some(Token::Stop) => {
+                self.pos += 1;
+                Ok(spanned(ShapeExpr::Any, start..self.end()))
+            }
+            _ if self.starts_shape() => self.shape_or_ref(),
+            _ => {
+                let constraint = self.node_constraint()?;
+                let constraint = spanned(ShapeExpr::NodeConstraint(constraint), start..self.end());
+                if self.starts_shape() {
+                    let shape = self.shape_or_ref()?;
+                    Ok(spanned(
+                        ShapeExpr::And(vec![constraint, shape]),
+                        start..self.end(),
+                    ))
+                } else {
+                    Ok(constraint)
+                }
+            }
+        }
+    }
+
+    fn shape_or_ref(&mut self) -> PResult<Spanned<ShapeExpr>> {
+        let start = self.start();
+        if self.eat(&Token::At) {
+            let name = self.name()?;
+            return Ok(spanned(ShapeExpr::Ref(name), start..self.end()));
+        }
+
+        let mut shape = Shape::default();
+        loop {
+            if self.eat_keyword(ShexKeyword::Closed) {
+                shape.closed = true;
+            } else if self.eat_keyword(ShexKeyword::Extra) {
+                shape.extra.push(self.predicate()?);
+                while Self::is_iri(self.peek()) || self.peek() == Some(&Token::PredType) {
+                    shape.extra.push(self.predicate()?);
+                }
+            } else {
+                break;
+            }
+        }
+
+        self.expect(Token::CurlOpen)?;
+        if self.peek() != Some(&Token::CurlClose) {
+            shape.expression = Some(self.triple_expr()?);
+        }
+        self.expect(Token::CurlClose)?;
+
+        Ok(spanned(ShapeExpr::Shape(shape), start..self.end()))
+    }
+
+    fn node_constraint(&mut self) -> PResult<NodeConstraint> {
+        let mut constraint = NodeConstraint::default();
+        let kind = match self.peek() {
+            Some(Token::ShexKeyword(ShexKeyword::Iri)) => Some(NodeKind::Iri),
+            Some(Token::ShexKeyword(ShexKeyword::BNode)) => Some(NodeKind::BNode),
+            Some(Token::ShexKeyword(ShexKeyword::NonLiteral)) => Some(NodeKind::NonLiteral),
+            Some(Token::ShexKeyword(ShexKeyword::Literal)) => Some(NodeKind::Literal),
+            _ => None,
+        };
+
+        if kind.is_some() {
+            self.pos += 1;
+            constraint.kind = kind;
+        } else if Self::is_iri(self.peek()) {
+            constraint.datatype = Some(self.iri()?);
+        } else if self.peek() == Some(&Token::SqOpen) {
+            constraint.values = Some(self.value_set()?);
+        }
+
+        while let Some(facet) = self.facet()? {
+            constraint.facets.push(facet);
+        }
+
+        if constraint == NodeConstraint::default() {
+            return self.error("a shape expression");
+        }
+        Ok(constraint)
+    }
+
+    fn facet(&mut self) -> PResult<Option<Spanned<Facet>>> {
+        let start = self.start();
+        let Some(Token::ShexKeyword(kwd)) = self.peek() else {
+            return Ok(None);
+        };
+        let kwd = kwd.clone();
+        let facet = match kwd {
+            ShexKeyword::Length
+            | ShexKeyword::MinLength
+            | ShexKeyword::MaxLength
+            | ShexKeyword::TotalDigits
+            | ShexKeyword::FractionDigits => {
+                self.pos += 1;
+                let x = self.integer()?;
+                match kwd {
+                    ShexKeyword::Length => Facet::Length(x),
+                    ShexKeyword::MinLength => Facet::MinLength(x),
+                    ShexKeyword::MaxLength => Facet::MaxLength(x),
+                    ShexKeyword::TotalDigits => Facet::TotalDigits(x),
+                    _ => Facet::FractionDigits(x),
+                }
+            }
+            ShexKeyword::MinInclusive
+            | ShexKeyword::MinExclusive
+            | ShexKeyword::MaxInclusive
+            | ShexKeyword::MaxExclusive => {
+                self.pos += 1;
+                let x = self.number()?;
+                match kwd {
+                    ShexKeyword::MinInclusive => Facet::MinInclusive(x),
+                    ShexKeyword::MinExclusive => Facet::MinExclusive(x),
+                    ShexKeyword::MaxInclusive => Facet::MaxInclusive(x),
+                    _ => Facet::MaxExclusive(x),
+                }
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(spanned(facet, start..self.end())))
+    }
+
+    fn value_set(&mut self) -> PResult<Vec<Spanned<Value>>> {
+        self.expect(Token::SqOpen)?;
+        let mut values = Vec::new();
+        while !self.eat(&Token::SqClose) {
+            let start = self.start();
+            let value = match self.peek() {
+                x if Self::is_iri(x) => Value::Iri(self.iri()?.into_value()),
+                Some(Token::Number(x)) => {
+                    self.pos += 1;
+                    Value::Literal {
+                        value: x.clone(),
+                        lang: None,
+                        datatype: None,
+                    }
+                }
+                Some(Token::True | Token::False) => {
+                    let value = self.peek() == Some(&Token::True);
+                    self.pos += 1;
+                    Value::Literal {
+                        value: value.to_string(),
+                        lang: None,
+                        datatype: None,
+                    }
+                }
+                Some(Token::Str(x, _)) => {
+                    self.pos += 1;
+                    let mut lang = None;
+                    let mut datatype = None;
+                    if let Some(Token::LangTag(l)) = self.peek() {
+                        self.pos += 1;
+                        lang = Some(l.clone());
+                    } else if self.eat(&Token::DataTypeDelim) {
+                        datatype = Some(self.iri()?.into_value());
+                    }
+                    Value::Literal {
+                        value: x.clone(),
+                        lang,
+                        datatype,
+                    }
+                }
+                _ => return self.error("a value or ']'"),
+            };
+            values.push(spanned(value, start..self.end()));
+        }
+        Ok(values)
+    }
+
+    fn predicate(&mut self) -> PResult<Spanned<Name>> {
+        if let Some(t) = self.tokens.get(self.pos).copied() {
+            if t.value() == &Token::PredType {
+                self.pos += 1;
+                return Ok(spanned(Name::A, t.span().clone()));
+            }
+        }
+        if Self::is_iri(self.peek()) {
+            return self.name();
+        }
+        self.error("a predicate")
+    }
+
+    fn triple_expr(&mut self) -> PResult<Spanned<TripleExpr>> {
+        let start = self.start();
+        let mut items = vec![self.group_triple()?];
+        while self.eat(&Token::Pipe) {
+            items.push(self.group_triple()?);
+        }
+        if items.len() == 1 {
+            return Ok(items.pop().unwrap());
+        }
+        Ok(spanned(TripleExpr::OneOf(items), start..self.end()))
+    }
+
+    fn group_triple(&mut self) -> PResult<Spanned<TripleExpr>> {
+        let start = self.start();
+        let mut items = vec![self.unary_triple()?];
+        while self.eat(&Token::PredicateSplit) {
+            // A trailing ';' is allowed
+            if matches!(
+                self.peek(),
+                None | Some(Token::CurlClose | Token::BracketClose | Token::Pipe)
+            ) {
+                break;
+            }
+            items.push(self.unary_triple()?);
+        }
+        if items.len() == 1 {
+            return Ok(items.pop().unwrap());
+        }
+        Ok(spanned(TripleExpr::EachOf(items), start..self.end()))
+    }
+
+    fn unary_triple(&mut self) -> PResult<Spanned<TripleExpr>> {
+        let start = self.start();
+        if self.eat(&Token::BracketOpen) {
+            let expr = self.triple_expr()?;
+            self.expect(Token::BracketClose)?;
+            let cardinality = self.cardinality()?;
+            return Ok(spanned(
+                TripleExpr::Group {
+                    expr: Box::new(expr),
+                    cardinality,
+                },
+                start..self.end(),
+            ));
+        }
+
+        let inverse = self.eat(&Token::Caret);
+        let predicate = self.predicate()?;
+        let value = if self.eat(&Token::Stop) {
+            None
+        } else {
+            Some(Box::new(self.shape_expr()?))
+        };
+        let cardinality = self.cardinality()?;
+
+        Ok(spanned(
+            TripleExpr::Constraint(TripleConstraint {
+                inverse,
+                predicate,
+                value,
+                cardinality,
+            }),
+            start..self.end(),
+        ))
+    }
+
+    fn cardinality(&mut self) -> PResult<Cardinality> {
+        let out = match self.peek() {
+            Some(Token::Star) => Cardinality { min: 0, max: None },
+            Some(Token::Plus) => Cardinality { min: 1, max: None },
+            Some(Token::QuestionMark) => Cardinality {
+                min: 0,
+                max: Some(1),
+            },
+            _ if self.starts_repeat() => {
+                self.pos += 1;
+                let min = self.integer()?;
+                let max = if self.eat(&Token::Comma) {
+                    if self.eat(&Token::Star) || self.peek() == Some(&Token::CurlClose) {
+                        None
+                    } else {
+                        Some(self.integer()?)
+                    }
+                } else {
+                    Some(min)
+                };
+                self.expect(Token::CurlClose)?;
+                return Ok(Cardinality { min, max });
+            }
+            _ => return Ok(Cardinality::default()),
+        };
+        self.pos += 1;
+        Ok(out)
+    }
+}
+
+/// Parse the tokens of a ShExC document into a [`Schema`].
+///
+/// Comments are ignored. Broken declarations are skipped, the other declarations are still
+/// part of the schema.
+pub fn parse(tokens: &[Spanned<Token>], location: &Url) -> (Schema, Vec<Simple<Token>>) {
+    let mut parser = Parser {
+        tokens: tokens
+            .iter()
+            .filter(|x| !matches!(x.value(), Token::Comment(_)))
+            .collect(),
+        pos: 0,
+        eof: tokens.last().map(|x| x.span().end).unwrap_or(0),
+        errors: Vec::new(),
+    };
+
+    let schema = parser.schema(location);
+    (schema, parser.errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::tokenizer::parse_tokens_str;
+
+    fn parse_str(text: &str) -> (Schema, Vec<Simple<Token>>) {
+        let (tokens, errors) = parse_tokens_str(text);
+        assert!(errors.is_empty(), "{:?}", errors);
+        let url = Url::parse("http://example.com/schema.shex").unwrap();
+        parse(&tokens, &url)
+    }
+
+    #[test]
+    fn parse_simple_schema() {
+        let (schema, errors) = parse_str(
+            r#"
+PREFIX ex: <http://example.com/>
+PREFIX xsd: <http://www.w3.org/2001/XMLSchema#>
+start = @ex:Person
+
+ex:Person CLOSED {
+  ex:name xsd:string MAXLENGTH 20 ;
+  ex:knows @ex:Person * ;
+  ex:status [ ex:Active ex:Inactive ] ? ;
+}
+"#,
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(schema.prefixes.len(), 2);
+        assert!(matches!(
+            schema.start.as_ref().map(|x| x.value()),
+            Some(ShapeExpr::Ref(_))
+        ));
+        assert_eq!(schema.shapes.len(), 1);
+
+        let person = schema
+            .shape("http://example.com/Person")
+            .expect("person shape");
+        let Some(ShapeExpr::Shape(shape)) = person.expr.as_ref().map(|x| x.value()) else {
+            panic!("expected a shape, found {:?}", person.expr);
+        };
+        assert!(shape.closed);
+
+        let mut constraints = Vec::new();
+        shape
+            .expression
+            .as_ref()
+            .unwrap()
+            .constraints(&mut constraints);
+        assert_eq!(constraints.len(), 3);
+        assert_eq!(
+            constraints[1].cardinality,
+            Cardinality { min: 0, max: None }
+        );
+        assert_eq!(
+            constraints[2].cardinality,
+            Cardinality {
+                min: 0,
+                max: Some(1)
+            }
+        );
+    }
+
+    #[test]
+    fn parse_cardinality_after_constraint() {
+        let (schema, errors) = parse_str("<S> { <p> IRI {2,3} | <q> . {1,} }");
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        let Some(ShapeExpr::Shape(shape)) = schema.shapes[0].expr.as_ref().map(|x| x.value())
+        else {
+            panic!("expected a shape");
+        };
+        let mut constraints = Vec::new();
+        shape
+            .expression
+            .as_ref()
+            .unwrap()
+            .constraints(&mut constraints);
+        assert_eq!(
+            constraints[0].cardinality,
+            Cardinality {
+                min: 2,
+                max: Some(3)
+            }
+        );
+        assert_eq!(
+            constraints[1].cardinality,
+            Cardinality { min: 1, max: None }
+        );
+    }
+
+    #[test]
+    fn parse_recovers() {
+        let (schema, errors) = parse_str(
+            r#"
+PREFIX ex: <http://example.com/>
+ex:Broken { ex:p ; }
+ex:Fine { ex:q . }
+"#,
+        );
+        assert_eq!(errors.len(), 1);
+        assert!(schema.shape("http://example.com/Fine").is_some());
+    }
+}
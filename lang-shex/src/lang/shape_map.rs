@@ -0,0 +1,168 @@
+use lsp_core::prelude::{ShexKeyword, Spanned, Token};
+
+use super::{model::Name, tokenizer::parse_tokens_str};
+
+/// Selects the focus nodes of a shape map association.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NodeSelector {
+    /// `ex:alice`
+    Node(Name),
+    /// `{FOCUS ex:p ex:o}`, every subject of matching triples, `object` is `None` for `_`.
+    Subjects {
+        predicate: Name,
+        object: Option<Name>,
+    },
+    /// `{ex:s ex:p FOCUS}`, every object of matching triples, `subject` is `None` for `_`.
+    Objects {
+        subject: Option<Name>,
+        predicate: Name,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ShapeSelector {
+    /// `START`, the start shape of the schema
+    Start,
+    Label(Name),
+}
+
+/// One `node@shape` association of a shape map.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Association {
+    pub node: NodeSelector,
+    pub shape: ShapeSelector,
+}
+
+fn name(token: &Token) -> Option<Name> {
+    match token {
+        Token::IRIRef(x) => Some(Name::Iri(x.clone())),
+        Token::PNameLN(p, l) => Some(Name::PName(p.clone().unwrap_or_default(), l.clone())),
+        Token::BlankNodeLabel(x) => Some(Name::BlankNode(x.clone())),
+        Token::PredType => Some(Name::A),
+        _ => None,
+    }
+}
+
+fn is_word(token: &Token, word: &str) -> bool {
+    matches!(token, Token::Invalid(x) if x.eq_ignore_ascii_case(word))
+}
+
+/// `_` or a name
+fn wildcard(token: &Token) -> Option<Option<Name>> {
+    if is_word(token, "_") {
+        Some(None)
+    } else {
+        name(token).map(Some)
+    }
+}
+
+fn node_selector(tokens: &[&Spanned<Token>], i: &mut usize) -> Result<NodeSelector, String> {
+    let token = |i: usize| tokens.get(i).map(|x| x.value());
+
+    if token(*i) != Some(&Token::CurlOpen) {
+        let node = token(*i)
+            .and_then(name)
+            .ok_or_else(|| String::from("Expected a node"))?;
+        *i += 1;
+        return Ok(NodeSelector::Node(node));
+    }
+
+    let [s, p, o, close] = [1, 2, 3, 4].map(|x| token(*i + x));
+    if close != Some(&Token::CurlClose) {
+        return Err(String::from(
+            "Expected a triple pattern like {FOCUS ex:p _}",
+        ));
+    }
+    *i += 5;
+
+    let predicate = p
+        .and_then(name)
+        .ok_or_else(|| String::from("Expected a predicate"))?;
+    match (s, o) {
+        (Some(s), Some(o)) if is_word(s, "focus") => Ok(NodeSelector::Subjects {
+            predicate,
+            object: wildcard(o).ok_or_else(|| String::from("Expected a node or '_'"))?,
+        }),
+        (Some(s), Some(o)) if is_word(o, "focus") => Ok(NodeSelector::Objects {
+            subject: wildcard(s).ok_or_else(|| String::from("Expected a node or '_'"))?,
+            predicate,
+        }),
+        _ => Err(String::from("Expected FOCUS as subject or object")),
+    }
+}
+
+/// Parse a shape map in the compact syntax, `ex:alice@ex:Person, {FOCUS a ex:Dog}@START`.
+pub fn parse_shape_map(text: &str) -> Result<Vec<Association>, String> {
+    // FOCUS and _ are not ShExC, they are tokenized as invalid tokens
+    let (tokens, _) = parse_tokens_str(text);
+    let tokens: Vec<_> = tokens
+        .iter()
+        .filter(|x| !matches!(x.value(), Token::Comment(_)))
+        .collect();
+
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        let node = node_selector(&tokens, &mut i)?;
+
+        if tokens.get(i).map(|x| x.value()) != Some(&Token::At) {
+            return Err(String::from("Expected '@' followed by a shape"));
+        }
+        i += 1;
+
+        let shape = match tokens.get(i).map(|x| x.value()) {
+            Some(Token::ShexKeyword(ShexKeyword::Start)) => ShapeSelector::Start,
+            Some(x) => {
+                ShapeSelector::Label(name(x).ok_or_else(|| String::from("Expected a shape"))?)
+            }
+            None => return Err(String::from("Expected a shape")),
+        };
+        i += 1;
+        out.push(Association { node, shape });
+
+        match tokens.get(i).map(|x| x.value()) {
+            Some(Token::Comma) => i += 1,
+            None => {}
+            Some(_) => return Err(String::from("Expected ',' between associations")),
+        }
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_shape_maps() {
+        let map = parse_shape_map("ex:alice@ex:Person, {FOCUS a ex:Dog}@START").unwrap();
+        assert_eq!(
+            map,
+            vec![
+                Association {
+                    node: NodeSelector::Node(Name::PName("ex".into(), "alice".into())),
+                    shape: ShapeSelector::Label(Name::PName("ex".into(), "Person".into())),
+                },
+                Association {
+                    node: NodeSelector::Subjects {
+                        predicate: Name::A,
+                        object: Some(Name::PName("ex".into(), "Dog".into())),
+                    },
+                    shape: ShapeSelector::Start,
+                },
+            ]
+        );
+
+        let map = parse_shape_map("{_ <knows> FOCUS}@<Person>").unwrap();
+        assert_eq!(
+            map[0].node,
+            NodeSelector::Objects {
+                subject: None,
+                predicate: Name::Iri("knows".into()),
+            }
+        );
+
+        assert!(parse_shape_map("ex:alice ex:Person").is_err());
+    }
+}
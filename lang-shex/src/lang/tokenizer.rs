@@ -0,0 +1,280 @@
+use std::str::FromStr as _;
+
+use chumsky::prelude::Simple;
+use lsp_core::prelude::{spanned, ShexKeyword, Spanned, StringStyle, Token};
+
+fn is_delimiter(c: char) -> bool {
+    c.is_whitespace() || "<>\"'{}[]();,|*+?@^#=".contains(c)
+}
+
+fn skip_digits(bytes: &[u8], mut i: usize) -> usize {
+    while i < bytes.len() && bytes[i].is_ascii_digit() {
+        i += 1;
+    }
+    i
+}
+
+/// End of the number starting at `i`, `[+-]? digits ('.' digits)? exponent?`.
+fn number_end(bytes: &[u8], mut i: usize) -> usize {
+    if matches!(bytes[i], b'+' | b'-') {
+        i += 1;
+    }
+    i = skip_digits(bytes, i);
+    if bytes.get(i) == Some(&b'.') && bytes.get(i + 1).is_some_and(u8::is_ascii_digit) {
+        i = skip_digits(bytes, i + 1);
+    }
+    if matches!(bytes.get(i), Some(b'e' | b'E')) {
+        let mut j = i + 1;
+        if matches!(bytes.get(j), Some(b'+' | b'-')) {
+            j += 1;
+        }
+        if bytes.get(j).is_some_and(u8::is_ascii_digit) {
+            i = skip_digits(bytes, j);
+        }
+    }
+    i
+}
+
+fn starts_number(bytes: &[u8], i: usize) -> bool {
+    match bytes[i] {
+        b'0'..=b'9' => true,
+        b'+' | b'-' | b'.' => bytes.get(i + 1).is_some_and(u8::is_ascii_digit),
+        _ => false,
+    }
+}
+
+fn word(word: &str) -> Option<Token> {
+    if let Some((prefix, local)) = word.split_once(':') {
+        return Some(Token::PNameLN(Some(prefix.to_string()), local.to_string()));
+    }
+
+    let token = match word {
+        "a" => Token::PredType,
+        "true" => Token::True,
+        "false" => Token::False,
+        x if x.eq_ignore_ascii_case("prefix") => Token::SparqlPrefix,
+        x if x.eq_ignore_ascii_case("base") => Token::SparqlBase,
+        x => Token::ShexKeyword(ShexKeyword::from_str(x).ok()?),
+    };
+    Some(token)
+}
+
+/// Tokenize a ShExC document.
+///
+/// Unknown or unterminated tokens become [`Token::Invalid`] and push an error, the rest of the
+/// document is still tokenized. Annotations, semantic actions and regular expressions are not
+/// supported and end up as invalid tokens.
+pub fn parse_tokens_str(text: &str) -> (Vec<Spanned<Token>>, Vec<Simple<char>>) {
+    let mut tokens: Vec<Spanned<Token>> = Vec::new();
+    let mut errors = Vec::new();
+    let bytes = text.as_bytes();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let start = i;
+        let token = match bytes[i] {
+            b if b.is_ascii_whitespace() => {
+                i += 1;
+                continue;
+            }
+            b'#' => {
+                i = text[start..].find('\n').map_or(text.len(), |x| start + x);
+                let comment = text[start..i].trim_end_matches('\r');
+                tokens.push(spanned(
+                    Token::Comment(comment.to_string()),
+                    start..start + comment.len(),
+                ));
+                continue;
+            }
+            b'<' => {
+                let end = text[start + 1..]
+                    .find(|c: char| c == '>' || c.is_whitespace())
+                    .map(|x| start + 1 + x);
+                match end {
+                    Some(end) if bytes[end] == b'>' => {
+                        i = end + 1;
+                        Token::IRIRef(text[start + 1..end].to_string())
+                    }
+                    _ => {
+                        i = end.unwrap_or(text.len());
+                        errors.push(Simple::custom(start..i, "Unterminated IRI, expected '>'"));
+                        Token::Invalid(text[start..i].to_string())
+                    }
+                }
+            }
+            b'"' | b'\'' => {
+                let quote = &text[start..start + 1];
+                let long = text[start..].starts_with(&quote.repeat(3));
+                let (delim, style) = match (quote, long) {
+                    ("\"", true) => ("\"\"\"", StringStyle::DoubleLong),
+                    ("\"", false) => ("\"", StringStyle::Double),
+                    (_, true) => ("'''", StringStyle::SingleLong),
+                    (_, false) => ("'", StringStyle::Single),
+                };
+                i += delim.len();
+
+                let mut closed = false;
+                while i < bytes.len() {
+                    if bytes[i] == b'\\' {
+                        // Skip the escaped character, which is not necessarily ascii
+                        i += 1 + text[i + 1..].chars().next().map_or(0, char::len_utf8);
+                        continue;
+                    }
+                    if text[i..].starts_with(delim) {
+                        i += delim.len();
+                        closed = true;
+                        break;
+                    }
+                    if !long && matches!(bytes[i], b'\n' | b'\r') {
+                        break;
+                    }
+                    i += text[i..].chars().next().map_or(1, char::len_utf8);
+                }
+
+                if closed {
+                    let value = &text[start + delim.len()..i - delim.len()];
+                    Token::Str(value.to_string(), style)
+                } else {
+                    errors.push(Simple::custom(
+                        start..i,
+                        format!("Unterminated string, expected {}", delim),
+                    ));
+                    Token::Invalid(text[start..i].to_string())
+                }
+            }
+            b'@' => {
+                i += 1;
+                // Directly after a string this is a language tag, otherwise a shape reference
+                let after_string = tokens
+                    .last()
+                    .is_some_and(|t| matches!(t.value(), Token::Str(..)) && t.span().end == start);
+                if after_string {
+                    while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'-')
+                    {
+                        i += 1;
+                    }
+                    Token::LangTag(text[start + 1..i].to_string())
+                } else {
+                    Token::At
+                }
+            }
+            b'^' if bytes.get(i + 1) == Some(&b'^') => {
+                i += 2;
+                Token::DataTypeDelim
+            }
+            _ if starts_number(bytes, i) => {
+                i = number_end(bytes, i);
+                Token::Number(text[start..i].to_string())
+            }
+            b'_' if bytes.get(i + 1) == Some(&b':') => {
+                i += 2;
+                while let Some(c) = text[i..].chars().next().filter(|c| !is_delimiter(*c)) {
+                    i += c.len_utf8();
+                }
+                // A blank node label cannot end with a '.'
+                while i > start + 2 && bytes[i - 1] == b'.' {
+                    i -= 1;
+                }
+                Token::BlankNodeLabel(text[start + 2..i].to_string())
+            }
+            b'{' | b'}' | b'[' | b']' | b'(' | b')' | b';' | b',' | b'.' | b'=' | b'|' | b'^'
+            | b'*' | b'+' | b'?' => {
+                i += 1;
+                match bytes[start] {
+                    b'{' => Token::CurlOpen,
+                    b'}' => Token::CurlClose,
+                    b'[' => Token::SqOpen,
+                    b']' => Token::SqClose,
+                    b'(' => Token::BracketOpen,
+                    b')' => Token::BracketClose,
+                    b';' => Token::PredicateSplit,
+                    b',' => Token::Comma,
+                    b'.' => Token::Stop,
+                    b'=' => Token::SameAs,
+                    b'|' => Token::Pipe,
+                    b'^' => Token::Caret,
+                    b'*' => Token::Star,
+                    b'+' => Token::Plus,
+                    _ => Token::QuestionMark,
+                }
+            }
+            _ => {
+                while let Some(c) = text[i..].chars().next().filter(|c| !is_delimiter(*c)) {
+                    i += c.len_utf8();
+                }
+                if i == start {
+                    // A lonely '>' or non ascii whitespace
+                    i += text[i..].chars().next().map_or(1, char::len_utf8);
+                }
+                // Names cannot end with a '.', that '.' is a token on its own
+                while i > start + 1 && bytes[i - 1] == b'.' {
+                    i -= 1;
+                }
+
+                match word(&text[start..i]) {
+                    Some(token) => token,
+                    None => {
+                        errors.push(Simple::custom(
+                            start..i,
+                            format!("Unexpected token '{}'", &text[start..i]),
+                        ));
+                        Token::Invalid(text[start..i].to_string())
+                    }
+                }
+            }
+        };
+
+        tokens.push(spanned(token, start..i));
+    }
+
+    (tokens, errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokenize_shape() {
+        let (tokens, errors) =
+            parse_tokens_str("ex:S CLOSED { ex:p @<T> * ; ^ex:q [ \"a\"@en 1.5 ] {1,2} }");
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        let tokens: Vec<_> = tokens.into_iter().map(|x| x.into_value()).collect();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::PNameLN(Some("ex".into()), "S".into()),
+                Token::ShexKeyword(ShexKeyword::Closed),
+                Token::CurlOpen,
+                Token::PNameLN(Some("ex".into()), "p".into()),
+                Token::At,
+                Token::IRIRef("T".into()),
+                Token::Star,
+                Token::PredicateSplit,
+                Token::Caret,
+                Token::PNameLN(Some("ex".into()), "q".into()),
+                Token::SqOpen,
+                Token::Str("a".into(), StringStyle::Double),
+                Token::LangTag("en".into()),
+                Token::Number("1.5".into()),
+                Token::SqClose,
+                Token::CurlOpen,
+                Token::Number("1".into()),
+                Token::Comma,
+                Token::Number("2".into()),
+                Token::CurlClose,
+                Token::CurlClose,
+            ]
+        );
+    }
+
+    #[test]
+    fn tokenize_errors() {
+        let (tokens, errors) = parse_tokens_str("ex:S { ex:p /regex/ } .");
+        assert_eq!(errors.len(), 1);
+        assert!(tokens
+            .iter()
+            .any(|x| matches!(x.value(), Token::Invalid(x) if x == "/regex/")));
+    }
+}
@@ -0,0 +1,635 @@
+use std::collections::HashSet;
+
+use lsp_core::prelude::{MyQuad, MyTerm};
+use sophia_api::term::{Term as _, TermKind};
+
+use super::model::*;
+
+const XSD: &str = "http://www.w3.org/2001/XMLSchema#";
+/// Splits of the triples over triple constraints that are tried for one node and shape.
+const MAX_SPLITS: usize = 4096;
+
+/// Validates nodes of a data graph against ShEx schemas.
+///
+/// This is not a complete ShEx implementation:
+/// - triples that match several triple constraints are split over them in every possible way, up
+/// to [`MAX_SPLITS`] splits per node and shape
+/// - repeated groups scale the cardinality of their constraints instead of matching every
+/// repetition on its own
+/// - [`MyTerm`] does not keep the datatype of literals, datatypes are checked against the lexical
+/// form, unknown datatypes accept every literal
+pub struct Validator<'a> {
+    schemas: &'a [&'a Schema],
+    triples: &'a [MyQuad<'static>],
+    /// Node and shape pairs that are being validated, recursive references to these are assumed
+    /// to conform.
+    visiting: HashSet<(String, String)>,
+}
+
+impl<'a> Validator<'a> {
+    pub fn new(schemas: &'a [&'a Schema], triples: &'a [MyQuad<'static>]) -> Self {
+        Self {
+            schemas,
+            triples,
+            visiting: HashSet::new(),
+        }
+    }
+
+    fn find_shape(&self, label: &str) -> Option<(&'a Schema, &'a ShapeDecl)> {
+        let schemas: &'a [&'a Schema] = self.schemas;
+        schemas
+            .iter()
+            .copied()
+            .find_map(|schema| Some((schema, schema.shape(label)?)))
+    }
+
+    /// Validate `node` against the shape with this (expanded) label.
+    pub fn check_label(&mut self, node: &MyTerm<'static>, label: &str) -> Result<(), String> {
+        let key = (node.to_string(), label.to_string());
+        if self.visiting.contains(&key) {
+            return Ok(());
+        }
+
+        let Some((schema, decl)) = self.find_shape(label) else {
+            return Err(format!("shape <{}> is not defined", label));
+        };
+        let Some(expr) = decl.expr.as_ref() else {
+            // External shapes cannot be checked
+            return Ok(());
+        };
+
+        self.visiting.insert(key.clone());
+        let out = self.check_expr(schema, expr.value(), node);
+        self.visiting.remove(&key);
+        out
+    }
+
+    /// Validate `node` against the start shape of the first schema that has one.
+    pub fn check_start(&mut self, node: &MyTerm<'static>) -> Result<(), String> {
+        let schemas: &'a [&'a Schema] = self.schemas;
+        let Some((schema, start)) = schemas
+            .iter()
+            .copied()
+            .find_map(|schema| Some((schema, schema.start.as_ref()?)))
+        else {
+            return Err(String::from("no start shape is defined"));
+        };
+        self.check_expr(schema, start.value(), node)
+    }
+
+    fn check_expr(
+        &mut self,
+        schema: &'a Schema,
+        expr: &'a ShapeExpr,
+        node: &MyTerm<'static>,
+    ) -> Result<(), String> {
+        match expr {
+            ShapeExpr::Or(xs) => {
+                let mut reasons = Vec::new();
+                for x in xs {
+                    match self.check_expr(schema, x.value(), node) {
+                        Ok(()) => return Ok(()),
+                        Err(e) => reasons.push(e),
+                    }
+                }
+                Err(format!(
+                    "none of the alternatives match ({})",
+                    reasons.join("; ")
+                ))
+            }
+            ShapeExpr::And(xs) => xs
+                .iter()
+                .try_for_each(|x| self.check_expr(schema, x.value(), node)),
+            ShapeExpr::Not(x) => match self.check_expr(schema, x.value(), node) {
+                Ok(()) => Err(String::from("matches a negated shape")),
+                Err(_) => Ok(()),
+            },
+            ShapeExpr::Ref(name) => {
+                let Some(label) = schema.expand(name.value()) else {
+                    return Err(format!("cannot expand {}", name.value()));
+                };
+                self.check_label(node, &label)
+            }
+            ShapeExpr::NodeConstraint(constraint) => {
+                check_node_constraint(schema, constraint, node)
+            }
+            ShapeExpr::Shape(shape) => self.check_shape(schema, shape, node),
+            ShapeExpr::Any => Ok(()),
+        }
+    }
+
+    fn check_shape(
+        &mut self,
+        schema: &'a Schema,
+        shape: &'a Shape,
+        node: &MyTerm<'static>,
+    ) -> Result<(), String> {
+        let mut constraints = Vec::new();
+        if let Some(expr) = shape.expression.as_ref() {
+            expr.constraints(&mut constraints);
+        }
+        let predicates: Vec<Option<String>> = constraints
+            .iter()
+            .map(|c| schema.expand(c.predicate.value()))
+            .collect();
+        let extra: HashSet<String> = shape
+            .extra
+            .iter()
+            .flat_map(|x| schema.expand(x.value()))
+            .collect();
+
+        let mut counts = vec![0; constraints.len()];
+        let mut groups: Vec<Group> = Vec::new();
+        let triples: &'a [MyQuad<'static>] = self.triples;
+
+        for t in triples {
+            let (inverse, neighbour) = if &t.subject == node {
+                (false, &t.object)
+            } else if &t.object == node {
+                (true, &t.subject)
+            } else {
+                continue;
+            };
+            let predicate = t.predicate.as_str();
+
+            let candidates: Vec<usize> = (0..constraints.len())
+                .filter(|i| {
+                    constraints[*i].inverse == inverse
+                        && predicates[*i].as_deref() == Some(predicate)
+                })
+                .collect();
+
+            if candidates.is_empty() {
+                if !inverse && shape.closed && !extra.contains(predicate) {
+                    return Err(format!(
+                        "property <{}> is not allowed in a closed shape",
+                        predicate
+                    ));
+                }
+                continue;
+            }
+
+            let mut reason = None;
+            let mut matching = Vec::new();
+            for i in candidates {
+                let constraint: &'a TripleConstraint = constraints[i];
+                let conforms = match constraint.value.as_deref() {
+                    Some(expr) => self.check_expr(schema, expr.value(), neighbour),
+                    None => Ok(()),
+                };
+                match conforms {
+                    Ok(()) => matching.push(i),
+                    Err(e) => reason = reason.or(Some(e)),
+                }
+            }
+
+            // Triples of extra properties do not have to match a constraint
+            let optional = extra.contains(predicate);
+            match matching.len() {
+                0 if inverse || optional => {}
+                0 => {
+                    return Err(format!(
+                        "value {} of <{}> does not conform: {}",
+                        neighbour,
+                        predicate,
+                        reason.unwrap_or_default()
+                    ));
+                }
+                1 if !optional => counts[matching[0]] += 1,
+                _ => match groups
+                    .iter_mut()
+                    .find(|g| g.candidates == matching && g.optional == optional)
+                {
+                    Some(group) => group.triples += 1,
+                    None => groups.push(Group {
+                        candidates: matching,
+                        optional,
+                        triples: 1,
+                    }),
+                },
+            }
+        }
+
+        let Some(expr) = shape.expression.as_ref() else {
+            return Ok(());
+        };
+        let mut check = |counts: &[usize]| {
+            let mut idx = 0;
+            check_counts(expr.value(), counts, &mut idx, 1, Some(1), schema)
+        };
+        let mut budget = MAX_SPLITS;
+        split(&groups, &mut counts, &mut check, &mut budget)
+    }
+}
+
+/// Triples that can be assigned to more than one triple constraint.
+struct Group {
+    candidates: Vec<usize>,
+    /// The triples can also be left unassigned
+    optional: bool,
+    triples: usize,
+}
+
+/// Tries the splits of the triples of each group over its constraints, until a split passes
+/// `check`. Returns the error of the first split otherwise.
+fn split(
+    groups: &[Group],
+    counts: &mut [usize],
+    check: &mut dyn FnMut(&[usize]) -> Result<(), String>,
+    budget: &mut usize,
+) -> Result<(), String> {
+    match groups.split_first() {
+        Some((group, rest)) => distribute(group, 0, group.triples, rest, counts, check, budget),
+        None => {
+            *budget = budget.saturating_sub(1);
+            check(counts)
+        }
+    }
+}
+
+/// Assigns `left` triples of `group` to its candidates from `at` on, the slot after the last
+/// candidate leaves triples unassigned when the group is optional.
+fn distribute(
+    group: &Group,
+    at: usize,
+    left: usize,
+    rest: &[Group],
+    counts: &mut [usize],
+    check: &mut dyn FnMut(&[usize]) -> Result<(), String>,
+    budget: &mut usize,
+) -> Result<(), String> {
+    let slots = group.candidates.len() + group.optional as usize;
+    let candidate = group.candidates.get(at).copied();
+
+    // The last slot takes the remaining triples
+    if at + 1 == slots {
+        if let Some(i) = candidate {
+            counts[i] += left;
+        }
+        let out = split(rest, counts, check, budget);
+        if let Some(i) = candidate {
+            counts[i] -= left;
+        }
+        return out;
+    }
+
+    let i = candidate.expect("only the last slot has no candidate");
+    let mut first = None;
+    for n in (0..=left).rev() {
+        counts[i] += n;
+        let out = distribute(group, at + 1, left - n, rest, counts, check, budget);
+        counts[i] -= n;
+        match out {
+            Ok(()) => return Ok(()),
+            Err(e) => {
+                first.get_or_insert(e);
+            }
+        }
+        if *budget == 0 {
+            break;
+        }
+    }
+    Err(first.unwrap_or_default())
+}
+
+/// Checks the triple expression against the number of triples assigned to each constraint,
+/// `idx` walks the constraints in the same order as [`TripleExpr::constraints`].
+fn check_counts(
+    expr: &TripleExpr,
+    counts: &[usize],
+    idx: &mut usize,
+    min_mult: usize,
+    max_mult: Option<usize>,
+    schema: &Schema,
+) -> Result<(), String> {
+    match expr {
+        TripleExpr::Constraint(c) => {
+            let count = counts[*idx];
+            *idx += 1;
+            let min = c.cardinality.min * min_mult;
+            let max = c.cardinality.max.zip(max_mult).map(|(a, b)| a * b);
+            if count >= min && max.map(|max| count <= max).unwrap_or(true) {
+                Ok(())
+            } else {
+                let predicate = schema
+                    .expand(c.predicate.value())
+                    .map(|x| format!("<{}>", x))
+                    .unwrap_or_else(|| c.predicate.value().to_string());
+                Err(format!(
+                    "expected {} value(s) for {}{}, found {}",
+                    Cardinality { min, max },
+                    if c.inverse { "^" } else { "" },
+                    predicate,
+                    count
+                ))
+            }
+        }
+        TripleExpr::EachOf(xs) => {
+            let mut out = Ok(());
+            for x in xs {
+                let res = check_counts(x.value(), counts, idx, min_mult, max_mult, schema);
+                out = out.and(res);
+            }
+            out
+        }
+        TripleExpr::OneOf(xs) => {
+            // An alternative is used when one of its constraints matched a triple
+            let mut results = Vec::new();
+            for x in xs {
+                let start = *idx;
+                let res = check_counts(x.value(), counts, idx, min_mult, max_mult, schema);
+                let used = counts[start..*idx].iter().any(|x| *x > 0);
+                results.push((used, res));
+            }
+
+            let mut used = results.iter().filter(|x| x.0).map(|x| &x.1);
+            match (used.next(), used.next()) {
+                (None, _) => results
+                    .iter()
+                    .find(|x| x.1.is_ok())
+                    .or(results.first())
+                    .map(|x| x.1.clone())
+                    .unwrap_or(Ok(())),
+                (Some(res), None) => res.clone(),
+                _ => Err(String::from(
+                    "more than one alternative of a one-of matched",
+                )),
+            }
+        }
+        TripleExpr::Group { expr, cardinality } => {
+            let start = *idx;
+            let mut end = start;
+            skip(expr.value(), &mut end);
+            if cardinality.min == 0 && counts[start..end].iter().all(|x| *x == 0) {
+                *idx = end;
+                return Ok(());
+            }
+
+            let min_mult = min_mult * cardinality.min.max(1);
+            let max_mult = cardinality.max.zip(max_mult).map(|(a, b)| a * b);
+            check_counts(expr.value(), counts, idx, min_mult, max_mult, schema)
+        }
+    }
+}
+
+/// Moves `idx` past the constraints of `expr`.
+fn skip(expr: &TripleExpr, idx: &mut usize) {
+    let mut constraints = Vec::new();
+    expr.constraints(&mut constraints);
+    *idx += constraints.len();
+}
+
+fn check_node_constraint(
+    schema: &Schema,
+    constraint: &NodeConstraint,
+    node: &MyTerm<'static>,
+) -> Result<(), String> {
+    let kind = node.kind();
+    if let Some(expected) = constraint.kind {
+        let ok = match expected {
+            NodeKind::Iri => kind == TermKind::Iri,
+            NodeKind::BNode => kind == TermKind::BlankNode,
+            NodeKind::NonLiteral => matches!(kind, TermKind::Iri | TermKind::BlankNode),
+            NodeKind::Literal => kind == TermKind::Literal,
+        };
+        if !ok {
+            let expected = match expected {
+                NodeKind::Iri => "an IRI",
+                NodeKind::BNode => "a blank node",
+                NodeKind::NonLiteral => "an IRI or blank node",
+                NodeKind::Literal => "a literal",
+            };
+            return Err(format!("{} is not {}", node, expected));
+        }
+    }
+
+    if let Some(datatype) = constraint.datatype.as_ref() {
+        let datatype = schema
+            .expand(datatype.value())
+            .ok_or_else(|| format!("cannot expand {}", datatype.value()))?;
+        if kind != TermKind::Literal || !lexical_matches(&datatype, node.as_str()) {
+            return Err(format!("{} is not a valid <{}>", node, datatype));
+        }
+    }
+
+    if let Some(values) = constraint.values.as_ref() {
+        let found = values.iter().any(|value| match value.value() {
+            Value::Iri(name) => {
+                kind == TermKind::Iri && schema.expand(name).as_deref() == Some(node.as_str())
+            }
+            Value::Literal { value, .. } => kind == TermKind::Literal && value == node.as_str(),
+        });
+        if !found {
+            return Err(format!("{} is not one of the allowed values", node));
+        }
+    }
+
+    for facet in &constraint.facets {
+        check_facet(facet.value(), node)?;
+    }
+
+    Ok(())
+}
+
+fn lexical_matches(datatype: &str, value: &str) -> bool {
+    let Some(local) = datatype.strip_prefix(XSD) else {
+        return true;
+    };
+
+    let integer = |x: &str| {
+        let digits = x.strip_prefix(['+', '-']).unwrap_or(x);
+        !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+    };
+    let decimal = |x: &str| {
+        let digits = x.strip_prefix(['+', '-']).unwrap_or(x);
+        let (int, frac) = digits.split_once('.').unwrap_or((digits, ""));
+        !(int.is_empty() && frac.is_empty())
+            && int.bytes().chain(frac.bytes()).all(|b| b.is_ascii_digit())
+    };
+
+    match local {
+        "integer" | "int" | "long" | "short" | "byte" => integer(value),
+        "nonNegativeInteger" | "positiveInteger" | "unsignedInt" | "unsignedLong" => {
+            integer(value) && !value.starts_with('-')
+        }
+        "decimal" => decimal(value),
+        "double" | "float" => {
+            matches!(value, "INF" | "-INF" | "NaN")
+                || (value.parse::<f64>().is_ok() && value.bytes().any(|b| b.is_ascii_digit()))
+        }
+        "boolean" => matches!(value, "true" | "false" | "1" | "0"),
+        "date" => {
+            let parts: Vec<_> = value.trim_start_matches('-').splitn(3, '-').collect();
+            parts.len() == 3
+                && parts
+                    .iter()
+                    .all(|x| x.bytes().take(2).all(|b| b.is_ascii_digit()))
+        }
+        _ => true,
+    }
+}
+
+fn check_facet(facet: &Facet, node: &MyTerm<'static>) -> Result<(), String> {
+    let value = node.as_str();
+    let length = value.chars().count();
+    let number = || {
+        value
+            .parse::<f64>()
+            .map_err(|_| format!("{} is not numeric", node))
+    };
+    let bound = |x: &str| x.parse::<f64>().unwrap_or(f64::NAN);
+    let digits = |x: &str| {
+        let x = x.trim_start_matches(['+', '-']);
+        let (int, frac) = x.split_once('.').unwrap_or((x, ""));
+        let int = int.trim_start_matches('0');
+        let frac = frac.trim_end_matches('0');
+        (int.len() + frac.len(), frac.len())
+    };
+
+    let ok = match facet {
+        Facet::Length(x) => length == *x,
+        Facet::MinLength(x) => length >= *x,
+        Facet::MaxLength(x) => length <= *x,
+        Facet::MinInclusive(x) => number()? >= bound(x),
+        Facet::MinExclusive(x) => number()? > bound(x),
+        Facet::MaxInclusive(x) => number()? <= bound(x),
+        Facet::MaxExclusive(x) => number()? < bound(x),
+        Facet::TotalDigits(x) => {
+            number()?;
+            digits(value).0 <= *x
+        }
+        Facet::FractionDigits(x) => {
+            number()?;
+            digits(value).1 <= *x
+        }
+    };
+
+    if ok {
+        Ok(())
+    } else {
+        Err(format!("{} violates {}", node, facet_name(facet)))
+    }
+}
+
+fn facet_name(facet: &Facet) -> String {
+    match facet {
+        Facet::Length(x) => format!("LENGTH {}", x),
+        Facet::MinLength(x) => format!("MINLENGTH {}", x),
+        Facet::MaxLength(x) => format!("MAXLENGTH {}", x),
+        Facet::MinInclusive(x) => format!("MININCLUSIVE {}", x),
+        Facet::MinExclusive(x) => format!("MINEXCLUSIVE {}", x),
+        Facet::MaxInclusive(x) => format!("MAXINCLUSIVE {}", x),
+        Facet::MaxExclusive(x) => format!("MAXEXCLUSIVE {}", x),
+        Facet::TotalDigits(x) => format!("TOTALDIGITS {}", x),
+        Facet::FractionDigits(x) => format!("FRACTIONDIGITS {}", x),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::Url;
+
+    use super::*;
+    use crate::lang::{parser::parse, tokenizer::parse_tokens_str};
+
+    fn schema(text: &str) -> Schema {
+        let (tokens, _) = parse_tokens_str(text);
+        let url = Url::parse("http://example.com/schema.shex").unwrap();
+        let (schema, errors) = parse(&tokens, &url);
+        assert!(errors.is_empty(), "{:?}", errors);
+        schema
+    }
+
+    fn iri(x: &str) -> MyTerm<'static> {
+        MyTerm::named_node(format!("http://example.com/{}", x), 0..0)
+    }
+
+    fn quad(s: &str, p: &str, o: MyTerm<'static>) -> MyQuad<'static> {
+        MyQuad {
+            subject: iri(s),
+            predicate: iri(p),
+            object: o,
+            graph: None,
+            span: 0..0,
+        }
+    }
+
+    const SCHEMA: &str = r#"
+PREFIX ex: <http://example.com/>
+PREFIX xsd: <http://www.w3.org/2001/XMLSchema#>
+
+ex:Person CLOSED {
+  ex:name xsd:string ;
+  ex:age xsd:integer MININCLUSIVE 0 ? ;
+  ex:knows @ex:Person *
+}
+"#;
+
+    #[test]
+    fn valid_nodes_conform() {
+        let schema = schema(SCHEMA);
+        let schemas = [&schema];
+        let triples = vec![
+            quad("alice", "name", MyTerm::literal("Alice", 0..0)),
+            quad("alice", "age", MyTerm::literal("32", 0..0)),
+            quad("alice", "knows", iri("bob")),
+            quad("bob", "name", MyTerm::literal("Bob", 0..0)),
+            quad("bob", "knows", iri("alice")),
+        ];
+
+        let mut validator = Validator::new(&schemas, &triples);
+        assert_eq!(
+            validator.check_label(&iri("alice"), "http://example.com/Person"),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn invalid_nodes_explain() {
+        let schema = schema(SCHEMA);
+        let schemas = [&schema];
+        let triples = vec![
+            quad("alice", "age", MyTerm::literal("-3", 0..0)),
+            quad("bob", "name", MyTerm::literal("Bob", 0..0)),
+            quad("bob", "email", MyTerm::literal("bob@example.com", 0..0)),
+            quad("carol", "name", MyTerm::literal("Carol", 0..0)),
+            quad("carol", "knows", iri("bob")),
+        ];
+
+        let mut validator = Validator::new(&schemas, &triples);
+        let person = "http://example.com/Person";
+
+        let err = validator.check_label(&iri("alice"), person).unwrap_err();
+        assert!(err.contains("MININCLUSIVE"), "{}", err);
+
+        let err = validator.check_label(&iri("bob"), person).unwrap_err();
+        assert!(err.contains("closed"), "{}", err);
+
+        let err = validator.check_label(&iri("carol"), person).unwrap_err();
+        assert!(err.contains("knows"), "{}", err);
+    }
+
+    #[test]
+    fn constraints_sharing_a_predicate_are_split() {
+        let schema = schema(
+            r#"
+PREFIX ex: <http://example.com/>
+PREFIX xsd: <http://www.w3.org/2001/XMLSchema#>
+
+ex:Pair { ex:value . ; ex:value xsd:integer }
+"#,
+        );
+        let schemas = [&schema];
+        // Giving the integer to `ex:value .` leaves nothing that matches `xsd:integer`
+        let triples = vec![
+            quad("pair", "value", MyTerm::literal("5", 0..0)),
+            quad("pair", "value", MyTerm::literal("five", 0..0)),
+        ];
+
+        let mut validator = Validator::new(&schemas, &triples);
+        assert_eq!(
+            validator.check_label(&iri("pair"), "http://example.com/Pair"),
+            Ok(())
+        );
+    }
+}
@@ -0,0 +1,272 @@
+#![doc(
+    html_logo_url = "https://ajuvercr.github.io/semantic-web-lsp/assets/icons/favicon.png",
+    html_favicon_url = "https://ajuvercr.github.io/semantic-web-lsp/assets/icons/favicon.ico"
+)]
+//! ShEx (ShExC) support.
+//!
+//! Schemas (`.shex` files) are tokenized and parsed into a [`Schema`], with semantic
+//! highlighting, keyword and prefix completion.
+//!
+//! Next to SHACL, documents are validated against ShEx schemas. The nodes to validate are
+//! selected with a shape map, configured in `shape_map` or written in a comment of the data
+//! document:
+//! ```turtle
+//! # shapeMap: ex:alice@ex:Person, {FOCUS a ex:Person}@START
+//! ```
+//! Failing nodes are reported with the `shex_validation` reason.
+use bevy_ecs::{component::Component, observer::Trigger, system::Commands, world::World};
+use chumsky::prelude::Simple;
+use ecs::{setup_code_action, setup_completion, setup_parsing, setup_validation};
+use lang::model::Schema;
+use lsp_core::{
+    feature::diagnostics::{publish_diagnostics, pull_syntax_diagnostics},
+    lang::{Lang, LangHelper},
+    prelude::*,
+    CreateEvent,
+};
+use lsp_types::SemanticTokenType;
+
+pub mod ecs;
+pub mod lang;
+
+/// Marker component for ShEx documents.
+#[derive(Component, Debug)]
+pub struct ShexLang;
+
+lazy_static::lazy_static! {
+    static ref KWDS: Vec<&'static str> = {
+        let mut m = vec!["PREFIX", "BASE"];
+        ShexKeyword::ITEMS.iter().for_each(|x| m.push(x.complete()));
+        m
+    };
+}
+
+#[derive(Debug)]
+pub struct ShexHelper;
+impl LangHelper for ShexHelper {
+    fn keyword(&self) -> &[&'static str] {
+        &KWDS
+    }
+}
+
+pub fn setup_world(world: &mut World) {
+    let mut semantic_token_dict = world.resource_mut::<SemanticTokensDict>();
+    ShexLang::LEGEND_TYPES.iter().for_each(|lt| {
+        if !semantic_token_dict.contains_key(lt) {
+            let l = semantic_token_dict.0.len();
+            semantic_token_dict.insert(lt.clone(), l);
+        }
+    });
+
+    world.observe(|trigger: Trigger<CreateEvent>, mut commands: Commands| {
+        let is_shex = match &trigger.event().language_id {
+            Some(x) => x == "shex",
+            None => trigger.event().url.as_str().ends_with(".shex"),
+        };
+
+        if is_shex {
+            commands
+                .entity(trigger.entity())
+                .insert((ShexLang, DynLang(Box::new(ShexHelper))));
+        }
+    });
+
+    world.schedule_scope(lsp_core::feature::DiagnosticsLabel, |_, schedule| {
+        schedule.add_systems(publish_diagnostics::<ShexLang>);
+    });
+    world.schedule_scope(lsp_core::feature::PullDiagnosticsLabel, |_, schedule| {
+        schedule.add_systems(pull_syntax_diagnostics::<ShexLang>);
+    });
+
+    setup_parsing(world);
+    setup_completion(world);
+    setup_code_action(world);
+    setup_validation(world);
+}
+
+impl Lang for ShexLang {
+    type Token = Token;
+
+    type TokenError = Simple<char>;
+
+    type Element = Schema;
+
+    type ElementError = Simple<Token>;
+
+    const LANG: &'static str = "shex";
+
+    const TRIGGERS: &'static [&'static str] = &[];
+    const CODE_ACTION: bool = true;
+    const HOVER: bool = true;
+
+    const LEGEND_TYPES: &'static [lsp_types::SemanticTokenType] = &[
+        semantic_token::BOOLEAN,
+        semantic_token::LANG_TAG,
+        SemanticTokenType::COMMENT,
+        SemanticTokenType::ENUM_MEMBER,
+        SemanticTokenType::KEYWORD,
+        SemanticTokenType::NAMESPACE,
+        SemanticTokenType::NUMBER,
+        SemanticTokenType::PROPERTY,
+        SemanticTokenType::STRING,
+    ];
+
+    const PATTERN: Option<&'static str> = None;
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_core::{components::*, feature::diagnostics::pull_diagnostics, prelude::*};
+    use test_utils::{create_file, setup_world, TestClient};
+
+    const SCHEMA: &str = r#"PREFIX ex: <http://example.com/>
+PREFIX xsd: <http://www.w3.org/2001/XMLSchema#>
+
+start = @ex:Person
+
+ex:Person {
+  ex:name xsd:string ;
+  ex:knows @ex:Person *
+}
+"#;
+
+    fn world() -> bevy_ecs::world::World {
+        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world);
+        lang_turtle::setup_world(&mut world);
+        world
+    }
+
+    fn conform_messages(
+        world: &mut bevy_ecs::world::World,
+        entity: bevy_ecs::entity::Entity,
+    ) -> Vec<String> {
+        pull_diagnostics(world, &[entity])
+            .remove(0)
+            .into_iter()
+            .map(|x| x.message)
+            .filter(|x| x.contains("does not conform"))
+            .collect()
+    }
+
+    #[test_log::test]
+    fn shex_document_is_parsed() {
+        let mut world = world();
+        let entity = create_file(
+            &mut world,
+            SCHEMA,
+            "http://example.com/schema.shex",
+            "",
+            Open,
+        );
+
+        assert!(world.entity(entity).contains::<crate::ShexLang>());
+        assert!(!world.entity(entity).contains::<Dirty>());
+
+        let schema: &Element<crate::ShexLang> = world.entity(entity).get().expect("schema");
+        assert_eq!(schema.shapes.len(), 1);
+        assert!(schema.start.is_some());
+
+        let prefixes: &Prefixes = world.entity(entity).get().expect("prefixes");
+        assert_eq!(prefixes.len(), 2);
+    }
+
+    #[test_log::test]
+    fn shex_errors_mark_document_dirty() {
+        let mut world = world();
+        let entity = create_file(
+            &mut world,
+            "PREFIX ex: <http://example.com/>\nex:S { ex:p ; }",
+            "http://example.com/schema.shex",
+            "shex",
+            Open,
+        );
+
+        assert!(world.entity(entity).contains::<Dirty>());
+    }
+
+    #[test_log::test]
+    fn shape_map_comment_validates_data() {
+        let mut world = world();
+        create_file(
+            &mut world,
+            SCHEMA,
+            "http://example.com/schema.shex",
+            "shex",
+            Open,
+        );
+
+        let data = r#"# shapeMap: ex:alice@ex:Person, ex:bob@START
+@prefix ex: <http://example.com/>.
+ex:alice ex:name "Alice"; ex:knows ex:bob.
+ex:bob ex:knows ex:alice.
+"#;
+        let entity = create_file(
+            &mut world,
+            data,
+            "http://example.com/data.ttl",
+            "turtle",
+            Open,
+        );
+
+        let messages = conform_messages(&mut world, entity);
+        assert_eq!(messages.len(), 2, "{:?}", messages);
+        assert!(messages[0].starts_with("Node ex:alice does not conform to ex:Person"));
+        assert!(messages[1].starts_with("Node ex:bob does not conform to START"));
+    }
+
+    #[test_log::test]
+    fn configured_shape_map_validates_data() {
+        let mut world = world();
+        world.resource_mut::<ServerConfig>().config.shape_map =
+            vec![String::from("{FOCUS ex:name _}@ex:Person")];
+        create_file(
+            &mut world,
+            SCHEMA,
+            "http://example.com/schema.shex",
+            "shex",
+            Open,
+        );
+
+        let data = r#"@prefix ex: <http://example.com/>.
+ex:alice ex:name "Alice".
+ex:bob ex:name "Bob"; ex:knows ex:carol.
+"#;
+        let entity = create_file(
+            &mut world,
+            data,
+            "http://example.com/data.ttl",
+            "turtle",
+            Open,
+        );
+
+        let messages = conform_messages(&mut world, entity);
+        assert_eq!(messages.len(), 1, "{:?}", messages);
+        assert!(messages[0].starts_with("Node ex:bob does not conform"));
+    }
+
+    #[test_log::test]
+    fn invalid_shape_map_comment_is_reported() {
+        let mut world = world();
+
+        let data = r#"@prefix ex: <http://example.com/>.
+ex:zoe ex:name "Zoë".
+# shapeMap: ex:zoe@
+"#;
+        let entity = create_file(
+            &mut world,
+            data,
+            "http://example.com/data.ttl",
+            "turtle",
+            Open,
+        );
+
+        let diagnostics = pull_diagnostics(&mut world, &[entity]).remove(0);
+        let invalid: Vec<_> = diagnostics
+            .iter()
+            .filter(|x| x.message.starts_with("Invalid shape map"))
+            .collect();
+        assert_eq!(invalid.len(), 1, "{:?}", diagnostics);
+        assert_eq!(invalid[0].range.start, lsp_types::Position::new(2, 0));
+        assert_eq!(invalid[0].range.end, lsp_types::Position::new(2, 19));
+    }
+}
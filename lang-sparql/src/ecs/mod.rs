@@ -6,7 +6,13 @@ use lang_turtle::lang::{
     context::{Context, TokenIdx},
     model::TriplesBuilder,
 };
-use lsp_core::{components::*, prelude::*, systems::prefix::prefix_completion_helper};
+use lsp_core::{
+    components::*,
+    prelude::*,
+    systems::prefix::{
+        prefix_completion_helper, prologue_insert_position, undefined_prefix_action_helper,
+    },
+};
 use lsp_types::CompletionItemKind;
 use sophia_iri::resolve::BaseIri;
 
@@ -15,6 +21,9 @@ use crate::{
     Sparql,
 };
 
+mod symbol;
+use symbol::sparql_document_symbols;
+
 pub fn setup_parse(world: &mut World) {
     use lsp_core::feature::parse::*;
     world.schedule_scope(Label, |_, schedule| {
@@ -39,6 +48,18 @@ pub fn setup_completion(world: &mut World) {
     });
 }
 
+pub fn setup_code_action(world: &mut World) {
+    world.schedule_scope(CodeActionLabel, |_, schedule| {
+        schedule.add_systems(sparql_undefined_prefix_action);
+    });
+}
+
+pub fn setup_document_symbol(world: &mut World) {
+    world.schedule_scope(DocumentSymbolLabel, |_, schedule| {
+        schedule.add_systems(sparql_document_symbols);
+    });
+}
+
 #[instrument(skip(query, commands))]
 fn parse_source(
     query: Query<(Entity, &Source), (Changed<Source>, With<Sparql>)>,
@@ -124,8 +145,15 @@ fn derive_triples(
             let mut builder = TriplesBuilder::new(query, base);
             let _ = query.ingest_triples(&mut builder);
             let triples: Vec<_> = builder.triples.into_iter().map(|x| x.to_owned()).collect();
+            let unasserted: Vec<_> = builder
+                .unasserted
+                .into_iter()
+                .map(|x| x.to_owned())
+                .collect();
 
-            commands.entity(e).insert(Triples(triples));
+            commands
+                .entity(e)
+                .insert((Triples(triples), UnassertedTriples(unasserted)));
         }
     }
 }
@@ -161,6 +189,26 @@ pub fn variable_completion(
     }
 }
 
+pub fn sparql_undefined_prefix_action(
+    mut query: Query<(&Element<Sparql>, &RopeC, &Label, &mut CodeActionRequest)>,
+) {
+    for (sparql, rope, label, mut req) in &mut query {
+        let ends = sparql
+            .base_statement
+            .iter()
+            .map(|x| x.span().end)
+            .chain(sparql.prefixes.iter().map(|x| x.span().end));
+        let (start, newline) = prologue_insert_position(ends, &rope.0);
+
+        undefined_prefix_action_helper(&mut req, label, |name, location| {
+            Some(vec![lsp_types::TextEdit {
+                range: lsp_types::Range::new(start, start),
+                new_text: format!("{}PREFIX {}: <{}>\n", newline, name, location),
+            }])
+        });
+    }
+}
+
 pub fn sparql_lov_undefined_prefix_completion(
     mut query: Query<(
         &TokenComponent,
@@ -185,3 +233,76 @@ pub fn sparql_lov_undefined_prefix_completion(
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+    use lsp_core::{components::*, prelude::*, Tasks};
+    use lsp_types::CompletionItemKind;
+    use ropey::Rope;
+    use test_utils::{create_file, setup_world, TestClient};
+
+    #[test_log::test]
+    fn path_segments_complete_properties() {
+        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world);
+        lang_turtle::setup_world(&mut world);
+
+        let t1 = "PREFIX foaf: <http://xmlns.com/foaf/0.1/>
+SELECT * { ?x foaf:knows/foaf:name ?y }";
+
+        let t2 = "PREFIX foaf: <http://xmlns.com/foaf/0.1/>
+SELECT * { ?x foaf:knows/foaf: ?y }";
+
+        let entity = create_file(
+            &mut world,
+            t1,
+            "http://example.com/query.rq",
+            "sparql",
+            Open,
+        );
+
+        let c = world.resource::<TestClient>().clone();
+        block_on(c.await_futures(|| world.run_schedule(Tasks)));
+
+        world
+            .entity_mut(entity)
+            .insert((Source(t2.to_string()), RopeC(Rope::from_str(t2)), Open));
+        world.run_schedule(ParseLabel);
+
+        block_on(c.await_futures(|| world.run_schedule(Tasks)));
+
+        // The cursor is on the second segment of the path
+        world.entity_mut(entity).insert((
+            CompletionRequest(vec![]),
+            PositionComponent(lsp_types::Position {
+                line: 1,
+                character: 27,
+            }),
+        ));
+        world.run_schedule(CompletionLabel);
+
+        let triple = world
+            .entity(entity)
+            .get::<TripleComponent>()
+            .expect("current triple");
+        assert_eq!(triple.target, TripleTarget::Predicate);
+        assert_eq!(triple.triple.predicate.value, "http://xmlns.com/foaf/0.1/");
+
+        let completions = world
+            .entity_mut(entity)
+            .take::<CompletionRequest>()
+            .expect("competion request")
+            .0;
+
+        assert!(completions.iter().any(|c| c.label == "foaf:name"
+            && matches!(
+                c.kind,
+                CompletionItemKind::PROPERTY | CompletionItemKind::FIELD
+            )));
+        // The intermediate node has the range of foaf:knows, foaf:Person, so properties with
+        // that domain are ranked first
+        assert!(completions
+            .iter()
+            .any(|c| c.label == "foaf:knows" && c.kind == CompletionItemKind::FIELD));
+    }
+}
@@ -0,0 +1,406 @@
+use bevy_ecs::prelude::*;
+use lsp_core::{feature::document_symbol::document_symbol, prelude::*};
+use lsp_types::{DocumentSymbol, SymbolKind};
+use ropey::Rope;
+
+use crate::{
+    lang::model::{
+        DescribeTarget, GroupGraphPattern, GroupGraphPatternSub, Query, QueryClause, Solution,
+        UpdateOperation,
+    },
+    Sparql,
+};
+
+fn prologue_symbol(query: &Query, rope: &Rope) -> Option<DocumentSymbol> {
+    let mut children = Vec::new();
+
+    if let Some(base) = &query.base_statement {
+        let mut symbol = document_symbol(
+            "BASE",
+            SymbolKind::NAMESPACE,
+            base.span(),
+            base.token.span(),
+            rope,
+        )?;
+        symbol.detail = Some(base.iri.to_string());
+        children.push(symbol);
+    }
+
+    for prefix in &query.prefixes {
+        let Some(mut symbol) = document_symbol(
+            format!("{}:", prefix.prefix.value()),
+            SymbolKind::NAMESPACE,
+            prefix.span(),
+            prefix.prefix.span(),
+            rope,
+        ) else {
+            continue;
+        };
+        symbol.detail = Some(prefix.value.to_string());
+        children.push(symbol);
+    }
+
+    let start = children.iter().map(|x| x.range.start).min()?;
+    let end = children.iter().map(|x| x.range.end).max()?;
+
+    #[allow(deprecated)]
+    Some(DocumentSymbol {
+        name: String::from("Prologue"),
+        detail: None,
+        kind: SymbolKind::MODULE,
+        tags: None,
+        deprecated: None,
+        range: lsp_types::Range::new(start, end),
+        selection_range: lsp_types::Range::new(start, start),
+        children: Some(children),
+    })
+}
+
+fn query_clause_symbol(query: &Query, rope: &Rope) -> Option<DocumentSymbol> {
+    match &query.kwds {
+        QueryClause::Select(select) => {
+            let start = select.kwd.span().start;
+            let end = select
+                .solutions
+                .last()
+                .map(|x| x.span().end)
+                .unwrap_or(select.kwd.span().end);
+
+            let mut symbol = document_symbol(
+                select.kwd.complete(),
+                SymbolKind::FUNCTION,
+                &(start..end),
+                select.kwd.span(),
+                rope,
+            )?;
+
+            let solutions: Vec<_> = select
+                .solutions
+                .iter()
+                .map(|x| match x.value() {
+                    Solution::All => String::from("*"),
+                    Solution::Var(var) => var.0.clone(),
+                    Solution::VarAs(bind) => bind.var.0.clone(),
+                })
+                .collect();
+            symbol.detail = Some(solutions.join(" "));
+
+            Some(symbol)
+        }
+        QueryClause::Construct(construct) => {
+            let start = construct.kwd.span().start;
+            let end = construct
+                .template
+                .as_ref()
+                .map(|(_, _, close)| close.span().end)
+                .unwrap_or(construct.kwd.span().end);
+
+            let mut symbol = document_symbol(
+                construct.kwd.complete(),
+                SymbolKind::FUNCTION,
+                &(start..end),
+                construct.kwd.span(),
+                rope,
+            )?;
+
+            if let Some((_, subs, _)) = &construct.template {
+                let children = subs.iter().flat_map(|x| sub_symbol(x, rope)).collect();
+                symbol.children = Some(children);
+            }
+
+            Some(symbol)
+        }
+        QueryClause::Ask(kwd) => document_symbol(
+            kwd.complete(),
+            SymbolKind::FUNCTION,
+            kwd.span(),
+            kwd.span(),
+            rope,
+        ),
+        QueryClause::Describe(describe) => {
+            let start = describe.kwd.span().start;
+            let end = describe
+                .targets
+                .last()
+                .map(|x| x.span().end)
+                .unwrap_or(describe.kwd.span().end);
+
+            let mut symbol = document_symbol(
+                describe.kwd.complete(),
+                SymbolKind::FUNCTION,
+                &(start..end),
+                describe.kwd.span(),
+                rope,
+            )?;
+
+            let targets: Vec<_> = describe
+                .targets
+                .iter()
+                .map(|x| match x.value() {
+                    DescribeTarget::All => String::from("*"),
+                    DescribeTarget::Var(var) => var.0.clone(),
+                    DescribeTarget::Iri(iri) => iri.to_string(),
+                })
+                .collect();
+            symbol.detail = Some(targets.join(" "));
+
+            Some(symbol)
+        }
+        QueryClause::Invalid => None,
+    }
+}
+
+fn group_symbols(ggp: &Spanned<GroupGraphPattern>, rope: &Rope) -> Vec<DocumentSymbol> {
+    match ggp.value() {
+        GroupGraphPattern::SubSelect(sub_select) => {
+            let Some(mut symbol) = document_symbol(
+                sub_select.select.kwd.complete(),
+                SymbolKind::FUNCTION,
+                ggp.span(),
+                sub_select.select.kwd.span(),
+                rope,
+            ) else {
+                return Vec::new();
+            };
+            symbol.children = Some(group_symbols(&sub_select.where_clause.ggp, rope));
+            vec![symbol]
+        }
+        GroupGraphPattern::GroupGraph(subs) => {
+            subs.iter().flat_map(|x| sub_symbol(x, rope)).collect()
+        }
+        GroupGraphPattern::Invalid => Vec::new(),
+    }
+}
+
+fn nested_symbol(
+    name: impl Into<String>,
+    kind: SymbolKind,
+    range: &std::ops::Range<usize>,
+    selection: &std::ops::Range<usize>,
+    ggp: &Spanned<GroupGraphPattern>,
+    rope: &Rope,
+) -> Option<DocumentSymbol> {
+    let mut symbol = document_symbol(name, kind, range, selection, rope)?;
+    symbol.children = Some(group_symbols(ggp, rope));
+    Some(symbol)
+}
+
+fn sub_symbol(sub: &Spanned<GroupGraphPatternSub>, rope: &Rope) -> Option<DocumentSymbol> {
+    match sub.value() {
+        GroupGraphPatternSub::Triple(triple) => {
+            let mut symbol = document_symbol(
+                triple.subject.to_string(),
+                SymbolKind::OBJECT,
+                sub.span(),
+                triple.subject.span(),
+                rope,
+            )?;
+            let predicates: Vec<_> = triple.po.iter().map(|x| x.predicate.to_string()).collect();
+            symbol.detail = Some(predicates.join(", "));
+            Some(symbol)
+        }
+        GroupGraphPatternSub::TriplePath(triple) => {
+            let mut symbol = document_symbol(
+                triple.subject.to_string(),
+                SymbolKind::OBJECT,
+                sub.span(),
+                triple.subject.span(),
+                rope,
+            )?;
+            let predicates: Vec<_> = triple.po.iter().map(|x| x.predicate.to_string()).collect();
+            symbol.detail = Some(predicates.join(", "));
+            Some(symbol)
+        }
+        GroupGraphPatternSub::Kwd(kwd, ggp) => nested_symbol(
+            kwd.complete(),
+            SymbolKind::STRUCT,
+            sub.span(),
+            kwd.span(),
+            ggp,
+            rope,
+        ),
+        GroupGraphPatternSub::Filter(kwd, _) => document_symbol(
+            kwd.complete(),
+            SymbolKind::OPERATOR,
+            sub.span(),
+            kwd.span(),
+            rope,
+        ),
+        GroupGraphPatternSub::Union(first, rest) => {
+            let selection = rest
+                .first()
+                .map(|(kwd, _)| kwd.span().clone())
+                .unwrap_or_else(|| first.span().clone());
+            let mut symbol = document_symbol(
+                SparqlKeyword::Union.complete(),
+                SymbolKind::STRUCT,
+                sub.span(),
+                &selection,
+                rope,
+            )?;
+
+            let children = std::iter::once(first)
+                .chain(rest.iter().map(|(_, x)| x))
+                .flat_map(|ggp| {
+                    nested_symbol("{ }", SymbolKind::STRUCT, ggp.span(), ggp.span(), ggp, rope)
+                })
+                .collect();
+            symbol.children = Some(children);
+            Some(symbol)
+        }
+        GroupGraphPatternSub::GraphOrService(kwd, _, name, ggp) => nested_symbol(
+            format!("{} {}", kwd.complete(), name.value()),
+            SymbolKind::NAMESPACE,
+            sub.span(),
+            name.span(),
+            ggp,
+            rope,
+        ),
+        GroupGraphPatternSub::Bind(kwd, _, bind, _) => document_symbol(
+            format!("{} {}", kwd.complete(), bind.var.0),
+            SymbolKind::VARIABLE,
+            sub.span(),
+            bind.var.span(),
+            rope,
+        ),
+        GroupGraphPatternSub::Inline(inline) => document_symbol(
+            SparqlKeyword::Values.complete(),
+            SymbolKind::ARRAY,
+            sub.span(),
+            inline.kwd.span(),
+            rope,
+        ),
+    }
+}
+
+fn operation_symbol(op: &Spanned<UpdateOperation>, rope: &Rope) -> Option<DocumentSymbol> {
+    let kwd = op.kwd()?;
+    let name = match op.value() {
+        UpdateOperation::Data { kwd, data_kwd, .. } => {
+            format!("{} {}", kwd.complete(), data_kwd.complete())
+        }
+        UpdateOperation::DeleteWhere { kwd, where_kwd, .. } => {
+            format!("{} {}", kwd.complete(), where_kwd.complete())
+        }
+        _ => kwd.complete().to_string(),
+    };
+
+    let mut symbol = document_symbol(name, SymbolKind::FUNCTION, op.span(), kwd.span(), rope)?;
+    if let UpdateOperation::Modify(modify) = op.value() {
+        symbol.children = Some(group_symbols(&modify.where_clause.ggp, rope));
+    }
+    Some(symbol)
+}
+
+pub fn sparql_document_symbols(
+    mut query: Query<(&Element<Sparql>, &RopeC, &mut DocumentSymbolRequest)>,
+) {
+    for (sparql, rope, mut req) in &mut query {
+        let sparql = sparql.value();
+        req.extend(prologue_symbol(sparql, &rope.0));
+        req.extend(query_clause_symbol(sparql, &rope.0));
+
+        if sparql.is_update() {
+            let operations = &sparql.operations;
+            req.extend(operations.iter().flat_map(|x| operation_symbol(x, &rope.0)));
+            continue;
+        }
+
+        // DESCRIBE queries can omit the where clause
+        let where_clause = &sparql.where_clause;
+        if !matches!(where_clause.ggp.value(), GroupGraphPattern::Invalid) {
+            let selection = where_clause
+                .kwd
+                .as_ref()
+                .map(|x| x.span().clone())
+                .unwrap_or_else(|| where_clause.ggp.span().clone());
+            req.extend(nested_symbol(
+                SparqlKeyword::Where.complete(),
+                SymbolKind::STRUCT,
+                where_clause.span(),
+                &selection,
+                &where_clause.ggp,
+                &rope.0,
+            ));
+        }
+
+        if let Some(values) = &sparql.values {
+            req.extend(document_symbol(
+                SparqlKeyword::Values.complete(),
+                SymbolKind::ARRAY,
+                values.span(),
+                values.kwd.span(),
+                &rope.0,
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_core::{components::*, prelude::*};
+    use lsp_types::SymbolKind;
+    use test_utils::{create_file, setup_world, TestClient};
+
+    #[test_log::test]
+    fn sparql_outline() {
+        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world);
+
+        let t1 = "PREFIX foaf: <http://xmlns.com/foaf/0.1/>
+SELECT ?name ?friend WHERE {
+  ?x foaf:name ?name.
+  OPTIONAL { ?x foaf:knows ?friend. }
+}";
+        let entity = create_file(&mut world, t1, "http://example.com/ns#", "sparql", Open);
+
+        world
+            .entity_mut(entity)
+            .insert(DocumentSymbolRequest::default());
+        world.run_schedule(DocumentSymbolLabel);
+
+        let symbols = world
+            .entity_mut(entity)
+            .take::<DocumentSymbolRequest>()
+            .expect("document symbol request")
+            .0;
+
+        let names: Vec<_> = symbols.iter().map(|x| x.name.as_str()).collect();
+        assert_eq!(names, vec!["Prologue", "SELECT", "WHERE"]);
+        assert_eq!(symbols[1].detail.as_deref(), Some("?name ?friend"));
+
+        let groups = symbols[2].children.as_ref().expect("where groups");
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].kind, SymbolKind::OBJECT);
+        assert_eq!(groups[1].name, "OPTIONAL");
+        assert_eq!(groups[1].children.as_ref().map(|x| x.len()), Some(1));
+    }
+
+    #[test_log::test]
+    fn sparql_update_outline() {
+        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world);
+
+        let t1 = "PREFIX ex: <http://example.org/>
+INSERT DATA { ex:a ex:p 1 . } ;
+DELETE { ?x ex:p ?y . } WHERE { ?x ex:q ?y . }";
+        let entity = create_file(&mut world, t1, "http://example.com/update.ru", "", Open);
+        assert!(world.entity(entity).contains::<crate::Sparql>());
+
+        world
+            .entity_mut(entity)
+            .insert(DocumentSymbolRequest::default());
+        world.run_schedule(DocumentSymbolLabel);
+
+        let symbols = world
+            .entity_mut(entity)
+            .take::<DocumentSymbolRequest>()
+            .expect("document symbol request")
+            .0;
+
+        let names: Vec<_> = symbols.iter().map(|x| x.name.as_str()).collect();
+        assert_eq!(names, vec!["Prologue", "INSERT DATA", "DELETE"]);
+        assert_eq!(symbols[2].children.as_ref().map(|x| x.len()), Some(1));
+
+        let triples: &Triples = world.entity(entity).get().expect("triples");
+        assert_eq!(triples.len(), 3);
+    }
+}
@@ -1,8 +1,15 @@
+use std::fmt::Display;
+
 use lang_turtle::lang::{
-    context::Context,
-    model::{Based, NamedNode, Triple, TriplesBuilder, TurtlePrefix, TurtleSimpleError},
+    context::{Context, ContextKind},
+    model::{
+        Based, Graph, Literal, NamedNode, Term, Triple, TriplesBuilder, TurtlePrefix,
+        TurtleSimpleError, Variable as TurtleVariable,
+    },
+};
+use lsp_core::prelude::{
+    MyQuad, MyTerm, Spanned, SparqlAggregate, SparqlCall, SparqlExpr, SparqlKeyword, Token,
 };
-use lsp_core::prelude::{Spanned, SparqlKeyword, Token};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Base {
@@ -69,6 +76,15 @@ pub struct WhereClause {
     pub kwd: Option<Spanned<SparqlKeyword>>,
     pub ggp: Spanned<GroupGraphPattern>,
 }
+impl Default for WhereClause {
+    /// The missing where clause of a `DESCRIBE` query
+    fn default() -> Self {
+        WhereClause {
+            kwd: None,
+            ggp: Spanned(GroupGraphPattern::Invalid, 0..0),
+        }
+    }
+}
 impl WhereClause {
     pub fn ingest_triples<'a>(
         &'a self,
@@ -87,7 +103,123 @@ impl WhereClause {
 pub struct Variable(pub String);
 
 #[derive(Clone, Debug, PartialEq, Eq)]
-pub struct Expression {}
+pub struct Aggregate {
+    pub func: Spanned<SparqlAggregate>,
+    pub distinct: Option<Spanned<SparqlKeyword>>,
+    /// `None` for `COUNT(*)`
+    pub arg: Option<Box<Spanned<Expression>>>,
+    /// `GROUP_CONCAT(?x; SEPARATOR = ", ")`
+    pub separator: Option<Spanned<String>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Expression {
+    /// `||`, `&&`, comparisons and arithmetic, the operator is the [`SparqlExpr`]
+    Binary(
+        Box<Spanned<Expression>>,
+        Spanned<SparqlExpr>,
+        Box<Spanned<Expression>>,
+    ),
+    /// `!`, `+` and `-`
+    Unary(Spanned<SparqlExpr>, Box<Spanned<Expression>>),
+    /// `?x IN (1, 2)` and `?x NOT IN (1, 2)`
+    In {
+        expr: Box<Spanned<Expression>>,
+        not: Option<Spanned<SparqlExpr>>,
+        list: Vec<Spanned<Expression>>,
+    },
+    Bracketed(Box<Spanned<Expression>>),
+    BuiltIn(Spanned<SparqlCall>, Vec<Spanned<Expression>>),
+    /// `REGEX`, `SUBSTR` and `REPLACE` are tokenized as keywords
+    KwdCall(Spanned<SparqlKeyword>, Vec<Spanned<Expression>>),
+    Aggregate(Aggregate),
+    /// `EXISTS { }` and `NOT EXISTS { }`
+    Exists {
+        not: Option<Spanned<SparqlExpr>>,
+        kwd: Spanned<SparqlKeyword>,
+        ggp: Spanned<GroupGraphPattern>,
+    },
+    FunctionCall {
+        iri: Spanned<NamedNode>,
+        distinct: Option<Spanned<SparqlKeyword>>,
+        args: Vec<Spanned<Expression>>,
+    },
+    Iri(NamedNode),
+    Literal(Literal),
+    Variable(Variable),
+}
+
+impl Expression {
+    /// Calls `f` for this expression and every nested expression, depth first.
+    ///
+    /// Expressions inside `EXISTS` patterns are not visited.
+    pub fn visit<'a>(&'a self, f: &mut impl FnMut(&'a Expression)) {
+        f(self);
+        match self {
+            Expression::Binary(a, _, b) => {
+                a.visit(f);
+                b.visit(f);
+            }
+            Expression::Unary(_, a) | Expression::Bracketed(a) => a.visit(f),
+            Expression::In { expr, list, .. } => {
+                expr.visit(f);
+                for x in list {
+                    x.visit(f);
+                }
+            }
+            Expression::BuiltIn(_, args)
+            | Expression::KwdCall(_, args)
+            | Expression::FunctionCall { args, .. } => {
+                for x in args {
+                    x.visit(f);
+                }
+            }
+            Expression::Aggregate(agg) => {
+                if let Some(arg) = &agg.arg {
+                    arg.visit(f);
+                }
+            }
+            Expression::Exists { .. }
+            | Expression::Iri(_)
+            | Expression::Literal(_)
+            | Expression::Variable(_) => {}
+        }
+    }
+
+    /// Variables used in this expression
+    pub fn variables(&self) -> Vec<&Variable> {
+        let mut out = Vec::new();
+        self.visit(&mut |x| {
+            if let Expression::Variable(v) = x {
+                out.push(v);
+            }
+        });
+        out
+    }
+
+    pub fn ingest_triples<'a>(
+        &'a self,
+        builder: &mut TriplesBuilder<'a, Query>,
+    ) -> Result<(), TurtleSimpleError> {
+        let mut out = Ok(());
+        self.visit(&mut |x| {
+            if let Expression::Exists { ggp, .. } = x {
+                if out.is_ok() {
+                    out = ggp.ingest_triples(builder);
+                }
+            }
+        });
+        out
+    }
+
+    fn add_to_context(&self, ctx: &mut Context) {
+        self.visit(&mut |x| {
+            if let Expression::Exists { ggp, .. } = x {
+                ggp.add_to_context(ctx);
+            }
+        });
+    }
+}
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Bind {
@@ -103,6 +235,20 @@ pub enum Solution {
     VarAs(Bind),
 }
 
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GroupCondition {
+    Expr(Expression),
+    /// `(?x + 1 AS ?y)`
+    Bind(Bind),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OrderCondition {
+    /// `ASC` or `DESC`
+    pub direction: Option<Spanned<SparqlKeyword>>,
+    pub expr: Spanned<Expression>,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct SelectClause {
     pub kwd: Spanned<SparqlKeyword>,
@@ -112,27 +258,77 @@ pub struct SelectClause {
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ConstructClause {
-    kwd: Spanned<SparqlKeyword>,
-    template: Option<(
+    pub kwd: Spanned<SparqlKeyword>,
+    pub template: Option<(
         Spanned<Token>,
         Vec<Spanned<GroupGraphPatternSub>>,
         Spanned<Token>,
     )>,
 }
 
+impl ConstructClause {
+    fn add_to_context(&self, ctx: &mut Context) {
+        for sub in self.template.iter().flat_map(|(_, subs, _)| subs) {
+            sub.add_to_context(ctx);
+        }
+    }
+
+    pub fn ingest_triples<'a>(
+        &'a self,
+        builder: &mut TriplesBuilder<'a, Query>,
+    ) -> Result<(), TurtleSimpleError> {
+        for sub in self.template.iter().flat_map(|(_, subs, _)| subs) {
+            sub.ingest_triples(builder)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DescribeTarget {
+    All,
+    Var(Variable),
+    Iri(NamedNode),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DescribeClause {
+    pub kwd: Spanned<SparqlKeyword>,
+    pub targets: Vec<Spanned<DescribeTarget>>,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum QueryClause {
     Select(SelectClause),
     Construct(ConstructClause),
+    Ask(Spanned<SparqlKeyword>),
+    Describe(DescribeClause),
     Invalid,
 }
 
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DataValue {
+    Iri(NamedNode),
+    Literal(Literal),
+    Undef,
+}
+
+/// `VALUES ?x { 1 2 }` or `VALUES (?x ?y) { (1 2) (UNDEF 3) }`
+///
+/// Rows of the single variable form contain one value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InlineData {
+    pub kwd: Spanned<SparqlKeyword>,
+    pub vars: Vec<Spanned<Variable>>,
+    pub rows: Vec<Spanned<Vec<Spanned<DataValue>>>>,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct SubSelect {
     pub select: SelectClause,
     pub where_clause: WhereClause,
     pub modifier: Vec<Spanned<Modifier>>,
-    // TODO values
+    pub values: Option<Spanned<InlineData>>,
 }
 impl SubSelect {
     fn add_to_context(&self, ctx: &mut Context) {
@@ -150,11 +346,261 @@ impl SubSelect {
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum GraphPatternNotTriples {}
 
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PathModifier {
+    /// `?`
+    ZeroOrOne,
+    /// `*`
+    ZeroOrMore,
+    /// `+`
+    OneOrMore,
+}
+
+impl Display for PathModifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PathModifier::ZeroOrOne => write!(f, "?"),
+            PathModifier::ZeroOrMore => write!(f, "*"),
+            PathModifier::OneOrMore => write!(f, "+"),
+        }
+    }
+}
+
+/// Predicate of a triple pattern, a property path like `foaf:knows+/foaf:name`.
+///
+/// Simple predicates are paths too, an [`Path::Iri`] or a [`Path::Variable`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Path {
+    /// An iri or `a`
+    Iri(NamedNode),
+    /// A variable, only allowed as the whole predicate
+    Variable(TurtleVariable),
+    /// `^ex:p`
+    Inverse(Box<Spanned<Path>>),
+    /// `ex:p/ex:q`
+    Sequence(Vec<Spanned<Path>>),
+    /// `ex:p|ex:q`
+    Alternative(Vec<Spanned<Path>>),
+    /// `ex:p*`, `ex:p+` or `ex:p?`
+    Modified(Box<Spanned<Path>>, Spanned<PathModifier>),
+    /// `!ex:p` or `!(ex:p|^ex:q)`, the set contains iris and inverse iris
+    Negated(Vec<Spanned<Path>>),
+}
+
+impl Path {
+    /// Whether this is a property path and not a simple predicate.
+    pub fn is_path(&self) -> bool {
+        !matches!(self, Path::Iri(_) | Path::Variable(_))
+    }
+
+    /// Visit the iris of the path, in order.
+    fn visit_segments<'a>(&'a self, f: &mut impl FnMut(&'a NamedNode)) {
+        match self {
+            Path::Iri(node) => f(node),
+            Path::Variable(_) => {}
+            Path::Inverse(path) | Path::Modified(path, _) => path.visit_segments(f),
+            Path::Sequence(paths) | Path::Alternative(paths) | Path::Negated(paths) => {
+                for path in paths {
+                    path.visit_segments(f);
+                }
+            }
+        }
+    }
+
+    fn add_to_context(&self, ctx: &mut Context) {
+        match self {
+            Path::Variable(TurtleVariable(_, idx)) => ctx.add(*idx, ContextKind::Predicate),
+            _ => self.visit_segments(&mut |node| match node {
+                NamedNode::Full(_, idx) | NamedNode::Prefixed { idx, .. } | NamedNode::A(idx) => {
+                    ctx.add(*idx, ContextKind::Predicate)
+                }
+                _ => {}
+            }),
+        }
+    }
+
+    fn fmt_nested(path: &Spanned<Path>, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match path.value() {
+            Path::Sequence(_) | Path::Alternative(_) => write!(f, "({})", path.value()),
+            x => write!(f, "{}", x),
+        }
+    }
+
+    /// Ingest the triples of `subject path object`.
+    ///
+    /// Every iri of the path becomes a triple, so that each segment can be completed and
+    /// hovered. Sequences are linked with blank nodes, inverse paths swap subject and object.
+    /// Negated property sets state that the triples do not exist, their triples end up in
+    /// [`TriplesBuilder::unasserted`].
+    pub fn ingest<'a>(
+        path: &'a Spanned<Path>,
+        subject: MyTerm<'a>,
+        object: MyTerm<'a>,
+        span: std::ops::Range<usize>,
+        builder: &mut TriplesBuilder<'a, Query>,
+    ) {
+        let predicate = match path.value() {
+            Path::Iri(node) => builder.named_node(node, path.span().clone()),
+            Path::Variable(var) => MyTerm::variable(&var.0, path.span().clone()),
+            Path::Inverse(inner) => return Path::ingest(inner, object, subject, span, builder),
+            Path::Modified(inner, _) => return Path::ingest(inner, subject, object, span, builder),
+            Path::Negated(paths) => {
+                let start = builder.triples.len();
+                for inner in paths {
+                    Path::ingest(
+                        inner,
+                        subject.clone(),
+                        object.clone(),
+                        span.clone(),
+                        builder,
+                    );
+                }
+                let negated = builder.triples.split_off(start);
+                builder.unasserted.extend(negated);
+                return;
+            }
+            Path::Alternative(paths) => {
+                for inner in paths {
+                    Path::ingest(
+                        inner,
+                        subject.clone(),
+                        object.clone(),
+                        span.clone(),
+                        builder,
+                    );
+                }
+                return;
+            }
+            Path::Sequence(paths) => {
+                let mut subject = subject;
+                for (i, inner) in paths.iter().enumerate() {
+                    // Intermediate nodes have an empty span, they never contain the cursor
+                    let next = if i + 1 == paths.len() {
+                        object.clone()
+                    } else {
+                        let end = inner.span().end;
+                        builder.new_blank_node(end..end)
+                    };
+                    Path::ingest(inner, subject, next.clone(), span.clone(), builder);
+                    subject = next;
+                }
+                return;
+            }
+        };
+
+        builder.triples.push(MyQuad {
+            subject,
+            predicate,
+            object,
+            graph: builder.graph.clone(),
+            span,
+        });
+    }
+}
+
+impl Display for Path {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Path::Iri(node) => write!(f, "{}", node),
+            Path::Variable(var) => write!(f, "{}", var.0),
+            Path::Inverse(path) => {
+                write!(f, "^")?;
+                Path::fmt_nested(path, f)
+            }
+            Path::Modified(path, modifier) => {
+                Path::fmt_nested(path, f)?;
+                write!(f, "{}", modifier.value())
+            }
+            Path::Sequence(paths) | Path::Alternative(paths) => {
+                let sep = if matches!(self, Path::Sequence(_)) {
+                    "/"
+                } else {
+                    "|"
+                };
+                for (i, path) in paths.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, "{}", sep)?;
+                    }
+                    if matches!(self, Path::Sequence(_)) {
+                        Path::fmt_nested(path, f)?;
+                    } else {
+                        write!(f, "{}", path.value())?;
+                    }
+                }
+                Ok(())
+            }
+            Path::Negated(paths) => {
+                let set: Vec<_> = paths.iter().map(|x| x.to_string()).collect();
+                if set.len() == 1 {
+                    write!(f, "!{}", set[0])
+                } else {
+                    write!(f, "!({})", set.join("|"))
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PathPO {
+    pub predicate: Spanned<Path>,
+    pub object: Vec<Spanned<Term>>,
+}
+
+/// Triple pattern with at least one property path, `?x foaf:knows+ ?y`.
+///
+/// Triple patterns without paths are parsed as Turtle [`Triple`]s.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TriplePath {
+    pub subject: Spanned<Term>,
+    pub po: Vec<Spanned<PathPO>>,
+}
+
+impl TriplePath {
+    fn add_to_context(&self, ctx: &mut Context) {
+        self.subject.set_context(ctx, ContextKind::Subject);
+        for po in &self.po {
+            po.predicate.add_to_context(ctx);
+            for o in &po.object {
+                o.set_context(ctx, ContextKind::Object);
+            }
+        }
+    }
+
+    pub fn ingest_triples<'a>(
+        &'a self,
+        span: &std::ops::Range<usize>,
+        builder: &mut TriplesBuilder<'a, Query>,
+    ) -> Result<(), TurtleSimpleError> {
+        let subject = builder.term(&self.subject)?;
+        for (i, po) in self.po.iter().enumerate() {
+            // Like Turtle, the first triple spans the whole pattern
+            let span = if i == 0 {
+                span.clone()
+            } else {
+                po.span().clone()
+            };
+            for object in &po.object {
+                let object = builder.term(object)?;
+                Path::ingest(
+                    &po.predicate,
+                    subject.clone(),
+                    object,
+                    span.clone(),
+                    builder,
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum GroupGraphPatternSub {
     Triple(Spanned<Triple>),
+    TriplePath(Spanned<TriplePath>),
     Kwd(Spanned<SparqlKeyword>, Spanned<GroupGraphPattern>),
-    Filter(Spanned<SparqlKeyword>, ()),
+    Filter(Spanned<SparqlKeyword>, Spanned<Expression>),
     Union(
         Spanned<GroupGraphPattern>,
         Vec<(Spanned<SparqlKeyword>, Spanned<GroupGraphPattern>)>,
@@ -162,7 +608,7 @@ pub enum GroupGraphPatternSub {
     GraphOrService(
         Spanned<SparqlKeyword>,
         Option<Spanned<SparqlKeyword>>,
-        Spanned<NamedNode>,
+        Spanned<Term>,
         Spanned<GroupGraphPattern>,
     ),
     Bind(
@@ -171,12 +617,13 @@ pub enum GroupGraphPatternSub {
         Spanned<Bind>,
         Spanned<Token>,
     ),
-    Inline(Spanned<()>),
+    Inline(InlineData),
 }
 impl GroupGraphPatternSub {
     fn add_to_context(&self, ctx: &mut Context) {
         match self {
             GroupGraphPatternSub::Triple(t) => t.value().set_context(ctx),
+            GroupGraphPatternSub::TriplePath(t) => t.add_to_context(ctx),
             GroupGraphPatternSub::Kwd(_, beta) => beta.add_to_context(ctx),
             GroupGraphPatternSub::Union(a, b) => {
                 a.add_to_context(ctx);
@@ -185,6 +632,8 @@ impl GroupGraphPatternSub {
                 }
             }
             GroupGraphPatternSub::GraphOrService(_, _, _, a) => a.add_to_context(ctx),
+            GroupGraphPatternSub::Filter(_, expr) => expr.add_to_context(ctx),
+            GroupGraphPatternSub::Bind(_, _, bind, _) => bind.expr.add_to_context(ctx),
             _ => {}
         }
     }
@@ -195,6 +644,9 @@ impl GroupGraphPatternSub {
     ) -> Result<(), TurtleSimpleError> {
         match self {
             GroupGraphPatternSub::Triple(triple) => builder.ingest(&triple)?,
+            GroupGraphPatternSub::TriplePath(triple) => {
+                triple.ingest_triples(triple.span(), builder)?
+            }
             GroupGraphPatternSub::Kwd(_, x) => x.ingest_triples(builder)?,
             GroupGraphPatternSub::Union(x, xs) => {
                 x.ingest_triples(builder)?;
@@ -203,6 +655,8 @@ impl GroupGraphPatternSub {
                 }
             }
             GroupGraphPatternSub::GraphOrService(_, _, _, x) => x.ingest_triples(builder)?,
+            GroupGraphPatternSub::Filter(_, expr) => expr.ingest_triples(builder)?,
+            GroupGraphPatternSub::Bind(_, _, bind, _) => bind.expr.ingest_triples(builder)?,
             _ => {}
         }
         Ok(())
@@ -211,12 +665,152 @@ impl GroupGraphPatternSub {
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Modifier {
-    GroupBy(Spanned<SparqlKeyword>, Spanned<SparqlKeyword>, Spanned<()>),
-    Having(Spanned<SparqlKeyword>, Spanned<()>),
-    OrderBy(Spanned<SparqlKeyword>, Spanned<SparqlKeyword>, Spanned<()>),
+    GroupBy(
+        Spanned<SparqlKeyword>,
+        Spanned<SparqlKeyword>,
+        Vec<Spanned<GroupCondition>>,
+    ),
+    Having(Spanned<SparqlKeyword>, Vec<Spanned<Expression>>),
+    OrderBy(
+        Spanned<SparqlKeyword>,
+        Spanned<SparqlKeyword>,
+        Vec<Spanned<OrderCondition>>,
+    ),
     LimitOffset(Spanned<SparqlKeyword>, Spanned<String>),
 }
 
+/// The graph of a graph management operation, `DEFAULT`, `NAMED`, `ALL` or `GRAPH <iri>`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GraphTarget {
+    Keyword(Spanned<SparqlKeyword>),
+    /// `GRAPH` can be omitted after `ADD`, `MOVE` and `COPY`
+    Graph(Option<Spanned<SparqlKeyword>>, Spanned<NamedNode>),
+}
+
+/// Quad data or quad pattern, the triples outside of a `GRAPH` block are in the default graph.
+pub type Quads = Vec<Spanned<Graph>>;
+
+fn ingest_quads<'a>(
+    quads: &'a Quads,
+    builder: &mut TriplesBuilder<'a, Query>,
+) -> Result<(), TurtleSimpleError> {
+    for graph in quads {
+        builder.ingest_graph(graph)?;
+    }
+    Ok(())
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Modify {
+    pub with: Option<(Spanned<SparqlKeyword>, Spanned<NamedNode>)>,
+    pub delete: Option<(Spanned<SparqlKeyword>, Spanned<Quads>)>,
+    pub insert: Option<(Spanned<SparqlKeyword>, Spanned<Quads>)>,
+    /// `USING` clauses, the `from` keyword is `USING`
+    pub using: Vec<Spanned<DatasetClause>>,
+    pub where_clause: Spanned<WhereClause>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum UpdateOperation {
+    Load {
+        kwd: Spanned<SparqlKeyword>,
+        silent: Option<Spanned<SparqlKeyword>>,
+        iri: Spanned<NamedNode>,
+        into: Option<(Spanned<SparqlKeyword>, Spanned<GraphTarget>)>,
+    },
+    /// `CLEAR`, `DROP` and `CREATE`
+    Manage {
+        kwd: Spanned<SparqlKeyword>,
+        silent: Option<Spanned<SparqlKeyword>>,
+        target: Spanned<GraphTarget>,
+    },
+    /// `ADD`, `MOVE` and `COPY`
+    Transfer {
+        kwd: Spanned<SparqlKeyword>,
+        silent: Option<Spanned<SparqlKeyword>>,
+        from: Spanned<GraphTarget>,
+        to_kwd: Spanned<SparqlKeyword>,
+        to: Spanned<GraphTarget>,
+    },
+    /// `INSERT DATA` and `DELETE DATA`
+    Data {
+        kwd: Spanned<SparqlKeyword>,
+        data_kwd: Spanned<SparqlKeyword>,
+        quads: Spanned<Quads>,
+    },
+    DeleteWhere {
+        kwd: Spanned<SparqlKeyword>,
+        where_kwd: Spanned<SparqlKeyword>,
+        quads: Spanned<Quads>,
+    },
+    /// `DELETE { } INSERT { } WHERE { }`
+    Modify(Modify),
+}
+
+impl UpdateOperation {
+    /// The keyword that starts the operation
+    pub fn kwd(&self) -> Option<&Spanned<SparqlKeyword>> {
+        match self {
+            UpdateOperation::Load { kwd, .. }
+            | UpdateOperation::Manage { kwd, .. }
+            | UpdateOperation::Transfer { kwd, .. }
+            | UpdateOperation::Data { kwd, .. }
+            | UpdateOperation::DeleteWhere { kwd, .. } => Some(kwd),
+            UpdateOperation::Modify(modify) => modify
+                .with
+                .as_ref()
+                .map(|(kwd, _)| kwd)
+                .or(modify.delete.as_ref().map(|(kwd, _)| kwd))
+                .or(modify.insert.as_ref().map(|(kwd, _)| kwd))
+                .or(modify.where_clause.kwd.as_ref()),
+        }
+    }
+
+    fn add_to_context(&self, ctx: &mut Context) {
+        let quads = match self {
+            UpdateOperation::Data { quads, .. } | UpdateOperation::DeleteWhere { quads, .. } => {
+                vec![quads]
+            }
+            UpdateOperation::Modify(modify) => {
+                modify.where_clause.add_to_context(ctx);
+                modify
+                    .delete
+                    .iter()
+                    .chain(modify.insert.iter())
+                    .map(|(_, quads)| quads)
+                    .collect()
+            }
+            _ => vec![],
+        };
+        for graph in quads.into_iter().flat_map(|x| x.iter()) {
+            graph.set_context(ctx);
+        }
+    }
+
+    pub fn ingest_triples<'a>(
+        &'a self,
+        builder: &mut TriplesBuilder<'a, Query>,
+    ) -> Result<(), TurtleSimpleError> {
+        match self {
+            UpdateOperation::Data { quads, .. } | UpdateOperation::DeleteWhere { quads, .. } => {
+                ingest_quads(quads, builder)?
+            }
+            UpdateOperation::Modify(modify) => {
+                for (_, quads) in modify.delete.iter().chain(modify.insert.iter()) {
+                    ingest_quads(quads, builder)?;
+                }
+                modify.where_clause.ingest_triples(builder)?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// A parsed SPARQL document, a query or an update request.
+///
+/// Update requests have no query clause, `kwds` is [`QueryClause::Invalid`] and their
+/// operations are in `operations`. The prefixes of every operation are in `prefixes`.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Query {
     pub base: lsp_types::Url,
@@ -226,20 +820,38 @@ pub struct Query {
     pub datasets: Vec<Spanned<DatasetClause>>,
     pub where_clause: Spanned<WhereClause>,
     pub modifier: Vec<Spanned<Modifier>>,
+    pub values: Option<Spanned<InlineData>>,
+    pub operations: Vec<Spanned<UpdateOperation>>,
 }
 impl Query {
     pub fn add_to_context(&self, ctx: &mut Context) {
+        if let QueryClause::Construct(construct) = &self.kwds {
+            construct.add_to_context(ctx);
+        }
         self.where_clause.add_to_context(ctx);
+        for op in &self.operations {
+            op.add_to_context(ctx);
+        }
     }
 
     pub fn ingest_triples<'a>(
         &'a self,
         builder: &mut TriplesBuilder<'a, Query>,
     ) -> Result<(), TurtleSimpleError> {
+        if let QueryClause::Construct(construct) = &self.kwds {
+            construct.ingest_triples(builder)?;
+        }
         self.where_clause.ingest_triples(builder)?;
+        for op in &self.operations {
+            op.ingest_triples(builder)?;
+        }
         Ok(())
     }
 
+    pub fn is_update(&self) -> bool {
+        !self.operations.is_empty()
+    }
+
     pub fn set_base(&mut self, base: lsp_types::Url) {
         self.base = self
             .base_statement
@@ -275,14 +887,10 @@ impl Default for Query {
             prefixes: vec![],
             kwds: QueryClause::Invalid,
             datasets: vec![],
-            where_clause: Spanned(
-                WhereClause {
-                    kwd: None,
-                    ggp: Spanned(GroupGraphPattern::Invalid, 0..0),
-                },
-                0..0,
-            ),
+            where_clause: Spanned(WhereClause::default(), 0..0),
             modifier: vec![],
+            values: None,
+            operations: vec![],
         }
     }
 }
@@ -345,6 +953,43 @@ SELECT  ?title ?price
         assert_eq!(builder.triples.len(), 3);
     }
 
+    #[test]
+    fn path_triples() {
+        let st = r#"
+PREFIX ex: <http://example.org/>
+SELECT * WHERE {
+  ?x ex:p+/ex:q ?y .
+  ?y ^ex:r|!(ex:s) ?z .
+}
+        "#;
+
+        let query = parse_sparql(st);
+        let base = BaseIri::new(query.base.to_string()).unwrap();
+        let mut builder = TriplesBuilder::new(&query, base);
+        query.ingest_triples(&mut builder).expect("builds fine");
+
+        assert_eq!(builder.triples.len(), 3);
+
+        // The sequence is linked with a blank node
+        let p = &builder.triples[0];
+        let q = &builder.triples[1];
+        assert_eq!(p.predicate.value, "http://example.org/p");
+        assert_eq!(q.predicate.value, "http://example.org/q");
+        assert_eq!(p.object, q.subject);
+
+        // The inverse path swaps subject and object
+        let r = &builder.triples[2];
+        assert_eq!(r.predicate.value, "http://example.org/r");
+        assert_eq!(r.object, q.object);
+
+        // The negated set is not asserted
+        assert_eq!(builder.unasserted.len(), 1);
+        assert_eq!(
+            builder.unasserted[0].predicate.value,
+            "http://example.org/s"
+        );
+    }
+
     #[test]
     fn simple_sparql_2() {
         let st = r#"
@@ -371,4 +1016,47 @@ SELECT  ?title ?price
 
         assert_eq!(builder.triples.len(), 4);
     }
+
+    #[test]
+    fn update_triples() {
+        let st = r#"
+PREFIX ex: <http://example.org/>
+INSERT DATA { ex:a ex:p 1 . GRAPH ex:g { ex:a ex:p 2 . } } ;
+DELETE { ?x ex:p ?y . }
+WHERE { ?x ex:q ?y . }
+        "#;
+
+        let query = parse_sparql(st);
+        let base = BaseIri::new(query.base.to_string()).unwrap();
+        let mut builder = TriplesBuilder::new(&query, base);
+        query.ingest_triples(&mut builder).expect("builds fine");
+
+        assert_eq!(builder.triples.len(), 4);
+        let graphs: Vec<_> = builder
+            .triples
+            .iter()
+            .map(|t| t.graph.as_ref().map(|x| x.as_str()))
+            .collect();
+        assert_eq!(graphs, vec![None, Some("http://example.org/g"), None, None]);
+    }
+
+    #[test]
+    fn construct_and_graph_triples() {
+        let st = r#"
+PREFIX ex: <http://example.org/>
+CONSTRUCT { ?x ex:q ?y . }
+WHERE {
+  GRAPH ?g { ?x ex:p ?y . }
+  OPTIONAL { ?y ex:r ?z }
+  VALUES ?x { ex:a }
+}
+        "#;
+
+        let query = parse_sparql(st);
+        let base = BaseIri::new(query.base.to_string()).unwrap();
+        let mut builder = TriplesBuilder::new(&query, base);
+        query.ingest_triples(&mut builder).expect("builds fine");
+
+        assert_eq!(builder.triples.len(), 3);
+    }
 }
@@ -1,20 +1,50 @@
 use chumsky::{prelude::*, Error};
 use lang_turtle::lang::{
     context::Ctx,
-    model::TurtlePrefix,
-    parser::{named_node, not, triple},
+    model::{Graph, Literal, NamedNode, Term, Triple, TurtlePrefix, Variable as TurtleVariable},
+    parser::{self as turtle, literal, named_node, node, not, triple_ending},
 };
 use lsp_core::prelude::{spanned, PToken, Spanned, SparqlExpr, SparqlKeyword, Token};
 
 use crate::lang::model::{
-    Base, Bind, DatasetClause, Expression, GroupGraphPattern, GroupGraphPatternSub, Modifier,
-    Prologue, Query, QueryClause, SelectClause, Solution, SubSelect, Variable, WhereClause,
+    Aggregate, Base, Bind, ConstructClause, DataValue, DatasetClause, DescribeClause,
+    DescribeTarget, Expression, GraphTarget, GroupCondition, GroupGraphPattern,
+    GroupGraphPatternSub, InlineData, Modifier, Modify, OrderCondition, Path, PathModifier, PathPO,
+    Prologue, Quads, Query, QueryClause, SelectClause, Solution, SubSelect, TriplePath,
+    UpdateOperation, Variable, WhereClause,
 };
 
 fn j(token: Token) -> impl Parser<PToken, Token, Error = Simple<PToken>> + Clone {
     just(PToken(token, 0)).map(|x| x.0)
 }
 
+/// End of a triple, the final `.` is also optional when the next graph pattern follows.
+fn triple_end() -> impl Parser<PToken, Token, Error = Simple<PToken>> + Clone {
+    filter(|PToken(t, _)| {
+        matches!(
+            t,
+            Token::SparqlKeyword(
+                SparqlKeyword::Optional
+                    | SparqlKeyword::Minus
+                    | SparqlKeyword::Filter
+                    | SparqlKeyword::Bind
+                    | SparqlKeyword::Graph
+                    | SparqlKeyword::Service
+                    | SparqlKeyword::Values
+            )
+        )
+    })
+    .rewind()
+    .to(Token::Stop)
+    .or(turtle::triple_end())
+}
+
+fn triple<'a>(
+    ctx: Ctx<'a>,
+) -> impl Parser<PToken, Triple, Error = Simple<PToken>> + Clone + use<'a> {
+    triple_ending(ctx, triple_end())
+}
+
 fn sparql_kwd(
     kwd: SparqlKeyword,
 ) -> impl Parser<PToken, Spanned<SparqlKeyword>, Error = Simple<PToken>> + Clone {
@@ -53,16 +83,289 @@ fn dataset_clause() -> impl Parser<PToken, DatasetClause, Error = Simple<PToken>
         .map(|((from, named), iri)| DatasetClause { from, named, iri })
 }
 
-fn expression() -> impl Parser<PToken, Expression, Error = Simple<PToken>> + Clone {
-    todo()
+fn sparql_expr(
+    expr: SparqlExpr,
+) -> impl Parser<PToken, Spanned<SparqlExpr>, Error = Simple<PToken>> + Clone {
+    just(PToken(Token::SparqlExpr(expr.clone()), 0))
+        .to(expr)
+        .map_with_span(spanned)
 }
 
-// DONE
-fn bind() -> impl Parser<PToken, Bind, Error = Simple<PToken>> + Clone {
-    sparql_kwd(SparqlKeyword::Bind)
-        .ignore_then(expression())
+/// One of the operators in `ops`
+fn sparql_op<const C: usize>(
+    ops: [SparqlExpr; C],
+) -> impl Parser<PToken, Spanned<SparqlExpr>, Error = Simple<PToken>> + Clone {
+    filter_map(
+        move |span: std::ops::Range<usize>, PToken(token, idx)| match token {
+            Token::SparqlExpr(x) if ops.contains(&x) => Ok(x),
+            token => Err(Simple::expected_input_found(
+                span,
+                ops.iter()
+                    .map(|x| Some(PToken(Token::SparqlExpr(x.clone()), 0))),
+                Some(PToken(token, idx)),
+            )),
+        },
+    )
+    .map_with_span(spanned)
+}
+
+fn binary(
+    lhs: Spanned<Expression>,
+    (op, rhs): (Spanned<SparqlExpr>, Spanned<Expression>),
+) -> Spanned<Expression> {
+    let span = lhs.span().start..rhs.span().end;
+    Spanned(Expression::Binary(Box::new(lhs), op, Box::new(rhs)), span)
+}
+
+/// The tail of a relational expression, `= ?y` or `NOT IN (1, 2)`
+enum Relation {
+    Compare(Spanned<SparqlExpr>, Spanned<Expression>),
+    In(Option<Spanned<SparqlExpr>>, Vec<Spanned<Expression>>),
+}
+
+fn iri() -> impl Parser<PToken, NamedNode, Error = Simple<PToken>> + Clone {
+    select! {
+        PToken(Token::IRIRef(x), idx) => NamedNode::Full(x, idx),
+        PToken(Token::PNameLN(x, b), idx) => NamedNode::Prefixed { prefix: x.unwrap_or_default(), value: b, idx },
+    }
+}
+
+/// `(?x, 2)`, `()` is allowed
+fn arg_list<E: Parser<PToken, Spanned<Expression>, Error = Simple<PToken>> + Clone>(
+    expr: E,
+) -> impl Parser<PToken, Vec<Spanned<Expression>>, Error = Simple<PToken>> + Clone {
+    expr.separated_by(j(Token::Comma))
+        .delimited_by(j(Token::BracketOpen), j(Token::BracketClose))
+}
+
+/// `(DISTINCT ?x, 2)`, the arguments of a function call
+fn function_args<E: Parser<PToken, Spanned<Expression>, Error = Simple<PToken>> + Clone>(
+    expr: E,
+) -> impl Parser<
+    PToken,
+    (Option<Spanned<SparqlKeyword>>, Vec<Spanned<Expression>>),
+    Error = Simple<PToken>,
+> + Clone {
+    j(Token::BracketOpen)
+        .ignore_then(sparql_kwd(SparqlKeyword::Distinct).or_not())
+        .then(expr.separated_by(j(Token::Comma)))
+        .then_ignore(j(Token::BracketClose))
+}
+
+fn bracketed<E: Parser<PToken, Spanned<Expression>, Error = Simple<PToken>> + Clone>(
+    expr: E,
+) -> impl Parser<PToken, Expression, Error = Simple<PToken>> + Clone {
+    expr.delimited_by(j(Token::BracketOpen), j(Token::BracketClose))
+        .map(|x| Expression::Bracketed(Box::new(x)))
+}
+
+fn function_call<E: Parser<PToken, Spanned<Expression>, Error = Simple<PToken>> + Clone>(
+    expr: E,
+) -> impl Parser<PToken, Expression, Error = Simple<PToken>> + Clone {
+    iri()
+        .map_with_span(spanned)
+        .then(function_args(expr))
+        .map(|(iri, (distinct, args))| Expression::FunctionCall {
+            iri,
+            distinct,
+            args,
+        })
+}
+
+/// Builtin calls, aggregates and `EXISTS`
+fn builtin_call<
+    E: Parser<PToken, Spanned<Expression>, Error = Simple<PToken>> + Clone,
+    T: Parser<PToken, GroupGraphPattern, Error = Simple<PToken>> + Clone,
+>(
+    expr: E,
+    ggp: T,
+) -> impl Parser<PToken, Expression, Error = Simple<PToken>> + Clone {
+    let call = select! { PToken(Token::SparqlCall(x), _) => x }
+        .map_with_span(spanned)
+        .then(arg_list(expr.clone()))
+        .map(|(call, args)| Expression::BuiltIn(call, args));
+
+    let kwd_call = sparql_kwd(SparqlKeyword::Regex)
+        .or(sparql_kwd(SparqlKeyword::Substr))
+        .or(sparql_kwd(SparqlKeyword::Replace))
+        .then(arg_list(expr.clone()))
+        .map(|(kwd, args)| Expression::KwdCall(kwd, args));
+
+    let separator = j(Token::PredicateSplit)
+        .ignore_then(sparql_kwd(SparqlKeyword::Separator))
+        .ignore_then(j(Token::SparqlExpr(SparqlExpr::Equal)))
+        .ignore_then(select! { PToken(Token::Str(x, _), _) => x }.map_with_span(spanned));
+
+    let arg = j(Token::SparqlExpr(SparqlExpr::Times))
+        .to(None)
+        .or(expr.map(Box::new).map(Some));
+
+    let aggregate = select! { PToken(Token::SparqlAggregate(x), _) => x }
         .map_with_span(spanned)
-        .then(sparql_kwd(SparqlKeyword::As))
+        .then_ignore(j(Token::BracketOpen))
+        .then(sparql_kwd(SparqlKeyword::Distinct).or_not())
+        .then(arg)
+        .then(separator.or_not())
+        .then_ignore(j(Token::BracketClose))
+        .map(|(((func, distinct), arg), separator)| {
+            Expression::Aggregate(Aggregate {
+                func,
+                distinct,
+                arg,
+                separator,
+            })
+        });
+
+    let exists = sparql_expr(SparqlExpr::Not)
+        .or_not()
+        .then(sparql_kwd(SparqlKeyword::Exists))
+        .then(ggp.map_with_span(spanned))
+        .map(|((not, kwd), ggp)| Expression::Exists { not, kwd, ggp });
+
+    call.or(kwd_call).or(aggregate).or(exists)
+}
+
+/// The expressions allowed after `FILTER` and `HAVING`, a bracketed expression, builtin call or
+/// function call.
+fn constraint<
+    E: Parser<PToken, Spanned<Expression>, Error = Simple<PToken>> + Clone,
+    T: Parser<PToken, GroupGraphPattern, Error = Simple<PToken>> + Clone,
+>(
+    expr: E,
+    ggp: T,
+) -> impl Parser<PToken, Expression, Error = Simple<PToken>> + Clone {
+    bracketed(expr.clone())
+        .or(builtin_call(expr.clone(), ggp))
+        .or(function_call(expr))
+}
+
+fn expression<'a, T: Parser<PToken, GroupGraphPattern, Error = Simple<PToken>> + Clone + 'a>(
+    ggp: T,
+) -> impl Parser<PToken, Expression, Error = Simple<PToken>> + Clone + use<'a, T> {
+    recursive(|expr| {
+        let iri_or_function = iri()
+            .map_with_span(spanned)
+            .then(function_args(expr.clone()).or_not())
+            .map(|(iri, args)| match args {
+                Some((distinct, args)) => Expression::FunctionCall {
+                    iri,
+                    distinct,
+                    args,
+                },
+                None => Expression::Iri(iri.into_value()),
+            });
+
+        let primary = bracketed(expr.clone())
+            .or(builtin_call(expr.clone(), ggp))
+            .or(iri_or_function)
+            .or(literal().map(Expression::Literal))
+            .or(variable().map(Expression::Variable))
+            .map_with_span(spanned);
+
+        let unary = sparql_op([SparqlExpr::Exclamation, SparqlExpr::Plus, SparqlExpr::Minus])
+            .repeated()
+            .then(primary)
+            .foldr(|op, expr| {
+                let span = op.span().start..expr.span().end;
+                Spanned(Expression::Unary(op, Box::new(expr)), span)
+            })
+            .boxed();
+
+        let multiplicative = unary
+            .clone()
+            .then(
+                sparql_op([SparqlExpr::Times, SparqlExpr::Divide])
+                    .then(unary.clone())
+                    .repeated(),
+            )
+            .foldl(binary);
+
+        // `?x -1` is tokenized as a variable and a negative number, the sign is the operator
+        let signed = filter_map(
+            |span: std::ops::Range<usize>, PToken(token, _)| match token {
+                Token::Number(x) if x.starts_with(['+', '-']) => {
+                    let op = if x.starts_with('-') {
+                        SparqlExpr::Minus
+                    } else {
+                        SparqlExpr::Plus
+                    };
+                    let number = Literal::Numeric(x[1..].to_string());
+                    Ok((
+                        Spanned(op, span.start..span.start + 1),
+                        Spanned(Expression::Literal(number), span.start + 1..span.end),
+                    ))
+                }
+                _ => Err(Simple::custom(span, "Expected a signed number")),
+            },
+        )
+        .then(
+            sparql_op([SparqlExpr::Times, SparqlExpr::Divide])
+                .then(unary)
+                .repeated(),
+        )
+        .map(|((op, number), rest)| (op, rest.into_iter().fold(number, binary)));
+
+        let additive = multiplicative
+            .clone()
+            .then(
+                sparql_op([SparqlExpr::Plus, SparqlExpr::Minus])
+                    .then(multiplicative)
+                    .or(signed)
+                    .repeated(),
+            )
+            .foldl(binary)
+            .boxed();
+
+        let compare = sparql_op([
+            SparqlExpr::Equal,
+            SparqlExpr::NotEqual,
+            SparqlExpr::Lt,
+            SparqlExpr::Gt,
+            SparqlExpr::Lte,
+            SparqlExpr::Gte,
+        ])
+        .then(additive.clone())
+        .map(|(op, rhs)| Relation::Compare(op, rhs));
+
+        let in_list = sparql_expr(SparqlExpr::Not)
+            .or_not()
+            .then_ignore(sparql_expr(SparqlExpr::In))
+            .then(arg_list(expr))
+            .map(|(not, list)| Relation::In(not, list));
+
+        let relational =
+            additive
+                .then(compare.or(in_list).or_not())
+                .map_with_span(|(lhs, relation), span| match relation {
+                    None => lhs,
+                    Some(Relation::Compare(op, rhs)) => binary(lhs, (op, rhs)),
+                    Some(Relation::In(not, list)) => Spanned(
+                        Expression::In {
+                            expr: Box::new(lhs),
+                            not,
+                            list,
+                        },
+                        span,
+                    ),
+                });
+
+        let and = relational
+            .clone()
+            .then(sparql_expr(SparqlExpr::And).then(relational).repeated())
+            .foldl(binary);
+
+        and.clone()
+            .then(sparql_expr(SparqlExpr::Or).then(and).repeated())
+            .foldl(binary)
+    })
+    .map(Spanned::into_value)
+}
+
+/// `?x + 1 AS ?y`, without the brackets
+fn bind<E: Parser<PToken, Spanned<Expression>, Error = Simple<PToken>> + Clone>(
+    expr: E,
+) -> impl Parser<PToken, Bind, Error = Simple<PToken>> + Clone {
+    expr.then(sparql_kwd(SparqlKeyword::As))
         .then(variable().map_with_span(spanned))
         .map(|((expr, kwd), var)| Bind { var, kwd, expr })
 }
@@ -74,14 +377,16 @@ fn variable() -> impl Parser<PToken, Variable, Error = Simple<PToken>> + Clone {
 }
 
 // DONE
-fn select_clause() -> impl Parser<PToken, SelectClause, Error = Simple<PToken>> + Clone {
+fn select_clause<'a, T: Parser<PToken, GroupGraphPattern, Error = Simple<PToken>> + Clone + 'a>(
+    ggp: T,
+) -> impl Parser<PToken, SelectClause, Error = Simple<PToken>> + Clone + use<'a, T> {
     let star = j(Token::SparqlExpr(SparqlExpr::Times))
         .to(Solution::All)
         .map_with_span(spanned)
         .map(|x| vec![x]);
 
-    let others = bind()
-        .delimited_by(j(Token::CurlOpen), j(Token::CurlClose))
+    let others = bind(expression(ggp).map_with_span(spanned))
+        .delimited_by(j(Token::BracketOpen), j(Token::BracketClose))
         .map(Solution::VarAs)
         .or(variable().map(Solution::Var))
         .map_with_span(spanned)
@@ -103,19 +408,221 @@ fn select_clause() -> impl Parser<PToken, SelectClause, Error = Simple<PToken>>
         })
 }
 
+/// `CONSTRUCT { template }`, the template is missing in the `CONSTRUCT WHERE { }` form
+fn construct_clause<'a>(
+    ctx: Ctx<'a>,
+) -> impl Parser<PToken, ConstructClause, Error = Simple<PToken>> + Clone + use<'a> {
+    let triples = not(Token::CurlClose)
+        .rewind()
+        .ignore_then(triple(ctx))
+        .map_with_span(spanned)
+        .map(GroupGraphPatternSub::Triple)
+        .map_with_span(spanned)
+        .repeated();
+
+    let template = j(Token::CurlOpen)
+        .map_with_span(spanned)
+        .then(triples)
+        .then(j(Token::CurlClose).map_with_span(spanned))
+        .map(|((open, triples), close)| (open, triples, close));
+
+    sparql_kwd(SparqlKeyword::Construct)
+        .then(template.or_not())
+        .map(|(kwd, template)| ConstructClause { kwd, template })
+}
+
+/// `DESCRIBE *` or `DESCRIBE ?x <iri>`
+fn describe_clause() -> impl Parser<PToken, DescribeClause, Error = Simple<PToken>> + Clone {
+    let star = j(Token::SparqlExpr(SparqlExpr::Times))
+        .to(DescribeTarget::All)
+        .map_with_span(spanned)
+        .map(|x| vec![x]);
+
+    let targets = variable()
+        .map(DescribeTarget::Var)
+        .or(iri().map(DescribeTarget::Iri))
+        .map_with_span(spanned)
+        .repeated()
+        .at_least(1);
+
+    sparql_kwd(SparqlKeyword::Describe)
+        .then(star.or(targets))
+        .map(|(kwd, targets)| DescribeClause { kwd, targets })
+}
+
+/// `?x` or an iri
+fn var_or_iri() -> impl Parser<PToken, Spanned<Term>, Error = Simple<PToken>> + Clone {
+    select! {
+        PToken(Token::Variable(x), idx) => Term::Variable(TurtleVariable(x, idx)),
+    }
+    .or(named_node().map(Term::NamedNode))
+    .map_with_span(spanned)
+}
+
+/// `VALUES ?x { 1 2 }` or `VALUES (?x ?y) { (1 2) (UNDEF 3) }`
+fn inline_data() -> impl Parser<PToken, InlineData, Error = Simple<PToken>> + Clone {
+    let value = iri()
+        .map(DataValue::Iri)
+        .or(literal().map(DataValue::Literal))
+        .or(sparql_kwd(SparqlKeyword::Undef).to(DataValue::Undef))
+        .map_with_span(spanned);
+    let var = variable().map_with_span(spanned);
+
+    let single = var.clone().map(|x| vec![x]).then(
+        value
+            .clone()
+            .map_with_span(|x, span| Spanned(vec![x], span))
+            .repeated()
+            .delimited_by(j(Token::CurlOpen), j(Token::CurlClose)),
+    );
+
+    let multi = var
+        .repeated()
+        .delimited_by(j(Token::BracketOpen), j(Token::BracketClose))
+        .then(
+            value
+                .repeated()
+                .delimited_by(j(Token::BracketOpen), j(Token::BracketClose))
+                .map_with_span(spanned)
+                .repeated()
+                .delimited_by(j(Token::CurlOpen), j(Token::CurlClose)),
+        );
+
+    sparql_kwd(SparqlKeyword::Values)
+        .then(single.or(multi))
+        .validate(|(kwd, (vars, rows)), _, emit| {
+            for row in &rows {
+                if row.len() != vars.len() {
+                    emit(Simple::custom(
+                        row.span().clone(),
+                        format!("Expected {} values, found {}", vars.len(), row.len()),
+                    ));
+                }
+            }
+            InlineData { kwd, vars, rows }
+        })
+}
+
+/// Property path, precedence from high to low: modifiers, `^`, `/` and `|`
+fn path() -> impl Parser<PToken, Path, Error = Simple<PToken>> + Clone {
+    recursive(|path| {
+        let iri = named_node().map(Path::Iri).map_with_span(spanned);
+
+        let one_in_set = j(Token::Caret)
+            .ignore_then(iri.clone())
+            .map(|x| Path::Inverse(Box::new(x)))
+            .map_with_span(spanned)
+            .or(iri.clone());
+        let negated_set = one_in_set
+            .clone()
+            .separated_by(j(Token::Pipe))
+            .delimited_by(j(Token::BracketOpen), j(Token::BracketClose))
+            .or(one_in_set.map(|x| vec![x]));
+        let negated = j(Token::SparqlExpr(SparqlExpr::Exclamation))
+            .ignore_then(negated_set)
+            .map(Path::Negated)
+            .map_with_span(spanned);
+
+        let group = path
+            .delimited_by(j(Token::BracketOpen), j(Token::BracketClose))
+            .map_with_span(spanned);
+        let primary = iri.or(negated).or(group);
+
+        let modifier = select! {
+            PToken(Token::QuestionMark, _) => PathModifier::ZeroOrOne,
+            PToken(Token::SparqlExpr(SparqlExpr::Times), _) => PathModifier::ZeroOrMore,
+            PToken(Token::SparqlExpr(SparqlExpr::Plus), _) => PathModifier::OneOrMore,
+        }
+        .map_with_span(spanned);
+        let elt = primary
+            .then(modifier.or_not())
+            .map_with_span(|(path, modifier), span| match modifier {
+                Some(modifier) => Spanned(Path::Modified(Box::new(path), modifier), span),
+                None => path,
+            });
+
+        let elt_or_inverse = j(Token::Caret)
+            .ignore_then(elt.clone())
+            .map(|x| Path::Inverse(Box::new(x)))
+            .map_with_span(spanned)
+            .or(elt);
+
+        let sequence = elt_or_inverse
+            .separated_by(j(Token::SparqlExpr(SparqlExpr::Divide)))
+            .at_least(1)
+            .map_with_span(|mut paths, span| {
+                if paths.len() == 1 {
+                    paths.remove(0)
+                } else {
+                    Spanned(Path::Sequence(paths), span)
+                }
+            });
+
+        sequence
+            .separated_by(j(Token::Pipe))
+            .at_least(1)
+            .map(|mut paths| {
+                if paths.len() == 1 {
+                    paths.remove(0).into_value()
+                } else {
+                    Path::Alternative(paths)
+                }
+            })
+    })
+}
+
+/// Triple pattern with at least one property path, other triples are left to [`triple`]
+fn triple_path<'a>(
+    ctx: Ctx<'a>,
+) -> impl Parser<PToken, TriplePath, Error = Simple<PToken>> + Clone + use<'a> {
+    let verb = select! {
+        PToken(Token::Variable(x), idx) => Path::Variable(TurtleVariable(x, idx)),
+    }
+    .or(path())
+    .map_with_span(spanned);
+
+    let objects = node(ctx)
+        .map_with_span(spanned)
+        .separated_by(j(Token::Comma))
+        .at_least(1);
+
+    let po = verb
+        .then(objects)
+        .map(|(predicate, object)| PathPO { predicate, object })
+        .map_with_span(spanned)
+        .separated_by(j(Token::PredicateSplit).repeated().at_least(1))
+        .allow_trailing()
+        .at_least(1);
+
+    node(ctx)
+        .map_with_span(spanned)
+        .then(po)
+        .then_ignore(triple_end())
+        .try_map(|(subject, po), span| {
+            if po.iter().any(|x| x.predicate.is_path()) {
+                Ok(TriplePath { subject, po })
+            } else {
+                Err(Simple::custom(span, "Expected a property path"))
+            }
+        })
+}
+
 // DONE
 fn sub_select<'a>(
     ctx: Ctx<'a>,
 ) -> impl Parser<PToken, SubSelect, Error = Simple<PToken>> + Clone + use<'a> {
     recursive(|sub_select| {
-        let modi = modifier().map_with_span(spanned).repeated();
-        select_clause()
+        let ggp = group_graph_pattern(sub_select.clone(), ctx);
+        let modi = modifier(ggp.clone()).map_with_span(spanned).repeated();
+        select_clause(ggp)
             .then(where_clause(sub_select, ctx))
             .then(modi)
-            .map(|((select, where_clause), modifier)| SubSelect {
+            .then(inline_data().map_with_span(spanned).or_not())
+            .map(|(((select, where_clause), modifier), values)| SubSelect {
                 modifier,
                 where_clause,
                 select,
+                values,
             })
     })
 }
@@ -123,25 +630,58 @@ fn sub_select<'a>(
 // DONE
 fn group_graph_pattern_sub<
     'a,
-    T: Parser<PToken, GroupGraphPattern, Error = Simple<PToken>> + Clone,
+    T: Parser<PToken, GroupGraphPattern, Error = Simple<PToken>> + Clone + 'a,
 >(
     ggp: T,
     ctx: Ctx<'a>,
 ) -> impl Parser<PToken, GroupGraphPatternSub, Error = Simple<PToken>> + Clone + use<'a, T> {
     let next_check = not(Token::CurlClose).rewind();
 
-    let trip = triple(ctx)
+    let trip = triple_path(ctx)
         .map_with_span(spanned)
-        .map(GroupGraphPatternSub::Triple)
+        .map(GroupGraphPatternSub::TriplePath)
+        .or(triple(ctx)
+            .map_with_span(spanned)
+            .map(GroupGraphPatternSub::Triple))
         .labelled("triple");
 
+    let expr = expression(ggp.clone()).map_with_span(spanned);
+    let stop = j(Token::Stop).or_not();
+
     let kwd = sparql_kwd(SparqlKeyword::Minus)
         .or(sparql_kwd(SparqlKeyword::Optional))
         .clone()
         .then_ignore(j(Token::CurlOpen).rewind())
         .then(ggp.clone().map_with_span(spanned))
+        .then_ignore(stop.clone())
         .map(|(kwd, ggp)| GroupGraphPatternSub::Kwd(kwd, ggp));
 
+    let filter = sparql_kwd(SparqlKeyword::Filter)
+        .then(constraint(expr.clone(), ggp.clone()).map_with_span(spanned))
+        .then_ignore(stop.clone())
+        .map(|(kwd, expr)| GroupGraphPatternSub::Filter(kwd, expr));
+
+    let binding = sparql_kwd(SparqlKeyword::Bind)
+        .then(j(Token::BracketOpen).map_with_span(spanned))
+        .then(bind(expr).map_with_span(spanned))
+        .then(j(Token::BracketClose).map_with_span(spanned))
+        .then_ignore(stop.clone())
+        .map(|(((kwd, open), bind), close)| GroupGraphPatternSub::Bind(kwd, open, bind, close));
+
+    let graph = sparql_kwd(SparqlKeyword::Graph)
+        .then(empty().to(None))
+        .or(sparql_kwd(SparqlKeyword::Service).then(sparql_kwd(SparqlKeyword::Silent).or_not()))
+        .then(var_or_iri())
+        .then(ggp.clone().map_with_span(spanned))
+        .then_ignore(stop.clone())
+        .map(|(((kwd, silent), name), ggp)| {
+            GroupGraphPatternSub::GraphOrService(kwd, silent, name, ggp)
+        });
+
+    let values = inline_data()
+        .then_ignore(stop.clone())
+        .map(GroupGraphPatternSub::Inline);
+
     let union = j(Token::CurlOpen)
         .rewind()
         .ignore_then(ggp.clone())
@@ -151,12 +691,19 @@ fn group_graph_pattern_sub<
                 .then(ggp.map_with_span(spanned))
                 .repeated(),
         )
+        .then_ignore(stop)
         .map(|(start, rest)| GroupGraphPatternSub::Union(start, rest));
 
-    // TODO add the others
-    //
-
-    next_check.ignore_then(trip.or(kwd).or(union).labelled("group_graph_pattern_sub"))
+    next_check.ignore_then(
+        filter
+            .or(binding)
+            .or(graph)
+            .or(values)
+            .or(trip)
+            .or(kwd)
+            .or(union)
+            .labelled("group_graph_pattern_sub"),
+    )
 }
 
 fn expect_it(
@@ -212,55 +759,358 @@ fn where_clause<'a, T: Parser<PToken, SubSelect, Error = Simple<PToken>> + Clone
 }
 
 // DONE
-fn modifier() -> impl Parser<PToken, Modifier, Error = Simple<PToken>> + Clone {
+fn modifier<'a, T: Parser<PToken, GroupGraphPattern, Error = Simple<PToken>> + Clone + 'a>(
+    ggp: T,
+) -> impl Parser<PToken, Modifier, Error = Simple<PToken>> + Clone + use<'a, T> {
+    let expr = expression(ggp.clone()).map_with_span(spanned);
     let num = select!(
         PToken(Token::Number(x), _) => x,
     )
     .map_with_span(spanned);
 
+    let group_condition = bind(expr.clone())
+        .delimited_by(j(Token::BracketOpen), j(Token::BracketClose))
+        .map(GroupCondition::Bind)
+        .or(constraint(expr.clone(), ggp.clone())
+            .or(variable().map(Expression::Variable))
+            .map(GroupCondition::Expr))
+        .map_with_span(spanned);
+
+    let group_by = sparql_kwd(SparqlKeyword::Group)
+        .then(sparql_kwd(SparqlKeyword::By))
+        .then(group_condition.repeated().at_least(1))
+        .map(|((group, by), conditions)| Modifier::GroupBy(group, by, conditions));
+
+    let having = sparql_kwd(SparqlKeyword::Having)
+        .then(
+            constraint(expr.clone(), ggp.clone())
+                .map_with_span(spanned)
+                .repeated()
+                .at_least(1),
+        )
+        .map(|(kwd, conditions)| Modifier::Having(kwd, conditions));
+
+    let order_condition = sparql_kwd(SparqlKeyword::Asc)
+        .or(sparql_kwd(SparqlKeyword::Desc))
+        .then(bracketed(expr.clone()).map_with_span(spanned))
+        .map(|(direction, expr)| OrderCondition {
+            direction: Some(direction),
+            expr,
+        })
+        .or(constraint(expr, ggp)
+            .or(variable().map(Expression::Variable))
+            .map_with_span(spanned)
+            .map(|expr| OrderCondition {
+                direction: None,
+                expr,
+            }))
+        .map_with_span(spanned);
+
+    let order_by = sparql_kwd(SparqlKeyword::Order)
+        .then(sparql_kwd(SparqlKeyword::By))
+        .then(order_condition.repeated().at_least(1))
+        .map(|((order, by), conditions)| Modifier::OrderBy(order, by, conditions));
+
     let limit_offset = sparql_kwd(SparqlKeyword::Limit)
         .or(sparql_kwd(SparqlKeyword::Offset))
         .then(num)
         .map(|(kwd, num)| Modifier::LimitOffset(kwd, num));
-    limit_offset
+
+    group_by.or(having).or(order_by).or(limit_offset)
+}
+
+/// `GRAPH <iri>`, the `GRAPH` keyword is optional when `optional_kwd` is set
+fn graph_ref(
+    optional_kwd: bool,
+) -> impl Parser<PToken, GraphTarget, Error = Simple<PToken>> + Clone {
+    let kwd = sparql_kwd(SparqlKeyword::Graph)
+        .map(Some)
+        .or(empty().try_map(move |_, span| {
+            if optional_kwd {
+                Ok(None)
+            } else {
+                Err(Simple::custom(span, "Expected GRAPH"))
+            }
+        }));
+
+    kwd.then(named_node().map_with_span(spanned))
+        .map(|(kwd, iri)| GraphTarget::Graph(kwd, iri))
+}
+
+fn graph_target<const C: usize>(
+    kwds: [SparqlKeyword; C],
+    optional_kwd: bool,
+) -> impl Parser<PToken, GraphTarget, Error = Simple<PToken>> + Clone {
+    select! { PToken(Token::SparqlKeyword(x), _) => x }
+        .try_map(move |x, span| {
+            if kwds.contains(&x) {
+                Ok(x)
+            } else {
+                Err(Simple::custom(span, "Expected a graph"))
+            }
+        })
+        .map_with_span(spanned)
+        .map(GraphTarget::Keyword)
+        .or(graph_ref(optional_kwd))
+}
+
+/// Triples and `GRAPH` blocks between curly brackets
+fn quads<'a>(ctx: Ctx<'a>) -> impl Parser<PToken, Quads, Error = Simple<PToken>> + Clone + use<'a> {
+    let triples = filter(|PToken(t, _)| {
+        !matches!(
+            t,
+            Token::CurlClose | Token::SparqlKeyword(SparqlKeyword::Graph)
+        )
+    })
+    .rewind()
+    .ignore_then(triple(ctx))
+    .map_with_span(spanned)
+    .repeated();
+
+    let named = sparql_kwd(SparqlKeyword::Graph)
+        .ignore_then(var_or_iri())
+        .then(
+            triples
+                .clone()
+                .delimited_by(j(Token::CurlOpen), j(Token::CurlClose)),
+        )
+        .then_ignore(j(Token::Stop).or_not())
+        .map(|(name, triples)| Graph {
+            name: Some(name),
+            triples,
+        });
+
+    let default = triples.at_least(1).map(|triples| Graph {
+        name: None,
+        triples,
+    });
+
+    named
+        .or(default)
+        .map_with_span(spanned)
+        .repeated()
+        .delimited_by(j(Token::CurlOpen), j(Token::CurlClose))
+}
+
+fn update_operation<
+    'a,
+    T: Parser<PToken, GroupGraphPattern, Error = Simple<PToken>> + Clone + 'a,
+>(
+    ggp: T,
+    ctx: Ctx<'a>,
+) -> impl Parser<PToken, UpdateOperation, Error = Simple<PToken>> + Clone + use<'a, T> {
+    let silent = sparql_kwd(SparqlKeyword::Silent).or_not();
+
+    let load = sparql_kwd(SparqlKeyword::Load)
+        .then(silent.clone())
+        .then(named_node().map_with_span(spanned))
+        .then(
+            sparql_kwd(SparqlKeyword::Into)
+                .then(graph_ref(false).map_with_span(spanned))
+                .or_not(),
+        )
+        .map(|(((kwd, silent), iri), into)| UpdateOperation::Load {
+            kwd,
+            silent,
+            iri,
+            into,
+        });
+
+    let manage_all = sparql_kwd(SparqlKeyword::Clear)
+        .or(sparql_kwd(SparqlKeyword::Drop))
+        .then(silent.clone())
+        .then(
+            graph_target(
+                [
+                    SparqlKeyword::Default,
+                    SparqlKeyword::Named,
+                    SparqlKeyword::All,
+                ],
+                false,
+            )
+            .map_with_span(spanned),
+        );
+    let create = sparql_kwd(SparqlKeyword::Create)
+        .then(silent.clone())
+        .then(graph_ref(false).map_with_span(spanned));
+    let manage = manage_all
+        .or(create)
+        .map(|((kwd, silent), target)| UpdateOperation::Manage {
+            kwd,
+            silent,
+            target,
+        });
+
+    let graph_or_default = graph_target([SparqlKeyword::Default], true).map_with_span(spanned);
+    let transfer = sparql_kwd(SparqlKeyword::Add)
+        .or(sparql_kwd(SparqlKeyword::Move))
+        .or(sparql_kwd(SparqlKeyword::Copy))
+        .then(silent)
+        .then(graph_or_default.clone())
+        .then(sparql_kwd(SparqlKeyword::To))
+        .then(graph_or_default)
+        .map(
+            |((((kwd, silent), from), to_kwd), to)| UpdateOperation::Transfer {
+                kwd,
+                silent,
+                from,
+                to_kwd,
+                to,
+            },
+        );
+
+    let quads = quads(ctx).map_with_span(spanned);
+
+    let data = sparql_kwd(SparqlKeyword::Insert)
+        .or(sparql_kwd(SparqlKeyword::Delete))
+        .then(sparql_kwd(SparqlKeyword::Data))
+        .then(quads.clone())
+        .map(|((kwd, data_kwd), quads)| UpdateOperation::Data {
+            kwd,
+            data_kwd,
+            quads,
+        });
+
+    let delete_where = sparql_kwd(SparqlKeyword::Delete)
+        .then(sparql_kwd(SparqlKeyword::Where))
+        .then(quads.clone())
+        .map(|((kwd, where_kwd), quads)| UpdateOperation::DeleteWhere {
+            kwd,
+            where_kwd,
+            quads,
+        });
+
+    let insert = sparql_kwd(SparqlKeyword::Insert).then(quads.clone());
+    let clauses = sparql_kwd(SparqlKeyword::Delete)
+        .then(quads)
+        .then(insert.clone().or_not())
+        .map(|(delete, insert)| (Some(delete), insert))
+        .or(insert.map(|insert| (None, Some(insert))));
+
+    let using = sparql_kwd(SparqlKeyword::Using)
+        .then(sparql_kwd(SparqlKeyword::Named).or_not())
+        .then(named_node().map_with_span(spanned))
+        .map(|((from, named), iri)| DatasetClause { from, named, iri })
+        .map_with_span(spanned)
+        .repeated();
+
+    let where_clause = sparql_kwd(SparqlKeyword::Where)
+        .then(ggp.map_with_span(spanned))
+        .map(|(kwd, ggp)| WhereClause {
+            kwd: Some(kwd),
+            ggp,
+        })
+        .map_with_span(spanned);
+
+    let modify = sparql_kwd(SparqlKeyword::With)
+        .then(named_node().map_with_span(spanned))
+        .or_not()
+        .then(clauses)
+        .then(using)
+        .then(where_clause)
+        .map(|(((with, (delete, insert)), using), where_clause)| {
+            UpdateOperation::Modify(Modify {
+                with,
+                delete,
+                insert,
+                using,
+                where_clause,
+            })
+        });
+
+    load.or(manage)
+        .or(transfer)
+        .or(data)
+        .or(delete_where)
+        .or(modify)
+}
+
+fn add_prologues(query: &mut Query, prologues: Vec<Spanned<Prologue>>) {
+    for Spanned(x, span) in prologues {
+        match x {
+            Prologue::Base { token, iri } => {
+                query.base_statement = Some(Spanned(Base { token, iri }, span))
+            }
+            Prologue::Prefix(prefix) => query.prefixes.push(Spanned(prefix, span)),
+        }
+    }
 }
 
 pub fn query<'a>(
     base: lsp_types::Url,
     ctx: Ctx<'a>,
 ) -> impl Parser<PToken, Query, Error = Simple<PToken>> + Clone + use<'a> {
-    let prologues = prologue().map_with_span(spanned).repeated().map(|xs| {
-        let mut base = None;
-        let mut prefixes = vec![];
-        xs.into_iter().for_each(|Spanned(x, span)| match x {
-            Prologue::Base { token, iri } => base = Some(Spanned(Base { token, iri }, span)),
-            Prologue::Prefix(prefix) => prefixes.push(Spanned(prefix, span)),
-        });
-        (base, prefixes)
-    });
-    let kwds = select_clause().map(QueryClause::Select);
+    let prologues = prologue().map_with_span(spanned).repeated();
+    let ggp = group_graph_pattern(sub_select(ctx), ctx);
+    let kwds = select_clause(ggp.clone())
+        .map(QueryClause::Select)
+        .or(construct_clause(ctx).map(QueryClause::Construct))
+        .or(sparql_kwd(SparqlKeyword::Ask).map(QueryClause::Ask));
+    let describe = describe_clause().map(QueryClause::Describe);
     let datasets = dataset_clause().map_with_span(spanned).repeated();
     let where_clause = where_clause(sub_select(ctx), ctx).map_with_span(spanned);
-    let modifiers = modifier().map_with_span(spanned).repeated();
+    let modifiers = modifier(ggp.clone()).map_with_span(spanned).repeated();
+    let values = inline_data().map_with_span(spanned).or_not();
 
-    prologues
-        .then(kwds)
-        .then(datasets)
+    // Only DESCRIBE queries can omit the where clause
+    let optional_where = where_clause
+        .clone()
+        .or_not()
+        .map(|x| x.unwrap_or_else(|| Spanned(WhereClause::default(), 0..0)));
+
+    let query_body = kwds
+        .then(datasets.clone())
         .then(where_clause)
+        .or(describe.then(datasets).then(optional_where))
         .then(modifiers)
+        .then(values)
         .map(
-            move |(((((base_statement, prefixes), kwds), datasets), where_clause), modifier)| {
-                Query {
-                    base_statement,
-                    prefixes,
-                    base: base.clone(),
-                    modifier,
-                    where_clause,
-                    datasets,
-                    kwds,
-                }
+            |((((kwds, datasets), where_clause), modifier), values)| Query {
+                kwds,
+                datasets,
+                where_clause,
+                modifier,
+                values,
+                ..Query::default()
             },
+        );
+
+    // Operations are separated by ';', and every operation can declare prefixes
+    let operation = update_operation(ggp, ctx).map_with_span(spanned);
+    let update_body = operation
+        .clone()
+        .then(
+            j(Token::PredicateSplit)
+                .ignore_then(prologues.clone())
+                .then(operation)
+                .repeated(),
         )
+        .then_ignore(j(Token::PredicateSplit).or_not())
+        .map(|(first, rest)| {
+            let mut query = Query::default();
+            query.operations.push(first);
+            for (prologues, op) in rest {
+                add_prologues(&mut query, prologues);
+                query.operations.push(op);
+            }
+            query
+        });
+
+    prologues
+        .then(query_body.or(update_body))
+        .map(move |(prologues, mut query)| {
+            let base_statement = query.base_statement.take();
+            let prefixes = std::mem::take(&mut query.prefixes);
+
+            add_prologues(&mut query, prologues);
+            query.prefixes.extend(prefixes);
+            if base_statement.is_some() {
+                query.base_statement = base_statement;
+            }
+            query.base = base.clone();
+
+            query
+        })
 }
 
 pub fn parse(
@@ -297,10 +1147,7 @@ mod tests {
     use lang_turtle::lang::context::Context;
 
     use super::*;
-    use crate::lang::{
-        parsing::select_clause,
-        tokenizer::{parse_tokens_str},
-    };
+    use crate::lang::{parsing::select_clause, tokenizer::parse_tokens_str};
     pub fn parse_it<T, P: Parser<PToken, T, Error = Simple<PToken>>>(
         turtle: &str,
         parser: P,
@@ -342,7 +1189,12 @@ PREFIX  dc:  <http://purl.org/dc/elements/1.1/>
 SELECT  ?title ?price
         "#;
 
-        let (q, tok) = parse_it(inp, select_clause());
+        let context = Context::new();
+        let ctx = context.ctx();
+        let (q, tok) = parse_it(
+            inp,
+            select_clause(group_graph_pattern(sub_select(ctx), ctx)),
+        );
 
         println!("q {:?}", q);
 
@@ -420,4 +1272,348 @@ SELECT  ?title ?price
         assert_eq!(tok, vec![]);
         assert!(q.is_some());
     }
+
+    fn parse_expression(inp: &str) -> Expression {
+        let context = Context::new();
+        let ctx = context.ctx();
+        let (q, tok) = parse_it(inp, expression(group_graph_pattern(sub_select(ctx), ctx)));
+
+        assert_eq!(tok, vec![]);
+        q.expect("expression")
+    }
+
+    fn op(expr: &Expression) -> Option<&SparqlExpr> {
+        match expr {
+            Expression::Binary(_, op, _) => Some(op.value()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn parse_expression_precedence() {
+        let expr = parse_expression("?a + 2 * ?b > 3 && !bound(?c) || ?d NOT IN (1, 2)");
+
+        let Expression::Binary(lhs, or, rhs) = &expr else {
+            panic!("expected ||, found {:?}", expr);
+        };
+        assert_eq!(or.value(), &SparqlExpr::Or);
+        assert!(
+            matches!(rhs.value(), Expression::In { not: Some(_), list, .. } if list.len() == 2)
+        );
+
+        let Expression::Binary(cmp, and, not) = lhs.value() else {
+            panic!("expected &&, found {:?}", lhs);
+        };
+        assert_eq!(and.value(), &SparqlExpr::And);
+        assert_eq!(op(cmp), Some(&SparqlExpr::Gt));
+        assert!(matches!(not.value(), Expression::Unary(op, x)
+            if op.value() == &SparqlExpr::Exclamation && matches!(x.value(), Expression::BuiltIn(_, _))));
+
+        let vars: Vec<_> = expr.variables().into_iter().map(|x| x.0.as_str()).collect();
+        assert_eq!(vars, vec!["?a", "?b", "?c", "?d"]);
+    }
+
+    #[test]
+    fn parse_expression_signed_number() {
+        let expr = parse_expression("?x -1");
+        let Expression::Binary(_, minus, one) = &expr else {
+            panic!("expected -, found {:?}", expr);
+        };
+        assert_eq!(minus.value(), &SparqlExpr::Minus);
+        assert_eq!(
+            one.value(),
+            &Expression::Literal(Literal::Numeric("1".into()))
+        );
+    }
+
+    #[test]
+    fn parse_expression_calls() {
+        let expr = parse_expression(r#"GROUP_CONCAT(DISTINCT ?name; SEPARATOR = ", ")"#);
+        let Expression::Aggregate(agg) = &expr else {
+            panic!("expected an aggregate, found {:?}", expr);
+        };
+        assert!(agg.distinct.is_some());
+        assert_eq!(agg.separator.as_ref().map(|x| x.as_str()), Some(", "));
+
+        let expr = parse_expression("COUNT(*)");
+        assert!(matches!(
+            expr,
+            Expression::Aggregate(Aggregate { arg: None, .. })
+        ));
+
+        let expr = parse_expression(r#"regex(?title, "^SPARQL", "i")"#);
+        assert!(matches!(expr, Expression::KwdCall(_, args) if args.len() == 3));
+
+        let expr = parse_expression("xsd:integer(?x)");
+        assert!(matches!(expr, Expression::FunctionCall { args, .. } if args.len() == 1));
+
+        let expr = parse_expression("NOT EXISTS { ?x a ?y . }");
+        assert!(matches!(expr, Expression::Exists { not: Some(_), .. }));
+    }
+
+    #[test]
+    fn parse_filter_bind_and_modifiers() {
+        let context = Context::new();
+        let ctx = context.ctx();
+        let inp = r#"PREFIX ns: <http://example.org/ns#>
+SELECT ?x (SUM(?p) AS ?total)
+{ ?x ns:price ?p .
+  FILTER (?p < 30.5)
+  FILTER NOT EXISTS { ?x ns:discount ?d . }
+  BIND (?p * 2 AS ?double)
+}
+GROUP BY ?x
+HAVING (SUM(?p) > 10)
+ORDER BY DESC(?total) ?x
+LIMIT 10
+        "#;
+
+        let (q, tok) = parse_it(
+            inp,
+            query(lsp_types::Url::parse("memory://myFile.sq").unwrap(), ctx),
+        );
+
+        assert_eq!(tok, vec![]);
+        let q = q.expect("query");
+
+        let QueryClause::Select(select) = &q.kwds else {
+            panic!("expected a select clause");
+        };
+        assert!(
+            matches!(select.solutions[1].value(), Solution::VarAs(bind) if bind.var.0 == "?total")
+        );
+
+        let GroupGraphPattern::GroupGraph(subs) = q.where_clause.ggp.value() else {
+            panic!("expected a group graph pattern");
+        };
+        assert_eq!(subs.len(), 4);
+        assert!(
+            matches!(subs[1].value(), GroupGraphPatternSub::Filter(_, expr)
+            if matches!(expr.value(), Expression::Bracketed(x) if op(x) == Some(&SparqlExpr::Lt)))
+        );
+        assert!(
+            matches!(subs[2].value(), GroupGraphPatternSub::Filter(_, expr)
+            if matches!(expr.value(), Expression::Exists { .. }))
+        );
+        assert!(
+            matches!(subs[3].value(), GroupGraphPatternSub::Bind(_, _, bind, _)
+            if bind.var.0 == "?double")
+        );
+
+        assert_eq!(q.modifier.len(), 4);
+        assert!(matches!(q.modifier[0].value(), Modifier::GroupBy(_, _, xs) if xs.len() == 1));
+        assert!(matches!(q.modifier[1].value(), Modifier::Having(_, xs) if xs.len() == 1));
+        let Modifier::OrderBy(_, _, conditions) = q.modifier[2].value() else {
+            panic!("expected order by");
+        };
+        assert_eq!(conditions.len(), 2);
+        assert!(conditions[0].direction.is_some());
+        assert!(conditions[1].direction.is_none());
+    }
+
+    #[test]
+    fn parse_update_request() {
+        let context = Context::new();
+        let ctx = context.ctx();
+        let inp = r#"PREFIX ex: <http://example.org/>
+INSERT DATA { ex:a ex:p 1 . GRAPH ex:g { ex:a ex:p 2 . } } ;
+PREFIX foaf: <http://xmlns.com/foaf/0.1/>
+WITH ex:g
+DELETE { ?x foaf:name ?name . }
+INSERT { ?x ex:name ?name . }
+USING ex:other
+WHERE { ?x foaf:name ?name . } ;
+LOAD SILENT <http://example.org/data.ttl> INTO GRAPH ex:g ;
+CLEAR ALL ;
+COPY DEFAULT TO ex:g ;
+DELETE WHERE { ?x ex:p ?y . }
+        "#;
+
+        let (q, tok) = parse_it(
+            inp,
+            query(lsp_types::Url::parse("memory://myFile.ru").unwrap(), ctx),
+        );
+
+        assert_eq!(tok, vec![]);
+        let q = q.expect("update");
+
+        assert!(q.is_update());
+        assert_eq!(q.prefixes.len(), 2);
+        assert_eq!(q.operations.len(), 6);
+
+        let UpdateOperation::Data { quads, .. } = q.operations[0].value() else {
+            panic!("expected INSERT DATA");
+        };
+        assert_eq!(quads.len(), 2);
+        assert!(quads[1].name.is_some());
+
+        let UpdateOperation::Modify(modify) = q.operations[1].value() else {
+            panic!("expected a modify operation");
+        };
+        assert!(modify.with.is_some() && modify.delete.is_some() && modify.insert.is_some());
+        assert_eq!(modify.using.len(), 1);
+
+        assert!(matches!(
+            q.operations[2].value(),
+            UpdateOperation::Load {
+                silent: Some(_),
+                into: Some(_),
+                ..
+            }
+        ));
+        assert!(matches!(
+            q.operations[3].value(),
+            UpdateOperation::Manage { target, .. } if matches!(target.value(), GraphTarget::Keyword(_))
+        ));
+        assert!(matches!(
+            q.operations[4].value(),
+            UpdateOperation::Transfer { to, .. } if matches!(to.value(), GraphTarget::Graph(None, _))
+        ));
+        assert!(matches!(
+            q.operations[5].value(),
+            UpdateOperation::DeleteWhere { .. }
+        ));
+    }
+
+    #[test]
+    fn parse_query_forms() {
+        let context = Context::new();
+        let ctx = context.ctx();
+        let base = || lsp_types::Url::parse("memory://myFile.sq").unwrap();
+
+        let (q, tok) = parse_it(
+            "PREFIX ex: <http://example.org/> ASK { ?x ex:p 1 OPTIONAL { ?x ex:q ?y } . }",
+            query(base(), ctx),
+        );
+        assert_eq!(tok, vec![]);
+        assert!(matches!(q.expect("ask").kwds, QueryClause::Ask(_)));
+
+        let (q, tok) = parse_it("DESCRIBE ?x <http://example.org/a>", query(base(), ctx));
+        assert_eq!(tok, vec![]);
+        let q = q.expect("describe");
+        let QueryClause::Describe(describe) = &q.kwds else {
+            panic!("expected DESCRIBE");
+        };
+        assert_eq!(describe.targets.len(), 2);
+        assert!(matches!(
+            q.where_clause.ggp.value(),
+            GroupGraphPattern::Invalid
+        ));
+
+        let (q, tok) = parse_it(
+            "PREFIX ex: <http://example.org/> CONSTRUCT { ?x ex:q ?y . } WHERE { ?x ex:p ?y }",
+            query(base(), ctx),
+        );
+        assert_eq!(tok, vec![]);
+        let QueryClause::Construct(construct) = q.expect("construct").kwds else {
+            panic!("expected CONSTRUCT");
+        };
+        assert_eq!(construct.template.map(|(_, x, _)| x.len()), Some(1));
+
+        let (q, tok) = parse_it(
+            "PREFIX ex: <http://example.org/> CONSTRUCT WHERE { ?x ex:p ?y }",
+            query(base(), ctx),
+        );
+        assert_eq!(tok, vec![]);
+        assert!(matches!(
+            q.expect("construct where").kwds,
+            QueryClause::Construct(ConstructClause { template: None, .. })
+        ));
+    }
+
+    #[test]
+    fn parse_values_graph_and_service() {
+        let context = Context::new();
+        let ctx = context.ctx();
+        let inp = r#"PREFIX ex: <http://example.org/>
+SELECT * WHERE {
+  VALUES ?x { ex:a ex:b }
+  GRAPH ?g { ?x ex:p ?y . }
+  SERVICE SILENT <http://example.org/sparql> { ?y ex:q ?z }
+  MINUS { ?x ex:r 1 } .
+}
+VALUES (?y ?z) { (1 "one") (UNDEF 2) }
+        "#;
+
+        let (q, tok) = parse_it(
+            inp,
+            query(lsp_types::Url::parse("memory://myFile.sq").unwrap(), ctx),
+        );
+        assert_eq!(tok, vec![]);
+        let q = q.expect("query");
+
+        let GroupGraphPattern::GroupGraph(subs) = q.where_clause.ggp.value() else {
+            panic!("expected a group graph pattern");
+        };
+        assert_eq!(subs.len(), 4);
+        assert!(
+            matches!(subs[0].value(), GroupGraphPatternSub::Inline(data) if data.vars.len() == 1 && data.rows.len() == 2)
+        );
+        assert!(
+            matches!(subs[1].value(), GroupGraphPatternSub::GraphOrService(_, None, name, _) if matches!(name.value(), Term::Variable(_)))
+        );
+        assert!(matches!(
+            subs[2].value(),
+            GroupGraphPatternSub::GraphOrService(_, Some(_), _, _)
+        ));
+        assert!(matches!(subs[3].value(), GroupGraphPatternSub::Kwd(_, _)));
+
+        let values = q.values.expect("values clause");
+        assert_eq!(values.vars.len(), 2);
+        assert_eq!(values.rows[1][0].value(), &DataValue::Undef);
+
+        let (_, tok) = parse_it(
+            "SELECT * { } VALUES (?x ?y) { (1) }",
+            query(lsp_types::Url::parse("memory://myFile.sq").unwrap(), ctx),
+        );
+        assert_eq!(tok.len(), 1);
+    }
+
+    #[test]
+    fn parse_property_paths() {
+        let context = Context::new();
+        let ctx = context.ctx();
+        let inp = r#"PREFIX foaf: <http://xmlns.com/foaf/0.1/>
+SELECT * WHERE {
+  ?x foaf:knows+/foaf:name ?n ;
+     ^foaf:member|!(a|^foaf:made) ?y .
+  ?y foaf:name ?m
+}
+        "#;
+
+        let (q, tok) = parse_it(
+            inp,
+            query(lsp_types::Url::parse("memory://myFile.sq").unwrap(), ctx),
+        );
+        assert_eq!(tok, vec![]);
+        let q = q.expect("query");
+
+        let GroupGraphPattern::GroupGraph(subs) = q.where_clause.ggp.value() else {
+            panic!("expected a group graph pattern");
+        };
+        assert_eq!(subs.len(), 2);
+        assert!(matches!(subs[1].value(), GroupGraphPatternSub::Triple(_)));
+
+        let GroupGraphPatternSub::TriplePath(triple) = subs[0].value() else {
+            panic!("expected a triple with a property path");
+        };
+        assert_eq!(triple.po.len(), 2);
+
+        let Path::Sequence(seq) = triple.po[0].predicate.value() else {
+            panic!("expected a sequence path");
+        };
+        assert_eq!(seq.len(), 2);
+        assert!(matches!(
+            seq[0].value(),
+            Path::Modified(_, modifier) if modifier.value() == &PathModifier::OneOrMore
+        ));
+
+        let Path::Alternative(alts) = triple.po[1].predicate.value() else {
+            panic!("expected an alternative path");
+        };
+        assert!(matches!(alts[0].value(), Path::Inverse(_)));
+        assert!(matches!(alts[1].value(), Path::Negated(set) if set.len() == 2));
+
+        assert_eq!(triple.po[0].predicate.to_string(), "foaf:knows+/foaf:name");
+    }
 }
@@ -54,6 +54,9 @@ enum SparqlToken {
     #[token("UNDEF", |_| SparqlKeyword::Undef, ignore(case))]
     #[token("MINUS", |_| SparqlKeyword::Minus, ignore(case))]
     #[token("FILTER", |_| SparqlKeyword::Filter, ignore(case))]
+    #[token("SEPARATOR", |_| SparqlKeyword::Separator, ignore(case))]
+    #[token("INTO", |_| SparqlKeyword::Into, ignore(case))]
+    #[token("TO", |_| SparqlKeyword::To, ignore(case))]
     Kwd(SparqlKeyword),
 
     #[token("COUNT", |_| SparqlAggregate::Count, ignore(case))]
@@ -115,7 +118,7 @@ enum SparqlToken {
     #[token("isURI", |_| SparqlCall::IsUri, ignore(case))]
     #[token("isBLANK", |_| SparqlCall::IsBlank, ignore(case))]
     #[token("isLITERAL", |_| SparqlCall::IsLiteral, ignore(case))]
-    #[token("isNUMBERIC", |_| SparqlCall::IsNumeric, ignore(case))]
+    #[token("isNUMERIC", |_| SparqlCall::IsNumeric, ignore(case))]
     #[token("hasLANG", |_| SparqlCall::HasLang, ignore(case))]
     #[token("hasLANGDIR", |_| SparqlCall::HasLangDir, ignore(case))]
     #[token("isTRIPLE", |_| SparqlCall::IsTriple, ignore(case))]
@@ -166,6 +169,15 @@ enum SparqlToken {
     #[token(";")]
     Semi,
 
+    #[token("|")]
+    Pipe,
+
+    #[token("^")]
+    Caret,
+
+    #[token("?")]
+    QuestionMark,
+
     #[token(",")]
     Comma,
     #[token(".")]
@@ -258,6 +270,9 @@ pub fn parse_tokens_str<'a>(text: &'a str) -> (Vec<Spanned<Token>>, Vec<Simple<c
                     SparqlToken::CurlOpen => Token::CurlOpen,
                     SparqlToken::CurlClose => Token::CurlClose,
                     SparqlToken::Semi => Token::PredicateSplit,
+                    SparqlToken::Pipe => Token::Pipe,
+                    SparqlToken::Caret => Token::Caret,
+                    SparqlToken::QuestionMark => Token::QuestionMark,
                     SparqlToken::Comma => Token::Comma,
                     SparqlToken::Stop => Token::Stop,
                     SparqlToken::DataTag => Token::DataTypeDelim,
@@ -494,4 +509,13 @@ SELECT  ?title (?p*(1-?discount) AS ?price)
         assert_eq!(tok.len(), 33);
         assert_eq!(er, vec![]);
     }
+
+    #[test]
+    fn parse_path_tokens() {
+        let inp = "?x foaf:knows+/^foaf:name? | !(a|^rdf:type)* ?y";
+
+        let (tok, er) = parse_tokens_str(inp);
+        assert_eq!(tok.len(), 17);
+        assert_eq!(er, vec![]);
+    }
 }
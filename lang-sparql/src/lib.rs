@@ -7,7 +7,7 @@ use lsp_core::prelude::*;
 use lsp_types::SemanticTokenType;
 
 pub mod ecs;
-use crate::ecs::{setup_completion, setup_parse};
+use crate::ecs::{setup_code_action, setup_completion, setup_document_symbol, setup_parse};
 pub mod lang;
 // pub mod model;
 // use crate::model::Query;
@@ -24,7 +24,7 @@ pub fn setup_world(world: &mut World) {
     });
     world.observe(|trigger: Trigger<CreateEvent>, mut commands: Commands| {
         match &trigger.event().language_id {
-            Some(x) if x == "sparql" => {
+            Some(x) if x == "sparql" || x == "sparql-update" => {
                 info!("Found sparql documnet!");
                 commands
                     .entity(trigger.entity())
@@ -32,10 +32,13 @@ pub fn setup_world(world: &mut World) {
                     .insert(DynLang(Box::new(SparqlHelper)));
                 return;
             }
-            _ => {}
+            // Extensions are only used when the client did not tell the language
+            Some(_) => return,
+            None => {}
         }
 
-        if trigger.event().url.as_str().ends_with(".sq") {
+        let url = trigger.event().url.as_str();
+        if url.ends_with(".sq") || url.ends_with(".rq") || url.ends_with(".ru") {
             info!("Found sparql documnet!");
             commands
                 .entity(trigger.entity())
@@ -48,10 +51,15 @@ pub fn setup_world(world: &mut World) {
     world.schedule_scope(DiagnosticsLabel, |_, schedule| {
         schedule.add_systems(diagnostics::publish_diagnostics::<Sparql>);
     });
+    world.schedule_scope(PullDiagnosticsLabel, |_, schedule| {
+        schedule.add_systems(diagnostics::pull_syntax_diagnostics::<Sparql>);
+    });
 
     setup_parse(world);
 
     setup_completion(world);
+    setup_code_action(world);
+    setup_document_symbol(world);
 }
 
 #[derive(Debug, Component)]
@@ -69,7 +77,7 @@ impl Lang for Sparql {
     const PATTERN: Option<&'static str> = None;
 
     const LANG: &'static str = "sparql";
-    const CODE_ACTION: bool = false;
+    const CODE_ACTION: bool = true;
     const HOVER: bool = true;
 
     const TRIGGERS: &'static [&'static str] = &[];
@@ -0,0 +1,135 @@
+#![doc(
+    html_logo_url = "https://ajuvercr.github.io/semantic-web-lsp/assets/icons/favicon.png",
+    html_favicon_url = "https://ajuvercr.github.io/semantic-web-lsp/assets/icons/favicon.ico"
+)]
+//! TriG support, Turtle with named graphs.
+//!
+//! TriG documents are parsed into the same [`Turtle`] model, so all Turtle systems (completion,
+//! formatting, diagnostics, ...) are reused through [`TurtleLike`].
+use bevy_ecs::{component::Component, world::World};
+use chumsky::prelude::Simple;
+use lang_turtle::{
+    lang::{model::Turtle, parser::Syntax},
+    setup_turtle_like, TurtleLike, TURTLE_LEGEND_TYPES,
+};
+use lsp_core::{
+    lang::{Lang, LangHelper},
+    prelude::*,
+};
+
+#[derive(Component, Default)]
+pub struct TrigLang;
+
+impl TurtleLike for TrigLang {
+    const SYNTAX: Syntax = Syntax::TriG;
+}
+
+#[derive(Debug, Clone)]
+pub struct TrigHelper;
+impl LangHelper for TrigHelper {
+    fn keyword(&self) -> &[&'static str] {
+        &["@prefix", "@base", "a", "GRAPH"]
+    }
+}
+
+pub fn setup_world(world: &mut World) {
+    setup_turtle_like::<TrigLang, _>(world, "trig", ".trig", TrigHelper);
+}
+
+impl Lang for TrigLang {
+    type Token = Token;
+
+    type TokenError = Simple<char>;
+
+    type Element = Turtle;
+
+    type ElementError = Simple<Token>;
+
+    const LANG: &'static str = "trig";
+
+    const TRIGGERS: &'static [&'static str] = &[":"];
+    const CODE_ACTION: bool = true;
+    const HOVER: bool = true;
+
+    const LEGEND_TYPES: &'static [lsp_types::SemanticTokenType] = TURTLE_LEGEND_TYPES;
+
+    const PATTERN: Option<&'static str> = None;
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_core::{components::*, prelude::*};
+    use lsp_types::Diagnostic;
+    use test_utils::{create_file, setup_world, TestClient};
+
+    fn setup(world: &mut bevy_ecs::world::World) {
+        lang_turtle::setup_world(world);
+        crate::setup_world(world);
+    }
+
+    #[test_log::test]
+    fn trig_quads_have_graph_names() {
+        let (mut world, _) = setup_world(TestClient::new(), setup);
+
+        let t1 = "@prefix foaf: <http://xmlns.com/foaf/0.1/>.
+<a> foaf:name \"A\".
+GRAPH <g> { <a> foaf:knows <b> }
+<h> { <b> foaf:name \"B\". }";
+        let entity = create_file(&mut world, t1, "http://example.com/ns.trig", "trig", Open);
+
+        assert!(world.entity(entity).contains::<crate::TrigLang>());
+        assert!(!world.entity(entity).contains::<Dirty>());
+
+        let triples: &Triples = world.entity(entity).get().expect("triples");
+        let graphs: Vec<_> = triples
+            .iter()
+            .map(|t| t.graph.as_ref().map(|g| g.as_str().to_string()))
+            .collect();
+        assert_eq!(
+            graphs,
+            vec![
+                None,
+                Some(String::from("http://example.com/g")),
+                Some(String::from("http://example.com/h")),
+            ]
+        );
+    }
+
+    #[test_log::test]
+    fn trig_graph_blocks_format_and_diagnose() {
+        let (mut world, mut rx) = setup_world(TestClient::new(), setup);
+
+        let t1 = "GRAPH <g> { <a> foaf:knows <b> . }";
+        let entity = create_file(&mut world, t1, "http://example.com/ns.trig", "trig", Open);
+        world.run_schedule(DiagnosticsLabel);
+
+        let mut diagnostics: Vec<Diagnostic> = Vec::new();
+        while let Ok(Some(x)) = rx.try_next() {
+            diagnostics = x.diagnostics;
+        }
+        assert_eq!(diagnostics.len(), 1, "undefined prefix inside the graph");
+
+        world.entity_mut(entity).insert(FormatRequest(None));
+        world.run_schedule(FormatLabel);
+        let formatted = world
+            .entity_mut(entity)
+            .take::<FormatRequest>()
+            .and_then(|x| x.0)
+            .expect("formatted");
+
+        assert_eq!(
+            formatted[0].new_text,
+            "GRAPH <g> {\n  <a> foaf:knows <b>.\n}\n\n"
+        );
+    }
+
+    #[test_log::test]
+    fn turtle_does_not_allow_graphs() {
+        let (mut world, _) = setup_world(TestClient::new(), setup);
+
+        let t1 = "GRAPH <g> { <a> <b> <c> . }";
+        let entity = create_file(&mut world, t1, "http://example.com/ns.ttl", "turtle", Open);
+
+        assert!(world.entity(entity).contains::<Dirty>());
+    }
+}
@@ -0,0 +1,168 @@
+use bevy_ecs::prelude::*;
+use lsp_core::{
+    prelude::*,
+    systems::prefix::{prologue_insert_position, undefined_prefix_action_helper},
+};
+use lsp_types::{Range, TextEdit};
+
+use crate::TurtleLike;
+
+pub fn turtle_undefined_prefix_action<L: TurtleLike>(
+    mut query: Query<(&Element<L>, &RopeC, &Label, &mut CodeActionRequest)>,
+) {
+    for (turtle, rope, label, mut req) in &mut query {
+        let ends = turtle
+            .base
+            .iter()
+            .map(|x| x.span().end)
+            .chain(turtle.prefixes.iter().map(|x| x.span().end));
+        let (start, newline) = prologue_insert_position(ends, &rope.0);
+
+        undefined_prefix_action_helper(&mut req, label, |name, location| {
+            Some(vec![TextEdit {
+                range: Range::new(start, start),
+                new_text: format!("{}@prefix {}: <{}>.\n", newline, name, location),
+            }])
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_core::{components::*, prelude::*};
+    use lsp_types::{CodeActionOrCommand, Diagnostic};
+    use test_utils::{create_file, setup_world, TestClient};
+
+    #[test_log::test]
+    fn undefined_prefix_quick_fix() {
+        let (mut world, mut rx) = setup_world(TestClient::new(), crate::setup_world);
+
+        let t1 = "<a> foaf:name \"Arthur\".";
+        let entity = create_file(&mut world, t1, "http://example.com/ns#", "turtle", Open);
+        world.run_schedule(DiagnosticsLabel);
+
+        let mut diagnostics: Vec<Diagnostic> = Vec::new();
+        while let Ok(Some(x)) = rx.try_next() {
+            diagnostics = x.diagnostics;
+        }
+        assert_eq!(diagnostics.len(), 1);
+
+        world
+            .entity_mut(entity)
+            .insert(CodeActionRequest::new(diagnostics[0].range, diagnostics));
+        world.run_schedule(CodeActionLabel);
+
+        let actions = world
+            .entity_mut(entity)
+            .take::<CodeActionRequest>()
+            .expect("code action request")
+            .actions;
+
+        assert_eq!(actions.len(), 1);
+        let CodeActionOrCommand::CodeAction(action) = &actions[0] else {
+            panic!("Expected code action");
+        };
+
+        let edits = action
+            .edit
+            .as_ref()
+            .and_then(|x| x.changes.as_ref())
+            .and_then(|x| x.values().next())
+            .expect("edits");
+        assert_eq!(
+            edits[0].new_text,
+            "@prefix foaf: <http://xmlns.com/foaf/0.1/>.\n"
+        );
+    }
+
+    #[test_log::test]
+    fn undefined_prefix_quick_fix_after_prologue() {
+        let (mut world, mut rx) = setup_world(TestClient::new(), crate::setup_world);
+
+        let t1 = "@base <http://example.com/>.
+# People
+@prefix ex: <http://example.com/ns#>.
+<a> foaf:name \"Arthur\"; ex:knows [ rdfs:label \"Julian\" ].";
+        let entity = create_file(&mut world, t1, "http://example.com/ns#", "turtle", Open);
+        world.run_schedule(DiagnosticsLabel);
+
+        let mut diagnostics: Vec<Diagnostic> = Vec::new();
+        while let Ok(Some(x)) = rx.try_next() {
+            diagnostics = x.diagnostics;
+        }
+        assert_eq!(diagnostics.len(), 2);
+        let foaf = diagnostics
+            .iter()
+            .find(|x| x.message.ends_with("foaf"))
+            .expect("foaf diagnostic")
+            .range;
+
+        world
+            .entity_mut(entity)
+            .insert(CodeActionRequest::new(foaf, diagnostics));
+        world.run_schedule(CodeActionLabel);
+
+        let actions = world
+            .entity_mut(entity)
+            .take::<CodeActionRequest>()
+            .expect("code action request")
+            .actions;
+
+        assert_eq!(actions.len(), 1);
+        let CodeActionOrCommand::CodeAction(action) = &actions[0] else {
+            panic!("Expected code action");
+        };
+
+        let edits = action
+            .edit
+            .as_ref()
+            .and_then(|x| x.changes.as_ref())
+            .and_then(|x| x.values().next())
+            .expect("edits");
+        assert_eq!(edits[0].range.start, lsp_types::Position::new(3, 0));
+        assert!(edits[0].new_text.starts_with("@prefix foaf:"));
+    }
+
+    #[test_log::test]
+    fn undefined_prefix_quick_fix_without_trailing_newline() {
+        let (mut world, mut rx) = setup_world(TestClient::new(), crate::setup_world);
+
+        let t1 = "@prefix ex: <http://example.com/ns#>. ex:a foaf:name \"Arthur\".";
+        let entity = create_file(&mut world, t1, "http://example.com/ns#", "turtle", Open);
+        world.run_schedule(DiagnosticsLabel);
+
+        let mut diagnostics: Vec<Diagnostic> = Vec::new();
+        while let Ok(Some(x)) = rx.try_next() {
+            diagnostics = x.diagnostics;
+        }
+        assert_eq!(diagnostics.len(), 1);
+
+        world
+            .entity_mut(entity)
+            .insert(CodeActionRequest::new(diagnostics[0].range, diagnostics));
+        world.run_schedule(CodeActionLabel);
+
+        let actions = world
+            .entity_mut(entity)
+            .take::<CodeActionRequest>()
+            .expect("code action request")
+            .actions;
+
+        assert_eq!(actions.len(), 1);
+        let CodeActionOrCommand::CodeAction(action) = &actions[0] else {
+            panic!("Expected code action");
+        };
+
+        let edits = action
+            .edit
+            .as_ref()
+            .and_then(|x| x.changes.as_ref())
+            .and_then(|x| x.values().next())
+            .expect("edits");
+        assert_eq!(edits[0].range.start, lsp_types::Position::new(0, 62));
+        assert_eq!(
+            edits[0].new_text,
+            "\n@prefix foaf: <http://xmlns.com/foaf/0.1/>.\n"
+        );
+    }
+}
@@ -4,12 +4,12 @@ use lsp_core::{components::*, prelude::*, systems::prefix::prefix_completion_hel
 use lsp_types::CompletionItemKind;
 use tracing::debug;
 
-use crate::{lang::model::NamedNode, TurtleLang};
+use crate::{lang::model::NamedNode, TurtleLike};
 
-pub fn turtle_lov_undefined_prefix_completion(
+pub fn turtle_lov_undefined_prefix_completion<L: TurtleLike>(
     mut query: Query<(
         &TokenComponent,
-        &Element<TurtleLang>,
+        &Element<L>,
         &Prefixes,
         &mut CompletionRequest,
     )>,
@@ -31,12 +31,8 @@ pub fn turtle_lov_undefined_prefix_completion(
     }
 }
 
-pub fn subject_completion(
-    mut query: Query<(
-        &TokenComponent,
-        &Element<TurtleLang>,
-        &mut CompletionRequest,
-    )>,
+pub fn subject_completion<L: TurtleLike>(
+    mut query: Query<(&TokenComponent, &Element<L>, &mut CompletionRequest)>,
     triples: Query<(&Triples, &Label), With<Open>>,
 ) {
     for (word, turtle, mut req) in &mut query {
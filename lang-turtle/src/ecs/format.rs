@@ -3,12 +3,17 @@ use lsp_core::{components::*, prelude::*};
 use lsp_types::{Position, Range};
 use tracing::info;
 
-use crate::{lang::formatter::format_turtle, TurtleLang};
+use crate::{lang::formatter::format_turtle, TurtleLike};
 
-pub fn format_turtle_system(
-    mut query: Query<(&RopeC, &Element<TurtleLang>, &Tokens, &mut FormatRequest), Without<Dirty>>,
+pub fn format_turtle_system<L: TurtleLike>(
+    mut query: Query<(&RopeC, &Element<L>, &Tokens, &mut FormatRequest), Without<Dirty>>,
+    config: Res<ServerConfig>,
 ) {
     info!("Format turtle system");
+    let format = &config.config.format;
+    if !format.enabled {
+        return;
+    }
 
     for (source, turtle, tokens, mut request) in &mut query {
         if request.0.is_some() {
@@ -26,7 +31,7 @@ pub fn format_turtle_system(
         let formatted = format_turtle(
             &turtle.0,
             lsp_types::FormattingOptions {
-                tab_size: 2,
+                tab_size: format.tab_size,
                 ..Default::default()
             },
             &comments,
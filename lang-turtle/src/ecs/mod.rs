@@ -1,45 +1,65 @@
 use bevy_ecs::{prelude::*, system::Query, world::World};
+use code_action::turtle_undefined_prefix_action;
 use completion::{subject_completion, turtle_lov_undefined_prefix_completion};
 use format::format_turtle_system;
 use lsp_core::prelude::*;
 use parse::{derive_triples, parse_source, parse_turtle_system};
+use symbol::turtle_document_symbols;
 
-use crate::TurtleLang;
+use crate::TurtleLike;
 
+mod code_action;
 mod completion;
 mod format;
 mod parse;
+mod symbol;
 
-pub fn setup_parsing(world: &mut World) {
+pub fn setup_parsing<L: TurtleLike>(world: &mut World) {
     use lsp_core::feature::parse::*;
     world.schedule_scope(ParseLabel, |_, schedule| {
         schedule.add_systems((
-            parse_source,
-            parse_turtle_system.after(parse_source),
-            derive_prefixes.after(parse_turtle_system).before(prefixes),
-            derive_triples.after(parse_turtle_system).before(triples),
+            parse_source::<L>,
+            parse_turtle_system::<L>.after(parse_source::<L>),
+            derive_prefixes::<L>
+                .after(parse_turtle_system::<L>)
+                .before(prefixes),
+            derive_triples::<L>
+                .after(parse_turtle_system::<L>)
+                .before(triples),
         ));
     });
 }
 
-pub fn setup_formatting(world: &mut World) {
+pub fn setup_formatting<L: TurtleLike>(world: &mut World) {
     world.schedule_scope(FormatLabel, |_, schedule| {
-        schedule.add_systems(format_turtle_system);
+        schedule.add_systems(format_turtle_system::<L>);
     });
 }
 
-pub fn setup_completion(world: &mut World) {
+pub fn setup_code_action<L: TurtleLike>(world: &mut World) {
+    world.schedule_scope(CodeActionLabel, |_, schedule| {
+        schedule.add_systems(turtle_undefined_prefix_action::<L>);
+    });
+}
+
+pub fn setup_document_symbol<L: TurtleLike>(world: &mut World) {
+    world.schedule_scope(DocumentSymbolLabel, |_, schedule| {
+        schedule.add_systems(turtle_document_symbols::<L>);
+    });
+}
+
+pub fn setup_completion<L: TurtleLike>(world: &mut World) {
     use lsp_core::feature::completion::*;
     world.schedule_scope(CompletionLabel, |_, schedule| {
         schedule.add_systems((
-            turtle_lov_undefined_prefix_completion.after(get_current_token),
-            subject_completion.after(get_current_token),
+            turtle_lov_undefined_prefix_completion::<L>.after(get_current_token),
+            subject_completion::<L>.after(get_current_token),
         ));
     });
 }
 
-fn derive_prefixes(
-    query: Query<(Entity, &Label, &Element<TurtleLang>), Changed<Element<TurtleLang>>>,
+fn derive_prefixes<L: TurtleLike>(
+    query: Query<(Entity, &Label, &Element<L>), Changed<Element<L>>>,
     mut commands: Commands,
 ) {
     for (entity, url, turtle) in &query {
@@ -164,4 +184,388 @@ foa
         assert_eq!(links[0].0.as_str(), "file:///tmp/swls/test/foaf.ttl");
         assert_eq!(links[0].1, "prefix import");
     }
+
+    #[test]
+    fn turtle_type_hierarchy() {
+        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world);
+
+        let t1 = "@prefix ex: <http://example.com/ns#>.
+@prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#>.
+ex:Animal a rdfs:Class; rdfs:label \"Animal\"; rdfs:comment \"An animal\".
+ex:Dog a rdfs:Class; rdfs:subClassOf ex:Animal; rdfs:label \"Dog\"; rdfs:comment \"A dog\".";
+        let entity = create_file(&mut world, t1, "http://example.com/ns.ttl", "turtle", Open);
+
+        world.entity_mut(entity).insert((
+            PrepareTypeHierarchyRequest::default(),
+            PositionComponent(lsp_types::Position::new(3, 2)),
+        ));
+        world.run_schedule(PrepareTypeHierarchyLabel);
+        let items = world
+            .entity_mut(entity)
+            .take::<PrepareTypeHierarchyRequest>()
+            .expect("prepare type hierarchy request")
+            .0;
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].name, "ex:Dog");
+        assert_eq!(items[0].range.start.line, 3);
+
+        let mut resolve = |item: lsp_types::TypeHierarchyItem, direction| {
+            world.insert_resource(TypeHierarchyRequest::new(item, direction));
+            world.run_schedule(TypeHierarchyLabel);
+            world
+                .remove_resource::<TypeHierarchyRequest>()
+                .expect("type hierarchy request")
+                .items
+        };
+
+        let supertypes = resolve(items[0].clone(), TypeHierarchyDirection::Supertypes);
+        assert_eq!(supertypes.len(), 1);
+        assert_eq!(supertypes[0].name, "ex:Animal");
+
+        let subtypes = resolve(supertypes[0].clone(), TypeHierarchyDirection::Subtypes);
+        assert_eq!(subtypes.len(), 1);
+        assert_eq!(subtypes[0].name, "ex:Dog");
+    }
+
+    #[test]
+    fn workspace_folders_are_indexed() {
+        use lsp_core::{Tasks, WorkspaceIndex};
+
+        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world);
+
+        let mut dir = std::env::temp_dir();
+        dir.push("swls");
+        dir.push("workspace-index");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("nested")).unwrap();
+        std::fs::write(dir.join("a.ttl"), "<a> <b> <c>.").unwrap();
+        std::fs::write(dir.join("nested").join("b.ttl"), "<d> <e> <f>.").unwrap();
+        std::fs::write(dir.join("notes.txt"), "not rdf").unwrap();
+
+        let uri = lsp_types::Url::from_directory_path(&dir).unwrap();
+        world
+            .resource_mut::<ServerConfig>()
+            .workspaces
+            .push(lsp_types::WorkspaceFolder {
+                uri,
+                name: String::from("workspace"),
+            });
+        world.run_schedule(WorkspaceIndex);
+
+        let c = world.resource::<TestClient>().clone();
+        block_on(c.await_futures(|| world.run_schedule(Tasks)));
+
+        let mut indexed: Vec<_> = world
+            .query_filtered::<(&Label, &Triples), (With<WorkspaceFile>, Without<Open>)>()
+            .iter(&world)
+            .map(|(label, triples)| {
+                let name = label.path().rsplit('/').next().unwrap().to_string();
+                (name, triples.len())
+            })
+            .collect();
+        indexed.sort();
+        assert_eq!(
+            indexed,
+            vec![(String::from("a.ttl"), 1), (String::from("b.ttl"), 1)]
+        );
+    }
+
+    #[test]
+    fn watched_files_refresh_and_remove() {
+        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world);
+
+        let url = lsp_types::Url::parse("file:///tmp/swls/watched/a.ttl").unwrap();
+        let entity = create_file(
+            &mut world,
+            "<a> <b> <c>.",
+            url.as_str(),
+            "turtle",
+            WorkspaceFile,
+        );
+
+        let content = String::from("<a> <b> <c>, <d>.");
+        systems::refresh_workspace_file(url.clone(), content)(&mut world);
+        let triples: &Triples = world.entity(entity).get().expect("triples exists");
+        assert_eq!(triples.len(), 2);
+
+        systems::remove_workspace_file(url)(&mut world);
+        assert!(world.get_entity(entity).is_none());
+    }
+
+    #[test]
+    fn removed_workspace_folders_are_despawned() {
+        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world);
+
+        let t1 = "<a> <b> <c>.";
+        let removed = create_file(
+            &mut world,
+            t1,
+            "file:///tmp/swls/removed/a.ttl",
+            "turtle",
+            WorkspaceFile,
+        );
+        let open = create_file(
+            &mut world,
+            t1,
+            "file:///tmp/swls/removed/b.ttl",
+            "turtle",
+            (Open, WorkspaceFile),
+        );
+        let kept = create_file(
+            &mut world,
+            t1,
+            "file:///tmp/swls/removed-not/c.ttl",
+            "turtle",
+            WorkspaceFile,
+        );
+
+        let folder = lsp_types::Url::parse("file:///tmp/swls/removed").unwrap();
+        systems::remove_workspace_folder(folder)(&mut world);
+
+        assert!(world.get_entity(removed).is_none());
+        assert!(!world.entity(open).contains::<WorkspaceFile>());
+        assert!(world.entity(kept).contains::<WorkspaceFile>());
+    }
+
+    #[test]
+    fn closed_documents_are_despawned_unless_kept() {
+        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world);
+
+        let t1 = "<a> <b> <c>.";
+        let scratch = create_file(&mut world, t1, "http://example.com/a.ttl", "turtle", Open);
+        let workspace = create_file(
+            &mut world,
+            t1,
+            "http://example.com/b.ttl",
+            "turtle",
+            (Open, WorkspaceFile),
+        );
+
+        assert_eq!(systems::close_document(scratch)(&mut world), None);
+        assert!(world.get_entity(scratch).is_none());
+
+        // Unsaved changes are dropped, the workspace file is read from disk again
+        let url = lsp_types::Url::parse("http://example.com/b.ttl").unwrap();
+        assert_eq!(
+            systems::close_document(workspace)(&mut world),
+            Some(url.clone())
+        );
+        let entity = world.entity(workspace);
+        assert!(!entity.contains::<Open>());
+        assert!(entity.contains::<Triples>());
+
+        systems::refresh_workspace_file(url, String::from("<a> <b> <c>, <d>."))(&mut world);
+        let triples: &Triples = world.entity(workspace).get().expect("triples exists");
+        assert_eq!(triples.len(), 2);
+    }
+
+    #[test]
+    fn configured_diagnostic_levels_apply() {
+        let (mut world, mut rx) = setup_world(TestClient::new(), crate::setup_world);
+
+        let t1 = "<a> foaf:name \"Arthur\".";
+        create_file(&mut world, t1, "http://example.com/ns#", "turtle", Open);
+        world.run_schedule(DiagnosticsLabel);
+
+        let last = |rx: &mut futures::channel::mpsc::UnboundedReceiver<DiagnosticItem>| {
+            let mut diagnostics = Vec::new();
+            while let Ok(Some(x)) = rx.try_next() {
+                diagnostics = x.diagnostics;
+            }
+            diagnostics
+        };
+        let diagnostics = last(&mut rx);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].severity,
+            Some(lsp_types::DiagnosticSeverity::ERROR)
+        );
+
+        let config: Config = serde_json::from_value(serde_json::json!({
+            "diagnostics": { "undefined_prefix": "hint" },
+            "format": { "tabSize": 4 },
+        }))
+        .unwrap();
+        assert_eq!(config.format.tab_size, 4);
+        systems::apply_config(config)(&mut world);
+
+        let diagnostics = last(&mut rx);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].severity,
+            Some(lsp_types::DiagnosticSeverity::HINT)
+        );
+
+        let config: Config = serde_json::from_value(serde_json::json!({
+            "diagnostics": { "undefined_prefix": "off" },
+        }))
+        .unwrap();
+        systems::apply_config(config)(&mut world);
+        assert!(last(&mut rx).is_empty());
+    }
+
+    #[test]
+    fn pull_diagnostics_for_closed_documents() {
+        use lsp_core::feature::diagnostics::{pull_diagnostics, result_id};
+
+        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world);
+
+        let t1 = "<a> foaf:name \"Arthur\".";
+        let entity = create_file(
+            &mut world,
+            t1,
+            "http://example.com/ns.ttl",
+            "turtle",
+            WorkspaceFile,
+        );
+
+        let diagnostics = pull_diagnostics(&mut world, &[entity]).pop().unwrap();
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics[0]
+            .message
+            .starts_with(systems::prefix::UNDEFINED_PREFIX));
+
+        let again = pull_diagnostics(&mut world, &[entity]).pop().unwrap();
+        assert_eq!(result_id(&diagnostics), result_id(&again));
+        assert!(!world.entity(entity).contains::<DiagnosticRequest>());
+    }
+
+    #[test]
+    fn language_id_decides_the_language() {
+        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world);
+
+        let rdfxml = "<?xml version=\"1.0\"?><rdf:RDF></rdf:RDF>";
+        let entity = create_file(
+            &mut world,
+            rdfxml,
+            "http://example.com/foaf.ttl",
+            "rdfxml",
+            Open,
+        );
+        assert!(!world.entity(entity).contains::<crate::TurtleLang>());
+
+        let entity = create_file(
+            &mut world,
+            "<a> <b> <c>.",
+            "http://example.com/other.ttl",
+            "plaintext",
+            Open,
+        );
+        assert!(world.entity(entity).contains::<crate::TurtleLang>());
+
+        let url = lsp_types::Url::parse("http://example.com/graphs.trig").unwrap();
+        let trig = "{ <a> <b> <c> }";
+        assert_eq!(systems::language_id_from_content(&url, None, trig), "trig");
+        let url = lsp_types::Url::parse("http://example.com/graphs").unwrap();
+        assert_eq!(
+            systems::language_id_from_content(&url, None, trig),
+            "jsonld"
+        );
+    }
+
+    #[test]
+    fn watched_files_match_indexed_extensions() {
+        let glob = systems::workspace_files_glob();
+        let extensions = glob
+            .strip_prefix("**/*.{")
+            .and_then(|x| x.strip_suffix('}'))
+            .expect("glob with extensions");
+
+        assert!(extensions.split(',').any(|x| x == "ru"));
+        for ext in extensions.split(',') {
+            let url = lsp_types::Url::parse(&format!("file:///tmp/swls/a.{}", ext)).unwrap();
+            assert!(systems::language_id_from_url(&url).is_some(), "{}", ext);
+        }
+    }
+
+    #[test]
+    fn workspace_diagnostics_only_revalidate_changed_documents() {
+        use lsp_core::feature::diagnostics::{pull_workspace_diagnostics, PulledDiagnostics};
+
+        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world);
+
+        let t1 = "<a> foaf:name \"Arthur\".";
+        let entity = create_file(
+            &mut world,
+            t1,
+            "http://example.com/ns.ttl",
+            "turtle",
+            WorkspaceFile,
+        );
+
+        let diagnostics = pull_workspace_diagnostics(&mut world, &[entity]);
+        assert_eq!(diagnostics[0].len(), 1);
+
+        // Unchanged documents return their cached diagnostics
+        world
+            .get_mut::<PulledDiagnostics>(entity)
+            .unwrap()
+            .diagnostics
+            .clear();
+        let diagnostics = pull_workspace_diagnostics(&mut world, &[entity]);
+        assert!(diagnostics[0].is_empty());
+
+        world.entity_mut(entity).insert(RopeC(Rope::from_str(t1)));
+        let diagnostics = pull_workspace_diagnostics(&mut world, &[entity]);
+        assert_eq!(diagnostics[0].len(), 1);
+    }
+
+    #[test]
+    fn current_triple_inside_quoted_triple() {
+        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world);
+
+        let t1 = "<< <a> <b> <c> >> <source> <d>.";
+        let entity = create_file(&mut world, t1, "http://example.com/ns.ttl", "turtle", Open);
+        assert!(!world.entity(entity).contains::<Dirty>());
+
+        let mut current_triple = |character| {
+            world.entity_mut(entity).insert((
+                completion::CompletionRequest(vec![]),
+                PositionComponent(lsp_types::Position::new(0, character)),
+            ));
+            world.run_schedule(CompletionLabel);
+            world
+                .entity_mut(entity)
+                .take::<TripleComponent>()
+                .expect("triple component")
+        };
+
+        let triple = current_triple(8);
+        assert_eq!(triple.target, TripleTarget::Predicate);
+        assert_eq!(triple.triple.predicate.as_str(), "http://example.com/b");
+        assert_eq!(triple.triple.span, 0..17);
+
+        let triple = current_triple(20);
+        assert_eq!(triple.target, TripleTarget::Predicate);
+        assert_eq!(
+            triple.triple.predicate.as_str(),
+            "http://example.com/source"
+        );
+        assert!(triple.triple.subject.quoted().is_some());
+    }
+
+    #[test]
+    fn subclasses_of_declared_classes_are_extracted() {
+        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world);
+
+        // Only the super class is declared as rdfs:Class, the sub class is found through
+        // `?x rdfs:subClassOf ex:Animal`
+        let t1 = "@prefix ex: <http://example.com/ns#>.
+@prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#>.
+ex:Animal a rdfs:Class.
+ex:Dog rdfs:subClassOf ex:Animal.";
+        create_file(&mut world, t1, "http://example.com/ns.ttl", "turtle", Open);
+
+        let hierarchy = world.resource::<TypeHierarchy<'static>>();
+        let animal = hierarchy
+            .get_id_ref("http://example.com/ns#Animal")
+            .expect("animal type");
+        let dog = hierarchy
+            .get_id_ref("http://example.com/ns#Dog")
+            .expect("dog type");
+
+        let supertypes: Vec<_> = hierarchy.direct_supertypes(dog).collect();
+        assert_eq!(supertypes, vec![animal]);
+        assert_eq!(hierarchy.direct_supertypes(animal).count(), 0);
+    }
 }
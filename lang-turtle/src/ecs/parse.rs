@@ -6,20 +6,23 @@ use tracing::{info, instrument};
 
 use crate::{
     lang::{
-        context::{Context, TokenIdx},
-        parser::parse_turtle,
-        tokenizer::parse_tokens_str,
+        context::{Context, Ctx, TokenIdx},
+        parser::{parse_n3, parse_trig, parse_turtle, Syntax},
+        tokenizer::{parse_n3_tokens_str, parse_tokens_str},
     },
-    TurtleLang,
+    TurtleLike,
 };
 
 #[instrument(skip(query, commands), name = "parse_source")]
-pub fn parse_source(
-    query: Query<(Entity, &Source), (Changed<Source>, With<TurtleLang>)>,
+pub fn parse_source<L: TurtleLike>(
+    query: Query<(Entity, &Source), (Changed<Source>, With<L>)>,
     mut commands: Commands,
 ) {
     for (entity, source) in &query {
-        let (tok, es) = parse_tokens_str(source.0.as_str());
+        let (tok, es) = match L::SYNTAX {
+            Syntax::N3 => parse_n3_tokens_str(source.0.as_str()),
+            Syntax::Turtle | Syntax::TriG => parse_tokens_str(source.0.as_str()),
+        };
         let t = Tokens(tok);
         commands.entity(entity).insert(t);
         commands.entity(entity).insert(Errors(es));
@@ -27,11 +30,8 @@ pub fn parse_source(
 }
 
 #[instrument(skip(query, commands, old), name = "parse_turtle")]
-pub fn parse_turtle_system(
-    query: Query<
-        (Entity, &Source, &Tokens, &Label, Option<&Open>),
-        (Changed<Tokens>, With<TurtleLang>),
-    >,
+pub fn parse_turtle_system<L: TurtleLike>(
+    query: Query<(Entity, &Source, &Tokens, &Label, Option<&Open>), (Changed<Tokens>, With<L>)>,
     mut commands: Commands,
     mut old: Local<HashMap<String, (Vec<Spanned<Token>>, Context)>>,
     config: Res<ServerConfig>,
@@ -49,14 +49,20 @@ pub fn parse_turtle_system(
             },
             old_tokens.len(),
         );
+        let parse = |ctx: Ctx<'_>| match L::SYNTAX {
+            Syntax::Turtle => parse_turtle(&label.0, tokens.0.clone(), source.0.len(), ctx),
+            Syntax::TriG => parse_trig(&label.0, tokens.0.clone(), source.0.len(), ctx),
+            Syntax::N3 => parse_n3(&label.0, tokens.0.clone(), source.0.len(), ctx),
+        };
         // First parse it without context
         // This assures that if the model is correct, the parser will parse it correctly
         let empty = Context::new();
-        let (turtle, es) = parse_turtle(&label.0, tokens.0.clone(), source.0.len(), empty.ctx());
+        let (turtle, es) = parse(empty.ctx());
         // If that didn't work, retry with the context
-        let (turtle, es) = es.is_empty().then_some((turtle, es)).unwrap_or_else(|| {
-            parse_turtle(&label.0, tokens.0.clone(), source.0.len(), context.ctx())
-        });
+        let (turtle, es) = es
+            .is_empty()
+            .then_some((turtle, es))
+            .unwrap_or_else(|| parse(context.ctx()));
 
         let es: Vec<_> = es.into_iter().map(|e| (e.map(|PToken(t, _)| t))).collect();
 
@@ -76,27 +82,30 @@ pub fn parse_turtle_system(
         turtle.set_context(context);
 
         if es.is_empty() {
-            let element = Element::<TurtleLang>(turtle);
+            let element = Element::<L>(turtle);
             commands
                 .entity(entity)
                 .insert((element, Errors(es)))
                 .remove::<Dirty>();
         } else {
-            let element = Element::<TurtleLang>(turtle);
+            let element = Element::<L>(turtle);
             commands.entity(entity).insert((Errors(es), element, Dirty));
         }
     }
 }
 
 // #[instrument(skip(query, commands), name = "derive_triples")]
-pub fn derive_triples(
-    query: Query<(Entity, &Element<TurtleLang>), (Changed<Element<TurtleLang>>, With<TurtleLang>)>,
+pub fn derive_triples<L: TurtleLike>(
+    query: Query<(Entity, &Element<L>), (Changed<Element<L>>, With<L>)>,
     mut commands: Commands,
 ) {
     for (entity, turtle) in &query {
         if let Ok(tripl) = turtle.0.get_simple_triples() {
             let triples: Vec<_> = tripl.iter().map(|x| x.to_owned()).collect();
-            commands.entity(entity).insert(Triples(triples));
+            let unasserted: Vec<_> = tripl.unasserted.iter().map(|x| x.to_owned()).collect();
+            commands
+                .entity(entity)
+                .insert((Triples(triples), UnassertedTriples(unasserted)));
         }
     }
 }
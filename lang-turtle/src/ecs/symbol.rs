@@ -0,0 +1,204 @@
+use bevy_ecs::prelude::*;
+use lsp_core::{
+    feature::document_symbol::document_symbol,
+    prelude::*,
+    systems::{DefinedClasses, DefinedProperties},
+};
+use lsp_types::SymbolKind;
+
+use crate::{
+    lang::model::{Term, Triple},
+    TurtleLike,
+};
+
+pub fn turtle_document_symbols<L: TurtleLike>(
+    mut query: Query<(
+        &Element<L>,
+        &RopeC,
+        Option<&Wrapped<DefinedClasses>>,
+        Option<&Wrapped<DefinedProperties>>,
+        &mut DocumentSymbolRequest,
+    )>,
+) {
+    for (turtle, rope, classes, properties, mut req) in &mut query {
+        let is_class = |iri: &str| {
+            classes
+                .map(|x| x.iter().any(|c| c.term.as_str() == iri))
+                .unwrap_or(false)
+        };
+        let is_property = |iri: &str| {
+            properties
+                .map(|x| x.iter().any(|p| p.predicate.as_str() == iri))
+                .unwrap_or(false)
+        };
+
+        let triple_symbol = |triple: &Spanned<Triple>| {
+            let subject = &triple.subject;
+            let kind = match subject.expand(turtle.value()) {
+                Some(iri) if is_class(&iri) => SymbolKind::CLASS,
+                Some(iri) if is_property(&iri) => SymbolKind::PROPERTY,
+                _ => match subject.value() {
+                    Term::BlankNode(_) => SymbolKind::STRUCT,
+                    _ => SymbolKind::OBJECT,
+                },
+            };
+
+            let mut symbol = document_symbol(
+                subject.to_string(),
+                kind,
+                triple.span(),
+                subject.span(),
+                &rope.0,
+            )?;
+
+            let children: Vec<_> = triple
+                .po
+                .iter()
+                .flat_map(|po| {
+                    let mut child = document_symbol(
+                        po.predicate.to_string(),
+                        SymbolKind::FIELD,
+                        po.span(),
+                        po.predicate.span(),
+                        &rope.0,
+                    )?;
+                    let objects: Vec<_> = po.object.iter().map(|x| x.to_string()).collect();
+                    child.detail = Some(objects.join(", "));
+                    Some(child)
+                })
+                .collect();
+
+            symbol.children = Some(children);
+            Some(symbol)
+        };
+
+        req.extend(turtle.triples.iter().flat_map(&triple_symbol));
+
+        // Graph blocks contain the symbols of their triples
+        for graph in &turtle.graphs {
+            let (name, selection) = match &graph.name {
+                Some(name) => (name.to_string(), name.span()),
+                None => (String::from("{ }"), graph.span()),
+            };
+
+            let Some(mut symbol) = document_symbol(
+                name,
+                SymbolKind::NAMESPACE,
+                graph.span(),
+                selection,
+                &rope.0,
+            ) else {
+                continue;
+            };
+
+            symbol.children = Some(graph.triples.iter().flat_map(&triple_symbol).collect());
+            req.push(symbol);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_core::{components::*, prelude::*};
+    use lsp_types::SymbolKind;
+    use test_utils::{create_file, setup_world, TestClient};
+
+    #[test_log::test]
+    fn turtle_outline() {
+        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world);
+
+        let t1 = "@prefix foaf: <http://xmlns.com/foaf/0.1/>.
+<a> foaf:name \"Arthur\"; foaf:knows <b>, <c>.
+[] foaf:name \"Julian\".";
+        let entity = create_file(&mut world, t1, "http://example.com/ns#", "turtle", Open);
+
+        world
+            .entity_mut(entity)
+            .insert(DocumentSymbolRequest::default());
+        world.run_schedule(DocumentSymbolLabel);
+
+        let symbols = world
+            .entity_mut(entity)
+            .take::<DocumentSymbolRequest>()
+            .expect("document symbol request")
+            .0;
+
+        assert_eq!(symbols.len(), 2);
+        assert_eq!(symbols[0].name, "<a>");
+        assert_eq!(symbols[0].kind, SymbolKind::OBJECT);
+        assert_eq!(symbols[1].kind, SymbolKind::STRUCT);
+
+        let children = symbols[0].children.as_ref().expect("children");
+        assert_eq!(children.len(), 2);
+        assert_eq!(children[0].name, "foaf:name");
+        assert_eq!(children[1].detail.as_deref(), Some("<b>, <c>"));
+    }
+
+    #[test_log::test]
+    fn turtle_outline_kinds() {
+        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world);
+
+        let t1 = "@prefix ex: <http://example.com/ns#>.
+@prefix rdf: <http://www.w3.org/1999/02/22-rdf-syntax-ns#>.
+@prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#>.
+ex:Person a rdfs:Class.
+ex:name a rdf:Property.
+ex:me ex:name \"Arthur\".";
+        let entity = create_file(&mut world, t1, "http://example.com/ns#", "turtle", Open);
+
+        world
+            .entity_mut(entity)
+            .insert(DocumentSymbolRequest::default());
+        world.run_schedule(DocumentSymbolLabel);
+
+        let symbols = world
+            .entity_mut(entity)
+            .take::<DocumentSymbolRequest>()
+            .expect("document symbol request")
+            .0;
+
+        assert_eq!(symbols.len(), 3);
+        assert_eq!(symbols[0].name, "ex:Person");
+        assert_eq!(symbols[0].kind, SymbolKind::CLASS);
+        assert_eq!(symbols[1].name, "ex:name");
+        assert_eq!(symbols[1].kind, SymbolKind::PROPERTY);
+        assert_eq!(symbols[2].kind, SymbolKind::OBJECT);
+    }
+
+    #[test_log::test]
+    fn workspace_symbols_match_iri_prefix_and_label() {
+        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world);
+
+        let t1 = "@prefix ex: <http://example.com/ns#>.
+@prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#>.
+ex:SensorObservation a rdfs:Class; rdfs:label \"Measurement\".
+ex:other ex:knows ex:SensorObservation.";
+        create_file(&mut world, t1, "http://example.com/ns.ttl", "turtle", Open);
+
+        let mut search = |query: &str| {
+            world.insert_resource(WorkspaceSymbolRequest::new(query.to_string()));
+            world.run_schedule(WorkspaceSymbolLabel);
+            world
+                .remove_resource::<WorkspaceSymbolRequest>()
+                .expect("workspace symbol request")
+                .symbols
+        };
+
+        let symbols = search("ex:SensorObs");
+        assert_eq!(symbols.len(), 1);
+        assert_eq!(symbols[0].name, "ex:SensorObservation");
+        assert_eq!(symbols[0].kind, SymbolKind::CLASS);
+        assert_eq!(symbols[0].location.range.start.line, 2);
+
+        let symbols = search("http://example.com/ns#sensor");
+        assert_eq!(symbols.len(), 1);
+
+        let symbols = search("measurement");
+        assert_eq!(symbols.len(), 1);
+        assert_eq!(symbols[0].name, "ex:SensorObservation");
+
+        let symbols = search("other");
+        assert_eq!(symbols.len(), 1);
+        assert_eq!(symbols[0].kind, SymbolKind::OBJECT);
+    }
+}
@@ -8,7 +8,9 @@ use lsp_types::FormattingOptions;
 use ropey::Rope;
 use tracing::info;
 
-use crate::lang::model::{Base, BlankNode, Term, Triple, Turtle, TurtlePrefix, PO};
+use crate::lang::model::{
+    Base, BlankNode, Formula, Graph, Quantifier, Term, Triple, Turtle, TurtlePrefix, PO,
+};
 
 #[allow(unused)]
 pub fn format(tokens: &[&Token], options: FormattingOptions) -> String {
@@ -85,6 +87,16 @@ pub fn format(tokens: &[&Token], options: FormattingOptions) -> String {
             }
             Token::BracketOpen => line += "(",
             Token::BracketClose => line += ")",
+            Token::QuotedOpen => line += "<<",
+            Token::QuotedClose => line += ">>",
+            Token::AnnotationOpen => line += "{|",
+            Token::AnnotationClose => line += "|}",
+            Token::Implies => line += "=>",
+            Token::ImpliedBy => line += "<=",
+            Token::SameAs => line += "=",
+            Token::ForAll => line += "@forAll",
+            Token::ForSome => line += "@forSome",
+            Token::Variable(x) => line += x,
             Token::DataTypeDelim => {
                 line += "^^";
             }
@@ -152,6 +164,11 @@ pub fn format(tokens: &[&Token], options: FormattingOptions) -> String {
 }
 
 type Buf = Cursor<Vec<u8>>;
+enum Block<'a> {
+    Triple(&'a Triple),
+    Graph(&'a Graph),
+}
+
 struct FormatState<'a> {
     indent_level: usize,
     indent: String,
@@ -240,16 +257,38 @@ impl FormatState<'_> {
             self.write_prefix(p)?;
             self.new_line()?;
         }
+        for q in &turtle.quantifiers {
+            self.check_comments(&q.1)?;
+            self.write_quantifier(q)?;
+            self.new_line()?;
+        }
+
+        // Graph blocks are written in between the default graph triples, in source order
+        let mut blocks: Vec<_> = turtle
+            .triples
+            .iter()
+            .map(|t| (t.1.clone(), Block::Triple(t.value())))
+            .chain(
+                turtle
+                    .graphs
+                    .iter()
+                    .map(|g| (g.1.clone(), Block::Graph(g.value()))),
+            )
+            .collect();
+        blocks.sort_by_key(|(span, _)| span.start);
 
         let mut prev_line = 0;
 
-        for t in &turtle.triples {
+        for (span, block) in blocks {
             if prev_line + 1 < self.line_count {
                 self.new_line()?;
             }
             prev_line = self.line_count;
-            self.check_comments(&t.1)?;
-            self.write_triple(&t)?;
+            self.check_comments(&span)?;
+            match block {
+                Block::Triple(t) => self.write_triple(t)?,
+                Block::Graph(g) => self.write_graph(g)?,
+            }
             self.new_line()?;
             // request_newline = t.0.po.len() > 1 || t.0.po[0].0.object.len() > 1;
         }
@@ -271,6 +310,37 @@ impl FormatState<'_> {
         write!(self.buf, "@base {}.", base.1 .0)
     }
 
+    fn write_quantifier(&mut self, quantifier: &Quantifier) -> io::Result<()> {
+        let keyword = if quantifier.universal {
+            "@forAll"
+        } else {
+            "@forSome"
+        };
+        write!(self.buf, "{}", keyword)?;
+        for (i, t) in quantifier.terms.iter().enumerate() {
+            write!(self.buf, "{}", if i == 0 { " " } else { ", " })?;
+            self.write_term(t)?;
+        }
+        write!(self.buf, ".")
+    }
+
+    fn write_formula(&mut self, formula: &Formula) -> io::Result<()> {
+        if formula.triples.is_empty() {
+            return write!(self.buf, "{{ }}");
+        }
+
+        write!(self.buf, "{{")?;
+        self.inc();
+        for t in &formula.triples {
+            self.new_line()?;
+            self.check_comments(&t.1)?;
+            self.write_triple(&t)?;
+        }
+        self.decr();
+        self.new_line()?;
+        write!(self.buf, "}}")
+    }
+
     fn write_bnode(&mut self, bnode: &BlankNode) -> io::Result<()> {
         match bnode {
             BlankNode::Named(x, _) => write!(self.buf, "_:{}", x)?,
@@ -358,32 +428,50 @@ impl FormatState<'_> {
             Term::BlankNode(b) => self.write_bnode(b)?,
             Term::NamedNode(n) => write!(self.buf, "{}", n)?,
             Term::Collection(ts) => self.write_collection(ts)?,
+            Term::Triple(triple) => {
+                write!(self.buf, "<< ")?;
+                self.write_term(&triple.subject)?;
+                write!(self.buf, " ")?;
+                self.write_term(&triple.predicate)?;
+                write!(self.buf, " ")?;
+                self.write_term(&triple.object)?;
+                write!(self.buf, " >>")?;
+            }
             Term::Invalid => {
                 return Err(io::Error::new(
                     io::ErrorKind::Other,
                     "cannot format turtle with invalid terms",
                 ))
             }
-            Term::Variable(_) => {
-                return Err(io::Error::new(
-                    io::ErrorKind::Other,
-                    "cannot format turtle with variables",
-                ))
+            Term::Variable(x) => write!(self.buf, "{}", x.0)?,
+            Term::Formula(formula) => self.write_formula(formula)?,
+        }
+        Ok(())
+    }
+
+    fn write_object(&mut self, po: &PO, i: usize) -> io::Result<()> {
+        self.write_term(&po.object[i])?;
+        if let Some(annotation) = po.annotation(i) {
+            write!(self.buf, " {{|")?;
+            for (j, po) in annotation.po.iter().enumerate() {
+                write!(self.buf, "{}", if j == 0 { " " } else { "; " })?;
+                self.write_po(po)?;
             }
+            write!(self.buf, " |}}")?;
         }
         Ok(())
     }
 
     fn write_po(&mut self, po: &PO) -> io::Result<()> {
         write!(self.buf, "{} ", po.predicate.0)?;
-        self.write_term(&po.object[0])?;
+        self.write_object(po, 0)?;
         let mut should_indent = false;
 
         let start = self.buf.position();
         let current_line = self.line_count;
         for i in 1..po.object.len() {
             write!(self.buf, ", ")?;
-            self.write_term(&po.object[i])?;
+            self.write_object(po, i)?;
 
             if self.current_line_length() > 80 {
                 should_indent = true;
@@ -399,7 +487,7 @@ impl FormatState<'_> {
                 write!(self.buf, ",")?;
                 self.new_line()?;
                 self.check_comments(&po.object[i].1)?;
-                self.write_term(&po.object[i])?;
+                self.write_object(po, i)?;
             }
             self.decr();
         }
@@ -407,10 +495,37 @@ impl FormatState<'_> {
         Ok(())
     }
 
+    fn write_graph(&mut self, graph: &Graph) -> io::Result<()> {
+        if let Some(name) = &graph.name {
+            write!(self.buf, "GRAPH ")?;
+            match &name.0 {
+                Term::BlankNode(bn) => self.write_bnode(bn)?,
+                Term::NamedNode(n) => write!(self.buf, "{}", n)?,
+                _ => write!(self.buf, "invalid")?,
+            }
+            write!(self.buf, " ")?;
+        }
+        write!(self.buf, "{{")?;
+
+        self.inc();
+        for t in &graph.triples {
+            self.new_line()?;
+            self.check_comments(&t.1)?;
+            self.write_triple(&t)?;
+        }
+        self.decr();
+
+        self.new_line()?;
+        write!(self.buf, "}}")
+    }
+
     fn write_triple(&mut self, triple: &Triple) -> io::Result<()> {
         match &triple.subject.0 {
             Term::BlankNode(bn) => self.write_bnode(bn)?,
             Term::NamedNode(n) => write!(self.buf, "{}", n)?,
+            Term::Triple(_) | Term::Formula(_) | Term::Variable(_) => {
+                self.write_term(&triple.subject)?
+            }
             _ => write!(self.buf, "invalid")?,
         }
         write!(self.buf, " ")?;
@@ -472,8 +587,11 @@ mod tests {
     use ropey::Rope;
 
     use crate::lang::{
-        context::Context, formatter::format_turtle, model::Turtle, parser as parser2,
-        tokenizer::parse_tokens_str_safe,
+        context::Context,
+        formatter::format_turtle,
+        model::Turtle,
+        parser as parser2,
+        tokenizer::{parse_n3_tokens_str, parse_tokens_str_safe},
     };
 
     #[derive(Debug)]
@@ -808,4 +926,118 @@ mod tests {
         .expect("formatting");
         assert_eq!(formatted, expected);
     }
+
+    #[test]
+    fn format_graphs() {
+        let txt = r#"
+@prefix foaf: <http://xmlns.com/foaf/0.1/> .
+<a> foaf:knows <b> .
+GRAPH <g> { <a> foaf:name "A" . <b> foaf:name "B" }
+{ <c> a foaf:Person }
+"#;
+
+        let expected = r#"@prefix foaf: <http://xmlns.com/foaf/0.1/>.
+<a> foaf:knows <b>.
+GRAPH <g> {
+  <a> foaf:name "A".
+  <b> foaf:name "B".
+}
+
+{
+  <c> a foaf:Person.
+}
+
+"#;
+
+        let url = lsp_types::Url::from_str("http://example.com/ns#").unwrap();
+        let context = Context::new();
+        let tokens = parse_tokens_str_safe(txt).expect("tokenizing");
+        let (output, errs) = parser2::parse_trig(&url, tokens, txt.len(), context.ctx());
+        assert!(errs.is_empty());
+
+        let formatted = format_turtle(
+            output.value(),
+            lsp_types::FormattingOptions {
+                tab_size: 2,
+                ..Default::default()
+            },
+            &[],
+            &Rope::from_str(txt),
+        )
+        .expect("formatting");
+        assert_eq!(formatted, expected);
+    }
+
+    #[test]
+    fn format_quoted_triples() {
+        let txt = r#"
+@prefix ex: <http://example.com/> .
+<<ex:a ex:b ex:c>> ex:source ex:d .
+ex:a ex:b ex:c {|ex:certainty 0.9;ex:source ex:e|} .
+"#;
+
+        let expected = r#"@prefix ex: <http://example.com/>.
+<< ex:a ex:b ex:c >> ex:source ex:d.
+ex:a ex:b ex:c {| ex:certainty 0.9; ex:source ex:e |}.
+
+"#;
+
+        let url = lsp_types::Url::from_str("http://example.com/ns#").unwrap();
+        let (output, comments) = parse_turtle(txt, &url).expect("Simple");
+        let formatted = format_turtle(
+            &output,
+            lsp_types::FormattingOptions {
+                tab_size: 2,
+                ..Default::default()
+            },
+            &comments,
+            &Rope::from_str(txt),
+        )
+        .expect("formatting");
+        assert_eq!(formatted, expected);
+    }
+
+    #[test]
+    fn format_n3_formulae() {
+        let txt = r#"
+@prefix : <http://example.com/> .
+@forAll ?x .
+{ ?x a :Person. ?x :knows [ ] } => { ?x a :Agent } .
+{} => {?x = :b}.
+"#;
+
+        let expected = r#"@prefix : <http://example.com/>.
+@forAll ?x.
+
+{
+  ?x a :Person.
+  ?x :knows [ ].
+} => {
+  ?x a :Agent.
+}.
+
+{ } => {
+  ?x = :b.
+}.
+
+"#;
+
+        let url = lsp_types::Url::from_str("http://example.com/ns#").unwrap();
+        let context = Context::new();
+        let (tokens, _) = parse_n3_tokens_str(txt);
+        let (output, errors) = parser2::parse_n3(&url, tokens, txt.len(), context.ctx());
+        assert!(errors.is_empty());
+
+        let formatted = format_turtle(
+            output.value(),
+            lsp_types::FormattingOptions {
+                tab_size: 2,
+                ..Default::default()
+            },
+            &[],
+            &Rope::from_str(txt),
+        )
+        .expect("formatting");
+        assert_eq!(formatted, expected);
+    }
 }
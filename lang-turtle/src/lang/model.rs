@@ -83,6 +83,12 @@ pub enum NamedNode {
         idx: usize,
     },
     A(usize),
+    /// N3 `=>`, short for `log:implies`.
+    Implies(usize),
+    /// N3 `<=`, `log:implies` with subject and object swapped.
+    ImpliedBy(usize),
+    /// N3 `=`, short for `owl:sameAs`.
+    SameAs(usize),
     Invalid,
 }
 
@@ -121,6 +127,10 @@ impl NamedNode {
                 Some(format!("{}{}", expaned, value))
             }
             Self::A(_) => Some("http://www.w3.org/1999/02/22-rdf-syntax-ns#type".to_string()),
+            Self::Implies(_) | Self::ImpliedBy(_) => {
+                Some("http://www.w3.org/2000/10/swap/log#implies".to_string())
+            }
+            Self::SameAs(_) => Some("http://www.w3.org/2002/07/owl#sameAs".to_string()),
             Self::Invalid => None,
         }
     }
@@ -136,6 +146,9 @@ impl Display for NamedNode {
                 idx: _,
             } => write!(f, "{}:{}", prefix, value),
             NamedNode::A(_) => write!(f, "a"),
+            NamedNode::Implies(_) => write!(f, "=>"),
+            NamedNode::ImpliedBy(_) => write!(f, "<="),
+            NamedNode::SameAs(_) => write!(f, "="),
             NamedNode::Invalid => write!(f, "invalid"),
         }
     }
@@ -188,6 +201,123 @@ impl Display for BlankNode {
     }
 }
 
+/// RDF 1.2 quoted triple `<< s p o >>`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QuotedTriple {
+    pub subject: Spanned<Term>,
+    pub predicate: Spanned<Term>,
+    pub object: Spanned<Term>,
+    // Token index of `<<` and `>>`, `>>` can be missing
+    pub start: usize,
+    pub end: Option<usize>,
+}
+
+impl QuotedTriple {
+    pub fn fix_spans(&mut self, len: usize) {
+        for term in [&mut self.subject, &mut self.predicate, &mut self.object] {
+            term.1 = rev_range(&term.1, len);
+            term.0.fix_spans(len);
+        }
+    }
+
+    pub fn set_context(&self, ctx: &mut Context, kind: ContextKind) {
+        ctx.add(self.start, kind);
+        if let Some(end) = self.end {
+            ctx.add(end, kind);
+        }
+        self.subject.set_context(ctx, ContextKind::Subject);
+        self.predicate.set_context(ctx, ContextKind::Predicate);
+        self.object.set_context(ctx, ContextKind::Object);
+    }
+}
+
+impl Display for QuotedTriple {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "<< {} {} {} >>",
+            self.subject.value(),
+            self.predicate.value(),
+            self.object.value()
+        )
+    }
+}
+
+/// N3 formula `{ ... }`, a graph that can be used as a term.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Formula {
+    pub triples: Vec<Spanned<Triple>>,
+    // Token index of `{` and `}`, `}` can be missing
+    pub start: usize,
+    pub end: Option<usize>,
+}
+
+impl Formula {
+    pub fn fix_spans(&mut self, len: usize) {
+        self.triples.iter_mut().for_each(|span| {
+            span.1 = rev_range(&span.1, len);
+            span.0.fix_spans(len);
+        });
+    }
+
+    pub fn set_context(&self, ctx: &mut Context, kind: ContextKind) {
+        ctx.add(self.start, kind);
+        if let Some(end) = self.end {
+            ctx.add(end, kind);
+        }
+        for t in &self.triples {
+            t.set_context(ctx);
+        }
+    }
+}
+
+impl Display for Formula {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.triples.is_empty() {
+            return write!(f, "{{ }}");
+        }
+
+        write!(f, "{{")?;
+        for t in &self.triples {
+            write!(f, " {}", t.value())?;
+        }
+        write!(f, " }}")
+    }
+}
+
+/// N3 quantification, `@forAll :x, :y .` or `@forSome :x .`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Quantifier {
+    /// `@forAll` when set, `@forSome` otherwise.
+    pub universal: bool,
+    pub terms: Vec<Spanned<Term>>,
+}
+
+impl Quantifier {
+    pub fn fix_spans(&mut self, len: usize) {
+        self.terms.iter_mut().for_each(|span| {
+            span.1 = rev_range(&span.1, len);
+            span.0.fix_spans(len);
+        });
+    }
+}
+
+impl Display for Quantifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let keyword = if self.universal {
+            "@forAll"
+        } else {
+            "@forSome"
+        };
+        write!(f, "{}", keyword)?;
+        for (i, t) in self.terms.iter().enumerate() {
+            let sep = if i == 0 { " " } else { ", " };
+            write!(f, "{}{}", sep, t.value())?;
+        }
+        write!(f, " .")
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Term {
     Literal(Literal),
@@ -195,6 +325,8 @@ pub enum Term {
     NamedNode(NamedNode),
     Collection(Vec<Spanned<Term>>),
     Variable(Variable),
+    Triple(Box<QuotedTriple>),
+    Formula(Formula),
     Invalid,
 }
 
@@ -218,13 +350,20 @@ impl Term {
             | Term::Variable(Variable(_, idx))
             | Term::NamedNode(NamedNode::Full(_, idx))
             | Term::NamedNode(NamedNode::A(idx))
+            | Term::NamedNode(NamedNode::Implies(idx))
+            | Term::NamedNode(NamedNode::ImpliedBy(idx))
+            | Term::NamedNode(NamedNode::SameAs(idx))
             | Term::NamedNode(NamedNode::Prefixed { idx, .. }) => ctx.add(*idx, kind),
+            Term::Triple(triple) => triple.set_context(ctx, kind),
+            Term::Formula(formula) => formula.set_context(ctx, kind),
             _ => {}
         }
     }
     pub fn fix_spans(&mut self, len: usize) {
         match self {
             Term::BlankNode(bn) => bn.fix_spans(len),
+            Term::Triple(triple) => triple.fix_spans(len),
+            Term::Formula(formula) => formula.fix_spans(len),
             Term::Collection(pos) => {
                 pos.iter_mut().for_each(|span| {
                     span.1 = rev_range(&span.1, len);
@@ -246,13 +385,29 @@ impl Term {
         match self {
             Term::BlankNode(_) => true,
             Term::Variable(_) => true,
-            Term::NamedNode(NamedNode::A(_)) => false,
+            Term::NamedNode(NamedNode::A(_))
+            | Term::NamedNode(NamedNode::Implies(_))
+            | Term::NamedNode(NamedNode::ImpliedBy(_))
+            | Term::NamedNode(NamedNode::SameAs(_)) => false,
             Term::NamedNode(_) => true,
             Term::Invalid => true,
             Term::Collection(_) => true,
+            Term::Triple(_) => true,
+            Term::Formula(_) => true,
             _ => false,
         }
     }
+
+    /// Terms that can be used inside a quoted triple: no collections or blank node property
+    /// lists.
+    pub fn is_quotable(&self) -> bool {
+        match self {
+            Term::BlankNode(BlankNode::Unnamed(pos, _, _)) => pos.is_empty(),
+            Term::Collection(_) => false,
+            Term::Formula(_) => false,
+            _ => true,
+        }
+    }
     pub fn is_predicate(&self) -> bool {
         match self {
             Term::NamedNode(_) => true,
@@ -264,7 +419,10 @@ impl Term {
 
     pub fn is_object(&self) -> bool {
         match self {
-            Term::NamedNode(NamedNode::A(_)) => false,
+            Term::NamedNode(NamedNode::A(_))
+            | Term::NamedNode(NamedNode::Implies(_))
+            | Term::NamedNode(NamedNode::ImpliedBy(_))
+            | Term::NamedNode(NamedNode::SameAs(_)) => false,
             Term::Variable(_) => true,
             Term::Invalid => true,
             Term::Collection(_) => true,
@@ -286,6 +444,8 @@ impl Term {
             Term::Collection(_) => "collection",
             Term::Invalid => "invalid",
             Term::Variable(_) => "variable",
+            Term::Triple(_) => "quoted triple",
+            Term::Formula(_) => "formula",
         }
     }
     pub fn expand<T: Based>(&self, turtle: &T) -> Option<String> {
@@ -316,6 +476,8 @@ impl Display for Term {
             }
             Term::Invalid => write!(f, "invalid"),
             Term::Variable(x) => write!(f, "{}", x.0),
+            Term::Triple(x) => x.fmt(f),
+            Term::Formula(x) => x.fmt(f),
         }
     }
 }
@@ -362,6 +524,8 @@ impl Display for Triple {
 pub struct PO {
     pub predicate: Spanned<Term>,
     pub object: Vec<Spanned<Term>>,
+    /// Annotations of the objects, `:s :p :o {| :q :r |}`.
+    pub annotations: Vec<Spanned<Annotation>>,
 }
 impl PO {
     pub fn fix_spans(&mut self, len: usize) {
@@ -372,6 +536,11 @@ impl PO {
             span.1 = rev_range(&span.1, len);
             span.0.fix_spans(len);
         });
+
+        self.annotations.iter_mut().for_each(|span| {
+            span.1 = rev_range(&span.1, len);
+            span.0.fix_spans(len);
+        });
     }
 
     pub fn set_context(&self, ctx: &mut Context) {
@@ -380,21 +549,110 @@ impl PO {
         for o in &self.object {
             o.set_context(ctx, ContextKind::Object);
         }
+
+        for a in &self.annotations {
+            a.set_context(ctx);
+        }
+    }
+
+    /// Annotation of the object at index `object`.
+    pub fn annotation(&self, object: usize) -> Option<&Spanned<Annotation>> {
+        self.annotations.iter().find(|x| x.object == object)
     }
 }
 
 impl Display for PO {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{} {}", self.predicate.value(), self.object[0].value())?;
+        write!(f, "{}", self.predicate.value())?;
 
-        for po in &self.object[1..] {
-            write!(f, ", {}", po.value())?;
+        for (i, o) in self.object.iter().enumerate() {
+            let sep = if i == 0 { " " } else { ", " };
+            write!(f, "{}{}", sep, o.value())?;
+            if let Some(annotation) = self.annotation(i) {
+                write!(f, " {}", annotation.value())?;
+            }
         }
 
         Ok(())
     }
 }
 
+/// RDF 1.2 annotation `{| ... |}`, the predicate objects describe the annotated triple.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Annotation {
+    /// Index of the annotated object in [`PO::object`].
+    pub object: usize,
+    pub po: Vec<Spanned<PO>>,
+}
+
+impl Annotation {
+    pub fn fix_spans(&mut self, len: usize) {
+        self.po.iter_mut().for_each(|span| {
+            span.1 = rev_range(&span.1, len);
+            span.0.fix_spans(len);
+        });
+    }
+
+    pub fn set_context(&self, ctx: &mut Context) {
+        for po in &self.po {
+            po.set_context(ctx);
+        }
+    }
+}
+
+impl Display for Annotation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{{|")?;
+        for (i, po) in self.po.iter().enumerate() {
+            let sep = if i == 0 { " " } else { "; " };
+            write!(f, "{}{}", sep, po.value())?;
+        }
+        write!(f, " |}}")
+    }
+}
+
+/// TriG graph block, `GRAPH <name> { ... }` or `{ ... }` for the default graph.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Graph {
+    pub name: Option<Spanned<Term>>,
+    pub triples: Vec<Spanned<Triple>>,
+}
+
+impl Graph {
+    pub fn fix_spans(&mut self, len: usize) {
+        self.name.iter_mut().for_each(|name| {
+            name.1 = rev_range(&name.1, len);
+            name.0.fix_spans(len);
+        });
+
+        self.triples.iter_mut().for_each(|span| {
+            span.1 = rev_range(&span.1, len);
+            span.0.fix_spans(len);
+        });
+    }
+
+    pub fn set_context(&self, ctx: &mut Context) {
+        for t in &self.triples {
+            t.set_context(ctx);
+        }
+    }
+}
+
+impl Display for Graph {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(name) = &self.name {
+            write!(f, "GRAPH {} ", name.value())?;
+        }
+        writeln!(f, "{{")?;
+
+        for t in &self.triples {
+            writeln!(f, "{}", t.value())?;
+        }
+
+        write!(f, "}}")
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Base(pub Range<usize>, pub Spanned<NamedNode>);
 impl Display for Base {
@@ -460,6 +718,10 @@ pub struct Turtle {
     pub set_base: lsp_types::Url,
     pub prefixes: Vec<Spanned<TurtlePrefix>>,
     pub triples: Vec<Spanned<Triple>>,
+    /// Graph blocks, only found in TriG documents.
+    pub graphs: Vec<Spanned<Graph>>,
+    /// Quantifications, only found in N3 documents.
+    pub quantifiers: Vec<Spanned<Quantifier>>,
 }
 impl Based for Turtle {
     fn get_base(&self) -> &lsp_types::Url {
@@ -485,9 +747,21 @@ impl Turtle {
             base.1 = rev_range(&base.1, len);
             base.0.fix_spans(len);
         });
+        self.graphs.iter_mut().for_each(|base| {
+            base.1 = rev_range(&base.1, len);
+            base.0.fix_spans(len);
+        });
+        self.quantifiers.iter_mut().for_each(|base| {
+            base.1 = rev_range(&base.1, len);
+            base.0.fix_spans(len);
+        });
     }
 
-    pub fn into_triples<'a>(&self, triples: Vec<MyQuad<'a>>) -> Triples2<'a> {
+    pub fn into_triples<'a>(
+        &self,
+        triples: Vec<MyQuad<'a>>,
+        unasserted: Vec<MyQuad<'a>>,
+    ) -> Triples2<'a> {
         let base = match &self.base {
             Some(Spanned(Base(_, Spanned(named_node, span)), _)) => named_node
                 .expand_step(self, HashSet::new())
@@ -498,6 +772,7 @@ impl Turtle {
         let base_url = self.set_base.to_string();
         Triples2 {
             triples,
+            unasserted,
             base,
             base_url,
         }
@@ -506,6 +781,11 @@ impl Turtle {
 
 pub struct TriplesBuilder<'a, T> {
     pub triples: Vec<MyQuad<'a>>,
+    /// Triples that are mentioned but not asserted, like the triples of N3 formulae.
+    pub unasserted: Vec<MyQuad<'a>>,
+    /// Graph name given to the ingested triples, `None` for the default graph.
+    pub graph: Option<MyTerm<'a>>,
+    in_formula: bool,
     blank_node: Box<dyn FnMut(std::ops::Range<usize>) -> MyTerm<'a>>,
     base: BaseIri<String>,
     based: &'a T,
@@ -520,12 +800,23 @@ impl<'a, T: Based> TriplesBuilder<'a, T> {
         });
         Self {
             triples: vec![],
+            unasserted: vec![],
+            graph: None,
+            in_formula: false,
             blank_node,
             base,
             based,
         }
     }
 
+    fn push(&mut self, quad: MyQuad<'a>) {
+        if self.in_formula {
+            self.unasserted.push(quad);
+        } else {
+            self.triples.push(quad);
+        }
+    }
+
     fn handle_po(
         &mut self,
         pos: &'a [Spanned<PO>],
@@ -540,14 +831,20 @@ impl<'a, T: Based> TriplesBuilder<'a, T> {
                 subject: subject.clone(),
                 predicate: predicate.clone(),
                 object,
+                graph: self.graph.clone(),
                 span: span.clone(),
             };
 
-            self.triples.push(quad);
+            self.push(quad);
         }
         let mut first = true;
 
-        for Spanned(PO { predicate, object }, span2) in pos.iter() {
+        for Spanned(po, span2) in pos.iter() {
+            let PO {
+                predicate, object, ..
+            } = po;
+            // `:a <= :b` states `:b => :a`
+            let reversed = matches!(predicate.value(), Term::NamedNode(NamedNode::ImpliedBy(_)));
             let this_span = if first {
                 first = false;
                 span.clone()
@@ -574,7 +871,7 @@ impl<'a, T: Based> TriplesBuilder<'a, T> {
             };
 
             let mut first_object = true;
-            for o in object.iter() {
+            for (i, o) in object.iter().enumerate() {
                 let this_span = if first_object {
                     first_object = false;
                     this_span.clone()
@@ -583,14 +880,33 @@ impl<'a, T: Based> TriplesBuilder<'a, T> {
                 };
                 let object = self.term_to_my_term(Ok(o.as_ref()))?;
 
+                let (s, o2) = if reversed {
+                    (object.clone(), subject.clone())
+                } else {
+                    (subject.clone(), object.clone())
+                };
                 let quad = MyQuad {
-                    subject: subject.clone(),
+                    subject: s,
                     predicate: predicate.clone(),
-                    object,
+                    object: o2,
+                    graph: self.graph.clone(),
                     span: this_span,
                 };
 
-                self.triples.push(quad);
+                self.push(quad);
+
+                // The annotated triple is asserted and described by the annotation
+                if let Some(Spanned(annotation, span)) = po.annotation(i) {
+                    if !annotation.po.is_empty() {
+                        let quoted = MyTerm::quoted_triple(
+                            subject.clone(),
+                            predicate.clone(),
+                            object,
+                            o.span().clone(),
+                        );
+                        self.handle_po(&annotation.po, span.clone(), quoted)?;
+                    }
+                }
             }
         }
         Ok(())
@@ -633,6 +949,13 @@ impl<'a, T: Based> TriplesBuilder<'a, T> {
                 }
             },
             Ok(Spanned(Term::Collection(terms), span)) => self.handle_collection(&terms, span)?,
+            Ok(Spanned(Term::Triple(triple), span)) => {
+                let subject = self.term_to_my_term(Ok(triple.subject.as_ref()))?;
+                let predicate = self.term_to_my_term(Ok(triple.predicate.as_ref()))?;
+                let object = self.term_to_my_term(Ok(triple.object.as_ref()))?;
+                MyTerm::quoted_triple(subject, predicate, object, span)
+            }
+            Ok(Spanned(Term::Formula(formula), span)) => self.handle_formula(formula, span)?,
             Ok(Spanned(Term::Invalid, span)) => MyTerm::invalid(span),
             Err(x) => x,
         };
@@ -640,6 +963,25 @@ impl<'a, T: Based> TriplesBuilder<'a, T> {
         Ok(object)
     }
 
+    /// A formula is a blank node naming the graph that holds its triples.
+    ///
+    /// These triples are quoted, they end up in [`TriplesBuilder::unasserted`].
+    fn handle_formula(
+        &mut self,
+        formula: &'a Formula,
+        span: std::ops::Range<usize>,
+    ) -> Result<MyTerm<'a>, TurtleSimpleError> {
+        let name = (self.blank_node)(span);
+        let outer = self.graph.replace(name.clone());
+        let outer_formula = std::mem::replace(&mut self.in_formula, true);
+
+        let out = formula.triples.iter().try_for_each(|t| self.ingest(t));
+
+        self.in_formula = outer_formula;
+        self.graph = outer;
+        out.map(|_| name)
+    }
+
     fn handle_collection(
         &mut self,
         collection: &'a [Spanned<Term>],
@@ -660,10 +1002,11 @@ impl<'a, T: Based> TriplesBuilder<'a, T> {
                     prev.span.start..prev.span.start,
                 ),
                 object: self.term_to_my_term(Ok(Spanned(term, s.clone())))?,
+                graph: self.graph.clone(),
                 span: span.clone(),
             };
 
-            self.triples.push(quad);
+            self.push(quad);
 
             let quad = MyQuad {
                 subject: next.clone(),
@@ -672,10 +1015,11 @@ impl<'a, T: Based> TriplesBuilder<'a, T> {
                     s.start..s.start,
                 ),
                 object: prev,
+                graph: self.graph.clone(),
                 span: s.clone(),
             };
 
-            self.triples.push(quad);
+            self.push(quad);
             prev = next;
         }
 
@@ -711,6 +1055,9 @@ impl<'a, T: Based> TriplesBuilder<'a, T> {
             ),
             Term::Invalid => MyTerm::invalid(triple.subject.span().clone()),
             Term::Variable(var) => MyTerm::variable(&var.0, triple.subject.span().clone()),
+            Term::Triple(_) | Term::Formula(_) => {
+                self.term_to_my_term(Ok(triple.subject.as_ref()))?
+            }
 
             x => {
                 info!("Failed, unexpected {}", x.ty());
@@ -725,6 +1072,43 @@ impl<'a, T: Based> TriplesBuilder<'a, T> {
 
         Ok(())
     }
+
+    /// Subject or object term, the triples of blank node property lists and collections are
+    /// ingested.
+    pub fn term(&mut self, term: &'a Spanned<Term>) -> Result<MyTerm<'a>, TurtleSimpleError> {
+        self.term_to_my_term(Ok(term.as_ref()))
+    }
+
+    /// Expanded named node, invalid when it cannot be expanded.
+    pub fn named_node(&self, node: &NamedNode, span: std::ops::Range<usize>) -> MyTerm<'a> {
+        node.expand_step(self.based, HashSet::new())
+            .and_then(|n| self.base.resolve(n.as_str()).ok())
+            .map(|x| MyTerm::named_node(x.unwrap(), span.clone()))
+            .unwrap_or_else(|| MyTerm::invalid(span))
+    }
+
+    /// A fresh blank node, like the ones of blank node property lists.
+    pub fn new_blank_node(&mut self, span: std::ops::Range<usize>) -> MyTerm<'a> {
+        (self.blank_node)(span)
+    }
+
+    /// Ingest the triples of a graph block, using its name as graph name.
+    pub fn ingest_graph(
+        &mut self,
+        Spanned(ref graph, _): &'a Spanned<Graph>,
+    ) -> Result<(), TurtleSimpleError> {
+        self.graph = match &graph.name {
+            Some(name) => Some(self.term_to_my_term(Ok(name.as_ref()))?),
+            None => None,
+        };
+
+        for t in &graph.triples {
+            self.ingest(t)?;
+        }
+
+        self.graph = None;
+        Ok(())
+    }
 }
 
 impl Turtle {
@@ -750,7 +1134,11 @@ impl Turtle {
             builder.ingest(&t)?;
         }
 
-        Ok(self.into_triples(builder.triples))
+        for g in &self.graphs {
+            builder.ingest_graph(g)?;
+        }
+
+        Ok(self.into_triples(builder.triples, builder.unasserted))
     }
 }
 
@@ -768,6 +1156,8 @@ impl Turtle {
             base,
             prefixes,
             triples,
+            graphs: Vec::new(),
+            quantifiers: Vec::new(),
             set_base: location.clone(),
         }
     }
@@ -776,6 +1166,10 @@ impl Turtle {
         for t in &self.triples {
             t.set_context(ctx);
         }
+
+        for g in &self.graphs {
+            g.set_context(ctx);
+        }
     }
 
     pub fn get_base(&self) -> &lsp_types::Url {
@@ -806,6 +1200,11 @@ impl Display for Turtle {
             .map(|x| x.value())
             .try_for_each(|x| writeln!(f, "{}", x))?;
 
+        self.graphs
+            .iter()
+            .map(|x| x.value())
+            .try_for_each(|x| writeln!(f, "{}", x))?;
+
         Ok(())
     }
 }
@@ -817,7 +1216,12 @@ mod test {
     use lsp_core::prelude::{spanned, MyQuad, Spanned};
 
     use super::Turtle;
-    use crate::lang::{context::Context, parser as parser2, tokenizer::parse_tokens_str_safe};
+    use crate::lang::{
+        context::Context,
+        parser as parser2,
+        parser::parse_n3,
+        tokenizer::{parse_n3_tokens_str, parse_tokens_str_safe},
+    };
 
     #[derive(Debug)]
     pub enum Err {
@@ -953,4 +1357,111 @@ _:internal_bnode_1 <http://www.w3.org/1999/02/22-rdf-syntax-ns#first> <http://ex
         let (output, _) = parse_turtle(txt, &url).expect("Simple collection");
         output.get_simple_triples().expect("Triples found");
     }
+
+    #[test]
+    fn graph_triples() {
+        let txt = r#"
+@prefix foaf: <http://xmlns.com/foaf/0.1/> .
+<a> foaf:knows <b> .
+GRAPH <g> { <a> foaf:name "A" . <b> foaf:name "B" }
+_:g2 { <c> a foaf:Person . }
+"#;
+
+        let url = lsp_types::Url::from_str("http://example.com/ns#").unwrap();
+        let tokens = parse_tokens_str_safe(txt).expect("tokenizing");
+        let context = Context::new();
+        let (output, errs) = parser2::parse_trig(&url, tokens, txt.len(), context.ctx());
+        assert!(errs.is_empty());
+        assert_eq!(output.graphs.len(), 2);
+
+        let triples = output.get_simple_triples().expect("Triples found");
+        let graphs: Vec<_> = triples
+            .iter()
+            .map(|t| t.graph.as_ref().map(|g| g.to_string()))
+            .collect();
+
+        assert_eq!(
+            graphs,
+            vec![
+                None,
+                Some(String::from("<http://example.com/g>")),
+                Some(String::from("<http://example.com/g>")),
+                Some(String::from("_:g2")),
+            ]
+        );
+    }
+
+    #[test]
+    fn quoted_triples_and_annotations() {
+        let txt = r#"
+@prefix ex: <http://example.com/> .
+<< ex:a ex:b ex:c >> ex:source ex:d .
+ex:a ex:b ex:c {| ex:certainty 0.9 |} .
+"#;
+
+        let url = lsp_types::Url::from_str("http://example.com/ns#").unwrap();
+        let (output, _) = parse_turtle(txt, &url).expect("quoted triples");
+        let triples = output.get_simple_triples().expect("Triples found");
+        let triples: Vec<_> = triples
+            .iter()
+            .map(|t| format!("{} {} {}", t.subject, t.predicate, t.object))
+            .collect();
+
+        let quoted = "<< <http://example.com/a> <http://example.com/b> <http://example.com/c> >>";
+        assert_eq!(
+            triples,
+            vec![
+                format!(
+                    "{} <http://example.com/source> <http://example.com/d>",
+                    quoted
+                ),
+                String::from(
+                    "<http://example.com/a> <http://example.com/b> <http://example.com/c>"
+                ),
+                format!("{} <http://example.com/certainty> \"0.9\"", quoted),
+            ]
+        );
+    }
+
+    #[test]
+    fn n3_formulae_are_graphs() {
+        let txt = r#"
+@prefix : <http://example.com/> .
+{ :a a :Person } => { :a a :Agent } .
+:b <= :c .
+"#;
+
+        let url = lsp_types::Url::from_str("http://example.com/ns#").unwrap();
+        let context = Context::new();
+        let (tokens, errors) = parse_n3_tokens_str(txt);
+        assert!(errors.is_empty());
+        let (output, errors) = parse_n3(&url, tokens, txt.len(), context.ctx());
+        assert!(errors.is_empty());
+
+        let output = output.into_value();
+        let triples = output.get_simple_triples().expect("Triples found");
+        let show = |ts: &[MyQuad<'_>]| -> Vec<String> {
+            ts.iter()
+                .map(|t| {
+                    let graph = t.graph.as_ref().map(|g| g.to_string()).unwrap_or_default();
+                    format!("{} {} {} {}", t.subject, t.predicate, t.object, graph)
+                })
+                .collect()
+        };
+
+        assert_eq!(
+            show(&triples.triples),
+            vec![
+                "_:internal_bnode_1 <http://www.w3.org/2000/10/swap/log#implies> _:internal_bnode_2 ",
+                "<http://example.com/c> <http://www.w3.org/2000/10/swap/log#implies> <http://example.com/b> ",
+            ]
+        );
+        assert_eq!(
+            show(&triples.unasserted),
+            vec![
+                "<http://example.com/a> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://example.com/Person> _:internal_bnode_1",
+                "<http://example.com/a> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://example.com/Agent> _:internal_bnode_2",
+            ]
+        );
+    }
 }
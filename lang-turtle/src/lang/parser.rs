@@ -4,12 +4,22 @@ use tracing::info;
 
 use super::context::{ContextKind, Ctx};
 use crate::lang::model::{
-    Base, BlankNode, Literal, NamedNode, RDFLiteral, Term, Triple, Turtle, TurtlePrefix, Variable,
-    PO,
+    Annotation, Base, BlankNode, Formula, Graph, Literal, NamedNode, Quantifier, QuotedTriple,
+    RDFLiteral, Term, Triple, Turtle, TurtlePrefix, Variable, PO,
 };
 
 type S = std::ops::Range<usize>;
 
+/// The syntaxes that are parsed into the [`Turtle`] model.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Syntax {
+    Turtle,
+    /// Turtle with `GRAPH <name> { ... }` blocks.
+    TriG,
+    /// Turtle with formulae, implications, quantifiers and variables.
+    N3,
+}
+
 // pub fn just(token: Token) -> impl Parser<PToken, Token, Error = Simple<PToken, S>> + Clone {
 //     let t2 = token.clone();
 //     select! {
@@ -92,7 +102,7 @@ impl LiteralHelper {
     }
 }
 
-fn literal() -> impl Parser<PToken, Literal, Error = Simple<PToken, S>> + Clone {
+pub fn literal() -> impl Parser<PToken, Literal, Error = Simple<PToken, S>> + Clone {
     let lt = select! { PToken(Token::LangTag(x), _) => LiteralHelper::LangTag(x)};
 
     let dt = just(PToken(Token::DataTypeDelim, 0))
@@ -126,6 +136,9 @@ pub fn named_node() -> impl Parser<PToken, NamedNode, Error = Simple<PToken, S>>
 
     select! {
         PToken(Token::PredType, idx) => NamedNode::A(idx),
+        PToken(Token::Implies, idx) => NamedNode::Implies(idx),
+        PToken(Token::ImpliedBy, idx) => NamedNode::ImpliedBy(idx),
+        PToken(Token::SameAs, idx) => NamedNode::SameAs(idx),
         PToken(Token::IRIRef(x), idx) => NamedNode::Full(x, idx),
         PToken(Token::PNameLN(x, b), idx) => NamedNode::Prefixed { prefix: x.unwrap_or_default(), value: b, idx },
     }
@@ -185,33 +198,93 @@ pub fn expect_token(
             }))
 }
 
+/// End of a triple, the final `.` is optional when the triple closes a graph block.
+pub fn triple_end() -> impl Parser<PToken, Token, Error = Simple<PToken, S>> + Clone {
+    just([Token::CurlClose.into()])
+        .rewind()
+        .to(Token::Stop)
+        .or(expect_token(Token::Stop, |_| true))
+}
+
+#[allow(unused)]
 fn blank_node<'a>(
     ctx: Ctx<'a>,
 ) -> impl Parser<PToken, BlankNode, Error = Simple<PToken>> + Clone + use<'a> + 'a {
-    recursive(|bn| {
-        let start = select! {
-            PToken(Token::SqOpen, idx) => idx
-        };
+    bnode_with(nested(ctx, false), ctx)
+}
 
-        let end = select! {
-            PToken(Token::SqClose, idx) => idx
+/// Terms that contain triples, blank node property lists and, in N3, formulae.
+fn nested<'a>(
+    ctx: Ctx<'a>,
+    n3: bool,
+) -> impl Parser<PToken, Term, Error = Simple<PToken>> + Clone + use<'a> + 'a {
+    recursive(move |nested| {
+        let bn = bnode_with(nested.clone(), ctx).map(Term::BlankNode);
+        if n3 {
+            bn.or(formula(nested, ctx)).boxed()
+        } else {
+            bn.boxed()
         }
-        .recover_with(skip_parser(empty().map(|_| 0)));
-
-        start
-            .then(po_list(bn, ctx))
-            .then(end)
-            .map(|((end, x), start)| BlankNode::Unnamed(x, start, end))
-            .or(select! {
-                PToken(Token::BlankNodeLabel(x), idx) => BlankNode::Named(x, idx),
-            })
     })
 }
 
-fn subject<'a>(
+fn bnode_with<'a, T: Clone + Parser<PToken, Term, Error = Simple<PToken>> + 'a>(
+    nested: T,
+    ctx: Ctx<'a>,
+) -> impl Parser<PToken, BlankNode, Error = Simple<PToken>> + Clone + use<'a, T> + 'a {
+    let start = select! {
+        PToken(Token::SqOpen, idx) => idx
+    };
+
+    let end = select! {
+        PToken(Token::SqClose, idx) => idx
+    }
+    .recover_with(skip_parser(empty().map(|_| 0)));
+
+    start
+        .then(po_list(nested, ctx))
+        .then(end)
+        .map(|((end, x), start)| BlankNode::Unnamed(x, start, end))
+        .or(select! {
+            PToken(Token::BlankNodeLabel(x), idx) => BlankNode::Named(x, idx),
+        })
+}
+
+/// N3 formula `{ ... }`, a quoted graph of triples.
+fn formula<'a, T: Clone + Parser<PToken, Term, Error = Simple<PToken>> + 'a>(
+    nested: T,
+    ctx: Ctx<'a>,
+) -> impl Parser<PToken, Term, Error = Simple<PToken>> + Clone + use<'a, T> + 'a {
+    let start = select! {
+        PToken(Token::CurlOpen, idx) => idx
+    };
+
+    let end = select! {
+        PToken(Token::CurlClose, idx) => Some(idx)
+    }
+    .recover_with(skip_parser(empty().map(|_| None)));
+
+    start
+        .then(
+            triple_with(nested, ctx, triple_end())
+                .map_with_span(spanned)
+                .repeated(),
+        )
+        .then(end)
+        .map(|((start, triples), end)| {
+            Term::Formula(Formula {
+                triples,
+                start,
+                end,
+            })
+        })
+}
+
+fn subject<'a, T: Clone + Parser<PToken, Term, Error = Simple<PToken>> + 'a>(
+    nested: T,
     ctx: Ctx<'a>,
-) -> impl Parser<PToken, Term, Error = Simple<PToken, S>> + Clone + use<'a> + 'a {
-    term(blank_node(ctx), ctx, [])
+) -> impl Parser<PToken, Term, Error = Simple<PToken, S>> + Clone + use<'a, T> + 'a {
+    term(nested, ctx, [])
     // let nn = named_node().map(|x| Term::NamedNode(x));
     // let bn = blank_node().map(|x| Term::BlankNode(x));
     // let var = variable().map(|x| Term::Variable(x));
@@ -219,13 +292,18 @@ fn subject<'a>(
     // nn.or(bn).or(var)
 }
 
+/// A subject or object term, including blank node property lists and collections.
+pub fn node(ctx: Ctx<'_>) -> impl Parser<PToken, Term, Error = Simple<PToken>> + Clone + use<'_> {
+    term(nested(ctx, false), ctx, [])
+}
+
 fn variable() -> impl Parser<PToken, Variable, Error = Simple<PToken, S>> + Clone {
     select! {
         PToken(Token::Variable(x), idx) => Variable(x, idx),
     }
 }
 
-fn term<'a, const C: usize, T: 'a + Clone + Parser<PToken, BlankNode, Error = Simple<PToken>>>(
+fn term<'a, const C: usize, T: 'a + Clone + Parser<PToken, Term, Error = Simple<PToken>>>(
     bn: T,
     ctx: Ctx<'a>,
     not: [ContextKind; C],
@@ -261,6 +339,7 @@ fn term<'a, const C: usize, T: 'a + Clone + Parser<PToken, BlankNode, Error = Si
     // .recover_with(skip_parser(empty().map(|_| Term::Invalid)))
     .ignore_then(recursive(|term| {
         let collection = term
+            .clone()
             .map_with_span(spanned)
             .repeated()
             .delimited_by(
@@ -269,37 +348,131 @@ fn term<'a, const C: usize, T: 'a + Clone + Parser<PToken, BlankNode, Error = Si
             )
             .map(|x| Term::Collection(x));
 
+        let quoted = quoted_triple(term);
         let nn = named_node().map(|x| Term::NamedNode(x));
-        let blank = bn.map(|x| Term::BlankNode(x));
+        let blank = bn;
         let literal = literal().map(|x| Term::Literal(x));
         let variable = variable().map(|x| Term::Variable(x));
 
-        choice((collection, literal, nn, blank, variable))
+        choice((collection, quoted, literal, nn, blank, variable))
     }))
 }
 
-fn po<'a, T: Clone + Parser<PToken, BlankNode, Error = Simple<PToken>> + 'a>(
+/// RDF 1.2 quoted triple `<< s p o >>`, only simple terms and quoted triples can be quoted.
+fn quoted_triple<T: Clone + Parser<PToken, Term, Error = Simple<PToken>>>(
+    term: T,
+) -> impl Parser<PToken, Term, Error = Simple<PToken>> + Clone {
+    let start = select! {
+        PToken(Token::QuotedOpen, idx) => idx
+    };
+
+    let end = select! {
+        PToken(Token::QuotedClose, idx) => Some(idx)
+    }
+    .recover_with(skip_parser(empty().map(|_| None)));
+
+    let term = term.map_with_span(spanned).validate(|term, _, emit| {
+        if !term.is_quotable() {
+            emit(Simple::custom(
+                term.span().clone(),
+                format!("a {} cannot be part of a quoted triple", term.ty()),
+            ));
+        }
+        term
+    });
+
+    start
+        .then(term.clone())
+        .then(term.clone())
+        .then(term)
+        .then(end)
+        .validate(|((((start, subject), predicate), object), end), _, emit| {
+            if !subject.is_subject() {
+                emit(Simple::custom(
+                    subject.span().clone(),
+                    "subject should be a subject",
+                ));
+            }
+            if !predicate.is_predicate() {
+                emit(Simple::custom(
+                    predicate.span().clone(),
+                    "predicate should be a named node",
+                ));
+            }
+            if !object.is_object() {
+                emit(Simple::custom(
+                    object.span().clone(),
+                    "object should be an object",
+                ));
+            }
+
+            Term::Triple(Box::new(QuotedTriple {
+                subject,
+                predicate,
+                object,
+                start,
+                end,
+            }))
+        })
+}
+
+fn po<'a, T: Clone + Parser<PToken, Term, Error = Simple<PToken>> + 'a>(
     bn: T,
     ctx: Ctx<'a>,
-) -> impl Parser<PToken, PO, Error = Simple<PToken>> + Clone + use<'a, T> {
-    term(bn.clone(), ctx, [ContextKind::Subject])
-        .labelled("predicate")
-        .map_with_span(spanned)
-        .then(
-            term(
-                bn.clone(),
-                ctx,
-                [ContextKind::Subject, ContextKind::Predicate],
+) -> impl Parser<PToken, PO, Error = Simple<PToken>> + Clone + use<'a, T> + 'a {
+    recursive(move |po| {
+        // RDF 1.2 annotation `{| :q :r |}` of the preceding object
+        let annotation = just(PToken(Token::AnnotationOpen, 0))
+            .ignore_then(
+                po.map_with_span(spanned)
+                    .separated_by(
+                        just(PToken(Token::PredicateSplit, 0))
+                            .repeated()
+                            .at_least(1),
+                    )
+                    .allow_trailing(),
             )
-            .recover_with(skip_parser(empty().map(|_| Term::Invalid)))
-            .labelled("object")
+            .then_ignore(
+                just(PToken(Token::AnnotationClose, 0))
+                    .ignored()
+                    .recover_with(skip_parser(empty())),
+            )
+            .map_with_span(spanned);
+
+        term(bn.clone(), ctx, [ContextKind::Subject])
+            .labelled("predicate")
             .map_with_span(spanned)
-            .separated_by(just(PToken(Token::Comma, 0)).labelled("comma")), // .at_least(1),
-        )
-        .map(|(predicate, object)| PO { predicate, object })
+            .then(
+                term(
+                    bn.clone(),
+                    ctx,
+                    [ContextKind::Subject, ContextKind::Predicate],
+                )
+                .recover_with(skip_parser(empty().map(|_| Term::Invalid)))
+                .labelled("object")
+                .map_with_span(spanned)
+                .then(annotation.or_not())
+                .separated_by(just(PToken(Token::Comma, 0)).labelled("comma")), // .at_least(1),
+            )
+            .map(|(predicate, objects)| {
+                let mut object = Vec::with_capacity(objects.len());
+                let mut annotations = Vec::new();
+                for (i, (o, annotation)) in objects.into_iter().enumerate() {
+                    object.push(o);
+                    if let Some(Spanned(po, span)) = annotation {
+                        annotations.push(spanned(Annotation { object: i, po }, span));
+                    }
+                }
+                PO {
+                    predicate,
+                    object,
+                    annotations,
+                }
+            })
+    })
 }
 
-fn po_list<'a, T: Clone + Parser<PToken, BlankNode, Error = Simple<PToken>> + 'a>(
+fn po_list<'a, T: Clone + Parser<PToken, Term, Error = Simple<PToken>> + 'a>(
     bn: T,
     ctx: Ctx<'a>,
 ) -> impl Parser<PToken, Vec<Spanned<PO>>, Error = Simple<PToken>> + Clone + use<'a, T> {
@@ -316,24 +489,32 @@ fn po_list<'a, T: Clone + Parser<PToken, BlankNode, Error = Simple<PToken>> + 'a
         .allow_trailing()
 }
 
-fn po_list_recovery<'a>(
+fn po_list_recovery<'a, T: Clone + Parser<PToken, Term, Error = Simple<PToken>> + 'a>(
+    nested: T,
     ctx: Ctx<'a>,
-) -> impl Parser<PToken, Vec<Spanned<PO>>, Error = Simple<PToken>> + Clone + use<'a> {
-    po_list(blank_node(ctx), ctx).recover_with(skip_parser(empty().map_with_span(|_, span: S| {
+) -> impl Parser<PToken, Vec<Spanned<PO>>, Error = Simple<PToken>> + Clone + use<'a, T> {
+    po_list(nested, ctx).recover_with(skip_parser(empty().map_with_span(|_, span: S| {
         vec![spanned(
             PO {
                 predicate: spanned(Term::Invalid, span.clone()),
                 object: vec![spanned(Term::Invalid, span.clone())],
+                annotations: Vec::new(),
             },
             span,
         )]
     })))
 }
 
-fn bn_triple(
-    ctx: Ctx<'_>,
-) -> impl Parser<PToken, Triple, Error = Simple<PToken>> + Clone + use<'_> {
-    let pos = po_list_recovery(ctx).validate(|po, span, emit| {
+fn bn_triple<'a, T, E>(
+    nested: T,
+    ctx: Ctx<'a>,
+    end: E,
+) -> impl Parser<PToken, Triple, Error = Simple<PToken>> + Clone + use<'a, T, E>
+where
+    T: Clone + Parser<PToken, Term, Error = Simple<PToken>> + 'a,
+    E: Clone + Parser<PToken, Token, Error = Simple<PToken>> + 'a,
+{
+    let pos = po_list_recovery(nested, ctx).validate(|po, span, emit| {
         if po.is_empty() {
             emit(Simple::custom(
                 span.clone(),
@@ -343,6 +524,7 @@ fn bn_triple(
                 PO {
                     predicate: spanned(Term::Invalid, span.clone()),
                     object: vec![spanned(Term::Invalid, span.clone())],
+                    annotations: Vec::new(),
                 },
                 span,
             )]
@@ -353,7 +535,7 @@ fn bn_triple(
     just([Token::SqOpen.into()])
         .ignore_then(pos)
         .then_ignore(just([Token::SqClose.into()]))
-        .then_ignore(expect_token(Token::Stop, |_| true))
+        .then_ignore(end)
         .map_with_span(|pos, span| Triple {
             subject: spanned(Term::BlankNode(BlankNode::Unnamed(pos, 0, 0)), span),
             po: Vec::new(),
@@ -363,7 +545,28 @@ fn bn_triple(
 pub fn triple(
     ctx: Ctx<'_>,
 ) -> impl Parser<PToken, Triple, Error = Simple<PToken>> + Clone + use<'_> {
-    let pos = po_list_recovery(ctx)
+    triple_with(nested(ctx, false), ctx, triple_end())
+}
+
+/// A triple that ends with `end` instead of [`triple_end`], for languages where other
+/// statements can follow a triple without a `.`.
+pub fn triple_ending<'a, E: Clone + Parser<PToken, Token, Error = Simple<PToken>> + 'a>(
+    ctx: Ctx<'a>,
+    end: E,
+) -> impl Parser<PToken, Triple, Error = Simple<PToken>> + Clone + use<'a, E> {
+    triple_with(nested(ctx, false), ctx, end)
+}
+
+fn triple_with<'a, T, E>(
+    nested: T,
+    ctx: Ctx<'a>,
+    end: E,
+) -> impl Parser<PToken, Triple, Error = Simple<PToken>> + Clone + use<'a, T, E>
+where
+    T: Clone + Parser<PToken, Term, Error = Simple<PToken>> + 'a,
+    E: Clone + Parser<PToken, Token, Error = Simple<PToken>> + 'a,
+{
+    let pos = po_list_recovery(nested.clone(), ctx)
         .validate(|po, span, emit| {
             if po.is_empty() {
                 emit(Simple::custom(
@@ -374,6 +577,7 @@ pub fn triple(
                     PO {
                         predicate: spanned(Term::Invalid, span.clone()),
                         object: vec![spanned(Term::Invalid, span.clone())],
+                        annotations: Vec::new(),
                     },
                     span,
                 )]
@@ -383,11 +587,11 @@ pub fn triple(
         })
         .labelled("po_list");
 
-    subject(ctx)
+    subject(nested.clone(), ctx)
         .labelled("subject")
         .map_with_span(spanned)
         .then(pos)
-        .then_ignore(expect_token(Token::Stop, |_| true))
+        .then_ignore(end.clone())
         .map(|(subject, po)| Triple { subject, po })
         .validate(|this: Triple, _, emit| {
             for po in &this.po {
@@ -417,7 +621,7 @@ pub fn triple(
 
             this
         })
-        .or(bn_triple(ctx))
+        .or(bn_triple(nested, ctx, end))
 
     // expect_token(Token::Stop, |_| true)
     //     .ignore_then(po_list())
@@ -525,50 +729,181 @@ fn prefix() -> impl Parser<PToken, TurtlePrefix, Error = Simple<PToken>> {
     turtle_prefix.or(sparql_prefix)
 }
 
+pub fn graph(ctx: Ctx<'_>) -> impl Parser<PToken, Graph, Error = Simple<PToken>> + Clone + use<'_> {
+    let name = just([Token::SparqlKeyword(SparqlKeyword::Graph).into()])
+        .or_not()
+        .ignore_then(term(nested(ctx, false), ctx, []).map_with_span(spanned))
+        .validate(|name, _, emit| {
+            let valid = match name.value() {
+                Term::NamedNode(NamedNode::A(_)) => false,
+                Term::NamedNode(_) | Term::BlankNode(_) | Term::Invalid => true,
+                _ => false,
+            };
+            if !valid {
+                emit(Simple::custom(
+                    name.span().clone(),
+                    "graph name should be a named node or blank node",
+                ));
+            }
+            name
+        });
+
+    let end = just([Token::CurlClose.into()])
+        .ignored()
+        .recover_with(skip_parser(empty()));
+
+    name.or_not()
+        .then_ignore(just([Token::CurlOpen.into()]))
+        .then(triple(ctx).map_with_span(spanned).repeated())
+        .then_ignore(end)
+        .map(|(name, triples)| Graph { name, triples })
+}
+
 // Makes it easier to handle parts that are not ordered
 enum Statement {
     Base(Spanned<Base>),
     Prefix(Spanned<TurtlePrefix>),
     Triple(Spanned<Triple>),
+    Graph(Spanned<Graph>),
+    Quantifier(Spanned<Quantifier>),
 }
 
-pub fn turtle<'a>(
+/// N3 quantification `@forAll :x, :y .` or `@forSome :x .`
+fn quantifier<'a>(
+    ctx: Ctx<'a>,
+) -> impl Parser<PToken, Quantifier, Error = Simple<PToken>> + Clone + use<'a> + 'a {
+    select! {
+        PToken(Token::ForAll, _) => true,
+        PToken(Token::ForSome, _) => false,
+    }
+    .then(
+        term(nested(ctx, true), ctx, [])
+            .map_with_span(spanned)
+            .separated_by(just(PToken(Token::Comma, 0))),
+    )
+    .then_ignore(expect_token(Token::Stop, |_| true))
+    .map(|(universal, terms)| Quantifier { universal, terms })
+}
+
+fn document<'a>(
     location: &'a lsp_types::Url,
     ctx: Ctx<'a>,
+    syntax: Syntax,
 ) -> impl Parser<PToken, Turtle, Error = Simple<PToken>> + 'a {
     let base = base().map_with_span(spanned).map(|b| Statement::Base(b));
     let prefix = prefix()
         .map_with_span(spanned)
         .map(|b| Statement::Prefix(b));
-    let triple = triple(ctx)
-        .map_with_span(spanned)
-        .map(|b| Statement::Triple(b));
 
-    let statement = base.or(prefix).or(triple);
+    let statement = match syntax {
+        Syntax::Turtle => {
+            let triple = triple(ctx)
+                .map_with_span(spanned)
+                .map(|b| Statement::Triple(b));
+            base.or(prefix).or(triple).boxed()
+        }
+        Syntax::TriG => {
+            let triple = triple(ctx)
+                .map_with_span(spanned)
+                .map(|b| Statement::Triple(b));
+            let graph = graph(ctx)
+                .map_with_span(spanned)
+                .map(|b| Statement::Graph(b));
+            base.or(prefix).or(graph).or(triple).boxed()
+        }
+        Syntax::N3 => {
+            let triple = triple_with(nested(ctx, true), ctx, triple_end())
+                .map_with_span(spanned)
+                .map(|b| Statement::Triple(b));
+            let quantifier = quantifier(ctx)
+                .map_with_span(spanned)
+                .map(|b| Statement::Quantifier(b));
+            base.or(prefix).or(quantifier).or(triple).boxed()
+        }
+    };
+
     statement
         .repeated()
         .map(|statements| {
             let mut base = None;
             let mut prefixes = Vec::new();
             let mut triples = Vec::new();
+            let mut graphs = Vec::new();
+            let mut quantifiers = Vec::new();
             for statement in statements {
                 match statement {
                     Statement::Base(b) => base = Some(b),
                     Statement::Prefix(p) => prefixes.push(p),
                     Statement::Triple(t) => triples.push(t),
+                    Statement::Graph(g) => graphs.push(g),
+                    Statement::Quantifier(q) => quantifiers.push(q),
                 }
             }
 
-            Turtle::new(base, prefixes, triples, location)
+            let mut turtle = Turtle::new(base, prefixes, triples, location);
+            turtle.graphs = graphs;
+            turtle.quantifiers = quantifiers;
+            turtle
         })
         .then_ignore(end())
 }
 
+pub fn turtle<'a>(
+    location: &'a lsp_types::Url,
+    ctx: Ctx<'a>,
+) -> impl Parser<PToken, Turtle, Error = Simple<PToken>> + 'a {
+    document(location, ctx, Syntax::Turtle)
+}
+
+/// Parser for TriG documents, Turtle with `GRAPH <name> { ... }` blocks.
+pub fn trig<'a>(
+    location: &'a lsp_types::Url,
+    ctx: Ctx<'a>,
+) -> impl Parser<PToken, Turtle, Error = Simple<PToken>> + 'a {
+    document(location, ctx, Syntax::TriG)
+}
+
+/// Parser for N3 documents, Turtle with formulae `{ ... }` and implications `=>`.
+pub fn n3<'a>(
+    location: &'a lsp_types::Url,
+    ctx: Ctx<'a>,
+) -> impl Parser<PToken, Turtle, Error = Simple<PToken>> + 'a {
+    document(location, ctx, Syntax::N3)
+}
+
 pub fn parse_turtle(
     location: &lsp_types::Url,
     tokens: Vec<Spanned<Token>>,
     len: usize,
     ctx: Ctx<'_>,
+) -> (Spanned<Turtle>, Vec<Simple<PToken>>) {
+    parse_document(location, tokens, len, ctx, Syntax::Turtle)
+}
+
+pub fn parse_trig(
+    location: &lsp_types::Url,
+    tokens: Vec<Spanned<Token>>,
+    len: usize,
+    ctx: Ctx<'_>,
+) -> (Spanned<Turtle>, Vec<Simple<PToken>>) {
+    parse_document(location, tokens, len, ctx, Syntax::TriG)
+}
+
+pub fn parse_n3(
+    location: &lsp_types::Url,
+    tokens: Vec<Spanned<Token>>,
+    len: usize,
+    ctx: Ctx<'_>,
+) -> (Spanned<Turtle>, Vec<Simple<PToken>>) {
+    parse_document(location, tokens, len, ctx, Syntax::N3)
+}
+
+fn parse_document(
+    location: &lsp_types::Url,
+    tokens: Vec<Spanned<Token>>,
+    len: usize,
+    ctx: Ctx<'_>,
+    syntax: Syntax,
 ) -> (Spanned<Turtle>, Vec<Simple<PToken>>) {
     let stream = chumsky::Stream::from_iter(
         0..len,
@@ -581,7 +916,7 @@ pub fn parse_turtle(
             .map(|Spanned(x, s)| (x, s)),
     );
 
-    let parser = turtle(location, ctx)
+    let parser = document(location, ctx, syntax)
         .map_with_span(spanned)
         .then_ignore(end().recover_with(skip_then_retry_until([])));
 
@@ -606,7 +941,8 @@ pub mod turtle_tests {
     use super::literal;
     use crate::lang::{
         context::{Context, TokenIdx},
-        parser::{blank_node, named_node, prefix, triple, turtle, BlankNode},
+        model::Term,
+        parser::{blank_node, named_node, prefix, trig, triple, turtle, BlankNode},
         tokenizer::{parse_tokens_str, parse_tokens_str_safe},
     };
 
@@ -751,6 +1087,40 @@ pub mod turtle_tests {
         assert_eq!(output.to_string(), "<a> <b> [ ].");
     }
 
+    #[test]
+    fn parse_quoted_triple_without_end() {
+        let context = Context::new();
+        let ctx = context.ctx();
+
+        let turtle = "<< <a> <b> <c> <d> <e> .";
+        let (output, errors) = parse_it(turtle, triple(ctx));
+        assert!(!errors.is_empty());
+        let output = output.expect("recovered triple");
+        match output.subject.value() {
+            Term::Triple(quoted) => {
+                assert_eq!(quoted.start, 0);
+                assert_eq!(quoted.end, None);
+            }
+            x => panic!("expected a quoted triple, found {:?}", x),
+        }
+    }
+
+    #[test]
+    fn trig_graph_does_not_end_a_triple() {
+        let context = Context::new();
+        let ctx = context.ctx();
+        let url = lsp_types::Url::from_str("http://example.com/ns#").unwrap();
+
+        let txt = "<a> <b> <c> GRAPH <g> { <d> <e> <f> }";
+        let (_, errors) = parse_it(txt, trig(&url, ctx));
+        assert!(!errors.is_empty());
+
+        let txt = "<a> <b> <c>. GRAPH <g> { <d> <e> <f> }";
+        let (output, errors) = parse_it(txt, trig(&url, ctx));
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(output.expect("trig").graphs.len(), 1);
+    }
+
     #[test]
     fn parse_triple_with_recovery_no_end() {
         let context = Context::new();
@@ -1,6 +1,6 @@
 use chumsky::prelude::*;
 use logos::Logos;
-use lsp_core::prelude::{spanned, Spanned, StringStyle, Token};
+use lsp_core::prelude::{spanned, Spanned, SparqlKeyword, StringStyle, Token};
 use token_helpers::*;
 
 #[allow(non_camel_case_types)]
@@ -25,6 +25,27 @@ enum TurtleToken {
     #[token("]")]
     SqClose,
 
+    #[token("{")]
+    CurlOpen,
+
+    #[token("}")]
+    CurlClose,
+
+    #[token("graph", ignore(case))]
+    Graph,
+
+    #[token("<<")]
+    QuotedOpen,
+
+    #[token(">>")]
+    QuotedClose,
+
+    #[token("{|")]
+    AnnotationOpen,
+
+    #[token("|}")]
+    AnnotationClose,
+
     #[token("(")]
     BraceOpen,
 
@@ -92,9 +113,37 @@ enum TurtleToken {
 
     #[regex(r#"#[^\u000D\u000A]*"#)]
     Comment,
+
+    // N3 only tokens
+    #[token("=>")]
+    Implies,
+
+    #[token("<=")]
+    ImpliedBy,
+
+    #[token("=")]
+    SameAs,
+
+    #[token("@forAll")]
+    ForAll,
+
+    #[token("@forSome")]
+    ForSome,
+
+    #[regex(r#"(\?((([A-Z]|[a-z]|[\u00C0-\u00D6\u00D8-\u00F6\u00F8-\u02FF\u0370-\u037D\u037F-\u1FFF\u200C-\u200D\u2070-\u218F\u2C00-\u2FEF\u3001-\uD7FF\uF900-\uFDCF\uFDF0-\uFFFD\U00010000-\U000EFFFF])|_)|[0-9])((([A-Z]|[a-z]|[\u00C0-\u00D6\u00D8-\u00F6\u00F8-\u02FF\u0370-\u037D\u037F-\u1FFF\u200C-\u200D\u2070-\u218F\u2C00-\u2FEF\u3001-\uD7FF\uF900-\uFDCF\uFDF0-\uFFFD\U00010000-\U000EFFFF])|_)|[0-9]|\u00B7|[\u0300-\u036F]|[\u203F-\u2040])*)"#)]
+    Variable,
 }
 
 pub fn parse_tokens_str<'a>(text: &'a str) -> (Vec<Spanned<Token>>, Vec<Simple<char>>) {
+    tokenize(text, false)
+}
+
+/// Tokenize an N3 document, Turtle with implications, quantifiers and variables.
+pub fn parse_n3_tokens_str<'a>(text: &'a str) -> (Vec<Spanned<Token>>, Vec<Simple<char>>) {
+    tokenize(text, true)
+}
+
+fn tokenize(text: &str, n3: bool) -> (Vec<Spanned<Token>>, Vec<Simple<char>>) {
     let mut tokens = Vec::new();
     let mut errors = Vec::new();
     let mut lex = TurtleToken::lexer(text);
@@ -116,6 +165,13 @@ pub fn parse_tokens_str<'a>(text: &'a str) -> (Vec<Spanned<Token>>, Vec<Simple<c
                     TurtleToken::SqBase => Token::SparqlBase,
                     TurtleToken::SqOpen => Token::SqOpen,
                     TurtleToken::SqClose => Token::SqClose,
+                    TurtleToken::CurlOpen => Token::CurlOpen,
+                    TurtleToken::CurlClose => Token::CurlClose,
+                    TurtleToken::Graph => Token::SparqlKeyword(SparqlKeyword::Graph),
+                    TurtleToken::QuotedOpen => Token::QuotedOpen,
+                    TurtleToken::QuotedClose => Token::QuotedClose,
+                    TurtleToken::AnnotationOpen => Token::AnnotationOpen,
+                    TurtleToken::AnnotationClose => Token::AnnotationClose,
                     TurtleToken::BraceOpen => Token::BracketOpen,
                     TurtleToken::BraceClose => Token::BracketClose,
                     TurtleToken::TypeTag => Token::PredType,
@@ -153,6 +209,25 @@ pub fn parse_tokens_str<'a>(text: &'a str) -> (Vec<Spanned<Token>>, Vec<Simple<c
                         Token::Str(t2(1, 1), StringStyle::Single)
                     }
                     TurtleToken::IRIREF => Token::IRIRef(t2(1, 1)),
+                    TurtleToken::Implies if n3 => Token::Implies,
+                    TurtleToken::ImpliedBy if n3 => Token::ImpliedBy,
+                    TurtleToken::SameAs if n3 => Token::SameAs,
+                    TurtleToken::ForAll if n3 => Token::ForAll,
+                    TurtleToken::ForSome if n3 => Token::ForSome,
+                    TurtleToken::Variable if n3 => Token::Variable(t()),
+                    TurtleToken::Implies
+                    | TurtleToken::ImpliedBy
+                    | TurtleToken::SameAs
+                    | TurtleToken::ForAll
+                    | TurtleToken::ForSome
+                    | TurtleToken::Variable => {
+                        tokens.push(spanned(Token::Invalid(t()), lex.span()));
+                        errors.push(Simple::custom(
+                            lex.span(),
+                            format!("Unexpected token '{}'", &text[lex.span()]),
+                        ));
+                        continue;
+                    }
                     TurtleToken::PNAME_LN | TurtleToken::PNAME_NS => {
                         let st = &text[lex.span()];
                         let ends_with_stop = st.ends_with('.');
@@ -338,6 +413,42 @@ mod tests {
         assert!(err.is_empty());
     }
 
+    #[test]
+    fn parse_quoted_triple_tokens() {
+        let input = "<< <a> :b <c> >> :d :e {| :f :g |}.";
+
+        let (tok, err) = parse_tokens_str(input);
+        assert!(err.is_empty());
+
+        let tok: Vec<_> = tok.into_iter().map(|x| x.into_value()).collect();
+        assert_eq!(tok[0], Token::QuotedOpen);
+        assert_eq!(tok[1], Token::IRIRef("a".into()));
+        assert_eq!(tok[4], Token::QuotedClose);
+        assert_eq!(tok[7], Token::AnnotationOpen);
+        assert_eq!(tok[10], Token::AnnotationClose);
+        assert_eq!(tok[11], Token::Stop);
+    }
+
+    #[test]
+    fn parse_n3_tokens() {
+        let input = "@forAll ?x. { ?x :p <a> } => { ?x = <b> }. :c <= :d.";
+
+        let (tok, err) = parse_n3_tokens_str(input);
+        assert!(err.is_empty());
+
+        let tok: Vec<_> = tok.into_iter().map(|x| x.into_value()).collect();
+        assert_eq!(tok[0], Token::ForAll);
+        assert_eq!(tok[1], Token::Variable("?x".into()));
+        assert_eq!(tok[8], Token::Implies);
+        assert_eq!(tok[11], Token::SameAs);
+        assert_eq!(tok[16], Token::ImpliedBy);
+
+        // Turtle doesn't know these tokens
+        let (tok, err) = parse_tokens_str(input);
+        assert_eq!(err.len(), 7);
+        assert_eq!(tok[0].value(), &Token::Invalid("@forAll".into()));
+    }
+
     #[test]
     fn parse_invalid() {
         let input = "
@@ -5,7 +5,7 @@
 use bevy_ecs::{component::Component, observer::Trigger, system::Commands, world::World};
 use chumsky::prelude::Simple;
 use lsp_core::{
-    feature::diagnostics::publish_diagnostics,
+    feature::diagnostics::{publish_diagnostics, pull_syntax_diagnostics},
     lang::{Lang, LangHelper},
     prelude::*,
     CreateEvent,
@@ -15,12 +15,25 @@ use lsp_types::SemanticTokenType;
 pub mod ecs;
 pub mod lang;
 
-use crate::ecs::{setup_completion, setup_formatting, setup_parsing};
+use crate::ecs::{
+    setup_code_action, setup_completion, setup_document_symbol, setup_formatting, setup_parsing,
+};
 
-#[derive(Component)]
+#[derive(Component, Default)]
 pub struct TurtleLang;
 
-#[derive(Debug)]
+/// Languages that are parsed into the [`Turtle`](crate::lang::model::Turtle) model, sharing the
+/// Turtle systems (i.e. Turtle, TriG and N3).
+pub trait TurtleLike: Lang<Element = crate::lang::model::Turtle> + Component {
+    /// The syntax used to tokenize and parse the document.
+    const SYNTAX: crate::lang::parser::Syntax;
+}
+
+impl TurtleLike for TurtleLang {
+    const SYNTAX: crate::lang::parser::Syntax = crate::lang::parser::Syntax::Turtle;
+}
+
+#[derive(Debug, Clone)]
 pub struct TurtleHelper;
 impl LangHelper for TurtleHelper {
     fn keyword(&self) -> &[&'static str] {
@@ -28,41 +41,73 @@ impl LangHelper for TurtleHelper {
     }
 }
 
-pub fn setup_world(world: &mut World) {
+/// Semantic token types of the languages that share the Turtle systems, see [`TurtleLike`].
+pub const TURTLE_LEGEND_TYPES: &[SemanticTokenType] = &[
+    semantic_token::BOOLEAN,
+    semantic_token::LANG_TAG,
+    SemanticTokenType::COMMENT,
+    SemanticTokenType::ENUM_MEMBER,
+    SemanticTokenType::ENUM,
+    SemanticTokenType::KEYWORD,
+    SemanticTokenType::NAMESPACE,
+    SemanticTokenType::NUMBER,
+    SemanticTokenType::PROPERTY,
+    SemanticTokenType::STRING,
+    SemanticTokenType::VARIABLE,
+];
+
+/// Set up a [`TurtleLike`] language with the shared Turtle systems.
+///
+/// Documents with `language_id` are marked with `L` and `helper`, documents without a language
+/// id are recognized by their `extension`.
+pub fn setup_turtle_like<L, H>(
+    world: &mut World,
+    language_id: &'static str,
+    extension: &'static str,
+    helper: H,
+) where
+    L: TurtleLike + Default,
+    H: LangHelper + Clone + Send + Sync + 'static,
+{
     let mut semantic_token_dict = world.resource_mut::<SemanticTokensDict>();
-    TurtleLang::LEGEND_TYPES.iter().for_each(|lt| {
+    L::LEGEND_TYPES.iter().for_each(|lt| {
         if !semantic_token_dict.contains_key(lt) {
             let l = semantic_token_dict.0.len();
             semantic_token_dict.insert(lt.clone(), l);
         }
     });
 
-    world.observe(|trigger: Trigger<CreateEvent>, mut commands: Commands| {
-        match &trigger.event().language_id {
-            Some(x) if x == "turtle" => {
+    world.observe(
+        move |trigger: Trigger<CreateEvent>, mut commands: Commands| {
+            let matches = match &trigger.event().language_id {
+                Some(x) => x == language_id,
+                // Extensions are only used when the client did not tell the language
+                None => trigger.event().url.as_str().ends_with(extension),
+            };
+            if matches {
                 commands
                     .entity(trigger.entity())
-                    .insert((TurtleLang, DynLang(Box::new(TurtleHelper))));
-                return;
+                    .insert((L::default(), DynLang(Box::new(helper.clone()))));
             }
-            _ => {}
-        }
-        // pass
-        if trigger.event().url.as_str().ends_with(".ttl") {
-            commands
-                .entity(trigger.entity())
-                .insert((TurtleLang, DynLang(Box::new(TurtleHelper))));
-            return;
-        }
-    });
+        },
+    );
 
     world.schedule_scope(lsp_core::feature::DiagnosticsLabel, |_, schedule| {
-        schedule.add_systems(publish_diagnostics::<TurtleLang>);
+        schedule.add_systems(publish_diagnostics::<L>);
+    });
+    world.schedule_scope(lsp_core::feature::PullDiagnosticsLabel, |_, schedule| {
+        schedule.add_systems(pull_syntax_diagnostics::<L>);
     });
 
-    setup_parsing(world);
-    setup_completion(world);
-    setup_formatting(world);
+    setup_parsing::<L>(world);
+    setup_completion::<L>(world);
+    setup_formatting::<L>(world);
+    setup_code_action::<L>(world);
+    setup_document_symbol::<L>(world);
+}
+
+pub fn setup_world(world: &mut World) {
+    setup_turtle_like::<TurtleLang, _>(world, "turtle", ".ttl", TurtleHelper);
 }
 
 impl Lang for TurtleLang {
@@ -80,19 +125,7 @@ impl Lang for TurtleLang {
     const CODE_ACTION: bool = true;
     const HOVER: bool = true;
 
-    const LEGEND_TYPES: &'static [lsp_types::SemanticTokenType] = &[
-        semantic_token::BOOLEAN,
-        semantic_token::LANG_TAG,
-        SemanticTokenType::COMMENT,
-        SemanticTokenType::ENUM_MEMBER,
-        SemanticTokenType::ENUM,
-        SemanticTokenType::KEYWORD,
-        SemanticTokenType::NAMESPACE,
-        SemanticTokenType::NUMBER,
-        SemanticTokenType::PROPERTY,
-        SemanticTokenType::STRING,
-        SemanticTokenType::VARIABLE,
-    ];
+    const LEGEND_TYPES: &'static [lsp_types::SemanticTokenType] = TURTLE_LEGEND_TYPES;
 
     const PATTERN: Option<&'static str> = None;
 }
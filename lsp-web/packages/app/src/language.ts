@@ -15,8 +15,8 @@ export const Languages = {
   },
   sparql: {
     id: "sparql",
-    extensions: [".sq", ".rq"],
-    aliases: ["sq", "rq"],
-    mimetypes: ["application/sparql-query"],
+    extensions: [".sq", ".rq", ".ru"],
+    aliases: ["sq", "rq", "ru"],
+    mimetypes: ["application/sparql-query", "application/sparql-update"],
   },
 };
@@ -172,6 +172,8 @@ export async function activate() {
             {
                 language: "sparql",
             },
+            { language: "html" },
+            { language: "markdown" },
         ],
         synchronize: {},
         initializationOptions: {
@@ -174,4 +174,12 @@ impl Client for WebClient {
         );
         self.client.publish_diagnostics(uri, diags, version).await;
     }
+
+    async fn refresh_diagnostics(&self) -> () {
+        info!("Refreshing diagnostics");
+        let _ = self
+            .client
+            .send_request::<lsp_types::request::WorkspaceDiagnosticRefresh>(())
+            .await;
+    }
 }
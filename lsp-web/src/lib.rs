@@ -69,7 +69,8 @@ fn setup_world<C: Client + ClientSync + Resource + Clone>(
 
     setup_schedule_labels::<C>(&mut world);
 
-    let (publisher, mut rx) = DiagnosticPublisher::new();
+    let (mut publisher, mut rx) = DiagnosticPublisher::new();
+    let mut refresh = publisher.refresh_receiver();
     world.insert_resource(publisher);
 
     let c = client.clone();
@@ -79,9 +80,23 @@ fn setup_world<C: Client + ClientSync + Resource + Clone>(
         }
     });
 
+    let c = client.clone();
+    client.spawn(async move {
+        while let Some(()) = refresh.next().await {
+            // Loads often finish in bursts, one refresh covers them all
+            while let Ok(Some(())) = refresh.try_next() {}
+            c.refresh_diagnostics().await;
+        }
+    });
+
     lang_turtle::setup_world(&mut world);
-    lang_jsonld::setup_world(&mut world);
+    lang_jsonld::setup_world::<C>(&mut world);
     lang_sparql::setup_world(&mut world);
+    lang_trig::setup_world(&mut world);
+    lang_ntriples::setup_world(&mut world);
+    lang_n3::setup_world(&mut world);
+    lang_rdfxml::setup_world(&mut world);
+    lang_shex::setup_world(&mut world);
 
     let (tx, mut rx) = unbounded();
     let sender = CommandSender(tx);
@@ -5,8 +5,9 @@ use futures::FutureExt;
 use lsp_core::{
     client::{Client, ClientSync, Resp},
     prelude::FsTrait,
+    systems::language_id_from_url,
 };
-use lsp_types::{Diagnostic, MessageType, Url};
+use lsp_types::{request::WorkspaceDiagnosticRefresh, Diagnostic, MessageType, Url};
 use tokio::fs::{self, read_to_string, write};
 use tracing::info;
 
@@ -17,6 +18,13 @@ pub mod reqwest {
     };
 }
 
+/// Directories that contain build output or dependencies, never workspace documents.
+const SKIPPED_DIRS: &[&str] = &["target", "node_modules"];
+/// Maximum depth of directories below a workspace folder that are listed.
+const MAX_DEPTH: usize = 16;
+/// Maximum number of files listed in a workspace folder.
+const MAX_FILES: usize = 10_000;
+
 // TODO: use other tmp file location
 #[derive(Debug)]
 pub struct BinFs(PathBuf);
@@ -60,6 +68,50 @@ impl FsTrait for BinFs {
         }
         write(fp, content.as_bytes()).await.ok()
     }
+
+    async fn list_files(&self, url: &lsp_types::Url) -> Vec<lsp_types::Url> {
+        let mut out = Vec::new();
+        let Ok(root) = url.to_file_path() else {
+            return out;
+        };
+
+        let mut stack = vec![(root, 0)];
+        while let Some((dir, depth)) = stack.pop() {
+            let Ok(mut entries) = fs::read_dir(&dir).await else {
+                continue;
+            };
+            while let Ok(Some(entry)) = entries.next_entry().await {
+                let name = entry.file_name();
+                let name = name.to_string_lossy();
+                if name.starts_with('.') {
+                    continue;
+                }
+
+                match entry.file_type().await {
+                    Ok(ft) if ft.is_dir() => {
+                        if depth < MAX_DEPTH && !SKIPPED_DIRS.contains(&name.as_ref()) {
+                            stack.push((entry.path(), depth + 1));
+                        }
+                    }
+                    Ok(ft) if ft.is_file() => {
+                        let Ok(file) = lsp_types::Url::from_file_path(entry.path()) else {
+                            continue;
+                        };
+                        if language_id_from_url(&file).is_some() {
+                            out.push(file);
+                        }
+                        if out.len() >= MAX_FILES {
+                            info!("Stopped listing {} after {} files", url, MAX_FILES);
+                            return out;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        out
+    }
 }
 
 #[derive(Resource, Clone)]
@@ -169,4 +221,11 @@ impl Client for TowerClient {
     ) -> () {
         self.client.publish_diagnostics(uri, diags, version).await;
     }
+
+    async fn refresh_diagnostics(&self) -> () {
+        let _ = self
+            .client
+            .send_request::<WorkspaceDiagnosticRefresh>(())
+            .await;
+    }
 }
@@ -97,6 +97,8 @@ impl Client for TestClient {
         let mut lock = self.diagnostics.lock().await;
         lock.push((uri, diags));
     }
+
+    async fn refresh_diagnostics(&self) -> () {}
 }
 
 struct Sendable<T>(pub T);
@@ -192,6 +194,31 @@ impl FsTrait for TestFs {
         }
         fs::write(fp, content.as_bytes()).await.ok()
     }
+
+    async fn list_files(&self, url: &lsp_types::Url) -> Vec<lsp_types::Url> {
+        let mut out = Vec::new();
+        let Ok(root) = url.to_file_path() else {
+            return out;
+        };
+
+        let mut stack = vec![root];
+        while let Some(dir) = stack.pop() {
+            let Ok(entries) = std::fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                match entry.file_type() {
+                    Ok(ft) if ft.is_dir() => stack.push(entry.path()),
+                    Ok(ft) if ft.is_file() => {
+                        out.extend(lsp_types::Url::from_file_path(entry.path()));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        out
+    }
 }
 
 pub fn setup_world(